@@ -5,5 +5,8 @@ export default interface Class {
     "members": Array<Member>,
     "name": string,
     "description": string,
-    "discordId"?: string
+    "discordId"?: string,
+    "timezone"?: string,
+    "requireTwoFactor"?: boolean,
+    "archived"?: boolean
 }
\ No newline at end of file
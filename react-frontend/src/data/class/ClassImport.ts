@@ -0,0 +1,6 @@
+import Class from "./Class";
+
+export default interface ClassImportResponse {
+    class: Class,
+    invited: number
+}
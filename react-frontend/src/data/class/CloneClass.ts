@@ -0,0 +1,16 @@
+import EventType from "../event/EventType";
+
+export type DiscordLink = 'keep' | 'move';
+
+export default interface CloneClassRequest {
+    name: string,
+    description?: string,
+    copyMembers: boolean,
+    resetRoles: boolean,
+    copyTimetable: boolean,
+    copyEvents: boolean,
+    eventTypes: EventType[],
+    shiftWeeks: number,
+    discord: DiscordLink,
+    archiveSource: boolean
+}
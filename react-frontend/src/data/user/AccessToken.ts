@@ -0,0 +1,14 @@
+export interface AccessTokenScope {
+    class: string,
+    write: boolean
+}
+
+export default interface AccessToken {
+    id: string,
+    name: string,
+    created: number,
+    expires?: number,
+    scopes: Array<AccessTokenScope>
+}
+
+export type PostAccessToken = Omit<AccessToken, 'id' | 'created'>
@@ -0,0 +1,9 @@
+import Member from "./Member";
+
+export type OwnedClassesAction = 'transfer' | 'delete';
+
+export default interface OwnedClassDeletion {
+    class: string,
+    name: string,
+    nextOwner?: Member
+}
@@ -0,0 +1,8 @@
+import MemberRole from "./MemberRole";
+
+export default interface Invite {
+    class: string,
+    className: string,
+    displayName: string,
+    role: MemberRole
+}
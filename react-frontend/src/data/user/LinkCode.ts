@@ -0,0 +1,4 @@
+export default interface LinkCode {
+    code: string,
+    expires: number
+}
@@ -0,0 +1,15 @@
+export const maxPasswordLength = 128;
+
+/** The message for a rejected password of the server, undefined for other errors */
+export const passwordErrorMessage = (code: string): string | undefined => {
+    switch (code) {
+        case 'password-too-short':
+            return 'Das Passwort ist zu kurz';
+        case 'password-too-long':
+            return `Das Passwort darf nicht länger als ${maxPasswordLength} Zeichen sein`;
+        case 'password-needs-letter':
+            return 'Das Passwort muss einen Buchstaben enthalten';
+        case 'password-needs-digit':
+            return 'Das Passwort muss eine Ziffer enthalten';
+    }
+}
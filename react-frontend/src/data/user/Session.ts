@@ -0,0 +1,7 @@
+export default interface Session {
+    id: string,
+    device: string,
+    created: number,
+    lastUsed: number,
+    current: boolean
+}
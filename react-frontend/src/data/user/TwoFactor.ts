@@ -0,0 +1,9 @@
+export interface LoginChallenge {
+    challenge: string,
+    expires: number
+}
+
+export interface TwoFactorEnrollment {
+    secret: string,
+    uri: string
+}
@@ -4,7 +4,9 @@ export default interface User {
     "id": string,
     "email": string,
     "description": string,
-    "classes"?: Array<Class>
+    "classes"?: Array<Class>,
+    "emailVerified"?: boolean,
+    "twoFactorEnabled"?: boolean
 }
 
 export type PostUser = User & { password: string }
\ No newline at end of file
@@ -1,6 +1,8 @@
 import Class from "../data/class/Class";
 import Axios from './AxiosInstance'
 import User from "../data/user/User";
+import ClassImportResponse from "../data/class/ClassImport";
+import CloneClassRequest from "../data/class/CloneClass";
 
 export default class ClassRequest {
     private readonly axios: Axios;
@@ -11,7 +13,7 @@ export default class ClassRequest {
 
     public async getClass(id: string): Promise<Class> {
         return await this.axios.axios.get<Class>(`/classes/${id}`).then(r => r.data).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
@@ -20,7 +22,34 @@ export default class ClassRequest {
             name,
             description
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
+        })
+    }
+
+    public async exportClass(classId: string): Promise<object> {
+        return await this.axios.axios.get<object>(`/classes/${classId}/export`).then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
+        })
+    }
+
+    public async importClass(archive: object): Promise<ClassImportResponse> {
+        return await this.axios.axios.post<ClassImportResponse>('/classes/import', archive)
+            .then(r => r.data).catch((err) => {
+                throw new Error(err.response.data.code)
+            })
+    }
+
+    public async cloneClass(classId: string, request: CloneClassRequest): Promise<Class> {
+        return await this.axios.axios.post<Class>(`/classes/${classId}/clone`, request).then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
+        })
+    }
+
+    public async setArchived(classId: string, archived: boolean): Promise<Class> {
+        const request = archived ? this.axios.axios.post<Class>(`/classes/${classId}/archive`) :
+            this.axios.axios.delete<Class>(`/classes/${classId}/archive`);
+        return await request.then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
         })
     }
 
@@ -30,7 +59,7 @@ export default class ClassRequest {
             ...response,
             name
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
@@ -40,19 +69,29 @@ export default class ClassRequest {
             ...response,
             description
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
+        })
+    }
+
+    public async changeRequireTwoFactor(requireTwoFactor: boolean, classId: string): Promise<void> {
+        const response = await this.getClass(classId);
+        await this.axios.axios.put(`/classes/${classId}`, {
+            ...response,
+            requireTwoFactor
+        }).catch((err) => {
+            throw new Error(err.response.data.code)
         })
     }
 
     public async deleteClass(classId: string) {
         await this.axios.axios.delete(`/classes/${classId}`).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
     }
 
     public async getClasses(): Promise<Array<Class> | undefined> {
         return this.axios.axios.get<User>('/users/me').then(r => r.data.classes).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
     }
 }
\ No newline at end of file
@@ -1,4 +1,5 @@
 import Axios from "./AxiosInstance";
+import LinkCode from "../data/user/LinkCode";
 
 export default class DiscordRequest {
     private readonly axios: Axios;
@@ -11,15 +12,27 @@ export default class DiscordRequest {
         await this.axios.axios.post(`/classes/${classId}/link`, {
             snowflake
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
-    public async linkAccountToDiscord(snowflake: string) {
+    public async linkAccountToDiscord(code: string) {
         await this.axios.axios.post(`/users/me/link`, {
-            snowflake
+            code
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
+        })
+    }
+
+    public async createLinkCode(): Promise<LinkCode> {
+        return (await this.axios.axios.post<LinkCode>(`/users/me/link-codes`).catch((err) => {
+            throw new Error(err.response.data.code)
+        })).data
+    }
+
+    public async unlinkDiscord() {
+        await this.axios.axios.delete(`/users/me/link`).catch((err) => {
+            throw new Error(err.response.data.code)
         })
     }
 
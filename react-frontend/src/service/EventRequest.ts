@@ -17,20 +17,20 @@ export default class EventRequest {
             description: event.description,
             notification: event.notification,
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
     public async getCalendar(classId: string): Promise<Array<Event>> {
         const response = await this.axios.axios.get<Array<Event>>(`/classes/${classId}/events`).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
         return response.data;
     }
 
     public async deleteEvent(classId: string, eventId: string) {
         await this.axios.axios.delete(`/classes/${classId}/events/${eventId}`).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 }
\ No newline at end of file
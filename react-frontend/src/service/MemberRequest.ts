@@ -14,43 +14,43 @@ export default class MemberRequest {
         await this.axios.axios.post(`/classes/${classID}/requests/${userID}`, {
             accept
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
     public async deleteClassMember(classId: string, memberId: string): Promise<void> {
         await this.axios.axios.delete(`/classes/${classId}/members/${memberId}`).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
     public async requestToJoinClass(classId: string): Promise<void> {
         await this.axios.axios.post(`/classes/${classId}/join`).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
     public async getPendingMembers(classId: string): Promise<Array<Member>> {
         return await this.axios.axios.get<Array<Member>>(`/classes/${classId}/requests`).then(r => r.data).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
     }
 
     public async getBannedMembers(classId: string): Promise<Array<Member>> {
         return await this.axios.axios.get<Array<Member>>(`/classes/${classId}/bans`).then(r => r.data).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
     }
 
     public async getMembers(classId: string): Promise<Array<Member>> {
         return await this.axios.axios.get<Class>(`/classes/${classId}`).then(r => r.data.members).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
     }
 
     public async updateClassMember(classId: string, member: Member): Promise<void> {
         await this.axios.axios.put(`/classes/${classId}/members/${member.user}`, member).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
         return;
     }
@@ -13,26 +13,26 @@ export default class TimetableRequest {
 
     public async createTimetable(classID: string): Promise<void> {
         await this.axios.axios.post(`/classes/${classID}/timetable`).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
     public async updateTimetable(classId: string, timetableDay: TimeTableDay, day: number): Promise<void> {
         const timetable = await this.axios.axios.get<TimeTable>(`/classes/${classId}/timetable`).then(r => r.data).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
         timetable[day] = timetableDay;
         await this.axios.axios.put(`/classes/${classId}/timetable`, timetable).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
     public async addLesson(classId: string, lesson: Lesson, day: number): Promise<void> {
         const timetable = await this.axios.axios.get<TimeTable>(`/classes/${classId}/timetable`).then(r => r.data).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
         timetable[day].push(lesson);
         await this.axios.axios.put(`/classes/${classId}/timetable`, timetable).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
@@ -41,7 +41,7 @@ export default class TimetableRequest {
             console.log('Stundenplan nicht vorhanden')
             return undefined
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
     }
 
@@ -1,6 +1,12 @@
 import {AxiosResponse} from "axios";
 import User, {PostUser} from "../data/user/User";
 import Axios from './AxiosInstance'
+import AccessToken, {PostAccessToken} from "../data/user/AccessToken";
+import Session from "../data/user/Session";
+import Invite from "../data/user/Invite";
+import Member from "../data/user/Member";
+import {TwoFactorEnrollment} from "../data/user/TwoFactor";
+import OwnedClassDeletion, {OwnedClassesAction} from "../data/user/AccountDeletion";
 
 export default class UserRequest {
     private readonly axios: Axios;
@@ -14,13 +20,30 @@ export default class UserRequest {
             email,
             password
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async loginWithDiscord(code: string): Promise<AxiosResponse<{ userid: string, expires: number }>> {
+        return await this.axios.axios.post<{ userid: string, expires: number }>('/login/discord', {
+            code
+        }).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async completeTwoFactorLogin(challenge: string, code: string): Promise<AxiosResponse<{ userid: string, expires: number }>> {
+        return await this.axios.axios.post<{ userid: string, expires: number }>('/login/2fa', {
+            challenge,
+            code
+        }).catch((err) => {
+            throw new Error(err.response.data.code)
         });
     }
 
     public async getCurrentUser(): Promise<User> {
         return await this.axios.axios.get<User>('/users/me').then(r => r.data).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         });
     }
 
@@ -30,7 +53,7 @@ export default class UserRequest {
             email: user.email,
             description: user.description,
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
@@ -40,7 +63,7 @@ export default class UserRequest {
             ...response,
             email
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
@@ -50,22 +73,139 @@ export default class UserRequest {
             ...response,
             description
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
-    public async changePassword(password: string, oldPassword: string) {
-        await this.axios.axios.patch('/users/me/password', {
+    public async changePassword(password: string, oldPassword: string): Promise<AxiosResponse<{ userid: string, expires: number }>> {
+        return await this.axios.axios.patch<{ userid: string, expires: number }>('/users/me/password', {
             password,
             oldPassword
         }).catch((err) => {
-            throw new Error(err.response.data)
+            throw new Error(err.response.data.code)
         })
     }
 
-    public async deleteUser(): Promise<void> {
-        await this.axios.axios.delete('/users/me').catch((err) => {
-            throw new Error(err.response.data)
+    public async exportData(): Promise<object> {
+        return await this.axios.axios.get<object>('/users/me/export').then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async getAccountDeletion(): Promise<Array<OwnedClassDeletion>> {
+        return await this.axios.axios.get<Array<OwnedClassDeletion>>('/users/me/deletion').then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async deleteUser(ownedClasses: OwnedClassesAction, confirm: Array<string>): Promise<void> {
+        await this.axios.axios.delete('/users/me', {data: {ownedClasses, confirm}}).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async getAccessTokens(): Promise<Array<AccessToken>> {
+        return await this.axios.axios.get<Array<AccessToken>>('/users/me/tokens').then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async createAccessToken(token: PostAccessToken): Promise<{ token: string, accessToken: AccessToken }> {
+        return await this.axios.axios.post<{ token: string, accessToken: AccessToken }>('/users/me/tokens', token)
+            .then(r => r.data).catch((err) => {
+                throw new Error(err.response.data.code)
+            });
+    }
+
+    public async deleteAccessToken(id: string): Promise<void> {
+        await this.axios.axios.delete(`/users/me/tokens/${id}`).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async getInvites(): Promise<Array<Invite>> {
+        return await this.axios.axios.get<Array<Invite>>('/users/me/invites').then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async acceptInvite(classId: string): Promise<Member> {
+        return await this.axios.axios.post<Member>(`/users/me/invites/${classId}`).then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async declineInvite(classId: string): Promise<void> {
+        await this.axios.axios.delete(`/users/me/invites/${classId}`).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async getSessions(): Promise<Array<Session>> {
+        return await this.axios.axios.get<Array<Session>>('/users/me/sessions').then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async deleteSession(id: string): Promise<void> {
+        await this.axios.axios.delete(`/users/me/sessions/${id}`).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async requestPasswordReset(email: string): Promise<void> {
+        await this.axios.axios.post('/password-reset/request', {email}).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async confirmPasswordReset(token: string, password: string): Promise<void> {
+        await this.axios.axios.post('/password-reset/confirm', {token, password}).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async verifyEmail(token: string): Promise<void> {
+        await this.axios.axios.post('/verify-email', {token}).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async resendVerificationMail(): Promise<void> {
+        await this.axios.axios.post('/users/me/verify-email').catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async enrollTwoFactor(): Promise<TwoFactorEnrollment> {
+        return await this.axios.axios.post<TwoFactorEnrollment>('/users/me/2fa').then(r => r.data).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async verifyTwoFactor(code: string): Promise<Array<string>> {
+        return await this.axios.axios.post<{ recoveryCodes: Array<string> }>('/users/me/2fa/verify', {code})
+            .then(r => r.data.recoveryCodes).catch((err) => {
+                throw new Error(err.response.data.code)
+            });
+    }
+
+    public async regenerateRecoveryCodes(code: string): Promise<Array<string>> {
+        return await this.axios.axios.post<{ recoveryCodes: Array<string> }>('/users/me/2fa/recovery-codes', {code})
+            .then(r => r.data.recoveryCodes).catch((err) => {
+                throw new Error(err.response.data.code)
+            });
+    }
+
+    public async disableTwoFactor(code: string): Promise<void> {
+        await this.axios.axios.delete('/users/me/2fa', {data: {code}}).catch((err) => {
+            throw new Error(err.response.data.code)
+        });
+    }
+
+    public async logout(): Promise<void> {
+        await this.axios.axios.post('/logout').catch((err) => {
+            throw new Error(err.response.data.code)
         });
     }
 
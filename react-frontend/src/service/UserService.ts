@@ -14,6 +14,14 @@ import Axios from "./AxiosInstance";
 import axios, {AxiosResponse} from "axios";
 import DiscordRequest from "./DiscordRequest";
 import Lesson from "../data/timetable/Lesson";
+import LinkCode from "../data/user/LinkCode";
+import AccessToken, {PostAccessToken} from "../data/user/AccessToken";
+import Session from "../data/user/Session";
+import Invite from "../data/user/Invite";
+import {LoginChallenge, TwoFactorEnrollment} from "../data/user/TwoFactor";
+import OwnedClassDeletion, {OwnedClassesAction} from "../data/user/AccountDeletion";
+import ClassImportResponse from "../data/class/ClassImport";
+import CloneClassRequest from "../data/class/CloneClass";
 
 const memberRoles: Array<MemberRole> = ['owner', "admin", "member"];
 
@@ -27,6 +35,8 @@ export default class UserService {
     private readonly discordRequest: DiscordRequest
     private readonly axios: Axios;
     private refreshToken?: string;
+    // refresh tokens can only be used once, so parallel refreshes have to share the request
+    private refreshing?: Promise<AxiosResponse>;
 
     public constructor() {
         this.axios = Axios.getInstance();
@@ -39,12 +49,7 @@ export default class UserService {
         const refreshToken = localStorage.getItem('refresh-token') as string
         if (refreshToken) {
             this.refreshToken = refreshToken;
-            axios.get(`https://api.cors-school.com/api/token`, {
-                headers: {
-                    Authorization: refreshToken
-                }
-            }).then(val => {
-                this.setToken(val.headers);
+            this.updateAuthToken().then(val => {
                 this.updateToken(val.data.expires);
                 this.getCurrentUser().then(value => {
                     this._currentUserID = value.id;
@@ -65,26 +70,127 @@ export default class UserService {
     }
 
     public logout() {
-        localStorage.removeItem('refresh-token');
-        window.location.href = `${window.location.protocol}//${window.location.host}/`;
+        const leave = () => {
+            localStorage.removeItem('refresh-token');
+            window.location.href = `${window.location.protocol}//${window.location.host}/`;
+        }
+        this.userRequest.logout().then(leave, leave);
     }
 
     public async requestToJoinClass(classID: string): Promise<void> {
         return await this.memberRequest.requestToJoinClass(classID)
     }
 
-    public async login(email: string, password: string): Promise<void> {
-        const loginResponse = await this.userRequest.login(email, password);
-        this._currentUserID = loginResponse.data.userid;
+    /**
+     * Returns a challenge if the account has two-factor authentication, the login is then
+     * finished with `completeTwoFactorLogin`
+     */
+    public async login(email: string, password: string): Promise<LoginChallenge | undefined> {
+        return await this.finishLogin(await this.userRequest.login(email, password));
+    }
 
-        this.setToken(loginResponse.headers);
-        this.updateToken(loginResponse.data.expires);
-        localStorage.setItem('refresh-token', loginResponse.headers['refresh-token'] as string)
-        this.triggerOnAuthStateChange(await this.getCurrentUser());
+    public async loginWithDiscord(code: string): Promise<LoginChallenge | undefined> {
+        return await this.finishLogin(await this.userRequest.loginWithDiscord(code));
+    }
+
+    public async completeTwoFactorLogin(challenge: string, code: string): Promise<void> {
+        await this.finishLogin(await this.userRequest.completeTwoFactorLogin(challenge, code));
+    }
+
+    public async enrollTwoFactor(): Promise<TwoFactorEnrollment> {
+        return await this.userRequest.enrollTwoFactor()
+    }
+
+    public async verifyTwoFactor(code: string): Promise<Array<string>> {
+        return await this.userRequest.verifyTwoFactor(code)
+    }
+
+    public async regenerateRecoveryCodes(code: string): Promise<Array<string>> {
+        return await this.userRequest.regenerateRecoveryCodes(code)
+    }
+
+    public async disableTwoFactor(code: string): Promise<void> {
+        await this.userRequest.disableTwoFactor(code)
+    }
+
+    public async exportData(): Promise<object> {
+        return await this.userRequest.exportData()
+    }
+
+    public async getAccountDeletion(): Promise<Array<OwnedClassDeletion>> {
+        return await this.userRequest.getAccountDeletion()
+    }
+
+    public async deleteAccount(ownedClasses: OwnedClassesAction, confirm: Array<string>): Promise<void> {
+        await this.userRequest.deleteUser(ownedClasses, confirm);
+        localStorage.removeItem('refresh-token');
+        window.location.href = `${window.location.protocol}//${window.location.host}/`;
+    }
+
+    public redirectToDiscordLogin() {
+        const state = Math.random().toString(36).substring(2);
+        sessionStorage.setItem('discord-login-state', state);
+        window.location.href = `https://api.cors-school.com/api/login/discord?state=${state}`;
+    }
+
+    public isDiscordLoginState(state: string | null): boolean {
+        const expected = sessionStorage.getItem('discord-login-state');
+        sessionStorage.removeItem('discord-login-state');
+        return !!state && state === expected;
     }
 
     public async changePassword(password: string, oldPassword: string) {
-        return this.userRequest.changePassword(password, oldPassword)
+        // changing the password ends all sessions and starts a new one
+        const response = await this.userRequest.changePassword(password, oldPassword);
+        this.setToken(response.headers);
+    }
+
+    public async getAccessTokens(): Promise<Array<AccessToken>> {
+        return await this.userRequest.getAccessTokens()
+    }
+
+    public async createAccessToken(token: PostAccessToken): Promise<{ token: string, accessToken: AccessToken }> {
+        return await this.userRequest.createAccessToken(token)
+    }
+
+    public async deleteAccessToken(id: string): Promise<void> {
+        await this.userRequest.deleteAccessToken(id)
+    }
+
+    public async requestPasswordReset(email: string): Promise<void> {
+        await this.userRequest.requestPasswordReset(email)
+    }
+
+    public async confirmPasswordReset(token: string, password: string): Promise<void> {
+        await this.userRequest.confirmPasswordReset(token, password)
+    }
+
+    public async verifyEmail(token: string): Promise<void> {
+        await this.userRequest.verifyEmail(token)
+    }
+
+    public async resendVerificationMail(): Promise<void> {
+        await this.userRequest.resendVerificationMail()
+    }
+
+    public async getInvites(): Promise<Array<Invite>> {
+        return await this.userRequest.getInvites()
+    }
+
+    public async acceptInvite(classId: string): Promise<void> {
+        await this.userRequest.acceptInvite(classId).then(() => window.location.reload())
+    }
+
+    public async declineInvite(classId: string): Promise<void> {
+        await this.userRequest.declineInvite(classId)
+    }
+
+    public async getSessions(): Promise<Array<Session>> {
+        return await this.userRequest.getSessions()
+    }
+
+    public async deleteSession(id: string): Promise<void> {
+        await this.userRequest.deleteSession(id)
     }
 
     public async changeDescription(description: string) {
@@ -117,10 +223,31 @@ export default class UserService {
         return await this.classRequest.createClass(name, description).then(() => window.location.reload())
     }
 
+    public async exportClass(classId: string): Promise<object> {
+        return await this.classRequest.exportClass(classId);
+    }
+
+    public async importClass(archive: object): Promise<ClassImportResponse> {
+        return await this.classRequest.importClass(archive);
+    }
+
+    public async cloneClass(classId: string, request: CloneClassRequest): Promise<void> {
+        return await this.classRequest.cloneClass(classId, request)
+            .then(clone => window.location.href = `${window.location.protocol}//${window.location.host}/class/${clone.id}`);
+    }
+
+    public async setClassArchived(classId: string, archived: boolean): Promise<void> {
+        return await this.classRequest.setArchived(classId, archived).then(() => window.location.reload());
+    }
+
     public async changeClassName(classId: string, name: string): Promise<void> {
         return await this.classRequest.changeName(name, classId).then(() => window.location.reload());
     }
 
+    public async changeClassRequireTwoFactor(classId: string, requireTwoFactor: boolean): Promise<void> {
+        return await this.classRequest.changeRequireTwoFactor(requireTwoFactor, classId).then(() => window.location.reload());
+    }
+
     public async changeClassDescription(classId: string, description: string): Promise<void> {
         return await this.classRequest.changeDescription(description, classId).then(() => window.location.reload());
     }
@@ -217,8 +344,16 @@ export default class UserService {
         await this.discordRequest.linkClassToGuild(classId, snowflake)
     }
 
-    public async linkAccountToDiscord(snowflake: string) {
-        await this.discordRequest.linkAccountToDiscord(snowflake)
+    public async linkAccountToDiscord(code: string) {
+        await this.discordRequest.linkAccountToDiscord(code)
+    }
+
+    public async createLinkCode(): Promise<LinkCode> {
+        return await this.discordRequest.createLinkCode()
+    }
+
+    public async unlinkDiscord() {
+        await this.discordRequest.unlinkDiscord()
     }
 
     public getMemberRole(role: MemberRole): string {
@@ -256,18 +391,37 @@ export default class UserService {
     }
 
     private async updateAuthToken(): Promise<AxiosResponse> {
-        const response = await axios.get(`https://api.cors-school.com/api/token`, {
-            headers: {
-                'Authorization': this.refreshToken
-            }
-        })
-        this.setToken(response.headers)
-        return response;
+        if (!this.refreshing) {
+            this.refreshing = axios.get(`https://api.cors-school.com/api/token`, {
+                headers: {
+                    'Authorization': this.refreshToken
+                }
+            }).then(response => {
+                this.setToken(response.headers)
+                return response;
+            }).finally(() => this.refreshing = undefined)
+        }
+        return this.refreshing;
+    }
+
+    private async finishLogin(loginResponse: AxiosResponse): Promise<LoginChallenge | undefined> {
+        // 202: the password was right, but the second factor is still missing
+        if (loginResponse.status === 202) {
+            return loginResponse.data as LoginChallenge;
+        }
+        this._currentUserID = loginResponse.data.userid;
+
+        this.setToken(loginResponse.headers);
+        this.updateToken(loginResponse.data.expires);
+        this.triggerOnAuthStateChange(await this.getCurrentUser());
     }
 
     private setToken(header: any) {
-        if (header['refresh-token'])
+        // the refresh token changes on every refresh
+        if (header['refresh-token']) {
             this.refreshToken = header['refresh-token']
+            localStorage.setItem('refresh-token', header['refresh-token'] as string)
+        }
         this.axios.setAxios(header['token']);
     }
 }
@@ -9,6 +9,9 @@ import MainSite from "./mainsite/MainSite";
 import SignUp from "./login/SignUp";
 import User from "../data/user/User";
 import RequestToJoin from "./account/RequestToJoin";
+import DiscordLogin from "./login/DiscordLogin";
+import PasswordReset from "./login/PasswordReset";
+import VerifyEmail from "./login/VerifyEmail";
 
 const userService = new UserService();
 const UserServiceContext = React.createContext<UserService>(userService);
@@ -35,12 +38,16 @@ const Router = () => {
                             <Route path={'/class/:id/admin'} component={MainSite}/>
                             <Route path={'/join/:id'} component={RequestToJoin}/>
                             <Route path={'/account'} component={Account}/>
+                            <Route path={'/verify-email'} component={VerifyEmail}/>
                             <Route exact path={'/'} component={() => (<Redirect to={'/class'}/>)}/>
                         </Switch>
                     ) : (
                         <Switch>
                             <Route exact path={'/'} component={Login}/>
                             <Route path={'/signup'} component={SignUp}/>
+                            <Route path={'/login/discord'} component={DiscordLogin}/>
+                            <Route path={'/password-reset'} component={PasswordReset}/>
+                            <Route path={'/verify-email'} component={VerifyEmail}/>
                         </Switch>
                     )
                 }
@@ -0,0 +1,160 @@
+import React, {useContext, useEffect, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import Container from 'react-bootstrap/Container';
+import Form from 'react-bootstrap/Form';
+import FormControl from "react-bootstrap/FormControl";
+import FormGroup from 'react-bootstrap/FormGroup';
+import FormLabel from 'react-bootstrap/FormLabel';
+import ModalBody from 'react-bootstrap/ModalBody';
+import Table from 'react-bootstrap/Table';
+import * as Yup from 'yup';
+import {useFormik} from "formik";
+import {UserServiceContext} from "../Router";
+import AccessToken, {AccessTokenScope} from "../../data/user/AccessToken";
+import Class from "../../data/class/Class";
+
+const DAY = 24 * 60 * 60 * 1000;
+
+const validationScheme = Yup.object().shape({
+    'name': Yup.string()
+        .max(50, 'Der Name kann nicht länger als 50 Zeichen sein')
+        .required('Der Name darf nicht leer sein')
+})
+
+type Access = 'none' | 'read' | 'write';
+
+const AccessTokens = () => {
+    const userService = useContext(UserServiceContext);
+    const [tokens, setTokens] = useState<Array<AccessToken>>([]);
+    const [classes, setClasses] = useState<Array<Class>>([]);
+    const [access, setAccess] = useState<{ [classId: string]: Access }>({});
+    const [createdToken, setCreatedToken] = useState<string>();
+    const [error, setError] = useState<string>();
+
+    const loadTokens = () => {
+        userService.getAccessTokens().then(setTokens).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(loadTokens)
+            }
+        });
+        userService.getCurrentUser().then(user => setClasses(user.classes || [])).catch(() => {
+        });
+    }
+    // eslint-disable-next-line
+    useEffect(loadTokens, [])
+
+    const onSubmit = ({name, validity}: { name: string, validity: string }) => {
+        const scopes: Array<AccessTokenScope> = Object.entries(access)
+            .filter(([, value]) => value !== 'none')
+            .map(([classId, value]) => ({class: classId, write: value === 'write'}));
+        const expires = validity === 'never' ? undefined : Date.now() + Number(validity) * DAY;
+
+        userService.createAccessToken({name, expires, scopes}).then(created => {
+            setCreatedToken(created.token);
+            setError(undefined);
+            loadTokens();
+        }).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(() => onSubmit({name, validity}))
+                    break;
+                case 'already-exists':
+                    setError('Du hast schon ein Token mit diesem Namen');
+                    break;
+                default:
+                    setError('Das Token konnte nicht erstellt werden');
+            }
+        })
+    }
+
+    const revoke = (id: string) => {
+        userService.deleteAccessToken(id).then(loadTokens).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(() => revoke(id))
+            }
+        })
+    }
+
+    const className = (id: string) => classes.find(c => c.id === id)?.name || id;
+
+    const formik = useFormik({
+        validationSchema: validationScheme,
+        onSubmit: onSubmit,
+        validateOnChange: false,
+        validateOnBlur: true,
+        initialValues: {
+            'name': '',
+            'validity': '90'
+        }
+    })
+    return (
+        <Container>
+            <h3>Zugriffstokens</h3>
+            <ModalBody>Mit einem Zugriffstoken können Skripte die API benutzen. Ein Token kann nur lesen, ausser in
+                den Klassen, in denen es schreiben darf.</ModalBody>
+            {tokens.length > 0 &&
+            <Table size={'sm'}>
+                <thead>
+                <tr>
+                    <th>Name</th>
+                    <th>Klassen</th>
+                    <th>Gültig bis</th>
+                    <th/>
+                </tr>
+                </thead>
+                <tbody>
+                {tokens.map(token =>
+                    <tr key={token.id}>
+                        <td>{token.name}</td>
+                        <td>{token.scopes.map(scope =>
+                            `${className(scope.class)} (${scope.write ? 'schreiben' : 'lesen'})`).join(', ')}</td>
+                        <td>{token.expires ? new Date(token.expires).toLocaleDateString() : 'unbegrenzt'}</td>
+                        <td><Button size={'sm'} variant={'danger'} onClick={() => revoke(token.id)}>Widerrufen</Button></td>
+                    </tr>
+                )}
+                </tbody>
+            </Table>}
+            {createdToken &&
+            <Alert variant={'success'}>Dein Token (wird nur einmal angezeigt): <code>{createdToken}</code></Alert>}
+            <Form onSubmit={(e) => {
+                e.preventDefault();
+                formik.handleSubmit(e);
+            }}>
+                <FormGroup>
+                    <FormLabel>Name</FormLabel>
+                    <FormControl type={'text'} name={'name'} onChange={formik.handleChange}
+                                 isInvalid={!!formik.errors.name || !!error}/>
+                    <FormControl.Feedback type={'invalid'}>{formik.errors.name || error}</FormControl.Feedback>
+                </FormGroup>
+                <FormGroup>
+                    <FormLabel>Gültigkeit</FormLabel>
+                    <FormControl as={'select'} name={'validity'} onChange={formik.handleChange}
+                                 value={formik.values.validity}>
+                        <option value={'30'}>30 Tage</option>
+                        <option value={'90'}>90 Tage</option>
+                        <option value={'365'}>1 Jahr</option>
+                        <option value={'never'}>Unbegrenzt</option>
+                    </FormControl>
+                </FormGroup>
+                {classes.map(c =>
+                    <FormGroup key={c.id}>
+                        <FormLabel>{c.name}</FormLabel>
+                        <FormControl as={'select'} value={access[c.id] || 'none'}
+                                     onChange={e => setAccess({...access, [c.id]: e.target.value as Access})}>
+                            <option value={'none'}>Kein Zugriff</option>
+                            <option value={'read'}>Lesen</option>
+                            <option value={'write'}>Lesen und schreiben</option>
+                        </FormControl>
+                    </FormGroup>
+                )}
+                <br/>
+                <Button type={'submit'}>Token erstellen</Button>
+            </Form>
+        </Container>
+    );
+};
+
+export default AccessTokens;
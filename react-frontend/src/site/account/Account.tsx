@@ -6,6 +6,13 @@ import ChangeEmail from "./ChangeEmail";
 import ChangePassword from "./ChangePassword";
 import User from "../../data/user/User";
 import LinkDiscord from "./LinkDiscord";
+import AccessTokens from "./AccessTokens";
+import Sessions from "./Sessions";
+import Invites from "./Invites";
+import TwoFactor from "./TwoFactor";
+import VerifyEmailNotice from "./VerifyEmailNotice";
+import ExportData from "./ExportData";
+import DeleteAccount from "./DeleteAccount";
 
 const Account = () => {
     const userService = useContext(UserServiceContext);
@@ -27,12 +34,24 @@ const Account = () => {
         <Container className={'text-center'}>
             <ModalTitle>Account von {currentUser?.email}</ModalTitle>
             <br/>
+            {currentUser && !currentUser.emailVerified && <VerifyEmailNotice/>}
+            <Invites/>
             <hr/>
             <ChangeEmail/>
             <hr/>
             <ChangePassword/>
             <hr/>
+            {currentUser && <TwoFactor enabled={!!currentUser.twoFactorEnabled}/>}
+            <hr/>
             <LinkDiscord/>
+            <hr/>
+            <AccessTokens/>
+            <hr/>
+            <Sessions/>
+            <hr/>
+            <ExportData/>
+            <hr/>
+            <DeleteAccount/>
         </Container>
     );
 };
@@ -11,11 +11,12 @@ import Row from 'react-bootstrap/Row';
 import * as Yup from 'yup';
 import {useFormik} from "formik";
 import {UserServiceContext} from "../Router";
+import {maxPasswordLength, passwordErrorMessage} from "../../data/user/Password";
 
 const validationScheme = Yup.object().shape({
     'password': Yup.string()
         .min(6, 'Das Passwort muss mindestens 6 Zeichen haben')
-        .max(50, 'Das Passwort kann nicht länger als 50 Zeichen sein')
+        .max(maxPasswordLength, `Das Passwort kann nicht länger als ${maxPasswordLength} Zeichen sein`)
         .required('Das Passwort-Feld darf nicht leer sein'),
     'confirmPassword': Yup.string()
         .oneOf([Yup.ref('password'), null], 'Die Passwörter stimmem nicht überein'),
@@ -36,6 +37,11 @@ const ChangePassword = () => {
                 case 'wrong-password':
                     setError('Das Passwort ist nicht korrekt');
                     break;
+                case 'too-many-requests':
+                    setError('Zu viele Versuche, bitte warte einen Moment');
+                    break;
+                default:
+                    setError(passwordErrorMessage(err.message));
             }
         })
     }
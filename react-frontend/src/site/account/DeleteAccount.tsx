@@ -0,0 +1,86 @@
+import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import Container from 'react-bootstrap/Container';
+import Form from 'react-bootstrap/Form';
+import FormCheck from 'react-bootstrap/FormCheck';
+import {UserServiceContext} from "../Router";
+import OwnedClassDeletion, {OwnedClassesAction} from "../../data/user/AccountDeletion";
+
+const DeleteAccount = () => {
+    const userService = useContext(UserServiceContext);
+    const [deletions, setDeletions] = useState<Array<OwnedClassDeletion>>();
+    const [ownedClasses, setOwnedClasses] = useState<OwnedClassesAction>('transfer');
+    const [confirmed, setConfirmed] = useState(false);
+    const [error, setError] = useState<string>();
+
+    const loadDeletions = () => {
+        userService.getAccountDeletion().then(setDeletions).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(loadDeletions)
+            }
+        });
+    }
+
+    const deleteAccount = () => {
+        const confirm = deletions!.map(deletion => deletion.class);
+        userService.deleteAccount(ownedClasses, confirm).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(deleteAccount);
+                    break;
+                case 'deletion-not-confirmed':
+                    // the owned classes changed since they were loaded
+                    setConfirmed(false);
+                    loadDeletions();
+                    setError('Deine Klassen haben sich geändert, bitte bestätige erneut');
+                    break;
+                default:
+                    setError('Der Account konnte nicht gelöscht werden');
+            }
+        })
+    }
+
+    const describe = (deletion: OwnedClassDeletion): string => {
+        if (ownedClasses === 'transfer' && deletion.nextOwner) {
+            return `${deletion.name} gehört danach ${deletion.nextOwner.displayName}`;
+        }
+        return `${deletion.name} wird gelöscht`;
+    }
+
+    return (
+        <Container>
+            <h3>Account löschen</h3>
+            {!deletions &&
+            <Button variant={'outline-danger'} onClick={loadDeletions}>Account löschen...</Button>}
+            {deletions &&
+            <Form onSubmit={(e) => {
+                e.preventDefault();
+                deleteAccount();
+            }}>
+                <Alert variant={'danger'} show={!!error}>{error}</Alert>
+                {deletions.length > 0 && <>
+                    <p>Dir gehören Klassen. Was soll mit ihnen passieren?</p>
+                    <FormCheck type={'radio'} id={'owned-classes-transfer'} name={'owned-classes'}
+                               label={'Dem ersten Administrator übergeben, Klassen ohne Administrator löschen'}
+                               checked={ownedClasses === 'transfer'}
+                               onChange={() => setOwnedClasses('transfer')}/>
+                    <FormCheck type={'radio'} id={'owned-classes-delete'} name={'owned-classes'}
+                               label={'Alle löschen'}
+                               checked={ownedClasses === 'delete'}
+                               onChange={() => setOwnedClasses('delete')}/>
+                    <ul className={'text-start'}>
+                        {deletions.map(deletion => <li key={deletion.class}>{describe(deletion)}</li>)}
+                    </ul>
+                </>}
+                <FormCheck type={'checkbox'} id={'confirm-delete-account'}
+                           label={'Ich verstehe, dass mein Account endgültig gelöscht wird'}
+                           checked={confirmed} onChange={e => setConfirmed(e.target.checked)}/>
+                <Button type={'submit'} variant={'danger'} disabled={!confirmed}>Account endgültig löschen</Button>
+            </Form>}
+        </Container>
+    );
+};
+
+export default DeleteAccount;
@@ -0,0 +1,37 @@
+import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import Container from 'react-bootstrap/Container';
+import {UserServiceContext} from "../Router";
+import {downloadJson} from "../../service/Download";
+
+const ExportData = () => {
+    const userService = useContext(UserServiceContext);
+    const [error, setError] = useState<string>();
+
+    const download = () => {
+        userService.exportData().then(data => {
+            setError(undefined);
+            downloadJson(data, 'cors-export.json');
+        }).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(download);
+                    break;
+                default:
+                    setError('Die Daten konnten nicht heruntergeladen werden');
+            }
+        })
+    }
+
+    return (
+        <Container>
+            <h3>Meine Daten</h3>
+            <p>Alle Daten deines Accounts, deiner Klassen und der Klassen, die dir gehören.</p>
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+            <Button onClick={download}>Daten herunterladen</Button>
+        </Container>
+    );
+};
+
+export default ExportData;
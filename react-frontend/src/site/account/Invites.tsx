@@ -0,0 +1,89 @@
+import React, {useContext, useEffect, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import Container from 'react-bootstrap/Container';
+import Table from 'react-bootstrap/Table';
+import {UserServiceContext} from "../Router";
+import Invite from "../../data/user/Invite";
+
+const errorMessage = (error: string): string => {
+    switch (error) {
+        case 'email-not-verified':
+            return 'Bestätige zuerst deine E-Mail Adresse, um eine Einladung anzunehmen'
+        case 'banned':
+            return 'Du wurdest aus dieser Klasse gebannt'
+        default:
+            return 'Die Einladung konnte nicht angenommen werden'
+    }
+}
+
+const Invites = () => {
+    const userService = useContext(UserServiceContext);
+    const [invites, setInvites] = useState<Array<Invite>>([]);
+    const [error, setError] = useState<string>();
+
+    const loadInvites = () => {
+        userService.getInvites().then(setInvites).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(loadInvites)
+            }
+        });
+    }
+    // eslint-disable-next-line
+    useEffect(loadInvites, [])
+
+    const accept = (invite: Invite) => {
+        setError(undefined);
+        userService.acceptInvite(invite.class).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(() => accept(invite));
+                    break;
+                default:
+                    setError(errorMessage(err.message));
+            }
+        })
+    }
+
+    const decline = (invite: Invite) => {
+        userService.declineInvite(invite.class).then(loadInvites).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(() => decline(invite))
+            }
+        })
+    }
+
+    return (
+        <Container>
+            <h3>Einladungen</h3>
+            {invites.length === 0 ?
+                <p>Du hast keine offenen Einladungen</p> :
+                <Table size={'sm'}>
+                    <thead>
+                    <tr>
+                        <th>Klasse</th>
+                        <th>Name</th>
+                        <th/>
+                    </tr>
+                    </thead>
+                    <tbody>
+                    {invites.map(invite =>
+                        <tr key={invite.class}>
+                            <td>{invite.className}</td>
+                            <td>{invite.displayName}</td>
+                            <td>
+                                <Button size={'sm'} onClick={() => accept(invite)}>Annehmen</Button>{' '}
+                                <Button size={'sm'} variant={'danger'} onClick={() => decline(invite)}>Ablehnen</Button>
+                            </td>
+                        </tr>
+                    )}
+                    </tbody>
+                </Table>}
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+        </Container>
+    );
+};
+
+export default Invites;
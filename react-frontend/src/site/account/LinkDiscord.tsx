@@ -1,4 +1,4 @@
-import React, {useContext} from 'react';
+import React, {useContext, useState} from 'react';
 import Button from 'react-bootstrap/Button';
 import Container from 'react-bootstrap/Container';
 import Form from 'react-bootstrap/Form';
@@ -10,28 +10,43 @@ import ModalTitle from 'react-bootstrap/ModalTitle';
 import {UserServiceContext} from "../Router";
 import {useFormik} from "formik";
 import * as Yup from "yup";
+import LinkCode from "../../data/user/LinkCode";
 
 const validationScheme = Yup.object().shape({
-    'snowflake': Yup.string()
-        .max(20, 'Die ID kann nicht länger als 20 Zeichen sein')
-        .required('Die ID darf nicht leer sein')
+    'code': Yup.string()
+        .length(8, 'Der Code ist 8 Zeichen lang')
+        .required('Der Code darf nicht leer sein')
 })
 
 const LinkDiscord = () => {
     const userService = useContext(UserServiceContext);
+    const [linkCode, setLinkCode] = useState<LinkCode>();
+    const [error, setError] = useState<string>();
 
-    const handleSubmit = ({snowflake}: { snowflake: string }) => {
-        userService.linkAccountToDiscord(snowflake).catch(err => {
+    const handleSubmit = ({code}: { code: string }) => {
+        userService.linkAccountToDiscord(code.trim()).then(() => window.location.reload()).catch(err => {
             switch (err.message) {
                 case 'token-expired':
-                    userService.forceUpdate().then(() => handleSubmit({snowflake}))
+                    userService.forceUpdate().then(() => handleSubmit({code}))
+                    break;
+                case 'invalid-link-code':
+                    setError('Der Code ist abgelaufen oder wurde schon verwendet');
+            }
+        });
+    }
+
+    const createCode = () => {
+        userService.createLinkCode().then(setLinkCode).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(createCode)
             }
         });
     }
 
     const formik = useFormik({
         initialValues: {
-            snowflake: ''
+            code: ''
         },
         onSubmit: handleSubmit,
         validationSchema: validationScheme,
@@ -41,23 +56,32 @@ const LinkDiscord = () => {
     return (
         <Container>
             <ModalTitle>Discord Account mit CORS Account verbinden</ModalTitle>
-            <ModalBody>Die Discord ID wird benötigt, um Benutzer zu authentifizeren, damit der Discord Bot weiss, wer
-                welche Einstellungen vornehmen kann. Wenn du nicht weisst wie man
-                eine Discord ID bekommt, kannst du <a href={'https://discordzoom.com/blog/discord-user-id/'}>diesem
-                    Link</a> folgen</ModalBody>
+            <ModalBody>Der Discord Account wird benötigt, um Benutzer zu authentifizeren, damit der Discord Bot weiss,
+                wer welche Einstellungen vornehmen kann. Schreibe <code>/link</code> in Discord und gib den Code, den
+                dir der Bot schickt, hier ein.</ModalBody>
             <Form onSubmit={(e) => {
                 e.preventDefault();
                 formik.handleSubmit(e);
             }}>
                 <FormGroup>
-                    <FormLabel>Discord ID</FormLabel>
-                    <FormControl type={'text'} name={'snowflake'} onChange={formik.handleChange}/>
+                    <FormLabel>Code</FormLabel>
+                    <FormControl type={'text'} name={'code'} onChange={formik.handleChange}
+                                 isInvalid={!!formik.errors.code || !!error}/>
+                    <FormControl.Feedback type={'invalid'}>{formik.errors.code || error}</FormControl.Feedback>
                 </FormGroup>
                 <br/>
                 <Button type={'submit'}>Discord verbinden</Button>
             </Form>
+            <br/>
+            <ModalBody>Du kannst auch hier einen Code erstellen und ihn in Discord
+                mit <code>/link code</code> eingeben.</ModalBody>
+            {linkCode ?
+                <ModalBody>Dein Code: <strong>{linkCode.code}</strong> (gültig
+                    bis {new Date(linkCode.expires).toLocaleTimeString()})</ModalBody> :
+                <Button variant={'secondary'} onClick={createCode}>Code für Discord erstellen</Button>
+            }
         </Container>
     );
 };
 
-export default LinkDiscord;
\ No newline at end of file
+export default LinkDiscord;
@@ -18,6 +18,10 @@ const possibleMessage: { [key: string]: { title: string, body: string } } = {
         title: 'Deine Anfrage wurde bereits verschickt',
         body: 'Überprüfe mal, ob du schon in der Klasse bist. Wenn nicht, musst du auf die Besättigung eines Admins warten'
     },
+    'email-not-verified': {
+        title: 'Deine E-Mail Adresse ist noch nicht bestätigt',
+        body: 'Bestätige sie mit dem Link, den wir dir geschickt haben. Unter Account kannst du einen neuen Link anfordern'
+    },
     'other-error': {
         title: 'Es gab einen Fehler bei der Verarbeitung',
         body: 'Versuche es später wieder'
@@ -0,0 +1,63 @@
+import React, {useContext, useEffect, useState} from 'react';
+import Button from 'react-bootstrap/Button';
+import Container from 'react-bootstrap/Container';
+import Table from 'react-bootstrap/Table';
+import {UserServiceContext} from "../Router";
+import Session from "../../data/user/Session";
+
+const Sessions = () => {
+    const userService = useContext(UserServiceContext);
+    const [sessions, setSessions] = useState<Array<Session>>([]);
+
+    const loadSessions = () => {
+        userService.getSessions().then(setSessions).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(loadSessions)
+            }
+        });
+    }
+    // eslint-disable-next-line
+    useEffect(loadSessions, [])
+
+    const end = (session: Session) => {
+        if (session.current) {
+            userService.logout();
+            return;
+        }
+        userService.deleteSession(session.id).then(loadSessions).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(() => end(session))
+            }
+        })
+    }
+
+    return (
+        <Container>
+            <h3>Angemeldete Geräte</h3>
+            <Table size={'sm'}>
+                <thead>
+                <tr>
+                    <th>Gerät</th>
+                    <th>Angemeldet seit</th>
+                    <th>Zuletzt benutzt</th>
+                    <th/>
+                </tr>
+                </thead>
+                <tbody>
+                {sessions.map(session =>
+                    <tr key={session.id}>
+                        <td>{session.device}{session.current && ' (dieses Gerät)'}</td>
+                        <td>{new Date(session.created).toLocaleDateString()}</td>
+                        <td>{new Date(session.lastUsed).toLocaleString()}</td>
+                        <td><Button size={'sm'} variant={'danger'} onClick={() => end(session)}>Abmelden</Button></td>
+                    </tr>
+                )}
+                </tbody>
+            </Table>
+        </Container>
+    );
+};
+
+export default Sessions;
@@ -0,0 +1,106 @@
+import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import Container from 'react-bootstrap/Container';
+import Form from 'react-bootstrap/Form';
+import FormControl from "react-bootstrap/FormControl";
+import FormGroup from 'react-bootstrap/FormGroup';
+import FormLabel from 'react-bootstrap/FormLabel';
+import {UserServiceContext} from "../Router";
+import {TwoFactorEnrollment} from "../../data/user/TwoFactor";
+
+const errorMessage = (error: string): string => {
+    switch (error) {
+        case 'invalid-code':
+            return 'Der Code ist ungültig'
+        case 'too-many-requests':
+            return 'Zu viele Versuche, bitte warte einen Moment'
+        case 'no-enrollment':
+            return 'Die Einrichtung ist abgelaufen, bitte starte sie neu'
+        default:
+            return 'Die Aktion ist fehlgeschlagen'
+    }
+}
+
+const TwoFactor = ({enabled}: { enabled: boolean }) => {
+    const userService = useContext(UserServiceContext);
+    const [isEnabled, setEnabled] = useState(enabled);
+    const [enrollment, setEnrollment] = useState<TwoFactorEnrollment>();
+    const [recoveryCodes, setRecoveryCodes] = useState<Array<string>>();
+    const [code, setCode] = useState('');
+    const [error, setError] = useState<string>();
+
+    const run = <T, >(action: () => Promise<T>, then: (value: T) => void) => {
+        action().then(value => {
+            setError(undefined);
+            setCode('');
+            then(value);
+        }).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(() => run(action, then));
+                    break;
+                default:
+                    setError(errorMessage(err.message));
+            }
+        })
+    }
+
+    const enroll = () => run(() => userService.enrollTwoFactor(), setEnrollment);
+
+    const verify = () => run(() => userService.verifyTwoFactor(code), codes => {
+        setEnrollment(undefined);
+        setEnabled(true);
+        setRecoveryCodes(codes);
+    });
+
+    const regenerate = () => run(() => userService.regenerateRecoveryCodes(code), setRecoveryCodes);
+
+    const disable = () => run(() => userService.disableTwoFactor(code), () => {
+        setEnabled(false);
+        setRecoveryCodes(undefined);
+    });
+
+    const codeInput = (
+        <FormGroup>
+            <FormLabel>Code aus der Authenticator App</FormLabel>
+            <FormControl type={'text'} value={code} onChange={e => setCode(e.target.value)}
+                         isInvalid={!!error} autoComplete={'one-time-code'} placeholder={'123456'}/>
+        </FormGroup>
+    );
+
+    return (
+        <Container>
+            <h3>Zwei-Faktor-Authentifizierung</h3>
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+            {recoveryCodes &&
+            <Alert variant={'success'}>
+                Speichere diese Wiederherstellungscodes, sie werden nur einmal angezeigt.
+                Jeder Code kann einmal statt eines Codes aus der App verwendet werden.
+                <pre>{recoveryCodes.join('\n')}</pre>
+            </Alert>}
+            {!isEnabled && !enrollment &&
+            <Button onClick={enroll}>Zwei-Faktor-Authentifizierung einrichten</Button>}
+            {!isEnabled && enrollment &&
+            <Form onSubmit={(e) => {
+                e.preventDefault();
+                verify();
+            }}>
+                <p>Füge den Schlüssel in deiner Authenticator App hinzu und bestätige mit einem Code.</p>
+                <p><a href={enrollment.uri}>{enrollment.secret}</a></p>
+                {codeInput}
+                <Button type={'submit'}>Aktivieren</Button>
+            </Form>}
+            {isEnabled &&
+            <Form onSubmit={(e) => e.preventDefault()}>
+                <p>Die Zwei-Faktor-Authentifizierung ist aktiviert.</p>
+                {codeInput}
+                <Button variant={'secondary'} onClick={regenerate}>Neue Wiederherstellungscodes</Button>
+                {' '}
+                <Button variant={'danger'} onClick={disable}>Deaktivieren</Button>
+            </Form>}
+        </Container>
+    );
+};
+
+export default TwoFactor;
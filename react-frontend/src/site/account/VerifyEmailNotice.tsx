@@ -0,0 +1,29 @@
+import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import {UserServiceContext} from "../Router";
+
+const VerifyEmailNotice = () => {
+    const userService = useContext(UserServiceContext);
+    const [sent, setSent] = useState(false);
+
+    const resend = () => {
+        userService.resendVerificationMail().then(() => setSent(true)).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(resend)
+            }
+        })
+    }
+
+    return (
+        <Alert variant={'warning'}>
+            Deine E-Mail Adresse ist noch nicht bestätigt. Wir haben dir einen Link geschickt.
+            <br/>
+            {sent ? 'Ein neuer Link wurde verschickt.' :
+                <Button size={'sm'} variant={'outline-dark'} onClick={resend}>Link erneut senden</Button>}
+        </Alert>
+    );
+};
+
+export default VerifyEmailNotice;
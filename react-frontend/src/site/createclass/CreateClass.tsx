@@ -1,26 +1,38 @@
 import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
 import Button from 'react-bootstrap/Button';
 import Container from 'react-bootstrap/Container';
 import CreatePopup from "./CreatePopup";
+import ImportClass from "./ImportClass";
 import {UserServiceContext} from "../Router";
 
 const CreateClass = () => {
     const [showPopUp, setShowPopUp] = useState(false);
+    const [error, setError] = useState<string>();
     const userService = useContext(UserServiceContext);
 
     const handleSubmit = ({name, description}: { name: string, description: string }) => {
         userService.createClass(name, description).then(() => setShowPopUp(false)).catch(err => {
-            if (err.message === 'token-expired')
-                userService.forceUpdate().then(() => handleSubmit({name, description}));
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(() => handleSubmit({name, description}));
+                    break;
+                case 'email-not-verified':
+                    setShowPopUp(false);
+                    setError('Bestätige zuerst deine E-Mail Adresse, um eine Klasse zu erstellen');
+            }
         });
     }
 
     return (
         <Container>
             <CreatePopup show={showPopUp} onSubmit={handleSubmit}/>
+            <Alert variant={'warning'} show={!!error}>{error}</Alert>
             <Button onClick={() => {
                 setShowPopUp(true)
             }}>Klasse erstellen</Button>
+            <hr/>
+            <ImportClass/>
         </Container>
     );
 };
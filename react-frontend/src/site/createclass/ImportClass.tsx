@@ -0,0 +1,86 @@
+import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import Form from 'react-bootstrap/Form';
+import FormControl from 'react-bootstrap/FormControl';
+import FormGroup from 'react-bootstrap/FormGroup';
+import FormLabel from 'react-bootstrap/FormLabel';
+import {UserServiceContext} from "../Router";
+import ClassImportResponse from "../../data/class/ClassImport";
+
+const errorMessage = (error: string): string => {
+    switch (error) {
+        case 'email-not-verified':
+            return 'Bestätige zuerst deine E-Mail Adresse, um eine Klasse zu importieren'
+        case 'unsupported-archive-version':
+            return 'Die Datei ist von einer neueren Version und kann nicht importiert werden'
+        case 'invalid-request':
+            return 'Die Datei enthält ungültige Mitglieder'
+        case 'two-factor-not-enabled':
+            return 'Die Klasse verlangt Zwei-Faktor-Authentifizierung, aktiviere sie zuerst für deinen Account'
+        default:
+            return 'Die Klasse konnte nicht importiert werden'
+    }
+}
+
+const ImportClass = () => {
+    const userService = useContext(UserServiceContext);
+    const [archive, setArchive] = useState<{ guild?: object }>();
+    const [result, setResult] = useState<ClassImportResponse>();
+    const [error, setError] = useState<string>();
+
+    const readFile = (file?: File) => {
+        setError(undefined);
+        file?.text().then(text => setArchive(JSON.parse(text))).catch(() => {
+            setArchive(undefined);
+            setError('Die Datei ist kein Export einer Klasse');
+        });
+    }
+
+    const importClass = () => {
+        userService.importClass(archive!).then(response => {
+            if (response.invited === 0 && !archive!.guild) {
+                window.location.reload();
+            }
+            setResult(response);
+        }).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(() => importClass());
+                    break;
+                default:
+                    setError(errorMessage(err.message));
+            }
+        })
+    }
+
+    if (result) {
+        return (
+            <Alert variant={'success'}>
+                Die Klasse {result.class.name} wurde importiert.
+                {result.invited > 0 &&
+                <p>{result.invited} Mitglieder wurden eingeladen und erscheinen, sobald sie die Einladung annehmen.</p>}
+                {archive?.guild &&
+                <p>Der Discord Server wurde nicht übernommen, verbinde ihn wie bei einer neuen Klasse.</p>}
+                <Button onClick={() => window.location.reload()}>Weiter</Button>
+            </Alert>
+        );
+    }
+
+    return (
+        <Form onSubmit={(e) => {
+            e.preventDefault();
+            importClass();
+        }}>
+            <FormGroup>
+                <FormLabel>Klasse aus einem Export importieren</FormLabel>
+                <FormControl type={'file'} accept={'application/json'}
+                             onChange={e => readFile((e.target as HTMLInputElement).files?.[0])}/>
+            </FormGroup>
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+            <Button type={'submit'} disabled={!archive}>Importieren</Button>
+        </Form>
+    );
+};
+
+export default ImportClass;
@@ -0,0 +1,56 @@
+import React, {useContext, useEffect, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Container from 'react-bootstrap/Container';
+import ModalTitle from 'react-bootstrap/ModalTitle';
+import {Link, useHistory, useLocation} from "react-router-dom";
+import UserService from "../../service/UserService";
+import {UserServiceContext} from "../Router";
+import TwoFactorLogin from "./TwoFactorLogin";
+import {LoginChallenge} from "../../data/user/TwoFactor";
+
+const errorMessage = (error: string): string => {
+    switch (error) {
+        case 'discord-email-missing':
+            return 'Dein Discord Account hat keine bestätigte E-Mail Adresse'
+        case 'email-linked-with-other-discord':
+            return 'Ein Account mit dieser E-Mail Adresse ist schon mit einem anderen Discord Account verbunden'
+        case 'email-account-not-verified':
+            return 'Es gibt schon einen Account mit dieser E-Mail Adresse. Melde dich dort an und verbinde Discord in den Einstellungen'
+        case 'signup-disabled':
+            return 'Es können keine neuen Accounts erstellt werden'
+        default:
+            return 'Die Anmeldung mit Discord ist fehlgeschlagen'
+    }
+}
+
+const DiscordLogin = () => {
+    const history = useHistory();
+    const location = useLocation();
+    const userService = useContext<UserService>(UserServiceContext);
+    const [error, setError] = useState<string>();
+    const [challenge, setChallenge] = useState<LoginChallenge>();
+
+    useEffect(() => {
+        const params = new URLSearchParams(location.search);
+        const code = params.get('code');
+        if (!code || !userService.isDiscordLoginState(params.get('state'))) {
+            setError(errorMessage('invalid-state'));
+            return;
+        }
+        userService.loginWithDiscord(code)
+            .then(challenge => challenge ? setChallenge(challenge) : history.push('/class'))
+            .catch(err => setError(errorMessage(err.message)));
+    }, [location, history, userService])
+
+    return (
+        <Container>
+            <ModalTitle>Mit Discord anmelden</ModalTitle>
+            <br/>
+            {challenge && <TwoFactorLogin challenge={challenge}/>}
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+            {error && <Link to={'/'}>Zurück zum Login</Link>}
+        </Container>
+    );
+};
+
+export default DiscordLogin;
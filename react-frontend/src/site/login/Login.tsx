@@ -13,6 +13,8 @@ import * as Yup from 'yup'
 import {Link, useHistory} from "react-router-dom";
 import UserService from "../../service/UserService";
 import {UserServiceContext} from "../Router";
+import TwoFactorLogin from "./TwoFactorLogin";
+import {LoginChallenge} from "../../data/user/TwoFactor";
 
 const LoginSchema = Yup.object().shape({
     email: Yup.string()
@@ -28,10 +30,19 @@ const Login = () => {
     const history = useHistory();
     const userService = useContext<UserService>(UserServiceContext);
     const [error, setError] = useState<string>();
+    const [challenge, setChallenge] = useState<LoginChallenge>();
     const handleSumbit = ({email, password}: { email: string, password: string }) => {
-        userService.login(email, password).then(() => history.push('/class')).catch(err => {
+        userService.login(email, password).then(challenge => {
+            if (challenge) {
+                setChallenge(challenge);
+            } else {
+                history.push('/class');
+            }
+        }).catch(err => {
             if (err.message === 'invalid-email-password')
                 setError('Email oder Passwort ungültig')
+            else if (err.message === 'too-many-requests')
+                setError('Zu viele Versuche, bitte warte einen Moment')
         });
     }
     const formik = useFormik({
@@ -44,6 +55,15 @@ const Login = () => {
         validateOnBlur: true,
         validateOnChange: false
     })
+    if (challenge) {
+        return (
+            <Container>
+                <ModalTitle>Log In</ModalTitle>
+                <br/>
+                <TwoFactorLogin challenge={challenge}/>
+            </Container>
+        );
+    }
     return (
         <Container>
             <ModalTitle>Log In</ModalTitle>
@@ -76,7 +96,13 @@ const Login = () => {
                 <Button type={'submit'}>Login</Button>
             </Form>
             <br/>
-            <ModalFooter>Noch nicht registriert? <Link to={'/signup'}>Hier registrieren!</Link></ModalFooter>
+            <Button variant={'secondary'} onClick={() => userService.redirectToDiscordLogin()}>Mit Discord
+                anmelden</Button>
+            <br/>
+            <ModalFooter>
+                <Link to={'/password-reset'}>Passwort vergessen?</Link>
+                Noch nicht registriert? <Link to={'/signup'}>Hier registrieren!</Link>
+            </ModalFooter>
         </Container>
     );
 };
@@ -0,0 +1,136 @@
+import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import Container from 'react-bootstrap/Container';
+import Form from 'react-bootstrap/Form';
+import FormGroup from 'react-bootstrap/FormGroup';
+import FormControl from "react-bootstrap/FormControl";
+import FormLabel from 'react-bootstrap/FormLabel';
+import ModalTitle from 'react-bootstrap/ModalTitle';
+import {useFormik} from "formik";
+import * as Yup from 'yup'
+import {Link, useLocation} from "react-router-dom";
+import {UserServiceContext} from "../Router";
+import {maxPasswordLength, passwordErrorMessage} from "../../data/user/Password";
+
+const RequestSchema = Yup.object().shape({
+    email: Yup.string()
+        .email('Ungültige E-Mail Adresse')
+        .required('E-Mail-Feld darf nicht leer sein.')
+})
+
+const ConfirmSchema = Yup.object().shape({
+    password: Yup.string()
+        .max(maxPasswordLength, `Das Passwort darf nicht länger als ${maxPasswordLength} Zeichen sein`)
+        .required('Passwort-Feld darf nicht leer sein'),
+    repeatPassword: Yup.string()
+        .oneOf([Yup.ref('password')], 'Die Passwörter stimmen nicht überein')
+})
+
+const RequestReset = () => {
+    const userService = useContext(UserServiceContext);
+    const [sent, setSent] = useState(false);
+    const [error, setError] = useState<string>();
+
+    const formik = useFormik({
+        initialValues: {
+            'email': ''
+        },
+        onSubmit: ({email}) => {
+            userService.requestPasswordReset(email).then(() => setSent(true))
+                .catch(() => setError('Die E-Mail konnte nicht gesendet werden'))
+        },
+        validationSchema: RequestSchema,
+        validateOnBlur: true,
+        validateOnChange: false
+    })
+
+    if (sent) {
+        return <Alert variant={'success'}>Falls ein Account mit dieser E-Mail Adresse existiert, haben wir dir einen
+            Link geschickt.</Alert>
+    }
+    return (
+        <Form onSubmit={(e) => {
+            e.preventDefault();
+            formik.handleSubmit(e)
+        }}>
+            <FormGroup>
+                <FormLabel>E-Mail Adresse</FormLabel>
+                <FormControl type={'text'} name={'email'} onChange={formik.handleChange}
+                             value={formik.values.email} isInvalid={!!formik.errors.email || !!error}
+                             placeholder={'E-Mail Adresse'}/>
+                <Alert variant={'danger'}
+                       show={!!formik.errors.email || !!error}>{formik.errors.email || error}</Alert>
+            </FormGroup>
+            <br/>
+            <Button type={'submit'}>Link senden</Button>
+        </Form>
+    );
+}
+
+const ConfirmReset = ({token}: { token: string }) => {
+    const userService = useContext(UserServiceContext);
+    const [done, setDone] = useState(false);
+    const [error, setError] = useState<string>();
+
+    const formik = useFormik({
+        initialValues: {
+            'password': '',
+            'repeatPassword': ''
+        },
+        onSubmit: ({password}) => {
+            userService.confirmPasswordReset(token, password).then(() => setDone(true)).catch(err => {
+                switch (err.message) {
+                    case 'invalid-reset-token':
+                        setError('Der Link ist ungültig oder abgelaufen');
+                        break;
+                    default:
+                        setError(passwordErrorMessage(err.message) || 'Das Passwort konnte nicht geändert werden');
+                }
+            })
+        },
+        validationSchema: ConfirmSchema,
+        validateOnBlur: true,
+        validateOnChange: false
+    })
+
+    if (done) {
+        return <Alert variant={'success'}>Dein Passwort wurde geändert. <Link to={'/'}>Zum Login</Link></Alert>
+    }
+    return (
+        <Form onSubmit={(e) => {
+            e.preventDefault();
+            formik.handleSubmit(e)
+        }}>
+            <FormGroup>
+                <FormLabel>Neues Passwort</FormLabel>
+                <FormControl type={'password'} name={'password'} onChange={formik.handleChange}
+                             value={formik.values.password} isInvalid={!!formik.errors.password}/>
+                <FormControl.Feedback type={'invalid'}>{formik.errors.password}</FormControl.Feedback>
+            </FormGroup>
+            <FormGroup>
+                <FormLabel>Passwort wiederholen</FormLabel>
+                <FormControl type={'password'} name={'repeatPassword'} onChange={formik.handleChange}
+                             value={formik.values.repeatPassword} isInvalid={!!formik.errors.repeatPassword}/>
+                <FormControl.Feedback type={'invalid'}>{formik.errors.repeatPassword}</FormControl.Feedback>
+            </FormGroup>
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+            <br/>
+            <Button type={'submit'}>Passwort ändern</Button>
+        </Form>
+    );
+}
+
+const PasswordReset = () => {
+    const token = new URLSearchParams(useLocation().search).get('token');
+
+    return (
+        <Container>
+            <ModalTitle>Passwort zurücksetzen</ModalTitle>
+            <br/>
+            {token ? <ConfirmReset token={token}/> : <RequestReset/>}
+        </Container>
+    );
+};
+
+export default PasswordReset;
@@ -10,6 +10,7 @@ import ModalTitle from "react-bootstrap/ModalTitle";
 import {useFormik} from "formik";
 import * as Yup from 'yup'
 import {UserServiceContext} from "../Router";
+import {maxPasswordLength, passwordErrorMessage} from "../../data/user/Password";
 import {useHistory} from "react-router-dom";
 
 const ValidationScheme = Yup.object().shape({
@@ -18,7 +19,7 @@ const ValidationScheme = Yup.object().shape({
         .email('Die E-Mail Adresse ist nicht gültig')
         .required('Das E-Mail Feld darf nicht leer sein'),
     password: Yup.string()
-        .max(50, 'Das Passwort darf nicht länger als 50 Zeichen sein')
+        .max(maxPasswordLength, `Das Passwort darf nicht länger als ${maxPasswordLength} Zeichen sein`)
         .min(6, 'Das Passwort muss mindestens 6 Zeichen lang sein')
         .required('Das Passwort Feld darf nicht leer sein'),
     confirmPassword: Yup.string()
@@ -28,6 +29,7 @@ const ValidationScheme = Yup.object().shape({
 const SignUp = () => {
     const userService = useContext(UserServiceContext);
     const [error, setError] = useState<string>();
+    const [passwordError, setPasswordError] = useState<string>();
     const history = useHistory();
 
     const onSubmit = ({email, password}: { email: string, password: string }) => {
@@ -39,6 +41,10 @@ const SignUp = () => {
         }).then(() => history.push('/classview')).catch(err => {
             if (err.message === 'already-exists')
                 setError('Die E-Mail wird schon verwendet')
+            else if (err.message === 'signup-disabled')
+                setError('Es können keine neuen Accounts erstellt werden')
+            else
+                setPasswordError(passwordErrorMessage(err.message))
         })
     }
 
@@ -73,9 +79,10 @@ const SignUp = () => {
                 <FormGroup>
                     <FormLabel>Passwort</FormLabel>
                     <FormControl type={'password'} placeholder={'Passwort (Mindestens 6 Zeichen)'} name={'password'}
-                                 isInvalid={!!formik.errors.password} value={formik.values.password}
+                                 isInvalid={!!formik.errors.password || !!passwordError} value={formik.values.password}
                                  onChange={formik.handleChange}/>
-                    <Alert variant={'danger'} show={!!formik.errors.password}>{formik.errors.password}</Alert>
+                    <Alert variant={'danger'}
+                           show={!!formik.errors.password || !!passwordError}>{formik.errors.password || passwordError}</Alert>
                 </FormGroup>
                 <br/>
                 <FormGroup>
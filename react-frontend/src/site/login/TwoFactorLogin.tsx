@@ -0,0 +1,57 @@
+import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import Form from 'react-bootstrap/Form';
+import FormGroup from 'react-bootstrap/FormGroup';
+import FormControl from "react-bootstrap/FormControl";
+import FormLabel from 'react-bootstrap/FormLabel';
+import FormText from 'react-bootstrap/FormText';
+import {useHistory} from "react-router-dom";
+import {UserServiceContext} from "../Router";
+import {LoginChallenge} from "../../data/user/TwoFactor";
+
+const errorMessage = (error: string): string => {
+    switch (error) {
+        case 'invalid-code':
+            return 'Der Code ist ungültig'
+        case 'too-many-requests':
+            return 'Zu viele Versuche, bitte warte einen Moment'
+        case 'invalid-challenge':
+            return 'Die Anmeldung ist abgelaufen, bitte melde dich neu an'
+        default:
+            return 'Die Anmeldung ist fehlgeschlagen'
+    }
+}
+
+/**
+ * The second step of a login with two-factor authentication
+ */
+const TwoFactorLogin = ({challenge}: { challenge: LoginChallenge }) => {
+    const history = useHistory();
+    const userService = useContext(UserServiceContext);
+    const [code, setCode] = useState('');
+    const [error, setError] = useState<string>();
+
+    const handleSubmit = (e: React.FormEvent) => {
+        e.preventDefault();
+        userService.completeTwoFactorLogin(challenge.challenge, code)
+            .then(() => history.push('/class'))
+            .catch(err => setError(errorMessage(err.message)));
+    }
+
+    return (
+        <Form onSubmit={handleSubmit}>
+            <FormGroup>
+                <FormLabel>Bestätigungscode</FormLabel>
+                <FormControl type={'text'} value={code} onChange={e => setCode(e.target.value)}
+                             isInvalid={!!error} autoComplete={'one-time-code'} placeholder={'123456'}/>
+                <FormText>Der Code aus deiner Authenticator App oder ein Wiederherstellungscode</FormText>
+                <Alert variant={'danger'} show={!!error}>{error}</Alert>
+            </FormGroup>
+            <br/>
+            <Button type={'submit'}>Bestätigen</Button>
+        </Form>
+    );
+};
+
+export default TwoFactorLogin;
@@ -0,0 +1,34 @@
+import React, {useContext, useEffect, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Container from 'react-bootstrap/Container';
+import ModalTitle from 'react-bootstrap/ModalTitle';
+import {Link, useLocation} from "react-router-dom";
+import {UserServiceContext} from "../Router";
+
+const VerifyEmail = () => {
+    const location = useLocation();
+    const userService = useContext(UserServiceContext);
+    const [verified, setVerified] = useState<boolean>();
+
+    useEffect(() => {
+        const token = new URLSearchParams(location.search).get('token');
+        if (!token) {
+            setVerified(false);
+            return;
+        }
+        userService.verifyEmail(token).then(() => setVerified(true)).catch(() => setVerified(false));
+    }, [location, userService])
+
+    return (
+        <Container>
+            <ModalTitle>E-Mail Adresse bestätigen</ModalTitle>
+            <br/>
+            <Alert variant={'success'} show={verified === true}>Deine E-Mail Adresse wurde bestätigt.</Alert>
+            <Alert variant={'danger'} show={verified === false}>Der Link ist ungültig oder abgelaufen. Unter Account
+                kannst du einen neuen Link anfordern.</Alert>
+            {verified !== undefined && <Link to={'/'}>Weiter</Link>}
+        </Container>
+    );
+};
+
+export default VerifyEmail;
@@ -3,8 +3,12 @@ import ModalTitle from 'react-bootstrap/ModalTitle';
 import Container from 'react-bootstrap/Container';
 import ChangeClassName from "./classsettings/ChangeClassName";
 import ChangeClassDescription from "./classsettings/ChangeClassDescription";
+import RequireTwoFactor from "./classsettings/RequireTwoFactor";
 import CopyLink from "./classsettings/CopyLink";
 import LinkWithDiscord from "./classsettings/LinkWithDiscord";
+import ExportClass from "./classsettings/ExportClass";
+import CloneClass from "./classsettings/CloneClass";
+import ArchiveClass from "./classsettings/ArchiveClass";
 import DeleteClass from "./classsettings/deleteclass/DeleteClass";
 
 const ChangeClassSettings = () => {
@@ -16,10 +20,18 @@ const ChangeClassSettings = () => {
             <br/>
             <ChangeClassDescription/>
             <br/>
+            <RequireTwoFactor/>
+            <br/>
             <CopyLink/>
             <br/>
             <LinkWithDiscord/>
             <br/>
+            <ExportClass/>
+            <br/>
+            <CloneClass/>
+            <br/>
+            <ArchiveClass/>
+            <br/>
             <DeleteClass/>
         </Container>
     );
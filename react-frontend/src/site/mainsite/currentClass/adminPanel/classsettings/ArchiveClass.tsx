@@ -0,0 +1,48 @@
+import React, {useContext, useEffect, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import ModalBody from 'react-bootstrap/ModalBody';
+import ModalTitle from 'react-bootstrap/ModalTitle';
+import {CurrentClass} from "../../ClassView";
+import {UserServiceContext} from "../../../../Router";
+
+const ArchiveClass = () => {
+    const currentClass = useContext(CurrentClass);
+    const userService = useContext(UserServiceContext);
+    const [isOwner, setIsOwner] = useState(false);
+    const [error, setError] = useState<string>();
+
+    useEffect(() => {
+        userService.getSelfInClass(currentClass!.id).then(val => setIsOwner(val.role === 'owner'))
+        //eslint-disable-next-line
+    }, [currentClass])
+
+    const archived = !!currentClass?.archived;
+
+    const change = () => {
+        userService.setClassArchived(currentClass!.id, !archived).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(change);
+                    break;
+                default:
+                    setError('Die Klasse konnte nicht geändert werden');
+            }
+        })
+    }
+
+    return (
+        <>
+            <ModalTitle>Archiv</ModalTitle>
+            <ModalBody>{archived ?
+                'Die Klasse ist archiviert, sie kann nur noch gelesen werden.' :
+                'Archivierte Klassen können nur noch gelesen werden.'}</ModalBody>
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+            <Button variant={'secondary'} onClick={change} disabled={!isOwner}>
+                {archived ? 'Archivierung aufheben' : 'Klasse archivieren'}
+            </Button>
+        </>
+    );
+};
+
+export default ArchiveClass;
@@ -0,0 +1,115 @@
+import React, {useContext, useEffect, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import Form from 'react-bootstrap/Form';
+import FormCheck from 'react-bootstrap/FormCheck';
+import FormControl from 'react-bootstrap/FormControl';
+import FormGroup from 'react-bootstrap/FormGroup';
+import FormLabel from 'react-bootstrap/FormLabel';
+import ModalBody from 'react-bootstrap/ModalBody';
+import ModalTitle from 'react-bootstrap/ModalTitle';
+import {CurrentClass} from "../../ClassView";
+import {UserServiceContext} from "../../../../Router";
+import CloneClassRequest from "../../../../../data/class/CloneClass";
+import EventType from "../../../../../data/event/EventType";
+
+const eventTypes: { [type in EventType]: string } = {
+    'holidays': 'Ferien',
+    'other': 'Anderes',
+    'homework': 'Hausaufgaben',
+    'exam': 'Prüfungen',
+}
+
+const CloneClass = () => {
+    const currentClass = useContext(CurrentClass);
+    const userService = useContext(UserServiceContext);
+    const [isOwner, setIsOwner] = useState(false);
+    const [request, setRequest] = useState<CloneClassRequest>({
+        name: '',
+        copyMembers: true,
+        resetRoles: false,
+        copyTimetable: true,
+        copyEvents: false,
+        eventTypes: ['holidays'],
+        shiftWeeks: 52,
+        discord: 'move',
+        archiveSource: true
+    });
+    const [error, setError] = useState<string>();
+
+    useEffect(() => {
+        userService.getSelfInClass(currentClass!.id).then(val => setIsOwner(val.role === 'owner'))
+        //eslint-disable-next-line
+    }, [currentClass])
+
+    const change = (changed: Partial<CloneClassRequest>) => setRequest({...request, ...changed});
+    const changeEventType = (type: EventType, copied: boolean) => change({
+        eventTypes: copied ? [...request.eventTypes, type] : request.eventTypes.filter(t => t !== type)
+    });
+
+    const clone = () => {
+        userService.cloneClass(currentClass!.id, request).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(clone);
+                    break;
+                case 'email-not-verified':
+                    setError('Bestätige zuerst deine E-Mail Adresse, um eine Klasse zu erstellen');
+                    break;
+                default:
+                    setError('Die Klasse konnte nicht kopiert werden');
+            }
+        })
+    }
+
+    if (!isOwner) {
+        return null;
+    }
+
+    return (
+        <Form onSubmit={(e) => {
+            e.preventDefault();
+            clone();
+        }}>
+            <ModalTitle>Neues Schuljahr</ModalTitle>
+            <ModalBody>Erstellt eine Kopie der Klasse für das neue Schuljahr.</ModalBody>
+            <FormGroup>
+                <FormLabel>Name der neuen Klasse</FormLabel>
+                <FormControl value={request.name} required onChange={e => change({name: e.target.value})}/>
+            </FormGroup>
+            <FormCheck type={'switch'} id={'clone-members'} label={'Mitglieder übernehmen'}
+                       checked={request.copyMembers} onChange={e => change({copyMembers: e.target.checked})}/>
+            <FormCheck type={'switch'} id={'clone-reset-roles'} label={'Alle Mitglieder ohne Administratorrechte'}
+                       disabled={!request.copyMembers}
+                       checked={request.resetRoles} onChange={e => change({resetRoles: e.target.checked})}/>
+            <FormCheck type={'switch'} id={'clone-timetable'} label={'Stundenplan übernehmen'}
+                       checked={request.copyTimetable} onChange={e => change({copyTimetable: e.target.checked})}/>
+            <FormCheck type={'switch'} id={'clone-events'} label={'Events übernehmen'}
+                       checked={request.copyEvents} onChange={e => change({copyEvents: e.target.checked})}/>
+            <FormGroup>
+                <FormLabel>Events dieser Arten übernehmen, alte Hausaufgaben und Prüfungen werden auch kopiert</FormLabel>
+                {(Object.keys(eventTypes) as EventType[]).map(type =>
+                    <FormCheck inline key={type} id={`clone-events-${type}`} label={eventTypes[type]}
+                               disabled={!request.copyEvents} checked={request.eventTypes.includes(type)}
+                               onChange={e => changeEventType(type, e.target.checked)}/>)}
+            </FormGroup>
+            <FormGroup>
+                <FormLabel>Events um so viele Wochen verschieben</FormLabel>
+                <FormControl type={'number'} min={-104} max={104} disabled={!request.copyEvents}
+                             value={request.shiftWeeks}
+                             onChange={e => change({shiftWeeks: parseInt(e.target.value) || 0})}/>
+            </FormGroup>
+            {currentClass?.discordId &&
+            <FormCheck type={'switch'} id={'clone-discord'} label={'Discord Server mit der neuen Klasse verbinden'}
+                       checked={request.discord === 'move'}
+                       onChange={e => change({discord: e.target.checked ? 'move' : 'keep'})}/>}
+            <FormCheck type={'switch'} id={'clone-archive'} label={'Diese Klasse archivieren'}
+                       checked={request.archiveSource} onChange={e => change({archiveSource: e.target.checked})}/>
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+            <Button type={'submit'}
+                    disabled={!request.name || (request.copyEvents && request.eventTypes.length === 0)}>Klasse kopieren</Button>
+        </Form>
+    );
+};
+
+export default CloneClass;
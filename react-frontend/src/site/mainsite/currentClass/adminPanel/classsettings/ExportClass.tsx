@@ -0,0 +1,41 @@
+import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Button from 'react-bootstrap/Button';
+import ModalBody from 'react-bootstrap/ModalBody';
+import ModalTitle from 'react-bootstrap/ModalTitle';
+import {CurrentClass} from "../../ClassView";
+import {UserServiceContext} from "../../../../Router";
+import {downloadJson} from "../../../../../service/Download";
+
+const ExportClass = () => {
+    const currentClass = useContext(CurrentClass);
+    const userService = useContext(UserServiceContext);
+    const [error, setError] = useState<string>();
+
+    const download = () => {
+        userService.exportClass(currentClass!.id).then(archive => {
+            setError(undefined);
+            downloadJson(archive, `cors-class-${currentClass!.id}.json`);
+        }).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(download);
+                    break;
+                default:
+                    setError('Die Klasse konnte nicht exportiert werden');
+            }
+        })
+    }
+
+    return (
+        <>
+            <ModalTitle>Klasse exportieren</ModalTitle>
+            <ModalBody>Eine Sicherung mit den Mitgliedern, Events, dem Stundenplan und den Discord Einstellungen.
+                Sie kann auf einem anderen Server wieder importiert werden.</ModalBody>
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+            <Button onClick={download}>Exportieren</Button>
+        </>
+    );
+};
+
+export default ExportClass;
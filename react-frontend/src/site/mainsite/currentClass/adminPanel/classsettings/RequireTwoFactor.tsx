@@ -0,0 +1,39 @@
+import React, {useContext, useState} from 'react';
+import Alert from 'react-bootstrap/Alert';
+import Form from 'react-bootstrap/Form';
+import FormCheck from 'react-bootstrap/FormCheck';
+import {CurrentClass} from "../../ClassView";
+import {UserServiceContext} from "../../../../Router";
+
+const RequireTwoFactor = () => {
+    const currentClass = useContext(CurrentClass);
+    const userService = useContext(UserServiceContext);
+    const [error, setError] = useState<string>();
+
+    const change = (requireTwoFactor: boolean) => {
+        userService.changeClassRequireTwoFactor(currentClass!.id, requireTwoFactor).catch(err => {
+            switch (err.message) {
+                case 'token-expired':
+                    userService.forceUpdate().then(() => change(requireTwoFactor));
+                    break;
+                case 'two-factor-not-enabled':
+                    setError('Aktiviere zuerst die Zwei-Faktor-Authentifizierung für deinen Account');
+                    break;
+                default:
+                    setError('Die Einstellung konnte nicht geändert werden');
+            }
+        })
+    }
+
+    return (
+        <Form>
+            <FormCheck type={'switch'} id={'require-two-factor'}
+                       label={'Zwei-Faktor-Authentifizierung für Administratoren verlangen'}
+                       checked={!!currentClass?.requireTwoFactor}
+                       onChange={e => change(e.target.checked)}/>
+            <Alert variant={'danger'} show={!!error}>{error}</Alert>
+        </Form>
+    );
+};
+
+export default RequireTwoFactor;
@@ -0,0 +1,98 @@
+use crate::members::role_name;
+use crate::output::{Output, Row};
+use crate::{users, Admin};
+use color_eyre::eyre::bail;
+use color_eyre::Report;
+use serde_json::json;
+use server::actions;
+use server::models::Class;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+pub fn find(admin: &Admin, class: Uuid) -> Result<Class, Report> {
+    match actions::class::get_class(&admin.db, class)? {
+        Some((class, _)) => Ok(class),
+        None => bail!("no class {}", class),
+    }
+}
+
+fn row(class: &Class) -> Row {
+    vec![
+        ("id", json!(class.id)),
+        ("name", json!(class.name)),
+        ("owner", json!(class.owner)),
+        ("guild", json!(class.discord_id)),
+    ]
+}
+
+pub fn list(admin: &Admin) -> Result<Output, Report> {
+    let classes = actions::class::get_classes(&admin.db)?;
+    Ok(Output::Rows(classes.iter().map(row).collect()))
+}
+
+pub fn show(admin: &Admin, class: Uuid) -> Result<Output, Report> {
+    let class = find(admin, class)?;
+    let owner = actions::user::get_user_by_id(&admin.db, class.owner)?;
+    let members = actions::class::get_all_members(&admin.db, class.id)?;
+    let events = actions::event::get_events_by_class(&admin.db, class.id)?;
+
+    let mut roles = BTreeMap::new();
+    for (member, _) in &members {
+        *roles.entry(member.role).or_insert(0) += 1;
+    }
+
+    let mut row = row(&class);
+    row[2] = ("owner", json!(owner.email));
+    row.extend(vec![
+        ("description", json!(class.description)),
+        ("timezone", json!(class.timezone)),
+        ("requireTwoFactor", json!(class.require_two_factor)),
+        ("archived", json!(class.archived)),
+        (
+            "members",
+            json!(roles
+                .iter()
+                .map(|(role, amount)| format!("{} {}", amount, role_name(*role)))
+                .collect::<Vec<_>>()),
+        ),
+        ("events", json!(events.len())),
+    ]);
+    Ok(Output::Record(row))
+}
+
+pub fn transfer_owner(admin: &Admin, class: Uuid, user: &str) -> Result<Output, Report> {
+    let class = find(admin, class)?;
+    let user = users::find(admin, user)?;
+    if class.owner == user.id {
+        bail!("{} already owns {}", user.email, class.name);
+    }
+    let display_name = match actions::class::get_member(&admin.db, user.id, class.id) {
+        Ok((member, _)) => member.display_name,
+        Err(_) => user.email.split('@').next().unwrap_or_default().to_string(),
+    };
+    if let Some(output) = admin.skip(format_args!(
+        "make {} the owner of {}, the previous owner stays as an admin",
+        user.email, class.name
+    )) {
+        return Ok(output);
+    }
+
+    actions::class::transfer_ownership(&admin.db, class.id, user.id, &display_name)?;
+    Ok(Output::Message(format!(
+        "{} is now the owner of {}",
+        user.email, class.name
+    )))
+}
+
+pub fn delete(admin: &Admin, class: Uuid) -> Result<Output, Report> {
+    let class = find(admin, class)?;
+    if let Some(output) = admin.skip(format_args!(
+        "delete {} with all of its members and events",
+        class.name
+    )) {
+        return Ok(output);
+    }
+
+    actions::class::delete_class(&admin.db, class.id)?;
+    Ok(Output::Message(format!("Deleted {}", class.name)))
+}
@@ -0,0 +1,75 @@
+use crate::output::Output;
+use crate::Admin;
+use color_eyre::eyre::bail;
+use color_eyre::Report;
+use serde_json::json;
+use server::actions;
+use server::error::ServiceErr;
+use server::models::{Class, Guild};
+
+fn find(admin: &Admin, guild: &str) -> Result<(Guild, Class), Report> {
+    let found = actions::class::get_guild_settings(&admin.db, guild).and_then(|settings| {
+        Ok((
+            settings,
+            actions::class::get_class_by_discord(&admin.db, guild)?,
+        ))
+    });
+    match found {
+        Ok(found) => Ok(found),
+        Err(ServiceErr::NotFound) => bail!("no guild {}", guild),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn list(admin: &Admin) -> Result<Output, Report> {
+    let guilds = actions::class::get_guilds(&admin.db)?;
+
+    Ok(Output::Rows(
+        guilds
+            .iter()
+            .map(|(guild, class)| {
+                vec![
+                    ("id", json!(guild.id)),
+                    ("class", json!(class.id)),
+                    ("className", json!(class.name)),
+                    ("language", json!(guild.language)),
+                ]
+            })
+            .collect(),
+    ))
+}
+
+pub fn show(admin: &Admin, guild: &str) -> Result<Output, Report> {
+    let (guild, class) = find(admin, guild)?;
+    let routes = actions::class::get_notif_routes(&admin.db, &guild.id)?;
+    let mappings = actions::class::get_role_mappings(&admin.db, &guild.id)?;
+
+    Ok(Output::Record(vec![
+        ("id", json!(guild.id)),
+        ("class", json!(class.id)),
+        ("className", json!(class.name)),
+        ("language", json!(guild.language)),
+        ("notifChannel", json!(guild.notif_channel)),
+        ("notifPingRole", json!(guild.notif_ping_role)),
+        ("notifPingEveryone", json!(guild.notif_ping_everyone)),
+        ("adminChannel", json!(guild.admin_channel)),
+        ("notifRoutes", json!(routes.len())),
+        ("roleMappings", json!(mappings.len())),
+    ]))
+}
+
+pub fn unlink(admin: &Admin, guild: &str) -> Result<Output, Report> {
+    let (guild, class) = find(admin, guild)?;
+    if let Some(output) = admin.skip(format_args!(
+        "unlink the guild {} from {} and delete its settings",
+        guild.id, class.name
+    )) {
+        return Ok(output);
+    }
+
+    actions::class::unlink_guild(&admin.db, &guild.id)?;
+    Ok(Output::Message(format!(
+        "Unlinked the guild {} from {}",
+        guild.id, class.name
+    )))
+}
@@ -0,0 +1,61 @@
+use crate::output::Output;
+use crate::Admin;
+use color_eyre::eyre::bail;
+use color_eyre::Report;
+use serde_json::json;
+use server::actions;
+use server::handlers::extractors::ApiScope;
+
+pub fn create(admin: &Admin, name: &str, scopes: &[ApiScope]) -> Result<Output, Report> {
+    let scopes = scopes.iter().map(ToString::to_string).collect::<Vec<_>>();
+    if let Some(output) = admin.skip(format_args!(
+        "create the api key {} with {}",
+        name,
+        scopes.join(" ")
+    )) {
+        return Ok(output);
+    }
+
+    let (api_key, key) = actions::api_key::create_api_key(&admin.db, name, scopes)?;
+    Ok(Output::Record(vec![
+        ("id", json!(api_key.id)),
+        ("name", json!(api_key.name)),
+        ("scopes", json!(api_key.scopes)),
+        // it can't be shown again, only its hash is stored
+        ("key", json!(key)),
+    ]))
+}
+
+pub fn list(admin: &Admin) -> Result<Output, Report> {
+    let keys = actions::api_key::get_api_keys(&admin.db)?;
+
+    Ok(Output::Rows(
+        keys.iter()
+            .map(|key| {
+                vec![
+                    ("name", json!(key.name)),
+                    (
+                        "created",
+                        json!(key.created.format("%Y-%m-%d %H:%M").to_string()),
+                    ),
+                    ("scopes", json!(key.scopes)),
+                ]
+            })
+            .collect(),
+    ))
+}
+
+pub fn revoke(admin: &Admin, name: &str) -> Result<Output, Report> {
+    let exists = actions::api_key::get_api_keys(&admin.db)?
+        .iter()
+        .any(|key| key.name == name);
+    if !exists {
+        bail!("no api key named {}", name);
+    }
+    if let Some(output) = admin.skip(format_args!("revoke the api key {}", name)) {
+        return Ok(output);
+    }
+
+    actions::api_key::delete_api_key(&admin.db, name)?;
+    Ok(Output::Message(format!("Revoked the api key {}", name)))
+}
@@ -0,0 +1,249 @@
+//! `cors-admin`, the commands for the operators of a CORS server
+//!
+//! It changes the database directly with the actions of the server, and reads the same config
+//! and `DATABASE_URL`, so it works without a running server.
+
+use crate::output::Output;
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Report;
+use server::actions::{self, Pool};
+use server::config::Config;
+use server::handlers::extractors::ApiScope;
+use server::models::MemberRole;
+use server::password::PasswordPolicy;
+use std::fmt::Display;
+use uuid::Uuid;
+
+mod classes;
+mod guilds;
+mod keys;
+mod members;
+mod output;
+mod users;
+
+const USAGE: &str = "usage: cors-admin [--json] [--dry-run] <command>
+
+    users list [<search>]
+    users show <user>
+    users verify-email <user>
+    users reset-password <user>     prints a new password, ends all sessions
+    users logout <user>             ends all sessions and invalidates all tokens
+    users disable-2fa <user>
+    users delete <user>             only users that own no classes
+
+    classes list
+    classes show <class>
+    classes transfer-owner <class> <user>
+    classes delete <class>
+
+    members list <class>
+    members set-role <class> <user> <admin|member>
+    members remove <class> <user>
+    members unban <class> <user>
+
+    guilds list
+    guilds show <guild>
+    guilds unlink <guild>           deletes the settings of the guild
+
+    keys create <name> <scope>...   prints the new api key once
+    keys list
+    keys revoke <name>
+
+<user> is an email or an id, <class> an id and <guild> a discord id.
+scopes: bot:notifications, bot:guilds, bot:users-lookup
+
+--json     prints json instead of tables
+--dry-run  shows what a command would change, without changing anything";
+
+#[derive(Debug, PartialEq)]
+enum Command<'a> {
+    ListUsers(Option<&'a str>),
+    ShowUser(&'a str),
+    VerifyEmail(&'a str),
+    ResetPassword(&'a str),
+    Logout(&'a str),
+    DisableTwoFactor(&'a str),
+    DeleteUser(&'a str),
+    ListClasses,
+    ShowClass(Uuid),
+    TransferOwner(Uuid, &'a str),
+    DeleteClass(Uuid),
+    ListMembers(Uuid),
+    SetRole(Uuid, &'a str, i32),
+    RemoveMember(Uuid, &'a str),
+    Unban(Uuid, &'a str),
+    ListGuilds,
+    ShowGuild(&'a str),
+    UnlinkGuild(&'a str),
+    CreateKey(&'a str, Vec<ApiScope>),
+    ListKeys,
+    RevokeKey(&'a str),
+}
+
+impl<'a> Command<'a> {
+    fn parse(args: &[&'a str]) -> Result<Self, Report> {
+        Ok(match *args {
+            ["users", "list"] => Command::ListUsers(None),
+            ["users", "list", search] => Command::ListUsers(Some(search)),
+            ["users", "show", user] => Command::ShowUser(user),
+            ["users", "verify-email", user] => Command::VerifyEmail(user),
+            ["users", "reset-password", user] => Command::ResetPassword(user),
+            ["users", "logout", user] => Command::Logout(user),
+            ["users", "disable-2fa", user] => Command::DisableTwoFactor(user),
+            ["users", "delete", user] => Command::DeleteUser(user),
+            ["classes", "list"] => Command::ListClasses,
+            ["classes", "show", class] => Command::ShowClass(id(class)?),
+            ["classes", "transfer-owner", class, user] => Command::TransferOwner(id(class)?, user),
+            ["classes", "delete", class] => Command::DeleteClass(id(class)?),
+            ["members", "list", class] => Command::ListMembers(id(class)?),
+            ["members", "set-role", class, user, role] => {
+                Command::SetRole(id(class)?, user, member_role(role)?)
+            }
+            ["members", "remove", class, user] => Command::RemoveMember(id(class)?, user),
+            ["members", "unban", class, user] => Command::Unban(id(class)?, user),
+            ["guilds", "list"] => Command::ListGuilds,
+            ["guilds", "show", guild] => Command::ShowGuild(guild),
+            ["guilds", "unlink", guild] => Command::UnlinkGuild(guild),
+            ["keys", "create", name, ref scopes @ ..] if !scopes.is_empty() => Command::CreateKey(
+                name,
+                scopes
+                    .iter()
+                    .map(|scope| api_scope(scope))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ["keys", "list"] => Command::ListKeys,
+            ["keys", "revoke", name] => Command::RevokeKey(name),
+            _ => bail!("{}", USAGE),
+        })
+    }
+}
+
+fn id(arg: &str) -> Result<Uuid, Report> {
+    arg.parse().map_err(|_| {
+        eyre!(
+            "{} is not an id like 8d1bd3ea-9dfb-4e5e-b6a4-8a32e4f1d9cf",
+            arg
+        )
+    })
+}
+
+fn member_role(arg: &str) -> Result<i32, Report> {
+    match arg {
+        "admin" => Ok(MemberRole::ADMIN),
+        "member" => Ok(MemberRole::MEMBER),
+        "owner" => bail!("the owner is changed with `classes transfer-owner`"),
+        _ => bail!("{} is not a role, only admin or member", arg),
+    }
+}
+
+fn api_scope(arg: &str) -> Result<ApiScope, Report> {
+    arg.parse()
+        .map_err(|_| eyre!("{} is not a scope\n\n{}", arg, USAGE))
+}
+
+/// What the commands work with
+pub struct Admin {
+    pub db: Pool,
+    pub dry_run: bool,
+    /// The rules for the new passwords of resets
+    pub password_policy: PasswordPolicy,
+}
+
+impl Admin {
+    /// The output instead of making a change with `--dry-run`
+    pub fn skip(&self, change: impl Display) -> Option<Output> {
+        match self.dry_run {
+            true => Some(Output::Message(format!("Would {}", change))),
+            false => None,
+        }
+    }
+}
+
+fn main() -> Result<(), Report> {
+    dotenv::dotenv().ok();
+    color_eyre::install()?;
+
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let json = take_flag(&mut args, "--json");
+    let dry_run = take_flag(&mut args, "--dry-run");
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    let command = Command::parse(&args)?;
+    let config = Config::load().map_err(Report::msg)?;
+    let admin = Admin {
+        db: actions::create_pool(&config.database)?,
+        dry_run,
+        password_policy: config.password,
+    };
+
+    let output = run(&admin, command)?;
+    match json {
+        true => println!("{}", output.to_json(dry_run)),
+        false => println!("{}", output.to_table()),
+    }
+    Ok(())
+}
+
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let found = args.iter().any(|arg| arg == flag);
+    args.retain(|arg| arg != flag);
+    found
+}
+
+fn run(admin: &Admin, command: Command) -> Result<Output, Report> {
+    match command {
+        Command::ListUsers(search) => users::list(admin, search),
+        Command::ShowUser(user) => users::show(admin, user),
+        Command::VerifyEmail(user) => users::verify_email(admin, user),
+        Command::ResetPassword(user) => users::reset_password(admin, user),
+        Command::Logout(user) => users::logout(admin, user),
+        Command::DisableTwoFactor(user) => users::disable_two_factor(admin, user),
+        Command::DeleteUser(user) => users::delete(admin, user),
+        Command::ListClasses => classes::list(admin),
+        Command::ShowClass(class) => classes::show(admin, class),
+        Command::TransferOwner(class, user) => classes::transfer_owner(admin, class, user),
+        Command::DeleteClass(class) => classes::delete(admin, class),
+        Command::ListMembers(class) => members::list(admin, class),
+        Command::SetRole(class, user, role) => members::set_role(admin, class, user, role),
+        Command::RemoveMember(class, user) => members::remove(admin, class, user),
+        Command::Unban(class, user) => members::unban(admin, class, user),
+        Command::ListGuilds => guilds::list(admin),
+        Command::ShowGuild(guild) => guilds::show(admin, guild),
+        Command::UnlinkGuild(guild) => guilds::unlink(admin, guild),
+        Command::CreateKey(name, scopes) => keys::create(admin, name, &scopes),
+        Command::ListKeys => keys::list(admin),
+        Command::RevokeKey(name) => keys::revoke(admin, name),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn commands() {
+        let class = "8d1bd3ea-9dfb-4e5e-b6a4-8a32e4f1d9cf";
+        assert_eq!(
+            Command::parse(&["users", "list"]).unwrap(),
+            Command::ListUsers(None)
+        );
+        assert_eq!(
+            Command::parse(&["classes", "transfer-owner", class, "a@b.ch"]).unwrap(),
+            Command::TransferOwner(class.parse().unwrap(), "a@b.ch")
+        );
+        assert_eq!(
+            Command::parse(&["members", "set-role", class, "a@b.ch", "admin"]).unwrap(),
+            Command::SetRole(class.parse().unwrap(), "a@b.ch", MemberRole::ADMIN)
+        );
+        assert_eq!(
+            Command::parse(&["keys", "create", "bot", "bot:guilds"]).unwrap(),
+            Command::CreateKey("bot", vec![ApiScope::Guilds])
+        );
+
+        assert!(Command::parse(&[]).is_err());
+        assert!(Command::parse(&["classes", "show", "hugo"]).is_err());
+        assert!(Command::parse(&["members", "set-role", class, "a@b.ch", "owner"]).is_err());
+        assert!(Command::parse(&["keys", "create", "bot"]).is_err());
+        assert!(Command::parse(&["keys", "create", "bot", "bot:everything"]).is_err());
+    }
+}
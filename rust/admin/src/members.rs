@@ -0,0 +1,122 @@
+use crate::output::Output;
+use crate::{classes, users, Admin};
+use color_eyre::eyre::bail;
+use color_eyre::Report;
+use serde_json::json;
+use server::actions;
+use server::error::ServiceErr;
+use server::models::{Class, Member, MemberRole, NewMember, User};
+use uuid::Uuid;
+
+pub fn role_name(role: i32) -> &'static str {
+    match role {
+        MemberRole::OWNER => "owner",
+        MemberRole::ADMIN => "admin",
+        MemberRole::MEMBER => "member",
+        MemberRole::PENDING => "pending",
+        MemberRole::BANNED => "banned",
+        _ => "unknown",
+    }
+}
+
+fn find(admin: &Admin, class: Uuid, user: &str) -> Result<(Class, Member, User), Report> {
+    let class = classes::find(admin, class)?;
+    let user = users::find(admin, user)?;
+    match actions::class::get_member(&admin.db, user.id, class.id) {
+        Ok((member, user)) => Ok((class, member, user)),
+        Err(ServiceErr::NotFound) => bail!("{} is no member of {}", user.email, class.name),
+        Err(err) => Err(err.into()),
+    }
+}
+
+pub fn list(admin: &Admin, class: Uuid) -> Result<Output, Report> {
+    let class = classes::find(admin, class)?;
+    let members = actions::class::get_all_members(&admin.db, class.id)?;
+
+    Ok(Output::Rows(
+        members
+            .iter()
+            .map(|(member, user)| {
+                vec![
+                    ("user", json!(user.id)),
+                    ("email", json!(user.email)),
+                    ("displayName", json!(member.display_name)),
+                    ("role", json!(role_name(member.role))),
+                ]
+            })
+            .collect(),
+    ))
+}
+
+pub fn set_role(admin: &Admin, class: Uuid, user: &str, role: i32) -> Result<Output, Report> {
+    let (class, member, user) = find(admin, class, user)?;
+    if member.role == MemberRole::OWNER {
+        bail!(
+            "{} owns {}, the owner is changed with `classes transfer-owner`",
+            user.email,
+            class.name
+        );
+    }
+    if let Some(output) = admin.skip(format_args!(
+        "make {} {} of {}, instead of {}",
+        user.email,
+        role_name(role),
+        class.name,
+        role_name(member.role)
+    )) {
+        return Ok(output);
+    }
+
+    actions::class::update_member(
+        &admin.db,
+        NewMember {
+            user: member.user,
+            class: member.class,
+            display_name: &member.display_name,
+            role,
+        },
+    )?;
+    Ok(Output::Message(format!(
+        "{} is now {} of {}",
+        user.email,
+        role_name(role),
+        class.name
+    )))
+}
+
+pub fn remove(admin: &Admin, class: Uuid, user: &str) -> Result<Output, Report> {
+    let (class, member, user) = find(admin, class, user)?;
+    if member.role == MemberRole::OWNER {
+        bail!(
+            "{} owns {}, the class needs another owner first",
+            user.email,
+            class.name
+        );
+    }
+    if let Some(output) = admin.skip(format_args!("remove {} from {}", user.email, class.name)) {
+        return Ok(output);
+    }
+
+    actions::class::delete_member(&admin.db, user.id, class.id)?;
+    Ok(Output::Message(format!(
+        "Removed {} from {}",
+        user.email, class.name
+    )))
+}
+
+/// Unbanned users are no members anymore, they can request to join again
+pub fn unban(admin: &Admin, class: Uuid, user: &str) -> Result<Output, Report> {
+    let (class, member, user) = find(admin, class, user)?;
+    if member.role != MemberRole::BANNED {
+        bail!("{} isn't banned from {}", user.email, class.name);
+    }
+    if let Some(output) = admin.skip(format_args!("unban {} from {}", user.email, class.name)) {
+        return Ok(output);
+    }
+
+    actions::class::delete_member(&admin.db, user.id, class.id)?;
+    Ok(Output::Message(format!(
+        "Unbanned {} from {}",
+        user.email, class.name
+    )))
+}
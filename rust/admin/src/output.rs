@@ -0,0 +1,148 @@
+//! What the commands print, as tables or as json with `--json`
+
+use serde_json::{json, Map, Value};
+
+/// The fields of a thing, in the order of the columns
+pub type Row = Vec<(&'static str, Value)>;
+
+pub enum Output {
+    /// A table with a row for every thing, or a json array
+    Rows(Vec<Row>),
+    /// A single thing, as `field: value` lines or a json object
+    Record(Row),
+    /// What a command did, or what it would do with `--dry-run`
+    Message(String),
+}
+
+impl Output {
+    pub fn to_table(&self) -> String {
+        match self {
+            Output::Rows(rows) => table(rows),
+            Output::Record(row) => {
+                let width = row.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+                row.iter()
+                    .map(|(key, value)| format!("{:width$}  {}", key, cell(value), width = width))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+            Output::Message(message) => message.clone(),
+        }
+    }
+
+    pub fn to_json(&self, dry_run: bool) -> String {
+        let value = match self {
+            Output::Rows(rows) => Value::Array(rows.iter().map(object).collect()),
+            Output::Record(row) => object(row),
+            Output::Message(message) => json!({ "message": message, "dryRun": dry_run }),
+        };
+        serde_json::to_string_pretty(&value).expect("json values can be serialized")
+    }
+}
+
+fn object(row: &Row) -> Value {
+    Value::Object(
+        row.iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+fn table(rows: &[Row]) -> String {
+    let first = match rows.first() {
+        Some(first) => first,
+        None => return "nothing found".to_string(),
+    };
+
+    let header = first.iter().map(|(key, _)| key.to_string()).collect();
+    let lines = std::iter::once(header)
+        .chain(
+            rows.iter()
+                .map(|row| row.iter().map(|(_, value)| cell(value)).collect()),
+        )
+        .collect::<Vec<Vec<String>>>();
+
+    let widths = (0..first.len())
+        .map(|column| {
+            lines
+                .iter()
+                .map(|line| line[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        })
+        .collect::<Vec<_>>();
+
+    lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => "-".to_string(),
+        Value::String(string) => string.clone(),
+        Value::Array(values) => values.iter().map(cell).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rows() -> Output {
+        Output::Rows(vec![
+            vec![
+                ("email", json!("hugo@cors-school.com")),
+                ("verified", json!(true)),
+                ("discord", Value::Null),
+            ],
+            vec![
+                ("email", json!("a@b.ch")),
+                ("verified", json!(false)),
+                ("discord", json!("8123456789012345")),
+            ],
+        ])
+    }
+
+    #[test]
+    fn tables() {
+        assert_eq!(
+            rows().to_table(),
+            "email                 verified  discord\n\
+             hugo@cors-school.com  true      -\n\
+             a@b.ch                false     8123456789012345"
+        );
+        assert_eq!(Output::Rows(vec![]).to_table(), "nothing found");
+
+        let record = Output::Record(vec![
+            ("name", json!("bot")),
+            ("scopes", json!(["bot:guilds", "bot:notifications"])),
+        ]);
+        assert_eq!(
+            record.to_table(),
+            "name    bot\nscopes  bot:guilds, bot:notifications"
+        );
+    }
+
+    #[test]
+    fn json() {
+        let value: Value = serde_json::from_str(&rows().to_json(false)).unwrap();
+        assert_eq!(value[0]["email"], "hugo@cors-school.com");
+        assert_eq!(value[1]["discord"], "8123456789012345");
+
+        let value: Value =
+            serde_json::from_str(&Output::Message("Would do it".to_string()).to_json(true))
+                .unwrap();
+        assert_eq!(value, json!({ "message": "Would do it", "dryRun": true }));
+    }
+}
@@ -0,0 +1,187 @@
+use crate::output::{Output, Row};
+use crate::Admin;
+use color_eyre::eyre::bail;
+use color_eyre::Report;
+use serde_json::json;
+use server::actions;
+use server::error::ServiceErr;
+use server::models::User;
+use server::token;
+
+const NEW_PASSWORD_LENGTH: usize = 20;
+/// How often a new password is generated until one of them passes the password policy
+const NEW_PASSWORD_ATTEMPTS: usize = 100;
+
+/// Finds a user by the email or the id
+pub fn find(admin: &Admin, user: &str) -> Result<User, Report> {
+    let found = match user.parse() {
+        Ok(id) => match actions::user::get_user_by_id(&admin.db, id) {
+            Ok(user) => Some(user),
+            Err(ServiceErr::NotFound) => None,
+            Err(err) => return Err(err.into()),
+        },
+        Err(_) => actions::user::get_user_by_email(&admin.db, user)?,
+    };
+    match found {
+        Some(found) => Ok(found),
+        None => bail!("no user {}", user),
+    }
+}
+
+fn row(user: &User) -> Row {
+    vec![
+        ("id", json!(user.id)),
+        ("email", json!(user.email)),
+        ("emailVerified", json!(user.email_verified)),
+        ("discordId", json!(user.discord_id)),
+    ]
+}
+
+pub fn list(admin: &Admin, search: Option<&str>) -> Result<Output, Report> {
+    let users = actions::user::get_users(&admin.db, search)?;
+    Ok(Output::Rows(users.iter().map(row).collect()))
+}
+
+pub fn show(admin: &Admin, user: &str) -> Result<Output, Report> {
+    let user = find(admin, user)?;
+    let two_factor = actions::two_factor::is_two_factor_enabled(&admin.db, user.id)?;
+    let sessions = actions::session::get_sessions(&admin.db, user.id)?;
+    let classes = actions::class::get_classes_by_user(&admin.db, user.id)?;
+
+    let mut row = row(&user);
+    row.extend(vec![
+        ("description", json!(user.description)),
+        ("twoFactor", json!(two_factor)),
+        ("sessions", json!(sessions.len())),
+        (
+            "classes",
+            json!(classes
+                .iter()
+                .map(|class| match class.owner == user.id {
+                    true => format!("{} ({}, owner)", class.name, class.id),
+                    false => format!("{} ({})", class.name, class.id),
+                })
+                .collect::<Vec<_>>()),
+        ),
+    ]);
+    Ok(Output::Record(row))
+}
+
+pub fn verify_email(admin: &Admin, user: &str) -> Result<Output, Report> {
+    let user = find(admin, user)?;
+    if user.email_verified {
+        bail!("the email of {} is already verified", user.email);
+    }
+    if let Some(output) = admin.skip(format_args!("verify the email of {}", user.email)) {
+        return Ok(output);
+    }
+
+    actions::user::set_email_verified(&admin.db, user.id, true)?;
+    Ok(Output::Message(format!(
+        "Verified the email of {}",
+        user.email
+    )))
+}
+
+pub fn reset_password(admin: &Admin, user: &str) -> Result<Output, Report> {
+    let user = find(admin, user)?;
+    if let Some(output) = admin.skip(format_args!(
+        "set a new password for {} and end all of their sessions",
+        user.email
+    )) {
+        return Ok(output);
+    }
+
+    let policy = admin.password_policy;
+    if policy.min_length > policy.max_length {
+        bail!(
+            "the password policy allows no passwords, the min_length {} is greater than the max_length {}",
+            policy.min_length,
+            policy.max_length
+        );
+    }
+    let length = NEW_PASSWORD_LENGTH.clamp(policy.min_length, policy.max_length);
+    let password = (0..NEW_PASSWORD_ATTEMPTS)
+        .map(|_| token::generate("", length).0)
+        .find(|password| policy.check(password).is_ok());
+    let password = match password {
+        Some(password) => password,
+        None => bail!(
+            "could not generate a password of {} characters that passes the password policy",
+            length
+        ),
+    };
+
+    let user = actions::user::change_user_password(
+        &admin.db,
+        User {
+            password: password.clone(),
+            ..user
+        },
+    )?;
+    end_sessions(admin, &user)?;
+    Ok(Output::Record(vec![
+        ("email", json!(user.email)),
+        ("password", json!(password)),
+    ]))
+}
+
+pub fn logout(admin: &Admin, user: &str) -> Result<Output, Report> {
+    let user = find(admin, user)?;
+    if let Some(output) = admin.skip(format_args!("end all sessions of {}", user.email)) {
+        return Ok(output);
+    }
+
+    let sessions = end_sessions(admin, &user)?;
+    Ok(Output::Message(format!(
+        "Ended {} sessions of {}",
+        sessions, user.email
+    )))
+}
+
+/// Ends the sessions and invalidates the tokens, returns the amount of ended sessions
+fn end_sessions(admin: &Admin, user: &User) -> Result<usize, Report> {
+    actions::user::increment_token_version(&admin.db, user.id)?;
+    Ok(actions::session::delete_sessions(&admin.db, user.id)?)
+}
+
+pub fn disable_two_factor(admin: &Admin, user: &str) -> Result<Output, Report> {
+    let user = find(admin, user)?;
+    if !actions::two_factor::is_two_factor_enabled(&admin.db, user.id)? {
+        bail!("{} has no two-factor authentication", user.email);
+    }
+    if let Some(output) = admin.skip(format_args!(
+        "disable the two-factor authentication of {}",
+        user.email
+    )) {
+        return Ok(output);
+    }
+
+    actions::two_factor::disable_two_factor(&admin.db, user.id)?;
+    Ok(Output::Message(format!(
+        "Disabled the two-factor authentication of {}",
+        user.email
+    )))
+}
+
+pub fn delete(admin: &Admin, user: &str) -> Result<Output, Report> {
+    let user = find(admin, user)?;
+    let owned = actions::class::get_owned_classes(&admin.db, user.id)?;
+    if !owned.is_empty() {
+        bail!(
+            "{} owns the classes {}, they need another owner first, with `classes transfer-owner`",
+            user.email,
+            owned
+                .iter()
+                .map(|class| format!("{} ({})", class.name, class.id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    if let Some(output) = admin.skip(format_args!("delete the user {}", user.email)) {
+        return Ok(output);
+    }
+
+    actions::user::delete_user(&admin.db, user.id)?;
+    Ok(Output::Message(format!("Deleted the user {}", user.email)))
+}
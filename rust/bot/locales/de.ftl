@@ -0,0 +1,214 @@
+## Slash commands
+
+command-wielangenoch-description = Zeigt an, wie lange die Lektion noch geht
+command-info-description = Botinformationen
+command-stundenplan-description = Zeigt den Stundenplan der Klasse an
+
+command-event-description = Events verwalten
+command-event-all-description = Alle Events anzeigen
+command-event-next-description = Die nächsten Events anzeigen
+command-event-filter-description = Events nach Typ filtern
+command-event-filter-typ-description = Der Typ nach dem gefiltert werden soll
+command-event-search-description = Events durchsuchen
+command-event-search-query-description = Der Suchterm
+
+command-event-create-description = Ein Event erstellen
+command-event-edit-description = Ein Event bearbeiten
+command-event-delete-description = Ein Event löschen
+command-event-typ-description = Der Typ des Events
+command-event-event-description = Das Event
+command-event-name-description = Der Name des Events
+command-event-start-description = Der Start, z.B. 24.12.2021 18:00
+command-event-end-description = Das Ende, z.B. 24.12.2021 20:00
+command-event-description-description = Die Beschreibung des Events
+command-event-reminder-description = Wann benachrichtigt wird, z.B. 23.12.2021 18:00
+
+command-link-description = Discord-Account mit dem CORS-Account verlinken
+command-link-code-description = Ein Code von der Website (leer, um einen Code für die Website zu erhalten)
+command-unlink-description = Die Verlinkung mit dem CORS-Account aufheben
+
+command-setting-description = Servereinstellungen
+command-setting-notification_channel-description = Channel für Benachrichtigungen (leer zum Ausschalten)
+command-setting-channel-description = Der Channel für Benachrichtigungen
+command-setting-notification_everyone_ping-description = @everyone in Benachrichtigungen pingen
+command-setting-everyone-description = @everyone pingen
+command-setting-notification_role_ping-description = Eine Rolle in Benachrichtigungen pingen (leer zum Ausschalten)
+command-setting-role-description = Die Rolle zum Pingen
+command-setting-notification_type_channel-description = Channel für Benachrichtigungen eines Typs (leer für Standard)
+command-setting-type-channel-description = Der Channel für Benachrichtigungen dieses Typs
+command-setting-notification_type_everyone_ping-description = @everyone in Benachrichtigungen eines Typs pingen (leer für Standard)
+command-setting-notification_type_role_ping-description = Eine Rolle in Benachrichtigungen eines Typs pingen (leer für Standard)
+command-setting-typ-description = Der Typ der Events
+command-setting-language-description = Sprache des Bots (leer für die Sprache von Discord)
+command-setting-language-language-description = Die Sprache
+command-setting-role_mapping-description = Einer Rolle der Klasse eine Discord-Rolle zuordnen (leer zum Entfernen)
+command-setting-cors_role-description = Die Rolle in der Klasse
+command-setting-discord_role-description = Die Discord-Rolle für diese Rolle
+command-setting-role_sync_dry_run-description = Zeigt, welche Rollen der Rollen-Sync ändern würde
+command-setting-admin_channel-description = Kanal für Beitrittsanfragen (leer zum Ausschalten)
+command-setting-admin-channel-description = Der Kanal für Beitrittsanfragen
+
+## Event types
+
+event-type-homework = Hausaufgabe
+event-type-exam = Prüfung
+event-type-holidays = Ferien
+event-type-other = Andere
+
+event-types-homework = Hausaufgaben
+event-types-exam = Prüfungen
+event-types-holidays = Ferien
+event-types-other = andere Events
+
+## Weekdays
+
+weekday-monday = Montag
+weekday-tuesday = Dienstag
+weekday-wednesday = Mittwoch
+weekday-thursday = Donnerstag
+weekday-friday = Freitag
+weekday-saturday = Samstag
+weekday-sunday = Sonntag
+
+## Info
+
+info-description = CORS ist ein Terminverwaltungssystem für Schulklassen, durch das nichts mehr vergessen werden kann
+
+## Timetable
+
+timetable-title = Stundenplan
+timetable-not-found = Stundenplan nicht gefunden
+timetable-not-found-admins = Für Admins
+timetable-not-found-hint = Aktiviere den Stundenplan in den Admineinstellungen
+
+## Wie lange noch
+
+lesson-no-school = Keine Schule mehr heute!
+lesson-none = Keine Lektion
+lesson-next = Nächste Lektion: { $subject }
+lesson-next-start = Start in: { $time }h
+lesson-next-in = in { $time }h
+lesson-current = Aktuelle Lektion: { $subject }
+lesson-remaining = Noch { $time }h
+
+## Events
+
+events-title = Events
+events-notification = Benachrichtigung um { $time }
+events-none = Keine Events gefunden
+events-none-hint = Admins können Events in der Web-Version eintragen
+events-footer = CORS - Seite { $page }/{ $pages }
+events-previous = Zurück
+events-next = Weiter
+events-all-types = Alle Typen
+
+event-created = Event { $name } erstellt
+event-edited = Event { $name } bearbeitet
+event-deleted = Event { $name } gelöscht
+event-no-rights-hint = Das Verwalten von Events braucht mindestens Administratorenrechte
+event-not-found = Event nicht gefunden
+event-not-found-hint = Wähle ein Event aus den Vorschlägen aus
+event-invalid-date = Ungültiges Datum: { $input }
+event-invalid-date-hint = Gib Daten als 24.12.2021 18:00 oder 24.12.2021 an
+event-end-before-start = Ungültiges Ende
+event-end-before-start-hint = Das Event kann nicht vor seinem Start enden
+
+## Notifications
+
+notification-title = Benachrichtigung für { $name }
+notification-start = Start: { $time }
+
+## Join requests
+
+join-request-title = Beitrittsanfrage für { $class }
+join-request-description = { $name } möchte der Klasse beitreten
+join-request-accept = Annehmen
+join-request-deny = Ablehnen
+join-request-accepted = Angenommen von { $admin }
+join-request-denied = Abgelehnt von { $admin }
+join-request-handled = Diese Anfrage wurde bereits beantwortet
+join-request-no-rights = Fehlende Berechtigungen
+join-request-no-rights-hint = Nur Administratoren der Klasse mit verknüpftem Konto können Beitrittsanfragen beantworten
+
+## Link
+
+link-code = Verlinkungscode: { $code }
+link-code-hint = Gib den Code auf der Website in den Accounteinstellungen ein. Er ist bis <t:{ $expires }:t> gültig.
+link-linked = Account verlinkt
+link-linked-hint = Dein Discord-Account ist jetzt mit deinem CORS-Account verlinkt
+link-invalid-code = Ungültiger Code
+link-invalid-code-hint = Der Code ist abgelaufen oder wurde schon verwendet. Erstelle auf der Website einen neuen Code.
+link-unlinked = Verlinkung aufgehoben
+link-unlinked-hint = Dein Discord-Account ist nicht mehr mit deinem CORS-Account verlinkt
+
+## Settings
+
+setting-class-not-registered = Klasse nicht registriert
+setting-class-not-registered-hint = Verbinde die Klasse mit dem Discord Server im Admin Panel
+setting-class-not-registered-admins = Diese Aktion kann nur von Administratoren durchgeführt werden.
+setting-not-linked = Account nicht verlinkt
+setting-not-linked-title = Discord-Account nicht mit CORS-Account verlinkt
+setting-not-linked-hint = Verlinke deinen Discord-Account mit deinem CORS-Account mit /link
+setting-no-rights = Keine Rechte
+setting-no-rights-title = Braucht: Administrator
+setting-no-rights-hint = Das Ändern von Servereinstellungen braucht mindestens Administratorenrechte
+setting-two-factor-required = Zwei-Faktor-Authentifizierung nötig
+setting-two-factor-required-hint = Diese Klasse verlangt Zwei-Faktor-Authentifizierung für Administratoren, aktiviere sie zuerst für deinen CORS Account
+
+setting-channel-set = Benachrichtigungschannel gesetzt
+setting-channel-set-hint = Benachrichtigungen werden jetzt in <#{ $channel }> geschickt
+setting-channel-off = Benachrichtigungen ausgeschaltet
+setting-channel-off-hint = Benachrichtigungen können jederzeit wieder eingeschaltet werden.
+
+setting-everyone-on = @everyone Ping eingeschaltet
+setting-everyone-on-hint = Bei Benachrichtigungen wird jetzt @everyone gepingt
+setting-everyone-off = @everyone Ping ausgeschaltet
+setting-everyone-off-hint = Bei Benachrichtigungen wird jetzt nicht mehr @everyone gepingt
+
+setting-role-set = Benachrichtigungsrolle gesetzt
+setting-role-set-hint = Bei Benachrichtigungen wird jetzt <@&{ $role }> gepingt
+setting-role-off = Benachrichtigungsrolle ausgeschaltet
+setting-role-off-hint = Die Benachrichtigungsrolle kann jederzeit wieder eingeschaltet werden.
+
+setting-type-channel-set = Benachrichtigungschannel für { $type } gesetzt
+setting-type-channel-set-hint = Benachrichtigungen für { $type } werden jetzt in <#{ $channel }> geschickt
+setting-type-channel-reset = Benachrichtigungschannel für { $type } zurückgesetzt
+setting-type-channel-reset-hint = Benachrichtigungen für { $type } werden jetzt im Standardchannel geschickt
+
+setting-type-everyone = @everyone Ping für { $type } gesetzt
+setting-type-everyone-on-hint = Bei Benachrichtigungen für { $type } wird jetzt @everyone gepingt
+setting-type-everyone-off-hint = Bei Benachrichtigungen für { $type } wird jetzt nicht mehr @everyone gepingt
+setting-type-everyone-reset-hint = Bei Benachrichtigungen für { $type } gilt jetzt die Standardeinstellung
+
+setting-type-role-set = Benachrichtigungsrolle für { $type } gesetzt
+setting-type-role-set-hint = Bei Benachrichtigungen für { $type } wird jetzt <@&{ $role }> gepingt
+setting-type-role-reset = Benachrichtigungsrolle für { $type } zurückgesetzt
+setting-type-role-reset-hint = Bei Benachrichtigungen für { $type } wird jetzt die Standardrolle gepingt
+
+setting-language-set = Sprache gesetzt
+setting-language-set-hint = Der Bot antwortet auf diesem Server jetzt auf Deutsch
+setting-language-reset = Sprache zurückgesetzt
+setting-language-reset-hint = Der Bot antwortet jetzt in der Sprache von Discord
+
+setting-role-mapping-set = Rolle zugeordnet
+setting-role-mapping-set-hint = { $role } der Klasse bekommen jetzt <@&{ $discord_role }>, alle anderen verlieren die Rolle
+setting-role-mapping-removed = Rollenzuordnung entfernt
+setting-role-mapping-removed-hint = { $role } der Klasse bekommen keine Discord-Rolle mehr
+
+setting-role-sync = Rollen-Sync Vorschau
+setting-role-sync-no-mappings = Keine Rollen zugeordnet
+setting-role-sync-no-mappings-hint = Ordne den Rollen der Klasse mit /setting role_mapping Discord-Rollen zu
+setting-role-sync-up-to-date = Alle Rollen sind aktuell
+setting-role-sync-up-to-date-hint = Der Rollen-Sync würde nichts ändern
+setting-role-sync-changes = { $count } Änderungen
+
+setting-admin-channel-set = Admin-Kanal gesetzt
+setting-admin-channel-set-hint = Beitrittsanfragen werden jetzt in <#{ $channel }> gepostet
+setting-admin-channel-off = Beitrittsanfragen ausgeschaltet
+setting-admin-channel-off-hint = Beitrittsanfragen werden nicht mehr auf Discord gepostet
+
+## Member roles
+
+member-roles-owner = Besitzer
+member-roles-admin = Administratoren
+member-roles-member = Mitglieder
@@ -0,0 +1,219 @@
+## Slash commands
+
+command-wielangenoch-name = howlong
+command-wielangenoch-description = Shows how long the current lesson lasts
+command-info-description = Bot information
+command-stundenplan-name = timetable
+command-stundenplan-description = Shows the timetable of the class
+
+command-event-description = Manage events
+command-event-all-description = Show all events
+command-event-next-description = Show the next events
+command-event-filter-description = Filter events by type
+command-event-filter-typ-name = type
+command-event-filter-typ-description = The type to filter by
+command-event-search-description = Search events
+command-event-search-query-description = The search term
+
+command-event-create-description = Create an event
+command-event-edit-description = Edit an event
+command-event-delete-description = Delete an event
+command-event-typ-name = type
+command-event-typ-description = The type of the event
+command-event-event-description = The event
+command-event-name-description = The name of the event
+command-event-start-description = The start, e.g. 24.12.2021 18:00
+command-event-end-description = The end, e.g. 24.12.2021 20:00
+command-event-description-description = The description of the event
+command-event-reminder-description = When to notify, e.g. 23.12.2021 18:00
+
+command-link-description = Link your Discord account with your CORS account
+command-link-code-description = A code from the website (empty to get a code for the website)
+command-unlink-description = Unlink your CORS account
+
+command-setting-description = Server settings
+command-setting-notification_channel-description = Channel for notifications (empty to turn off)
+command-setting-channel-description = The channel for notifications
+command-setting-notification_everyone_ping-description = Ping @everyone in notifications
+command-setting-everyone-description = Ping @everyone
+command-setting-notification_role_ping-description = Ping a role in notifications (empty to turn off)
+command-setting-role-description = The role to ping
+command-setting-notification_type_channel-description = Channel for notifications of one type (empty for default)
+command-setting-type-channel-description = The channel for notifications of this type
+command-setting-notification_type_everyone_ping-description = Ping @everyone in notifications of one type (empty for default)
+command-setting-notification_type_role_ping-description = Ping a role in notifications of one type (empty for default)
+command-setting-typ-name = type
+command-setting-typ-description = The type of the events
+command-setting-language-description = Language of the bot (empty for the language of Discord)
+command-setting-language-language-description = The language
+command-setting-role_mapping-description = Map a role of the class to a Discord role (empty to remove)
+command-setting-cors_role-description = The role in the class
+command-setting-discord_role-description = The Discord role for this role
+command-setting-role_sync_dry_run-description = Shows which roles the role sync would change
+command-setting-admin_channel-description = Channel for join requests (empty to turn off)
+command-setting-admin-channel-description = The channel for join requests
+
+## Event types
+
+event-type-homework = Homework
+event-type-exam = Exam
+event-type-holidays = Holidays
+event-type-other = Other
+
+event-types-homework = homework
+event-types-exam = exams
+event-types-holidays = holidays
+event-types-other = other events
+
+## Weekdays
+
+weekday-monday = Monday
+weekday-tuesday = Tuesday
+weekday-wednesday = Wednesday
+weekday-thursday = Thursday
+weekday-friday = Friday
+weekday-saturday = Saturday
+weekday-sunday = Sunday
+
+## Info
+
+info-description = CORS is an appointment management system for school classes that makes sure nothing gets forgotten
+
+## Timetable
+
+timetable-title = Timetable
+timetable-not-found = Timetable not found
+timetable-not-found-admins = For admins
+timetable-not-found-hint = Enable the timetable in the admin settings
+
+## Wie lange noch
+
+lesson-no-school = No more school today!
+lesson-none = No lesson
+lesson-next = Next lesson: { $subject }
+lesson-next-start = Starts in: { $time }h
+lesson-next-in = in { $time }h
+lesson-current = Current lesson: { $subject }
+lesson-remaining = { $time }h left
+
+## Events
+
+events-title = Events
+events-notification = Notification at { $time }
+events-none = No events found
+events-none-hint = Admins can add events in the web version
+events-footer = CORS - Page { $page }/{ $pages }
+events-previous = Previous
+events-next = Next
+events-all-types = All types
+
+event-created = Created event { $name }
+event-edited = Edited event { $name }
+event-deleted = Deleted event { $name }
+event-no-rights-hint = Managing events requires at least administrator permissions
+event-not-found = Event not found
+event-not-found-hint = Choose an event from the suggestions
+event-invalid-date = Invalid date: { $input }
+event-invalid-date-hint = Enter dates as 24.12.2021 18:00 or 24.12.2021
+event-end-before-start = Invalid end
+event-end-before-start-hint = The event can't end before it starts
+
+## Notifications
+
+notification-title = Notification for { $name }
+notification-start = Start: { $time }
+
+## Join requests
+
+join-request-title = Join request for { $class }
+join-request-description = { $name } wants to join the class
+join-request-accept = Accept
+join-request-deny = Deny
+join-request-accepted = Accepted by { $admin }
+join-request-denied = Denied by { $admin }
+join-request-handled = This request was already answered
+join-request-no-rights = Missing permissions
+join-request-no-rights-hint = Only administrators of the class with a linked account can answer join requests
+
+## Link
+
+link-code = Link code: { $code }
+link-code-hint = Enter the code in the account settings on the website. It is valid until <t:{ $expires }:t>.
+link-linked = Account linked
+link-linked-hint = Your Discord account is now linked with your CORS account
+link-invalid-code = Invalid code
+link-invalid-code-hint = The code expired or was already used. Create a new code on the website.
+link-unlinked = Account unlinked
+link-unlinked-hint = Your Discord account is no longer linked with your CORS account
+
+## Settings
+
+setting-class-not-registered = Class not registered
+setting-class-not-registered-hint = Connect the class with the Discord server in the admin panel
+setting-class-not-registered-admins = Only administrators can do this.
+setting-not-linked = Account not linked
+setting-not-linked-title = Discord account not linked with a CORS account
+setting-not-linked-hint = Link your Discord account with your CORS account using /link
+setting-no-rights = Missing permissions
+setting-no-rights-title = Requires: Administrator
+setting-no-rights-hint = Changing server settings requires at least administrator permissions
+setting-two-factor-required = Two-factor authentication required
+setting-two-factor-required-hint = This class requires two-factor authentication for administrators, enable it for your CORS account first
+
+setting-channel-set = Notification channel set
+setting-channel-set-hint = Notifications are now sent in <#{ $channel }>
+setting-channel-off = Notifications turned off
+setting-channel-off-hint = Notifications can be turned on again at any time.
+
+setting-everyone-on = @everyone ping turned on
+setting-everyone-on-hint = Notifications now ping @everyone
+setting-everyone-off = @everyone ping turned off
+setting-everyone-off-hint = Notifications no longer ping @everyone
+
+setting-role-set = Notification role set
+setting-role-set-hint = Notifications now ping <@&{ $role }>
+setting-role-off = Notification role turned off
+setting-role-off-hint = The notification role can be turned on again at any time.
+
+setting-type-channel-set = Notification channel for { $type } set
+setting-type-channel-set-hint = Notifications for { $type } are now sent in <#{ $channel }>
+setting-type-channel-reset = Notification channel for { $type } reset
+setting-type-channel-reset-hint = Notifications for { $type } are now sent in the default channel
+
+setting-type-everyone = @everyone ping for { $type } set
+setting-type-everyone-on-hint = Notifications for { $type } now ping @everyone
+setting-type-everyone-off-hint = Notifications for { $type } no longer ping @everyone
+setting-type-everyone-reset-hint = Notifications for { $type } now use the default setting
+
+setting-type-role-set = Notification role for { $type } set
+setting-type-role-set-hint = Notifications for { $type } now ping <@&{ $role }>
+setting-type-role-reset = Notification role for { $type } reset
+setting-type-role-reset-hint = Notifications for { $type } now ping the default role
+
+setting-language-set = Language set
+setting-language-set-hint = The bot now answers in English on this server
+setting-language-reset = Language reset
+setting-language-reset-hint = The bot now answers in the language of Discord
+
+setting-role-mapping-set = Role mapped
+setting-role-mapping-set-hint = { $role } of the class now get <@&{ $discord_role }>, everyone else loses the role
+setting-role-mapping-removed = Role mapping removed
+setting-role-mapping-removed-hint = { $role } of the class no longer get a Discord role
+
+setting-role-sync = Role sync preview
+setting-role-sync-no-mappings = No roles mapped
+setting-role-sync-no-mappings-hint = Map the roles of the class to Discord roles with /setting role_mapping
+setting-role-sync-up-to-date = All roles are up to date
+setting-role-sync-up-to-date-hint = The role sync wouldn't change anything
+setting-role-sync-changes = { $count } changes
+
+setting-admin-channel-set = Admin channel set
+setting-admin-channel-set-hint = Join requests are now posted in <#{ $channel }>
+setting-admin-channel-off = Join requests turned off
+setting-admin-channel-off-hint = Join requests are no longer posted on Discord
+
+## Member roles
+
+member-roles-owner = Owners
+member-roles-admin = Administrators
+member-roles-member = Members
@@ -0,0 +1,242 @@
+## Slash commands
+
+command-wielangenoch-name = combiendetemps
+command-wielangenoch-description = Affiche combien de temps dure encore la leçon
+command-info-name = infos
+command-info-description = Informations sur le bot
+command-stundenplan-name = horaire
+command-stundenplan-description = Affiche l'horaire de la classe
+
+command-event-name = evenement
+command-event-description = Gérer les événements
+command-event-all-name = tous
+command-event-all-description = Afficher tous les événements
+command-event-next-name = prochains
+command-event-next-description = Afficher les prochains événements
+command-event-filter-name = filtrer
+command-event-filter-description = Filtrer les événements par type
+command-event-filter-typ-name = type
+command-event-filter-typ-description = Le type selon lequel filtrer
+command-event-search-name = chercher
+command-event-search-description = Chercher des événements
+command-event-search-query-name = recherche
+command-event-search-query-description = Le terme de recherche
+
+command-setting-name = parametre
+command-event-create-name = creer
+command-event-create-description = Créer un événement
+command-event-edit-name = modifier
+command-event-edit-description = Modifier un événement
+command-event-delete-name = supprimer
+command-event-delete-description = Supprimer un événement
+command-event-typ-name = type
+command-event-typ-description = Le type de l'événement
+command-event-event-name = evenement
+command-event-event-description = L'événement
+command-event-name-name = nom
+command-event-name-description = Le nom de l'événement
+command-event-start-name = debut
+command-event-start-description = Le début, p. ex. 24.12.2021 18:00
+command-event-end-name = fin
+command-event-end-description = La fin, p. ex. 24.12.2021 20:00
+command-event-description-description = La description de l'événement
+command-event-reminder-name = rappel
+command-event-reminder-description = Quand notifier, p. ex. 23.12.2021 18:00
+
+command-link-name = lier
+command-link-description = Lier ton compte Discord à ton compte CORS
+command-link-code-description = Un code du site web (vide pour obtenir un code pour le site web)
+command-unlink-name = delier
+command-unlink-description = Délier ton compte CORS
+
+command-setting-description = Paramètres du serveur
+command-setting-notification_channel-description = Salon pour les notifications (vide pour désactiver)
+command-setting-channel-name = salon
+command-setting-channel-description = Le salon pour les notifications
+command-setting-notification_everyone_ping-description = Mentionner @everyone dans les notifications
+command-setting-everyone-description = Mentionner @everyone
+command-setting-notification_role_ping-description = Mentionner un rôle dans les notifications (vide pour désactiver)
+command-setting-role-name = role
+command-setting-role-description = Le rôle à mentionner
+command-setting-notification_type_channel-description = Salon pour les notifications d'un type (vide pour le défaut)
+command-setting-type-channel-name = salon
+command-setting-type-channel-description = Le salon pour les notifications de ce type
+command-setting-notification_type_everyone_ping-description = Mentionner @everyone dans les notifications d'un type (vide pour le défaut)
+command-setting-notification_type_role_ping-description = Mentionner un rôle dans les notifications d'un type (vide pour le défaut)
+command-setting-typ-name = type
+command-setting-typ-description = Le type des événements
+command-setting-language-name = langue
+command-setting-language-description = Langue du bot (vide pour la langue de Discord)
+command-setting-language-language-name = langue
+command-setting-language-language-description = La langue
+command-setting-role_mapping-description = Associer un rôle de la classe à un rôle Discord (vide pour supprimer)
+command-setting-cors_role-description = Le rôle dans la classe
+command-setting-discord_role-description = Le rôle Discord pour ce rôle
+command-setting-role_sync_dry_run-description = Montre quels rôles la synchronisation changerait
+command-setting-admin_channel-description = Salon pour les demandes d'adhésion (vide pour désactiver)
+command-setting-admin-channel-description = Le salon pour les demandes d'adhésion
+
+## Event types
+
+event-type-homework = Devoir
+event-type-exam = Examen
+event-type-holidays = Vacances
+event-type-other = Autre
+
+event-types-homework = les devoirs
+event-types-exam = les examens
+event-types-holidays = les vacances
+event-types-other = les autres événements
+
+## Weekdays
+
+weekday-monday = Lundi
+weekday-tuesday = Mardi
+weekday-wednesday = Mercredi
+weekday-thursday = Jeudi
+weekday-friday = Vendredi
+weekday-saturday = Samedi
+weekday-sunday = Dimanche
+
+## Info
+
+info-description = CORS est un système de gestion des rendez-vous pour les classes, grâce auquel plus rien n'est oublié
+
+## Timetable
+
+timetable-title = Horaire
+timetable-not-found = Horaire introuvable
+timetable-not-found-admins = Pour les admins
+timetable-not-found-hint = Active l'horaire dans les paramètres d'administration
+
+## Wie lange noch
+
+lesson-no-school = Plus d'école aujourd'hui !
+lesson-none = Pas de leçon
+lesson-next = Prochaine leçon : { $subject }
+lesson-next-start = Commence dans : { $time }h
+lesson-next-in = dans { $time }h
+lesson-current = Leçon actuelle : { $subject }
+lesson-remaining = Encore { $time }h
+
+## Events
+
+events-title = Événements
+events-notification = Notification à { $time }
+events-none = Aucun événement trouvé
+events-none-hint = Les admins peuvent ajouter des événements dans la version web
+events-footer = CORS - Page { $page }/{ $pages }
+events-previous = Précédent
+events-next = Suivant
+events-all-types = Tous les types
+
+event-created = Événement { $name } créé
+event-edited = Événement { $name } modifié
+event-deleted = Événement { $name } supprimé
+event-no-rights-hint = Gérer les événements requiert au moins les droits d'administrateur
+event-not-found = Événement introuvable
+event-not-found-hint = Choisis un événement parmi les suggestions
+event-invalid-date = Date invalide : { $input }
+event-invalid-date-hint = Entre les dates comme 24.12.2021 18:00 ou 24.12.2021
+event-end-before-start = Fin invalide
+event-end-before-start-hint = L'événement ne peut pas finir avant son début
+
+## Notifications
+
+notification-title = Notification pour { $name }
+notification-start = Début : { $time }
+
+## Join requests
+
+join-request-title = Demande d'adhésion pour { $class }
+join-request-description = { $name } veut rejoindre la classe
+join-request-accept = Accepter
+join-request-deny = Refuser
+join-request-accepted = Acceptée par { $admin }
+join-request-denied = Refusée par { $admin }
+join-request-handled = Cette demande a déjà reçu une réponse
+join-request-no-rights = Permissions manquantes
+join-request-no-rights-hint = Seuls les administrateurs de la classe avec un compte lié peuvent répondre aux demandes d'adhésion
+
+## Link
+
+link-code = Code de liaison : { $code }
+link-code-hint = Entre le code dans les paramètres du compte sur le site web. Il est valable jusqu'à <t:{ $expires }:t>.
+link-linked = Compte lié
+link-linked-hint = Ton compte Discord est maintenant lié à ton compte CORS
+link-invalid-code = Code invalide
+link-invalid-code-hint = Le code a expiré ou a déjà été utilisé. Crée un nouveau code sur le site web.
+link-unlinked = Compte délié
+link-unlinked-hint = Ton compte Discord n'est plus lié à ton compte CORS
+
+## Settings
+
+setting-class-not-registered = Classe non enregistrée
+setting-class-not-registered-hint = Connecte la classe avec le serveur Discord dans le panneau d'administration
+setting-class-not-registered-admins = Seuls les administrateurs peuvent faire cette action.
+setting-not-linked = Compte non lié
+setting-not-linked-title = Compte Discord non lié à un compte CORS
+setting-not-linked-hint = Lie ton compte Discord à ton compte CORS avec /link
+setting-no-rights = Pas de droits
+setting-no-rights-title = Requiert : Administrateur
+setting-no-rights-hint = Modifier les paramètres du serveur requiert au moins les droits d'administrateur
+setting-two-factor-required = Authentification à deux facteurs requise
+setting-two-factor-required-hint = Cette classe requiert l'authentification à deux facteurs pour les administrateurs, active-la d'abord pour ton compte CORS
+
+setting-channel-set = Salon de notification défini
+setting-channel-set-hint = Les notifications sont maintenant envoyées dans <#{ $channel }>
+setting-channel-off = Notifications désactivées
+setting-channel-off-hint = Les notifications peuvent être réactivées à tout moment.
+
+setting-everyone-on = Mention @everyone activée
+setting-everyone-on-hint = Les notifications mentionnent maintenant @everyone
+setting-everyone-off = Mention @everyone désactivée
+setting-everyone-off-hint = Les notifications ne mentionnent plus @everyone
+
+setting-role-set = Rôle de notification défini
+setting-role-set-hint = Les notifications mentionnent maintenant <@&{ $role }>
+setting-role-off = Rôle de notification désactivé
+setting-role-off-hint = Le rôle de notification peut être réactivé à tout moment.
+
+setting-type-channel-set = Salon de notification pour { $type } défini
+setting-type-channel-set-hint = Les notifications pour { $type } sont maintenant envoyées dans <#{ $channel }>
+setting-type-channel-reset = Salon de notification pour { $type } réinitialisé
+setting-type-channel-reset-hint = Les notifications pour { $type } sont maintenant envoyées dans le salon par défaut
+
+setting-type-everyone = Mention @everyone pour { $type } définie
+setting-type-everyone-on-hint = Les notifications pour { $type } mentionnent maintenant @everyone
+setting-type-everyone-off-hint = Les notifications pour { $type } ne mentionnent plus @everyone
+setting-type-everyone-reset-hint = Les notifications pour { $type } utilisent maintenant le paramètre par défaut
+
+setting-type-role-set = Rôle de notification pour { $type } défini
+setting-type-role-set-hint = Les notifications pour { $type } mentionnent maintenant <@&{ $role }>
+setting-type-role-reset = Rôle de notification pour { $type } réinitialisé
+setting-type-role-reset-hint = Les notifications pour { $type } mentionnent maintenant le rôle par défaut
+
+setting-language-set = Langue définie
+setting-language-set-hint = Le bot répond maintenant en français sur ce serveur
+setting-language-reset = Langue réinitialisée
+setting-language-reset-hint = Le bot répond maintenant dans la langue de Discord
+
+setting-role-mapping-set = Rôle associé
+setting-role-mapping-set-hint = Les { $role } de la classe reçoivent maintenant <@&{ $discord_role }>, tous les autres perdent le rôle
+setting-role-mapping-removed = Association supprimée
+setting-role-mapping-removed-hint = Les { $role } de la classe ne reçoivent plus de rôle Discord
+
+setting-role-sync = Aperçu de la synchronisation des rôles
+setting-role-sync-no-mappings = Aucun rôle associé
+setting-role-sync-no-mappings-hint = Associe les rôles de la classe à des rôles Discord avec /setting role_mapping
+setting-role-sync-up-to-date = Tous les rôles sont à jour
+setting-role-sync-up-to-date-hint = La synchronisation ne changerait rien
+setting-role-sync-changes = { $count } changements
+
+setting-admin-channel-set = Salon d'administration défini
+setting-admin-channel-set-hint = Les demandes d'adhésion sont maintenant publiées dans <#{ $channel }>
+setting-admin-channel-off = Demandes d'adhésion désactivées
+setting-admin-channel-off-hint = Les demandes d'adhésion ne sont plus publiées sur Discord
+
+## Member roles
+
+member-roles-owner = propriétaires
+member-roles-admin = administrateurs
+member-roles-member = membres
@@ -1,25 +1,49 @@
+use serenity::model::interactions::application_command::{
+    ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+};
+use serenity::model::interactions::autocomplete::AutocompleteInteraction;
+use serenity::model::interactions::message_component::{ButtonStyle, MessageComponentInteraction};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
-use crate::commands::format_datetime;
+use super::language;
+use super::settings::{event_type_option, find_option, validate_admin_perms};
 use crate::error::{BotError, BotResult};
-use crate::functions::{format_date, limit_length};
+use crate::functions::{
+    format_date, format_datetime, limit_length, parse_local_datetime, parse_timezone, EventPage,
+};
+use crate::i18n::Language;
 use crate::requests::CorsClient;
 use chrono::Utc;
-use serenity::builder::CreateEmbed;
+use chrono_tz::Tz;
+use dto::{ErrorCode, EventType};
+use serenity::builder::{CreateComponents, CreateEmbed};
 use tracing::{debug, warn};
+use uuid::Uuid;
+
+type ManageEmbed = (String, String);
+
+/// The value of the select menu option that removes the type filter
+const ALL_TYPES: &str = "all";
+
+const EVENT_TYPES: [EventType; 4] = [
+    EventType::Homework,
+    EventType::Exam,
+    EventType::Holidays,
+    EventType::Other,
+];
 
 pub async fn handle_event_command(
     ctx: &Context,
-    interaction: &Interaction,
+    interaction: &ApplicationCommandInteraction,
     options: &[ApplicationCommandInteractionDataOption],
 ) -> BotResult<()> {
     match options.first() {
         Some(subcommand) => match subcommand.name.as_str() {
-            "all" => show_all_events(ctx, interaction).await?,
-            "next" => show_next_events(ctx, interaction).await?,
-            "filter" => show_filter_events(ctx, interaction, subcommand).await?,
-            "search" => show_search_events(ctx, interaction, subcommand).await?,
+            "all" | "next" | "filter" | "search" => {
+                show_events(ctx, interaction, subcommand).await?
+            }
+            "create" | "edit" | "delete" => manage_event(ctx, interaction, subcommand).await?,
             _ => warn!(?subcommand, "Invalid subcommand"),
         },
         None => warn!("No subcommand"),
@@ -28,114 +52,436 @@ pub async fn handle_event_command(
     Ok(())
 }
 
-async fn show_all_events(ctx: &Context, interaction: &Interaction) -> BotResult<()> {
-    let events = get_events(ctx, interaction.guild_id, None, None).await?;
-    debug!(events = ?events);
+async fn show_events(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    option: &ApplicationCommandInteractionDataOption,
+) -> BotResult<()> {
+    let state = match option.name.as_str() {
+        "all" => EventPage::new(false, None, None),
+        "next" => EventPage::new(true, None, None),
+        "filter" => EventPage::new(false, Some(event_type_option(option)?), None),
+        "search" => {
+            let query = string_option(option, "query")
+                .ok_or(BotError::Other("event show search has no query"))?;
+            EventPage::new(false, None, Some(query.to_string()))
+        }
+        _ => unreachable!(),
+    };
+
+    let lang = language(ctx, interaction.guild_id, &interaction.locale).await;
+    let (state, tz, events, pages) = get_event_page(ctx, interaction.guild_id, state).await?;
+    debug!(?state, len = %events.len(), "show events");
 
-    send_events(ctx, interaction, events.as_slice()).await
+    Ok(interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message
+                        .create_embed(|embed| event_embed(embed, lang, &events, tz, &state, pages))
+                        .components(|components| event_components(components, lang, &state, pages))
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                })
+        })
+        .await?)
 }
 
-async fn show_next_events(ctx: &Context, interaction: &Interaction) -> BotResult<()> {
-    let current_time = Utc::now().timestamp_millis();
-    let events = get_events(ctx, interaction.guild_id, None, Some(current_time)).await?;
+/// Switch the page or the type filter of an event list
+pub async fn handle_event_component(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+) -> BotResult<()> {
+    let (prefix, mut state) = EventPage::from_custom_id(&interaction.data.custom_id)
+        .ok_or(BotError::Other("Invalid event page custom id"))?;
+
+    if prefix == EventPage::SELECT_PREFIX {
+        state.page = 0;
+        state.typ = match interaction.data.values.first().map(String::as_str) {
+            Some(ALL_TYPES) | None => None,
+            Some(typ) => Some(
+                typ.parse()
+                    .map_err(|_| BotError::Other("Invalid event type in select menu"))?,
+            ),
+        };
+    }
+
+    let lang = language(ctx, interaction.guild_id, &interaction.locale).await;
+    let (state, tz, events, pages) = get_event_page(ctx, interaction.guild_id, state).await?;
+    debug!(?state, len = %events.len(), "switch event page");
 
-    debug!(len = %events.len());
-    send_events(ctx, interaction, events.as_slice()).await
+    Ok(interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|message| {
+                    message
+                        .create_embed(|embed| event_embed(embed, lang, &events, tz, &state, pages))
+                        .components(|components| event_components(components, lang, &state, pages))
+                })
+        })
+        .await?)
 }
 
-async fn show_filter_events(
+/// Suggest the events of the class for the `event` option of `edit` and `delete`
+pub async fn handle_event_autocomplete(
     ctx: &Context,
-    interaction: &Interaction,
-    option: &ApplicationCommandInteractionDataOption,
+    interaction: &AutocompleteInteraction,
 ) -> BotResult<()> {
-    let typ = option
+    const MAX_CHOICES: usize = 25;
+    const MAX_NAME_LENGTH: usize = 90;
+
+    let query = interaction
+        .data
         .options
         .first()
-        .ok_or(BotError::Other("event show filter has no option"))?;
+        .and_then(|subcommand| subcommand.options.iter().find(|option| option.focused))
+        .and_then(|option| option.value.as_ref())
+        .and_then(|value| value.as_str())
+        .unwrap_or_default()
+        .to_lowercase();
 
-    if let Some(serde_json::Value::String(typ)) = &typ.value {
-        let events = get_events(ctx, interaction.guild_id, None, None)
-            .await?
-            .into_iter()
-            .filter(|event| event.r#type.as_str() == typ)
-            .collect::<Vec<_>>();
+    let (tz, mut events) = get_events(ctx, interaction.guild_id, None, None).await?;
+    events.sort_unstable_by_key(|event| event.start);
 
-        let events = events;
+    let choices = events
+        .iter()
+        .filter(|event| event.name.to_lowercase().contains(&query))
+        .take(MAX_CHOICES)
+        .map(|event| {
+            let name = format!("{} | {}", format_date(event.start, tz), event.name);
+            (limit_length(&name, MAX_NAME_LENGTH), event.id)
+        })
+        .collect::<Vec<_>>();
 
-        send_events(ctx, interaction, events.as_slice()).await
-    } else {
-        Err(BotError::Other("event show filter has invalid option"))
-    }
+    Ok(interaction
+        .create_autocomplete_response(&ctx.http, |response| {
+            for (name, id) in choices {
+                response.add_string_choice(name, id);
+            }
+            response
+        })
+        .await?)
 }
 
-async fn show_search_events(
+async fn manage_event(
     ctx: &Context,
-    interaction: &Interaction,
-    option: &ApplicationCommandInteractionDataOption,
+    interaction: &ApplicationCommandInteraction,
+    subcommand: &ApplicationCommandInteractionDataOption,
 ) -> BotResult<()> {
-    let typ = option
-        .options
-        .first()
-        .ok_or(BotError::Other("event show search has no option"))?;
-
-    if let Some(serde_json::Value::String(query)) = &typ.value {
-        let query = query.to_lowercase();
-        let events = get_events(ctx, interaction.guild_id, None, None)
-            .await?
-            .into_iter()
-            .filter(|event| {
-                event.name.to_lowercase().contains(&query)
-                    || event.description.to_lowercase().contains(&query)
-            })
-            .collect::<Vec<_>>();
+    let lang = language(ctx, interaction.guild_id, &interaction.locale).await;
+    // the api checks the rights of the user again, including the two-factor requirement of the class
+    let (title, description) = match manage_event_embed(ctx, interaction, lang, subcommand).await {
+        Err(BotError::NoPermission(ErrorCode::TwoFactorRequired)) => (
+            tr!(lang, "setting-two-factor-required"),
+            tr!(lang, "setting-two-factor-required-hint"),
+        ),
+        result => result?,
+    };
 
-        send_events(ctx, interaction, events.as_slice()).await
-    } else {
-        Err(BotError::Other("event show search has invalid option"))
+    Ok(interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message
+                        .create_embed(|embed| embed.title(title).description(description))
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                })
+        })
+        .await?)
+}
+
+async fn manage_event_embed(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    lang: Language,
+    subcommand: &ApplicationCommandInteractionDataOption,
+) -> BotResult<ManageEmbed> {
+    let guild_id = interaction
+        .guild_id
+        .ok_or(BotError::Other("Guild id not found"))?;
+    let user = interaction
+        .member
+        .as_ref()
+        .ok_or(BotError::Other("Member not found"))?;
+
+    let map = ctx.data.read().await;
+    let client = map.get::<CorsClient>().unwrap();
+
+    let class = client.get_class(guild_id.0).await?;
+    match validate_admin_perms(client, user.user.id, class.id).await? {
+        None => {
+            return Ok((
+                tr!(lang, "setting-not-linked"),
+                tr!(lang, "setting-not-linked-hint"),
+            ))
+        }
+        Some(false) => {
+            return Ok((
+                tr!(lang, "setting-no-rights"),
+                tr!(lang, "event-no-rights-hint"),
+            ))
+        }
+        Some(true) => {}
     }
+
+    let tz = parse_timezone(class.timezone.as_deref().unwrap_or_default());
+
+    // edit and delete only work on events of the class of the guild
+    let existing = match string_option(subcommand, "event") {
+        Some(id) => {
+            let events = client.get_events(class.id, None, None).await?;
+            let event = id
+                .parse::<Uuid>()
+                .ok()
+                .and_then(|id| events.into_iter().find(|event| event.id == id));
+            match event {
+                Some(event) => Some(event),
+                None => {
+                    return Ok((
+                        tr!(lang, "event-not-found"),
+                        tr!(lang, "event-not-found-hint"),
+                    ))
+                }
+            }
+        }
+        None => None,
+    };
+
+    match (subcommand.name.as_str(), existing) {
+        ("create", _) => {
+            let mut event = dto::Event {
+                id: Uuid::nil(),
+                r#type: event_type_option(subcommand)?,
+                name: string_option(subcommand, "name")
+                    .ok_or(BotError::Other("event create has no name"))?
+                    .to_string(),
+                start: 0,
+                end: None,
+                description: string_option(subcommand, "description")
+                    .unwrap_or_default()
+                    .to_string(),
+                notification: None,
+            };
+            if let Err(embed) = apply_dates(lang, subcommand, &mut event, tz) {
+                return Ok(embed);
+            }
+
+            let event = client.create_event(class.id, &event, user.user.id).await?;
+            debug!(?event, "created event");
+            Ok((
+                tr!(lang, "event-created", name = event.name.as_str()),
+                event_summary(lang, &event, tz),
+            ))
+        }
+        ("edit", Some(mut event)) => {
+            if find_option(subcommand, "typ").is_some() {
+                event.r#type = event_type_option(subcommand)?;
+            }
+            if let Some(name) = string_option(subcommand, "name") {
+                event.name = name.to_string();
+            }
+            if let Some(description) = string_option(subcommand, "description") {
+                event.description = description.to_string();
+            }
+            if let Err(embed) = apply_dates(lang, subcommand, &mut event, tz) {
+                return Ok(embed);
+            }
+
+            let event = client.edit_event(class.id, &event, user.user.id).await?;
+            debug!(?event, "edited event");
+            Ok((
+                tr!(lang, "event-edited", name = event.name.as_str()),
+                event_summary(lang, &event, tz),
+            ))
+        }
+        ("delete", Some(event)) => {
+            client
+                .delete_event(class.id, event.id, user.user.id)
+                .await?;
+            debug!(?event, "deleted event");
+            Ok((
+                tr!(lang, "event-deleted", name = event.name.as_str()),
+                event_summary(lang, &event, tz),
+            ))
+        }
+        _ => Err(BotError::Other("event subcommand has no event")),
+    }
+}
+
+/// Set the dates of the options that are present on the event,
+/// returns the embed for the user if a date is invalid
+fn apply_dates(
+    lang: Language,
+    option: &ApplicationCommandInteractionDataOption,
+    event: &mut dto::Event,
+    tz: Tz,
+) -> Result<(), ManageEmbed> {
+    let date = |name: &str| match string_option(option, name) {
+        Some(input) => match parse_local_datetime(input, tz) {
+            Some(ms) => Ok(Some(ms)),
+            None => Err((
+                tr!(lang, "event-invalid-date", input = input),
+                tr!(lang, "event-invalid-date-hint"),
+            )),
+        },
+        None => Ok(None),
+    };
+
+    if let Some(start) = date("start")? {
+        event.start = start;
+    }
+    if let Some(end) = date("end")? {
+        event.end = Some(end);
+    }
+    if let Some(reminder) = date("reminder")? {
+        event.notification = Some(reminder);
+    }
+
+    match event.end {
+        Some(end) if end < event.start => Err((
+            tr!(lang, "event-end-before-start"),
+            tr!(lang, "event-end-before-start-hint"),
+        )),
+        _ => Ok(()),
+    }
+}
+
+fn event_summary(lang: Language, event: &dto::Event, tz: Tz) -> String {
+    let end = match event.end {
+        Some(end) => format!(" - {}", format_datetime(end, tz)),
+        None => "".to_string(),
+    };
+    let notification = match event.notification {
+        Some(time) => {
+            let time = format_datetime(time, tz).to_string();
+            format!("\n> {}", tr!(lang, "events-notification", time = time))
+        }
+        None => "".to_string(),
+    };
+
+    format!(
+        "{}: {}{}{}",
+        tr!(lang, &format!("event-type-{}", event.r#type.as_str())),
+        format_datetime(event.start, tz),
+        end,
+        notification
+    )
+}
+
+fn string_option<'a>(
+    option: &'a ApplicationCommandInteractionDataOption,
+    name: &str,
+) -> Option<&'a str> {
+    find_option(option, name)
+        .and_then(|option| option.value.as_ref())
+        .and_then(|value| value.as_str())
 }
 
+/// Get the events of the class of the guild, together with the timezone of the class
 async fn get_events(
     ctx: &Context,
     guild_id: Option<GuildId>,
     before: Option<i64>,
     after: Option<i64>,
-) -> BotResult<Vec<dto::Event>> {
+) -> BotResult<(Tz, Vec<dto::Event>)> {
     let guild_id = guild_id.ok_or(BotError::Other("Guild id not found"))?;
 
     let map = ctx.data.read().await;
     let client = map.get::<CorsClient>().unwrap();
 
-    Ok(client.get_events(guild_id.0, before, after).await?)
+    let class = client.get_class(guild_id.0).await?;
+    let events = client.get_events(class.id, before, after).await?;
+    Ok((
+        parse_timezone(class.timezone.as_deref().unwrap_or_default()),
+        events,
+    ))
 }
 
-async fn send_events(
+/// Get the events on the page, the page is clamped to the last page
+async fn get_event_page(
     ctx: &Context,
-    interaction: &Interaction,
-    events: &[dto::Event],
-) -> BotResult<()> {
-    let mut events = events
-        .iter()
-        .take(10)
-        .cloned() // todo oh
-        .collect::<Vec<_>>();
+    guild_id: Option<GuildId>,
+    mut state: EventPage,
+) -> BotResult<(EventPage, Tz, Vec<dto::Event>, usize)> {
+    let after = if state.upcoming {
+        Some(Utc::now().timestamp_millis())
+    } else {
+        None
+    };
+    let (tz, events) = get_events(ctx, guild_id, None, after).await?;
 
-    events.sort_unstable_by(|e1, e2| e1.start.cmp(&e2.start));
+    let events = state.filter(events);
+    let pages = EventPage::page_count(events.len());
+    let page = state.page_of(&events).to_vec();
+    Ok((state, tz, page, pages))
+}
 
-    Ok(interaction
-        .create_interaction_response(&ctx.http, |response| {
-            response
-                .kind(InteractionResponseType::ChannelMessageWithSource)
-                .interaction_response_data(|message| {
-                    message
-                        .create_embed(|embed| event_embed(embed, events.as_slice()))
-                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
-                })
+fn event_components<'a>(
+    components: &'a mut CreateComponents,
+    lang: Language,
+    state: &EventPage,
+    pages: usize,
+) -> &'a mut CreateComponents {
+    let previous = EventPage {
+        page: state.page.saturating_sub(1),
+        ..state.clone()
+    };
+    let next = EventPage {
+        page: state.page + 1,
+        ..state.clone()
+    };
+
+    components
+        .create_action_row(|row| {
+            row.create_button(|button| {
+                button
+                    .custom_id(previous.to_custom_id(EventPage::BUTTON_PREFIX))
+                    .label(tr!(lang, "events-previous"))
+                    .style(ButtonStyle::Secondary)
+                    .disabled(state.page == 0)
+            })
+            .create_button(|button| {
+                button
+                    .custom_id(next.to_custom_id(EventPage::BUTTON_PREFIX))
+                    .label(tr!(lang, "events-next"))
+                    .style(ButtonStyle::Secondary)
+                    .disabled(state.page + 1 >= pages)
+            })
+        })
+        .create_action_row(|row| {
+            row.create_select_menu(|menu| {
+                menu.custom_id(state.to_custom_id(EventPage::SELECT_PREFIX))
+                    .options(|options| {
+                        options.create_option(|option| {
+                            option
+                                .label(tr!(lang, "events-all-types"))
+                                .value(ALL_TYPES)
+                                .default_selection(state.typ.is_none())
+                        });
+                        for typ in EVENT_TYPES.iter() {
+                            options.create_option(|option| {
+                                option
+                                    .label(tr!(lang, &format!("event-type-{}", typ.as_str())))
+                                    .value(typ.as_str())
+                                    .default_selection(state.typ == Some(*typ))
+                            });
+                        }
+                        options
+                    })
+            })
         })
-        .await?)
 }
 
-fn event_embed<'a>(embed: &'a mut CreateEmbed, events: &[dto::Event]) -> &'a mut CreateEmbed {
+fn event_embed<'a>(
+    embed: &'a mut CreateEmbed,
+    lang: Language,
+    events: &[dto::Event],
+    tz: Tz,
+    state: &EventPage,
+    pages: usize,
+) -> &'a mut CreateEmbed {
     const MAX_DESCRIPTION_LENGTH: usize = 100;
 
     let mut fields = events
@@ -144,22 +490,23 @@ fn event_embed<'a>(embed: &'a mut CreateEmbed, events: &[dto::Event]) -> &'a mut
             let description = limit_length(&event.description, MAX_DESCRIPTION_LENGTH);
 
             let end_value = if let Some(end) = event.end {
-                format!(" - {}", format_datetime(end))
+                format!(" - {}", format_datetime(end, tz))
             } else {
                 "".to_string()
             };
 
             let notification = if let Some(time) = event.notification {
-                format!("\n\n> Benachrichtigung um {}", format_datetime(time))
+                let time = format_datetime(time, tz).to_string();
+                format!("\n\n> {}", tr!(lang, "events-notification", time = time))
             } else {
                 "".to_string()
             };
 
             (
-                format!("{} | {}", format_date(event.start), event.name),
+                format!("{} | {}", format_date(event.start, tz), event.name),
                 format!(
                     "{}{} \n\n {}{}",
-                    format_datetime(event.start),
+                    format_datetime(event.start, tz),
                     end_value,
                     description,
                     notification
@@ -171,13 +518,21 @@ fn event_embed<'a>(embed: &'a mut CreateEmbed, events: &[dto::Event]) -> &'a mut
 
     if fields.is_empty() {
         fields.push((
-            "Keine Events gefunden".to_string(),
-            "Admins können Events in der Web-Version eintragen".to_string(),
+            tr!(lang, "events-none"),
+            tr!(lang, "events-none-hint"),
             true,
         ));
     }
 
-    embed.title("Events").fields(fields).footer(|f| {
-        f.text("CORS - Es werden maximal 10 Events angezeigt - Nutz 'filter' oder 'search'")
-    })
+    embed
+        .title(tr!(lang, "events-title"))
+        .fields(fields)
+        .footer(|f| {
+            f.text(tr!(
+                lang,
+                "events-footer",
+                page = state.page + 1,
+                pages = pages
+            ))
+        })
 }
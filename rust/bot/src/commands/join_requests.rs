@@ -0,0 +1,87 @@
+use serenity::builder::{CreateComponents, CreateEmbed};
+use serenity::model::interactions::message_component::MessageComponentInteraction;
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::debug;
+
+use crate::error::{BotError, BotResult};
+use crate::functions::JoinRequestButton;
+use crate::requests::{CorsClient, JoinRequestReply};
+
+/// Accept or deny a join request posted in the admin channel, the server checks the rights
+/// of the linked account of the admin
+pub async fn handle_join_request_component(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+) -> BotResult<()> {
+    let button = JoinRequestButton::from_custom_id(&interaction.data.custom_id)
+        .ok_or(BotError::Other("Invalid join request custom id"))?;
+    let lang = super::language(ctx, interaction.guild_id, &interaction.locale).await;
+
+    let reply = {
+        let map = ctx.data.read().await;
+        let client = map.get::<CorsClient>().unwrap();
+        client
+            .reply_to_join_request(
+                button.class,
+                button.user,
+                button.accept,
+                interaction.user.id,
+            )
+            .await?
+    };
+    debug!(?button, ?reply, "join request answered");
+
+    let outcome = match reply {
+        JoinRequestReply::NoRights => {
+            return Ok(interaction
+                .create_interaction_response(&ctx.http, |response| {
+                    response
+                        .kind(InteractionResponseType::ChannelMessageWithSource)
+                        .interaction_response_data(|message| {
+                            message
+                                .create_embed(|embed| {
+                                    embed
+                                        .title(tr!(lang, "join-request-no-rights"))
+                                        .description(tr!(lang, "join-request-no-rights-hint"))
+                                })
+                                .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                        })
+                })
+                .await?);
+        }
+        JoinRequestReply::Done if button.accept => tr!(
+            lang,
+            "join-request-accepted",
+            admin = interaction.user.mention().to_string()
+        ),
+        JoinRequestReply::Done => tr!(
+            lang,
+            "join-request-denied",
+            admin = interaction.user.mention().to_string()
+        ),
+        JoinRequestReply::AlreadyHandled => tr!(lang, "join-request-handled"),
+    };
+
+    // keep the request in the message and replace the buttons with the outcome
+    let mut embed = interaction
+        .message
+        .embeds
+        .first()
+        .cloned()
+        .map(CreateEmbed::from)
+        .unwrap_or_default();
+    embed.field("\u{200b}", outcome, false);
+
+    Ok(interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|message| {
+                    message
+                        .add_embed(embed)
+                        .set_components(CreateComponents::default())
+                })
+        })
+        .await?)
+}
@@ -0,0 +1,117 @@
+use serenity::model::interactions::application_command::{
+    ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+};
+use serenity::model::prelude::*;
+use serenity::prelude::*;
+use tracing::debug;
+
+use super::language;
+use crate::error::BotResult;
+use crate::i18n::Language;
+use crate::requests::CorsClient;
+
+type LinkEmbed = (String, String);
+
+pub async fn handle_link_command(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    options: &[ApplicationCommandInteractionDataOption],
+) -> BotResult<()> {
+    let lang = language(ctx, interaction.guild_id, &interaction.locale).await;
+    let user = interaction.user.id;
+
+    let code = options
+        .iter()
+        .find(|option| option.name == "code")
+        .and_then(|option| option.value.as_ref())
+        .and_then(|value| value.as_str());
+
+    let (title, description) = {
+        let map = ctx.data.read().await;
+        let client = map.get::<CorsClient>().unwrap();
+        match code {
+            Some(code) => link_with_code(client, lang, user, code).await?,
+            None => create_code(client, lang, user).await?,
+        }
+    };
+
+    respond(ctx, interaction, title, description).await
+}
+
+pub async fn handle_unlink_command(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+) -> BotResult<()> {
+    let lang = language(ctx, interaction.guild_id, &interaction.locale).await;
+
+    let unlinked = {
+        let map = ctx.data.read().await;
+        let client = map.get::<CorsClient>().unwrap();
+        client.unlink(interaction.user.id).await?
+    };
+    debug!(%unlinked, user = %interaction.user.id, "unlink");
+
+    let (title, description) = if unlinked {
+        (tr!(lang, "link-unlinked"), tr!(lang, "link-unlinked-hint"))
+    } else {
+        (
+            tr!(lang, "setting-not-linked"),
+            tr!(lang, "setting-not-linked-hint"),
+        )
+    };
+
+    respond(ctx, interaction, title, description).await
+}
+
+/// The code is entered on the website to link the CORS account with the discord user
+async fn create_code(client: &CorsClient, lang: Language, user: UserId) -> BotResult<LinkEmbed> {
+    let code = client.create_link_code(user).await?;
+    debug!(%user, "created link code");
+
+    Ok((
+        tr!(lang, "link-code", code = code.code),
+        tr!(
+            lang,
+            "link-code-hint",
+            expires = (code.expires / 1000).to_string()
+        ),
+    ))
+}
+
+async fn link_with_code(
+    client: &CorsClient,
+    lang: Language,
+    user: UserId,
+    code: &str,
+) -> BotResult<LinkEmbed> {
+    let linked = client.link_with_code(user, code.trim()).await?;
+    debug!(%linked, %user, "link with code");
+
+    Ok(if linked {
+        (tr!(lang, "link-linked"), tr!(lang, "link-linked-hint"))
+    } else {
+        (
+            tr!(lang, "link-invalid-code"),
+            tr!(lang, "link-invalid-code-hint"),
+        )
+    })
+}
+
+async fn respond(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+    title: String,
+    description: String,
+) -> BotResult<()> {
+    Ok(interaction
+        .create_interaction_response(&ctx.http, |response| {
+            response
+                .kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|message| {
+                    message
+                        .create_embed(|embed| embed.title(title).description(description))
+                        .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
+                })
+        })
+        .await?)
+}
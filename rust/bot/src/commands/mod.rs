@@ -1,52 +1,119 @@
 use chrono::format::{DelayedFormat, StrftimeItems};
 use chrono::Utc;
 use serenity::builder::CreateEmbed;
+use serenity::model::interactions::application_command::ApplicationCommandInteraction;
+use serenity::model::interactions::autocomplete::AutocompleteInteraction;
+use serenity::model::interactions::message_component::MessageComponentInteraction;
 use serenity::model::prelude::*;
 use serenity::prelude::*;
-use tracing::debug;
+use tracing::{debug, warn};
 
 use dto::Lesson;
 pub use setup::setup_slash_commands;
 
 use crate::error::{BotError, BotResult};
 use crate::functions;
-use crate::functions::{absolute_time_as_weekday, from_utc_timestamp, from_utc_to_cest};
+use crate::functions::{
+    absolute_time_as_weekday, from_utc_timestamp, parse_timezone, EventPage, JoinRequestButton,
+};
+use crate::i18n::Language;
 use crate::requests::CorsClient;
 
 mod events;
+mod join_requests;
+mod link;
 mod settings;
 mod setup;
 mod stundenplan;
 
-pub async fn create_interaction_response<'a>(
+pub async fn create_interaction_response(
     ctx: &Context,
-    data: &ApplicationCommandInteractionData,
-    interaction: &Interaction,
+    interaction: &ApplicationCommandInteraction,
 ) -> BotResult<()> {
+    let data = &interaction.data;
     match data.name.as_str() {
         "info" => info(ctx, interaction).await?,
         "event" => events::handle_event_command(ctx, interaction, &data.options).await?,
         "wielangenoch" => wie_lange_noch(ctx, interaction).await?,
         "setting" => settings::handle_setup_command(ctx, interaction, &data.options).await?,
         "stundenplan" => stundenplan::handle_timetable(ctx, interaction).await?,
+        "link" => link::handle_link_command(ctx, interaction, &data.options).await?,
+        "unlink" => link::handle_unlink_command(ctx, interaction).await?,
         name => debug!("{}, {:#?}", name, data.options),
     }
     Ok(())
 }
 
-async fn wie_lange_noch(ctx: &Context, interaction: &Interaction) -> BotResult<()> {
+pub async fn create_autocomplete_response(
+    ctx: &Context,
+    interaction: &AutocompleteInteraction,
+) -> BotResult<()> {
+    match interaction.data.name.as_str() {
+        "event" => events::handle_event_autocomplete(ctx, interaction).await?,
+        name => debug!("{}, {:#?}", name, interaction.data.options),
+    }
+    Ok(())
+}
+
+pub async fn create_component_response(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+) -> BotResult<()> {
+    let custom_id = interaction.data.custom_id.as_str();
+    match custom_id.split(':').next() {
+        Some(EventPage::BUTTON_PREFIX) | Some(EventPage::SELECT_PREFIX) => {
+            events::handle_event_component(ctx, interaction).await?
+        }
+        Some(JoinRequestButton::PREFIX) => {
+            join_requests::handle_join_request_component(ctx, interaction).await?
+        }
+        _ => debug!(%custom_id, "Unknown component"),
+    }
+    Ok(())
+}
+
+/// The language to answer an interaction in, see [`Language::resolve`]
+async fn language(ctx: &Context, guild_id: Option<GuildId>, locale: &str) -> Language {
+    let guild_language = match guild_id {
+        Some(guild_id) => {
+            let map = ctx.data.read().await;
+            let client = map.get::<CorsClient>().unwrap();
+            match client.get_guild(guild_id.0).await {
+                Ok(guild) => guild.and_then(|guild| guild.language),
+                Err(why) => {
+                    warn!(%why, "Could not get the language of the guild");
+                    None
+                }
+            }
+        }
+        None => None,
+    };
+
+    Language::resolve(guild_language.as_deref(), Some(locale))
+}
+
+async fn wie_lange_noch(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+) -> BotResult<()> {
     let guild_id = interaction
         .guild_id
         .ok_or(BotError::Other("Guild id not found"))?;
+    let lang = language(ctx, interaction.guild_id, &interaction.locale).await;
 
     let map = ctx.data.read().await;
     let client = map.get::<CorsClient>().unwrap();
 
-    let timetable = client.get_timetable(guild_id.0).await?;
+    let class = client.get_class(guild_id.0).await?;
+    let timetable = client.get_timetable(class.id).await?;
     let found = timetable.is_some();
 
+    let now = Utc::now().with_timezone(&parse_timezone(
+        class.timezone.as_deref().unwrap_or_default(),
+    ));
+    let (diff, weekday) = absolute_time_as_weekday(now);
+
     let (lesson, next) = if let Some(ref timetable) = timetable {
-        let (diff, weekday) = functions::absolute_time_as_weekday(chrono::Utc::now());
         functions::wie_lange_noch(timetable, diff, weekday)
     } else {
         (None, None)
@@ -58,20 +125,23 @@ async fn wie_lange_noch(ctx: &Context, interaction: &Interaction) -> BotResult<(
                 .kind(InteractionResponseType::ChannelMessageWithSource)
                 .interaction_response_data(|message| {
                     message
-                        .create_embed(|embed| wie_lange_noch_embed(embed, found, lesson, next))
+                        .create_embed(|embed| {
+                            wie_lange_noch_embed(embed, lang, found, diff, lesson, next)
+                        })
                         .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
                 })
         })
         .await?)
 }
 
-async fn info(ctx: &Context, interaction: &Interaction) -> BotResult<()> {
+async fn info(ctx: &Context, interaction: &ApplicationCommandInteraction) -> BotResult<()> {
     let (corsin, nils) = tokio::join!(
         UserId(546052568619679744).to_user(&ctx.http),
         UserId(414755070161453076).to_user(&ctx.http)
     );
     let (corsin, nils) = (corsin?, nils?);
     let corsin_first = rand::random::<bool>();
+    let lang = language(ctx, interaction.guild_id, &interaction.locale).await;
 
     Ok(interaction
         .create_interaction_response(&ctx.http, |response| {
@@ -79,7 +149,7 @@ async fn info(ctx: &Context, interaction: &Interaction) -> BotResult<()> {
                 .kind(InteractionResponseType::ChannelMessageWithSource)
                 .interaction_response_data(|message| {
                     message
-                        .create_embed(|embed| info_embed(embed, corsin, nils, corsin_first))
+                        .create_embed(|embed| info_embed(embed, lang, corsin, nils, corsin_first))
                         .flags(InteractionApplicationCommandCallbackDataFlags::EPHEMERAL)
                 })
         })
@@ -88,34 +158,41 @@ async fn info(ctx: &Context, interaction: &Interaction) -> BotResult<()> {
 
 fn wie_lange_noch_embed<'a>(
     embed: &'a mut CreateEmbed,
+    lang: Language,
     found_timetable: bool,
+    now: i64,
     lesson: Option<&Lesson>,
     next: Option<&Lesson>,
 ) -> &'a mut CreateEmbed {
-    let now = absolute_time_as_weekday(Utc::now()).0;
-
     match (found_timetable, lesson, next) {
-        (false, _, _) => embed.title("Stundenplan nicht gefunden").field(
-            "Für Admins",
-            "Aktiviere den Stundenplan in den Admineinstellungen",
+        (false, _, _) => embed.title(tr!(lang, "timetable-not-found")).field(
+            tr!(lang, "timetable-not-found-admins"),
+            tr!(lang, "timetable-not-found-hint"),
             false,
         ),
-        (_, None, None) => embed.title("Keine Schule mehr heute!"),
+        (_, None, None) => embed.title(tr!(lang, "lesson-no-school")),
         (_, None, Some(next)) => {
             let time_to_next = format_time(next.start - now);
-            embed.title("Keine Lektion").field(
-                format!("Nächste Lektion: {}", next.subject),
-                format!("Start in: {}h", time_to_next),
+            embed.title(tr!(lang, "lesson-none")).field(
+                tr!(lang, "lesson-next", subject = next.subject.as_str()),
+                tr!(lang, "lesson-next-start", time = time_to_next.to_string()),
                 false,
             )
         }
         (_, Some(lesson), next) => {
-            let (current, _) = absolute_time_as_weekday(Utc::now());
-            let remaining = lesson.end - current;
+            let remaining = lesson.end - now;
             embed
-                .title(format!("Aktuelle Lektion: {}", lesson.subject))
+                .title(tr!(
+                    lang,
+                    "lesson-current",
+                    subject = lesson.subject.as_str()
+                ))
                 .field(
-                    format!("Noch {}h", format_time(remaining),),
+                    tr!(
+                        lang,
+                        "lesson-remaining",
+                        time = format_time(remaining).to_string()
+                    ),
                     &lesson.description,
                     false,
                 );
@@ -123,8 +200,8 @@ fn wie_lange_noch_embed<'a>(
                 let time_to_next = format_time(next.start - now);
 
                 embed.field(
-                    format!("Nächste Lektion: {}", &next.subject),
-                    format!("in {}h", time_to_next),
+                    tr!(lang, "lesson-next", subject = next.subject.as_str()),
+                    tr!(lang, "lesson-next-in", time = time_to_next.to_string()),
                     false,
                 );
             }
@@ -139,6 +216,7 @@ fn format_time(time: i64) -> DelayedFormat<StrftimeItems<'static>> {
 
 fn info_embed(
     embed: &mut CreateEmbed,
+    lang: Language,
     user1: User,
     user2: User,
     user1_first: bool,
@@ -154,7 +232,7 @@ fn info_embed(
         .fields(vec![
             (
                 "Create Organized Relaxed School",
-                "CORS ist ein Terminverwaltungssystem für Schulklassen, durch das nichts mehr vergessen werden kann",
+                tr!(lang, "info-description").as_str(),
                 false,
             ),
             (
@@ -173,9 +251,3 @@ fn info_embed(
         ])
         .footer(|f| f.text("Bot Version 0.0"))
 }
-
-pub fn format_datetime(
-    time: i64,
-) -> chrono::format::DelayedFormat<chrono::format::StrftimeItems<'static>> {
-    from_utc_to_cest(from_utc_timestamp(time)).format("%d.%m.%Y %H:%M CEST")
-}
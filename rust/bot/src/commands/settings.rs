@@ -1,16 +1,23 @@
 use crate::error::{BotError, BotResult};
+use serenity::model::interactions::application_command::{
+    ApplicationCommandInteraction, ApplicationCommandInteractionDataOption,
+    ApplicationCommandInteractionDataOptionValue,
+};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
 
+use crate::i18n::Language;
 use crate::requests::CorsClient;
+use crate::role_sync;
+use dto::{ErrorCode, EventType, MemberRole, RoleMapping};
 use tracing::{debug, warn};
 use uuid::Uuid;
 
-type SetupEmbed = (&'static str, (String, String, bool));
+type SetupEmbed = (String, (String, String, bool));
 
 pub async fn handle_setup_command(
     ctx: &Context,
-    interaction: &Interaction,
+    interaction: &ApplicationCommandInteraction,
     options: &[ApplicationCommandInteractionDataOption],
 ) -> BotResult<()> {
     let (title, (f1, f2, f3)) = setup_command(ctx, interaction, options).await?;
@@ -30,7 +37,7 @@ pub async fn handle_setup_command(
 
 pub async fn setup_command(
     ctx: &Context,
-    interaction: &Interaction,
+    interaction: &ApplicationCommandInteraction,
     options: &[ApplicationCommandInteractionDataOption],
 ) -> BotResult<SetupEmbed> {
     let map = ctx.data.read().await;
@@ -48,60 +55,103 @@ pub async fn setup_command(
     let mut guild = match client.get_guild(guild_id.0).await? {
         Some(guild) => guild,
         None => {
+            let lang = Language::resolve(None, Some(&interaction.locale));
             return Ok((
-                "Klasse nicht registriert",
+                tr!(lang, "setting-class-not-registered"),
                 (
-                    "Verbinde die Klasse mit dem Discord Server im Admin Panel".to_string(),
-                    "Diese Aktion kann nur von Administratoren durchgeführt werden.".to_string(),
+                    tr!(lang, "setting-class-not-registered-hint"),
+                    tr!(lang, "setting-class-not-registered-admins"),
                     false,
                 ),
-            ))
+            ));
         }
     };
+    let lang = Language::resolve(guild.language.as_deref(), Some(&interaction.locale));
 
     let class_id = client.get_class(guild_id.0).await?;
     let has_rights = validate_admin_perms(client, user.user.id, class_id.id).await?;
 
     match has_rights {
         None => Ok((
-            "Account nicht verlinkt",
+            tr!(lang, "setting-not-linked"),
             (
-                "Discord-Account nicht mit CORS-Account verlinkt".to_string(),
-                "Verlinke deinen Discord Account mit deinem CORS-Account auf der Website"
-                    .to_string(),
+                tr!(lang, "setting-not-linked-title"),
+                tr!(lang, "setting-not-linked-hint"),
                 false,
             ),
         )),
         Some(false) => Ok((
-            "Keine Rechte",
+            tr!(lang, "setting-no-rights"),
             (
-                "Braucht: Administrator".to_string(),
-                "Das ändern von Servereinstellungen braucht mindestens Administratorenrechte"
-                    .to_string(),
+                tr!(lang, "setting-no-rights-title"),
+                tr!(lang, "setting-no-rights-hint"),
                 false,
             ),
         )),
-        Some(true) => match options.first() {
-            Some(subcommand) => match subcommand.name.as_str() {
-                "notification_channel" => {
-                    Ok(notification_channel(subcommand, client, &mut guild).await?)
-                }
-                "notification_everyone_ping" => {
-                    Ok(notification_everyone_ping(subcommand, client, &mut guild).await?)
-                }
-                "notification_role_ping" => {
-                    Ok(notification_role_ping(subcommand, client, &mut guild).await?)
-                }
-                _ => unreachable!(),
-            },
-            None => unreachable!(),
-        },
+        Some(true) => {
+            let acting = user.user.id;
+            let result = match options.first() {
+                Some(subcommand) => match subcommand.name.as_str() {
+                    "notification_channel" => {
+                        notification_channel(subcommand, lang, client, acting, &mut guild).await
+                    }
+                    "notification_everyone_ping" => {
+                        notification_everyone_ping(subcommand, lang, client, acting, &mut guild)
+                            .await
+                    }
+                    "notification_role_ping" => {
+                        notification_role_ping(subcommand, lang, client, acting, &mut guild).await
+                    }
+                    "notification_type_channel" => {
+                        notification_type_channel(subcommand, lang, client, acting, &mut guild)
+                            .await
+                    }
+                    "notification_type_everyone_ping" => {
+                        notification_type_everyone_ping(
+                            subcommand, lang, client, acting, &mut guild,
+                        )
+                        .await
+                    }
+                    "notification_type_role_ping" => {
+                        notification_type_role_ping(subcommand, lang, client, acting, &mut guild)
+                            .await
+                    }
+                    "role_mapping" => {
+                        role_mapping(subcommand, lang, client, acting, &mut guild).await
+                    }
+                    "role_sync_dry_run" => role_sync_dry_run(ctx, lang, client, *guild_id).await,
+                    "admin_channel" => {
+                        admin_channel(subcommand, lang, client, acting, &mut guild).await
+                    }
+                    "language" => {
+                        language(subcommand, client, acting, &mut guild, &interaction.locale).await
+                    }
+                    _ => unreachable!(),
+                },
+                None => unreachable!(),
+            };
+
+            // the api checks the rights of the user again, including the two-factor requirement of the class
+            match result {
+                Err(BotError::NoPermission(ErrorCode::TwoFactorRequired)) => Ok((
+                    tr!(lang, "setting-two-factor-required"),
+                    (
+                        tr!(lang, "setting-no-rights-title"),
+                        tr!(lang, "setting-two-factor-required-hint"),
+                        false,
+                    ),
+                )),
+                result => result,
+            }
+        }
     }
 }
 
 async fn notification_channel(
     option: &ApplicationCommandInteractionDataOption,
+    lang: Language,
     client: &CorsClient,
+    acting: UserId,
     guild: &mut dto::Guild,
 ) -> BotResult<SetupEmbed> {
     match option.options.first() {
@@ -111,18 +161,22 @@ async fn notification_channel(
         }) => {
             debug!(?channel, "notification channel");
             client
-                .edit_guild_settings({
-                    guild.notif_channel = Some(channel.id.to_string());
-                    guild
-                })
+                .edit_guild_settings(
+                    {
+                        guild.notif_channel = Some(channel.id.to_string());
+                        guild
+                    },
+                    acting,
+                )
                 .await?;
             Ok((
-                "Benachrichtigungschannel gesetzt",
+                tr!(lang, "setting-channel-set"),
                 (
-                    "Benachrichtigungschannel gesetzt.".to_string(),
-                    format!(
-                        "Benachrichtigungen werden jetzt in <#{}> geschickt",
-                        channel.id
+                    tr!(lang, "setting-channel-set"),
+                    tr!(
+                        lang,
+                        "setting-channel-set-hint",
+                        channel = channel.id.to_string()
                     ),
                     false,
                 ),
@@ -135,16 +189,19 @@ async fn notification_channel(
         None => {
             debug!("no channel provided");
             client
-                .edit_guild_settings({
-                    guild.notif_channel = None;
-                    guild
-                })
+                .edit_guild_settings(
+                    {
+                        guild.notif_channel = None;
+                        guild
+                    },
+                    acting,
+                )
                 .await?;
             Ok((
-                "Benachrichtigungen ausgeschaltet",
+                tr!(lang, "setting-channel-off"),
                 (
-                    "Benachrichtigungen ausgeschaltet.".to_string(),
-                    "Benachrichtigungen können jederzeit wieder eingeschaltet werden.".to_string(),
+                    tr!(lang, "setting-channel-off"),
+                    tr!(lang, "setting-channel-off-hint"),
                     false,
                 ),
             ))
@@ -152,9 +209,65 @@ async fn notification_channel(
     }
 }
 
+async fn admin_channel(
+    option: &ApplicationCommandInteractionDataOption,
+    lang: Language,
+    client: &CorsClient,
+    acting: UserId,
+    guild: &mut dto::Guild,
+) -> BotResult<SetupEmbed> {
+    let channel = match option.options.first() {
+        Some(ApplicationCommandInteractionDataOption {
+            resolved: Some(ApplicationCommandInteractionDataOptionValue::Channel(channel)),
+            ..
+        }) => Some(channel.id),
+        Some(_) => {
+            warn!("Not a channel");
+            unreachable!()
+        }
+        None => None,
+    };
+    debug!(?channel, "admin channel");
+
+    client
+        .edit_guild_settings(
+            {
+                guild.admin_channel = channel.map(|channel| channel.to_string());
+                guild
+            },
+            acting,
+        )
+        .await?;
+
+    Ok(match channel {
+        Some(channel) => (
+            tr!(lang, "setting-admin-channel-set"),
+            (
+                tr!(lang, "setting-admin-channel-set"),
+                tr!(
+                    lang,
+                    "setting-admin-channel-set-hint",
+                    channel = channel.to_string()
+                ),
+                false,
+            ),
+        ),
+        None => (
+            tr!(lang, "setting-admin-channel-off"),
+            (
+                tr!(lang, "setting-admin-channel-off"),
+                tr!(lang, "setting-admin-channel-off-hint"),
+                false,
+            ),
+        ),
+    })
+}
+
 async fn notification_everyone_ping(
     option: &ApplicationCommandInteractionDataOption,
+    lang: Language,
     client: &CorsClient,
+    acting: UserId,
     guild: &mut dto::Guild,
 ) -> BotResult<SetupEmbed> {
     match option.options.first() {
@@ -164,30 +277,22 @@ async fn notification_everyone_ping(
         }) => {
             debug!(?everyone, "everyone ping");
             client
-                .edit_guild_settings({
-                    guild.notif_ping_everyone = *everyone;
-                    guild
-                })
+                .edit_guild_settings(
+                    {
+                        guild.notif_ping_everyone = *everyone;
+                        guild
+                    },
+                    acting,
+                )
                 .await?;
-            Ok(match everyone {
-                true => (
-                    "@everyone Ping eingeschaltet",
-                    (
-                        "Ping ausgeschaltet".to_string(),
-                        "Bei Benachrichtigungen wird jetzt in @everyone gepingt".to_string(),
-                        false,
-                    ),
-                ),
-                false => (
-                    "@everyone Ping ausgeschaltet",
-                    (
-                        "Ping ausgeschaltet".to_string(),
-                        "Bei Benachrichtigungen wird jetzt nicht mehr @everyone gepingt"
-                            .to_string(),
-                        false,
-                    ),
-                ),
-            })
+            let (title, description) = match everyone {
+                true => ("setting-everyone-on", "setting-everyone-on-hint"),
+                false => ("setting-everyone-off", "setting-everyone-off-hint"),
+            };
+            Ok((
+                tr!(lang, title),
+                (tr!(lang, title), tr!(lang, description), false),
+            ))
         }
         value => {
             warn!(?value, "Invalid boolean");
@@ -198,7 +303,9 @@ async fn notification_everyone_ping(
 
 async fn notification_role_ping(
     option: &ApplicationCommandInteractionDataOption,
+    lang: Language,
     client: &CorsClient,
+    acting: UserId,
     guild: &mut dto::Guild,
 ) -> BotResult<SetupEmbed> {
     match option.options.first() {
@@ -208,19 +315,19 @@ async fn notification_role_ping(
         }) => {
             debug!(?role, "notification role");
             client
-                .edit_guild_settings({
-                    guild.notif_ping_role = Some(role.id.to_string());
-                    guild
-                })
+                .edit_guild_settings(
+                    {
+                        guild.notif_ping_role = Some(role.id.to_string());
+                        guild
+                    },
+                    acting,
+                )
                 .await?;
             Ok((
-                "Benachrichtigungsrolle gesetzt",
+                tr!(lang, "setting-role-set"),
                 (
-                    "Benachrichtigungsrolle gesetzt".to_string(),
-                    format!(
-                        "Bei Benachrichtigungen wird jetzt in <@&{}> gepingt",
-                        role.id
-                    ),
+                    tr!(lang, "setting-role-set"),
+                    tr!(lang, "setting-role-set-hint", role = role.id.to_string()),
                     false,
                 ),
             ))
@@ -232,17 +339,165 @@ async fn notification_role_ping(
         None => {
             debug!("no role provided");
             client
-                .edit_guild_settings({
-                    guild.notif_ping_role = None;
-                    guild
-                })
+                .edit_guild_settings(
+                    {
+                        guild.notif_ping_role = None;
+                        guild
+                    },
+                    acting,
+                )
                 .await?;
             Ok((
-                "Benachrichtigungsrolle ausgeschaltet",
+                tr!(lang, "setting-role-off"),
+                (
+                    tr!(lang, "setting-role-off"),
+                    tr!(lang, "setting-role-off-hint"),
+                    false,
+                ),
+            ))
+        }
+    }
+}
+
+async fn notification_type_channel(
+    option: &ApplicationCommandInteractionDataOption,
+    lang: Language,
+    client: &CorsClient,
+    acting: UserId,
+    guild: &mut dto::Guild,
+) -> BotResult<SetupEmbed> {
+    let typ = event_type_option(option)?;
+    let type_name = type_name(lang, typ);
+
+    match find_option(option, "channel") {
+        Some(ApplicationCommandInteractionDataOption {
+            resolved: Some(ApplicationCommandInteractionDataOptionValue::Channel(channel)),
+            ..
+        }) => {
+            debug!(?typ, ?channel, "notification type channel");
+            guild.notif_route_mut(typ).channel = Some(channel.id.to_string());
+            client.edit_guild_settings(guild, acting).await?;
+            Ok((
+                tr!(lang, "setting-channel-set"),
+                (
+                    tr!(lang, "setting-type-channel-set", type = type_name.as_str()),
+                    tr!(
+                        lang,
+                        "setting-type-channel-set-hint",
+                        type = type_name.as_str(),
+                        channel = channel.id.to_string()
+                    ),
+                    false,
+                ),
+            ))
+        }
+        Some(_) => {
+            warn!("Not a channel");
+            unreachable!()
+        }
+        None => {
+            debug!(?typ, "no channel provided");
+            guild.notif_route_mut(typ).channel = None;
+            client.edit_guild_settings(guild, acting).await?;
+            Ok((
+                tr!(lang, "setting-type-channel-reset", type = type_name.as_str()),
+                (
+                    tr!(lang, "setting-type-channel-reset", type = type_name.as_str()),
+                    tr!(lang, "setting-type-channel-reset-hint", type = type_name.as_str()),
+                    false,
+                ),
+            ))
+        }
+    }
+}
+
+async fn notification_type_everyone_ping(
+    option: &ApplicationCommandInteractionDataOption,
+    lang: Language,
+    client: &CorsClient,
+    acting: UserId,
+    guild: &mut dto::Guild,
+) -> BotResult<SetupEmbed> {
+    let typ = event_type_option(option)?;
+    let type_name = type_name(lang, typ);
+
+    let everyone = match find_option(option, "everyone") {
+        Some(ApplicationCommandInteractionDataOption {
+            resolved: Some(ApplicationCommandInteractionDataOptionValue::Boolean(everyone)),
+            ..
+        }) => Some(*everyone),
+        Some(value) => {
+            warn!(?value, "Invalid boolean");
+            unreachable!()
+        }
+        None => None,
+    };
+
+    debug!(?typ, ?everyone, "notification type everyone ping");
+    guild.notif_route_mut(typ).everyone_ping = everyone;
+    client.edit_guild_settings(guild, acting).await?;
+
+    let description = match everyone {
+        Some(true) => "setting-type-everyone-on-hint",
+        Some(false) => "setting-type-everyone-off-hint",
+        None => "setting-type-everyone-reset-hint",
+    };
+
+    Ok((
+        tr!(lang, "setting-type-everyone", type = type_name.as_str()),
+        (
+            tr!(lang, "setting-type-everyone", type = type_name.as_str()),
+            tr!(lang, description, type = type_name.as_str()),
+            false,
+        ),
+    ))
+}
+
+async fn notification_type_role_ping(
+    option: &ApplicationCommandInteractionDataOption,
+    lang: Language,
+    client: &CorsClient,
+    acting: UserId,
+    guild: &mut dto::Guild,
+) -> BotResult<SetupEmbed> {
+    let typ = event_type_option(option)?;
+    let type_name = type_name(lang, typ);
+
+    match find_option(option, "role") {
+        Some(ApplicationCommandInteractionDataOption {
+            resolved: Some(ApplicationCommandInteractionDataOptionValue::Role(role)),
+            ..
+        }) => {
+            debug!(?typ, ?role, "notification type role");
+            guild.notif_route_mut(typ).role_ping = Some(role.id.to_string());
+            client.edit_guild_settings(guild, acting).await?;
+            Ok((
+                tr!(lang, "setting-role-set"),
+                (
+                    tr!(lang, "setting-type-role-set", type = type_name.as_str()),
+                    tr!(
+                        lang,
+                        "setting-type-role-set-hint",
+                        type = type_name.as_str(),
+                        role = role.id.to_string()
+                    ),
+                    false,
+                ),
+            ))
+        }
+        Some(_) => {
+            warn!("Not a role");
+            unreachable!()
+        }
+        None => {
+            debug!(?typ, "no role provided");
+            guild.notif_route_mut(typ).role_ping = None;
+            client.edit_guild_settings(guild, acting).await?;
+            Ok((
+                tr!(lang, "setting-type-role-reset", type = type_name.as_str()),
                 (
-                    "Benachrichtigungsrolle ausgeschaltet.".to_string(),
-                    "Die Benachrichtigungsrolle kann jederzeit wieder eingeschaltet werden."
-                        .to_string(),
+                    tr!(lang, "setting-type-role-reset", type = type_name.as_str()),
+                    tr!(lang, "setting-type-role-reset-hint", type = type_name.as_str()),
                     false,
                 ),
             ))
@@ -250,7 +505,170 @@ async fn notification_role_ping(
     }
 }
 
-async fn validate_admin_perms(
+async fn role_mapping(
+    option: &ApplicationCommandInteractionDataOption,
+    lang: Language,
+    client: &CorsClient,
+    acting: UserId,
+    guild: &mut dto::Guild,
+) -> BotResult<SetupEmbed> {
+    let (role, role_key) =
+        match find_option(option, "cors_role").and_then(|role| role.value.as_ref()) {
+            Some(serde_json::Value::String(role)) => match role.as_str() {
+                "owner" => (MemberRole::Owner, "member-roles-owner"),
+                "admin" => (MemberRole::Admin, "member-roles-admin"),
+                "member" => (MemberRole::Member, "member-roles-member"),
+                _ => return Err(BotError::Other("setting has invalid member role")),
+            },
+            _ => return Err(BotError::Other("setting has no member role")),
+        };
+    let role_name = tr!(lang, role_key);
+
+    guild.role_mappings.retain(|mapping| mapping.role != role);
+    match find_option(option, "role") {
+        Some(ApplicationCommandInteractionDataOption {
+            resolved: Some(ApplicationCommandInteractionDataOptionValue::Role(discord_role)),
+            ..
+        }) => {
+            debug!(?role, ?discord_role, "role mapping");
+            guild.role_mappings.push(RoleMapping {
+                role,
+                discord_role: discord_role.id.to_string(),
+            });
+            client.edit_guild_settings(guild, acting).await?;
+            Ok((
+                tr!(lang, "setting-role-mapping-set"),
+                (
+                    tr!(lang, "setting-role-mapping-set"),
+                    tr!(
+                        lang,
+                        "setting-role-mapping-set-hint",
+                        role = role_name.as_str(),
+                        discord_role = discord_role.id.to_string()
+                    ),
+                    false,
+                ),
+            ))
+        }
+        Some(_) => {
+            warn!("Not a role");
+            unreachable!()
+        }
+        None => {
+            debug!(?role, "no role mapping");
+            client.edit_guild_settings(guild, acting).await?;
+            Ok((
+                tr!(lang, "setting-role-mapping-removed"),
+                (
+                    tr!(lang, "setting-role-mapping-removed"),
+                    tr!(
+                        lang,
+                        "setting-role-mapping-removed-hint",
+                        role = role_name.as_str()
+                    ),
+                    false,
+                ),
+            ))
+        }
+    }
+}
+
+/// Shows the changes the role sync would make, without changing anything
+async fn role_sync_dry_run(
+    ctx: &Context,
+    lang: Language,
+    client: &CorsClient,
+    guild_id: GuildId,
+) -> BotResult<SetupEmbed> {
+    // the value of an embed field can't be longer than 1024 characters
+    const MAX_REPORT_LENGTH: usize = 1000;
+
+    let changes = role_sync::plan_guild(&ctx.http, client, guild_id).await?;
+    debug!(?changes, "role sync dry run");
+
+    let (name, value) = match changes {
+        None => (
+            tr!(lang, "setting-role-sync-no-mappings"),
+            tr!(lang, "setting-role-sync-no-mappings-hint"),
+        ),
+        Some(changes) if changes.is_empty() => (
+            tr!(lang, "setting-role-sync-up-to-date"),
+            tr!(lang, "setting-role-sync-up-to-date-hint"),
+        ),
+        Some(changes) => {
+            // only whole lines, a cut off mention would be unreadable
+            let mut report = String::new();
+            for line in changes.iter().map(role_sync::format_change) {
+                if report.len() + line.len() > MAX_REPORT_LENGTH {
+                    report.push_str("...");
+                    break;
+                }
+                report.push_str(&line);
+                report.push('\n');
+            }
+            (
+                tr!(lang, "setting-role-sync-changes", count = changes.len()),
+                report,
+            )
+        }
+    };
+
+    Ok((tr!(lang, "setting-role-sync"), (name, value, false)))
+}
+
+async fn language(
+    option: &ApplicationCommandInteractionDataOption,
+    client: &CorsClient,
+    acting: UserId,
+    guild: &mut dto::Guild,
+    locale: &str,
+) -> BotResult<SetupEmbed> {
+    let language = match find_option(option, "language").and_then(|lang| lang.value.as_ref()) {
+        Some(serde_json::Value::String(code)) => {
+            Some(Language::from_code(code).ok_or(BotError::Other("setting has invalid language"))?)
+        }
+        _ => None,
+    };
+
+    debug!(?language, "language");
+    guild.language = language.map(|lang| lang.code().to_string());
+    client.edit_guild_settings(guild, acting).await?;
+
+    // the confirmation is already in the new language
+    let lang = Language::resolve(guild.language.as_deref(), Some(locale));
+    let (title, description) = match language {
+        Some(_) => ("setting-language-set", "setting-language-set-hint"),
+        None => ("setting-language-reset", "setting-language-reset-hint"),
+    };
+    Ok((
+        tr!(lang, title),
+        (tr!(lang, title), tr!(lang, description), false),
+    ))
+}
+
+pub(super) fn find_option<'a>(
+    option: &'a ApplicationCommandInteractionDataOption,
+    name: &str,
+) -> Option<&'a ApplicationCommandInteractionDataOption> {
+    option.options.iter().find(|option| option.name == name)
+}
+
+pub(super) fn event_type_option(
+    option: &ApplicationCommandInteractionDataOption,
+) -> BotResult<EventType> {
+    match find_option(option, "typ").and_then(|typ| typ.value.as_ref()) {
+        Some(serde_json::Value::String(typ)) => typ
+            .parse()
+            .map_err(|_| BotError::Other("setting has invalid event type")),
+        _ => Err(BotError::Other("setting has no event type")),
+    }
+}
+
+fn type_name(lang: Language, typ: EventType) -> String {
+    tr!(lang, &format!("event-types-{}", typ.as_str()))
+}
+
+pub(super) async fn validate_admin_perms(
     client: &CorsClient,
     userid: UserId,
     class_id: Uuid,
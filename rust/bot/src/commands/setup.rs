@@ -1,16 +1,23 @@
-use serenity::builder::CreateApplicationCommands;
-use serenity::model::interactions::ApplicationCommandOptionType;
+use crate::i18n::Language;
+use serde_json::{Map, Value};
+use serenity::builder::{
+    CreateApplicationCommand, CreateApplicationCommandOption, CreateApplicationCommands,
+};
+use serenity::model::interactions::application_command::{
+    ApplicationCommand, ApplicationCommandOptionType,
+};
 use serenity::model::prelude::*;
 use serenity::prelude::*;
+use std::collections::HashMap;
 use tracing::info;
 
 pub async fn setup_slash_commands(ctx: &Context) {
-    ApplicationCommand::create_global_application_commands(&ctx.http, create_commands)
+    ApplicationCommand::set_global_application_commands(&ctx.http, create_commands)
         .await
         .expect("Could not create slash commands");
 
     GuildId(865480040682749982)
-        .create_application_commands(&ctx.http, create_commands)
+        .set_application_commands(&ctx.http, create_commands)
         .await
         .expect("Could not create slash commands");
 
@@ -22,74 +29,131 @@ fn create_commands(commands: &mut CreateApplicationCommands) -> &mut CreateAppli
         .create_application_command(|command| {
             command
                 .name("wielangenoch")
-                .description("Zeigt an, wie lange die Lektion nocht geht")
+                .localized("command-wielangenoch")
         })
         .create_application_command(|command| {
             command
                 .name("event")
-                .description("Events verwalten")
+                .localized("command-event")
                 .create_option(|option| {
                     option
                         .name("all")
-                        .description("Alle Events anzeigen")
+                        .localized("command-event-all")
                         .kind(ApplicationCommandOptionType::SubCommand)
                 })
                 .create_option(|option| {
                     option
                         .name("next")
-                        .description("Die nächsten Events anzeigen")
+                        .localized("command-event-next")
                         .kind(ApplicationCommandOptionType::SubCommand)
                 })
                 .create_option(|option| {
                     option
                         .name("filter")
-                        .description("Die nächsten Events anzeigen")
+                        .localized("command-event-filter")
                         .kind(ApplicationCommandOptionType::SubCommand)
                         .create_sub_option(|option| {
                             option
                                 .name("typ")
-                                .description("Der Typ nach dem gefiltert werden soll")
+                                .localized("command-event-filter-typ")
                                 .kind(ApplicationCommandOptionType::String)
-                                .add_string_choice("Hausaufgabe", "homework")
-                                .add_string_choice("Prüfung", "exam")
-                                .add_string_choice("Ferien", "holidays")
-                                .add_string_choice("Andere", "other")
+                                .event_type_choices()
                                 .required(true)
                         })
                 })
                 .create_option(|option| {
                     option
                         .name("search")
-                        .description("Die nächsten Events anzeigen")
+                        .localized("command-event-search")
                         .kind(ApplicationCommandOptionType::SubCommand)
                         .create_sub_option(|option| {
                             option
                                 .name("query")
-                                .description("Der Suchterm")
+                                .localized("command-event-search-query")
                                 .kind(ApplicationCommandOptionType::String)
                                 .required(true)
                         })
                 })
+                .create_option(|option| {
+                    option
+                        .name("create")
+                        .localized("command-event-create")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("typ")
+                                .localized("command-event-typ")
+                                .kind(ApplicationCommandOptionType::String)
+                                .event_type_choices()
+                                .required(true)
+                        })
+                        .create_sub_option(|option| {
+                            event_string_option(option, "name").required(true)
+                        })
+                        .create_sub_option(|option| {
+                            event_string_option(option, "start").required(true)
+                        })
+                        .create_sub_option(|option| event_string_option(option, "end"))
+                        .create_sub_option(|option| event_string_option(option, "description"))
+                        .create_sub_option(|option| event_string_option(option, "reminder"))
+                })
+                .create_option(|option| {
+                    option
+                        .name("edit")
+                        .localized("command-event-edit")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(event_choice_option)
+                        .create_sub_option(|option| {
+                            option
+                                .name("typ")
+                                .localized("command-event-typ")
+                                .kind(ApplicationCommandOptionType::String)
+                                .event_type_choices()
+                        })
+                        .create_sub_option(|option| event_string_option(option, "name"))
+                        .create_sub_option(|option| event_string_option(option, "start"))
+                        .create_sub_option(|option| event_string_option(option, "end"))
+                        .create_sub_option(|option| event_string_option(option, "description"))
+                        .create_sub_option(|option| event_string_option(option, "reminder"))
+                })
+                .create_option(|option| {
+                    option
+                        .name("delete")
+                        .localized("command-event-delete")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(event_choice_option)
+                })
+        })
+        .create_application_command(|command| command.name("info").localized("command-info"))
+        .create_application_command(|command| {
+            command.name("stundenplan").localized("command-stundenplan")
         })
-        .create_application_command(|command| command.name("info").description("Botinformationen"))
         .create_application_command(|command| {
             command
-                .name("stundenplan")
-                .description("Zeigt den Stundenplan der Klasse an")
+                .name("link")
+                .localized("command-link")
+                .create_option(|option| {
+                    option
+                        .name("code")
+                        .localized("command-link-code")
+                        .kind(ApplicationCommandOptionType::String)
+                        .required(false)
+                })
         })
+        .create_application_command(|command| command.name("unlink").localized("command-unlink"))
         .create_application_command(|command| {
             command
                 .name("setting")
-                .description("Servereinstellungen")
+                .localized("command-setting")
                 .create_option(|option| {
                     option
                         .name("notification_channel")
-                        .description("Channel für Benachrichtigungen (leer zum Aussschalten)")
+                        .localized("command-setting-notification_channel")
                         .kind(ApplicationCommandOptionType::SubCommand)
                         .create_sub_option(|option| {
                             option
                                 .name("channel")
-                                .description("Der Channel für Benachrichtigungen")
+                                .localized("command-setting-channel")
                                 .kind(ApplicationCommandOptionType::Channel)
                                 .required(false)
                         })
@@ -97,12 +161,12 @@ fn create_commands(commands: &mut CreateApplicationCommands) -> &mut CreateAppli
                 .create_option(|option| {
                     option
                         .name("notification_everyone_ping")
-                        .description("@everyone in Benachrichtigungen pingen")
+                        .localized("command-setting-notification_everyone_ping")
                         .kind(ApplicationCommandOptionType::SubCommand)
                         .create_sub_option(|option| {
                             option
                                 .name("everyone")
-                                .description("@everyone pingen")
+                                .localized("command-setting-everyone")
                                 .kind(ApplicationCommandOptionType::Boolean)
                                 .required(true)
                         })
@@ -110,17 +174,230 @@ fn create_commands(commands: &mut CreateApplicationCommands) -> &mut CreateAppli
                 .create_option(|option| {
                     option
                         .name("notification_role_ping")
-                        .description(
-                            "Eine Rolle in Benachrichtigungen pingen (leer zum Aussschalten)",
-                        )
+                        .localized("command-setting-notification_role_ping")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("role")
+                                .localized("command-setting-role")
+                                .kind(ApplicationCommandOptionType::Role)
+                                .required(false)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("notification_type_channel")
+                        .localized("command-setting-notification_type_channel")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(event_type_option)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .localized("command-setting-type-channel")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(false)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("notification_type_everyone_ping")
+                        .localized("command-setting-notification_type_everyone_ping")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(event_type_option)
+                        .create_sub_option(|option| {
+                            option
+                                .name("everyone")
+                                .localized("command-setting-everyone")
+                                .kind(ApplicationCommandOptionType::Boolean)
+                                .required(false)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("notification_type_role_ping")
+                        .localized("command-setting-notification_type_role_ping")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(event_type_option)
+                        .create_sub_option(|option| {
+                            option
+                                .name("role")
+                                .localized("command-setting-role")
+                                .kind(ApplicationCommandOptionType::Role)
+                                .required(false)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("role_mapping")
+                        .localized("command-setting-role_mapping")
                         .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("cors_role")
+                                .localized("command-setting-cors_role")
+                                .kind(ApplicationCommandOptionType::String)
+                                .localized_choices("member-roles", &["owner", "admin", "member"])
+                                .required(true)
+                        })
                         .create_sub_option(|option| {
                             option
                                 .name("role")
-                                .description("Die role zum pingen")
+                                .localized("command-setting-discord_role")
                                 .kind(ApplicationCommandOptionType::Role)
                                 .required(false)
                         })
                 })
+                .create_option(|option| {
+                    option
+                        .name("role_sync_dry_run")
+                        .localized("command-setting-role_sync_dry_run")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                })
+                .create_option(|option| {
+                    option
+                        .name("admin_channel")
+                        .localized("command-setting-admin_channel")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("channel")
+                                .localized("command-setting-admin-channel")
+                                .kind(ApplicationCommandOptionType::Channel)
+                                .required(false)
+                        })
+                })
+                .create_option(|option| {
+                    option
+                        .name("language")
+                        .localized("command-setting-language")
+                        .kind(ApplicationCommandOptionType::SubCommand)
+                        .create_sub_option(|option| {
+                            option
+                                .name("language")
+                                .localized("command-setting-language-language")
+                                .kind(ApplicationCommandOptionType::String)
+                                .add_string_choice("Deutsch", "de")
+                                .add_string_choice("English", "en")
+                                .add_string_choice("Français", "fr")
+                                .required(false)
+                        })
+                })
         })
 }
+
+fn event_type_option(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("typ")
+        .localized("command-setting-typ")
+        .kind(ApplicationCommandOptionType::String)
+        .event_type_choices()
+        .required(true)
+}
+
+/// An optional string option of `/event create` and `/event edit`
+fn event_string_option<'a>(
+    option: &'a mut CreateApplicationCommandOption,
+    name: &str,
+) -> &'a mut CreateApplicationCommandOption {
+    option
+        .name(name)
+        .localized(&format!("command-event-{}", name))
+        .kind(ApplicationCommandOptionType::String)
+}
+
+/// The event to edit or delete, the choices are suggested by autocomplete
+fn event_choice_option(
+    option: &mut CreateApplicationCommandOption,
+) -> &mut CreateApplicationCommandOption {
+    option
+        .name("event")
+        .localized("command-event-event")
+        .kind(ApplicationCommandOptionType::String)
+        .set_autocomplete(true)
+        .required(true)
+}
+
+/// Adds the translations of the name and description of a command or option
+///
+/// The German description is the default, names are only translated if the catalog of
+/// the language has a `<key>-name` message.
+trait Localize {
+    fn fields(&mut self) -> &mut HashMap<&'static str, Value>;
+
+    fn localized(&mut self, key: &str) -> &mut Self {
+        let name_id = format!("{}-name", key);
+        let description_id = format!("{}-description", key);
+
+        let mut names = Map::new();
+        let mut descriptions = Map::new();
+        for lang in Language::ALL
+            .iter()
+            .filter(|&&lang| lang != Language::German)
+        {
+            for locale in lang.discord_locales() {
+                if lang.has_message(&name_id) {
+                    names.insert(locale.to_string(), tr!(lang, &name_id).into());
+                }
+                descriptions.insert(locale.to_string(), tr!(lang, &description_id).into());
+            }
+        }
+
+        let fields = self.fields();
+        fields.insert("description", tr!(Language::German, &description_id).into());
+        fields.insert("name_localizations", names.into());
+        fields.insert("description_localizations", descriptions.into());
+        self
+    }
+}
+
+impl Localize for CreateApplicationCommand {
+    fn fields(&mut self) -> &mut HashMap<&'static str, Value> {
+        &mut self.0
+    }
+}
+
+impl Localize for CreateApplicationCommandOption {
+    fn fields(&mut self) -> &mut HashMap<&'static str, Value> {
+        &mut self.0
+    }
+}
+
+trait LocalizeChoices: Localize {
+    /// Adds the event types as string choices with translated names
+    fn event_type_choices(&mut self) -> &mut Self {
+        self.localized_choices("event-type", &["homework", "exam", "holidays", "other"])
+    }
+
+    /// Adds string choices, the name of a choice is the message `<prefix>-<value>`
+    fn localized_choices(&mut self, prefix: &str, values: &[&str]) -> &mut Self {
+        let choices = values
+            .iter()
+            .map(|value| {
+                let id = format!("{}-{}", prefix, value);
+                let names = Language::ALL
+                    .iter()
+                    .filter(|&&lang| lang != Language::German)
+                    .flat_map(|lang| {
+                        let name = tr!(lang, &id);
+                        lang.discord_locales()
+                            .iter()
+                            .map(move |locale| (locale.to_string(), Value::from(name.clone())))
+                    })
+                    .collect::<Map<_, _>>();
+
+                serde_json::json!({
+                    "name": tr!(Language::German, &id),
+                    "name_localizations": names,
+                    "value": value,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        self.fields().insert("choices", choices.into());
+        self
+    }
+}
+
+impl LocalizeChoices for CreateApplicationCommandOption {}
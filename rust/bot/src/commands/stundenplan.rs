@@ -1,31 +1,39 @@
+use super::language;
 use crate::error::{BotError, BotResult};
 use crate::functions::format_day_time;
+use crate::i18n::Language;
 use crate::requests::CorsClient;
 use dto::Timetable;
 use serenity::client::Context;
+use serenity::model::interactions::application_command::ApplicationCommandInteraction;
 use serenity::model::prelude::*;
 
 type Embed = (String, Vec<(String, String, bool)>);
 
-pub async fn handle_timetable(ctx: &Context, interaction: &Interaction) -> BotResult<()> {
+pub async fn handle_timetable(
+    ctx: &Context,
+    interaction: &ApplicationCommandInteraction,
+) -> BotResult<()> {
     let guild_id = interaction
         .guild_id
         .ok_or(BotError::Other("Guild id not found"))?;
+    let lang = language(ctx, interaction.guild_id, &interaction.locale).await;
 
     let timetable = {
         let map = ctx.data.read().await;
         let client = map.get::<CorsClient>().unwrap();
-        client.get_timetable(guild_id.0).await?
+        let class = client.get_class(guild_id.0).await?;
+        client.get_timetable(class.id).await?
     };
 
     let result = if let Some(timetable) = timetable {
         if timetable.iter().all(|vec| vec.is_empty()) {
-            not_found()
+            not_found(lang)
         } else {
-            show_timetable(timetable)
+            show_timetable(lang, timetable)
         }
     } else {
-        not_found()
+        not_found(lang)
     };
 
     Ok(interaction
@@ -41,36 +49,36 @@ pub async fn handle_timetable(ctx: &Context, interaction: &Interaction) -> BotRe
         .await?)
 }
 
-fn not_found() -> Embed {
+fn not_found(lang: Language) -> Embed {
     (
-        ("Stundenplan nicht gefunden".to_string()),
+        tr!(lang, "timetable-not-found"),
         vec![(
-            "Für Admins".to_string(),
-            "Aktiviere den Stundenplan in den Admineinstellungen".to_string(),
+            tr!(lang, "timetable-not-found-admins"),
+            tr!(lang, "timetable-not-found-hint"),
             false,
         )],
     )
 }
 
-fn show_timetable(timetable: Timetable) -> Embed {
+fn show_timetable(lang: Language, timetable: Timetable) -> Embed {
     const DAY_NAMES: [&str; 7] = [
-        "Montag",
-        "Dienstag",
-        "Mittwoch",
-        "Donnerstag",
-        "Freitag",
-        "Samstag",
-        "Sonntag",
+        "weekday-monday",
+        "weekday-tuesday",
+        "weekday-wednesday",
+        "weekday-thursday",
+        "weekday-friday",
+        "weekday-saturday",
+        "weekday-sunday",
     ];
 
-    let title = "Stundenplan".to_string();
+    let title = tr!(lang, "timetable-title");
     let fields = timetable
         .iter()
         .zip(DAY_NAMES.iter())
         .filter(|(day, _)| !day.is_empty())
         .map(|(day, name)| {
             (
-                name.to_string(),
+                tr!(lang, name),
                 day.iter()
                     .map(|lesson| {
                         format!(
@@ -1,14 +1,48 @@
-use reqwest::Error;
+use dto::{ApiError, ErrorCode};
+use reqwest::{Error, StatusCode};
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 pub type BotResult<T> = std::result::Result<T, BotError>;
 
 pub enum BotError {
     DiscordError(serenity::Error),
+    /// The request to the CORS api failed, or its response was not what we expected
     CorsApiError(reqwest::Error),
+    /// The api knows nothing about the requested thing
+    NotFound,
+    /// The bot or the discord user it acts for may not do this, like `no-access` or `missing-scope`
+    NoPermission(ErrorCode),
+    /// The api refused the request, like `invalid-link-code` or `member-not-pending`
+    Rejected(ErrorCode),
+    /// Too many requests, with the time until the next one can be made
+    RateLimited(Option<Duration>),
+    /// The api failed, with the id of the request in its logs
+    ServerError(Option<String>),
     Other(&'static str),
 }
 
+impl BotError {
+    /// The error for an error response of the api
+    pub fn from_api_error(status: StatusCode, error: ApiError) -> Self {
+        match error.code {
+            ErrorCode::NotFound => BotError::NotFound,
+            ErrorCode::TooManyRequests => BotError::RateLimited(
+                error
+                    .details
+                    .and_then(|details| details.get("retryAfter")?.as_u64())
+                    .map(Duration::from_secs),
+            ),
+            ErrorCode::InternalError => BotError::ServerError(error.request_id),
+            _ if status.is_server_error() => BotError::ServerError(error.request_id),
+            code if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN => {
+                BotError::NoPermission(code)
+            }
+            code => BotError::Rejected(code),
+        }
+    }
+}
+
 impl Display for BotError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -17,6 +51,17 @@ impl Display for BotError {
             match self {
                 BotError::DiscordError(err) => format!("Discord Error: {:?}", err),
                 BotError::CorsApiError(err) => format!("CORS APi Error: {:?}", err),
+                BotError::NotFound => "CORS API Error: not found".to_string(),
+                BotError::NoPermission(code) => format!("CORS API Error: no permission, {}", code),
+                BotError::Rejected(code) => format!("CORS API Error: rejected, {}", code),
+                BotError::RateLimited(retry_after) => format!(
+                    "CORS API Error: rate limited, retry after {:?}",
+                    retry_after
+                ),
+                BotError::ServerError(request_id) => format!(
+                    "CORS API Error: server error, request {}",
+                    request_id.as_deref().unwrap_or("without id")
+                ),
                 BotError::Other(msg) => format!("Other Error: {}", msg),
             }
         )
@@ -34,3 +79,57 @@ impl From<reqwest::Error> for BotError {
         Self::CorsApiError(err)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::BotError;
+    use dto::{ApiError, ErrorCode};
+    use reqwest::StatusCode;
+    use std::time::Duration;
+
+    fn api_error(json: &str) -> ApiError {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn api_errors() {
+        let err = api_error(r#"{"code": "not-found", "message": "", "requestId": "a"}"#);
+        assert!(matches!(
+            BotError::from_api_error(StatusCode::NOT_FOUND, err),
+            BotError::NotFound
+        ));
+
+        let err = api_error(r#"{"code": "missing-scope", "message": "", "requestId": "a"}"#);
+        assert!(matches!(
+            BotError::from_api_error(StatusCode::UNAUTHORIZED, err),
+            BotError::NoPermission(ErrorCode::MissingScope)
+        ));
+
+        let err = api_error(r#"{"code": "invalid-link-code", "message": ""}"#);
+        assert!(matches!(
+            BotError::from_api_error(StatusCode::BAD_REQUEST, err),
+            BotError::Rejected(ErrorCode::InvalidLinkCode)
+        ));
+
+        let err = api_error(
+            r#"{"code": "too-many-requests", "message": "", "details": {"retryAfter": 3}}"#,
+        );
+        assert!(matches!(
+            BotError::from_api_error(StatusCode::TOO_MANY_REQUESTS, err),
+            BotError::RateLimited(Some(retry)) if retry == Duration::from_secs(3)
+        ));
+
+        let err = api_error(r#"{"code": "internal-error", "message": "", "requestId": "a"}"#);
+        assert!(matches!(
+            BotError::from_api_error(StatusCode::INTERNAL_SERVER_ERROR, err),
+            BotError::ServerError(Some(id)) if id == "a"
+        ));
+
+        // codes of newer servers
+        let err = api_error(r#"{"code": "something-new", "message": ""}"#);
+        assert!(matches!(
+            BotError::from_api_error(StatusCode::CONFLICT, err),
+            BotError::Rejected(ErrorCode::Unknown)
+        ));
+    }
+}
@@ -2,29 +2,60 @@
 //! This makes parts of this bot easily testable
 
 use chrono::format::{DelayedFormat, StrftimeItems};
-use chrono::{DateTime, Datelike, FixedOffset, NaiveDateTime, TimeZone, Utc, Weekday};
-use dto::Timetable;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use dto::{DiscordMember, Event, EventType, RoleMapping, Timetable};
+use uuid::Uuid;
+
+/// Parse the timezone of a class, invalid names fall back to the default timezone
+pub fn parse_timezone(name: &str) -> Tz {
+    name.parse().unwrap_or_else(|_| {
+        dto::DEFAULT_TIMEZONE
+            .parse()
+            .expect("default timezone is valid")
+    })
+}
 
 pub fn from_utc_timestamp(ms: i64) -> DateTime<Utc> {
     Utc.from_utc_datetime(&NaiveDateTime::from_timestamp(ms / 1000, 0))
 }
 
-pub fn from_utc_to_cest(utc: DateTime<Utc>) -> DateTime<FixedOffset> {
-    chrono::FixedOffset::east(2 * 3600).from_utc_datetime(&utc.naive_utc())
+pub fn from_utc_to_local(utc: DateTime<Utc>, tz: Tz) -> DateTime<Tz> {
+    utc.with_timezone(&tz)
+}
+
+pub fn format_date(ms: i64, tz: Tz) -> DelayedFormat<StrftimeItems<'static>> {
+    from_utc_to_local(from_utc_timestamp(ms), tz).format("%d.%m")
 }
 
-pub fn format_date(ms: i64) -> DelayedFormat<StrftimeItems<'static>> {
-    from_utc_to_cest(from_utc_timestamp(ms)).format("%d.%m")
+pub fn format_datetime(ms: i64, tz: Tz) -> DelayedFormat<StrftimeItems<'static>> {
+    from_utc_to_local(from_utc_timestamp(ms), tz).format("%d.%m.%Y %H:%M %Z")
+}
+
+/// Parse a date like `24.12.2021 18:00` or `24.12.2021` in the timezone of the class,
+/// returns the milliseconds since the unix epoch
+pub fn parse_local_datetime(input: &str, tz: Tz) -> Option<i64> {
+    let input = input.trim();
+    let naive = NaiveDateTime::parse_from_str(input, "%d.%m.%Y %H:%M")
+        .or_else(|_| NaiveDate::parse_from_str(input, "%d.%m.%Y").map(|date| date.and_hms(0, 0, 0)))
+        .ok()?;
+
+    // times that are skipped by daylight saving don't exist
+    tz.from_local_datetime(&naive)
+        .earliest()
+        .map(|datetime| datetime.timestamp_millis())
 }
 
 pub fn format_day_time(ms: i64) -> DelayedFormat<StrftimeItems<'static>> {
     NaiveDateTime::from_timestamp(ms / 1000, 0).format("%H:%M")
 }
 
-pub fn absolute_time_as_weekday(now: chrono::DateTime<Utc>) -> (i64, Weekday) {
-    let day_0 = now.date().and_hms(0, 0, 0);
-    let diff = (now - day_0).num_milliseconds();
-    let weekday = now.weekday();
+/// The milliseconds since the local midnight and the local weekday of a point in time
+pub fn absolute_time_as_weekday<T: TimeZone>(now: DateTime<T>) -> (i64, Weekday) {
+    let local = now.naive_local();
+    let day_0 = local.date().and_hms(0, 0, 0);
+    let diff = (local - day_0).num_milliseconds();
+    let weekday = local.weekday();
 
     (diff, weekday)
 }
@@ -57,10 +88,232 @@ pub fn limit_length(string: &str, len: usize) -> String {
     result
 }
 
+/// The state of a paginated event list
+///
+/// It is stored in the custom id of the message components, so the buttons keep
+/// working after a restart of the bot.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct EventPage {
+    pub page: usize,
+    /// Only show events that didn't start yet
+    pub upcoming: bool,
+    pub typ: Option<EventType>,
+    pub query: Option<String>,
+}
+
+impl EventPage {
+    pub const EVENTS_PER_PAGE: usize = 10;
+    pub const BUTTON_PREFIX: &'static str = "events";
+    pub const SELECT_PREFIX: &'static str = "eventtype";
+
+    /// Discord doesn't allow custom ids longer than 100 characters
+    const MAX_CUSTOM_ID_LENGTH: usize = 100;
+
+    pub fn new(upcoming: bool, typ: Option<EventType>, query: Option<String>) -> Self {
+        Self {
+            page: 0,
+            upcoming,
+            typ,
+            query,
+        }
+    }
+
+    /// The custom id for a component, the query gets cut off if it's too long
+    pub fn to_custom_id(&self, prefix: &str) -> String {
+        let mut id = format!(
+            "{}:{}:{}:{}:",
+            prefix,
+            self.page,
+            self.upcoming as u8,
+            self.typ.map(|typ| typ.as_str()).unwrap_or_default()
+        );
+        if let Some(query) = &self.query {
+            id.extend(
+                query
+                    .chars()
+                    .scan(id.len(), |len, c| {
+                        *len += c.len_utf8();
+                        Some((*len, c))
+                    })
+                    .take_while(|(len, _)| *len <= Self::MAX_CUSTOM_ID_LENGTH)
+                    .map(|(_, c)| c),
+            );
+        }
+        id
+    }
+
+    /// Parse a custom id created by [`EventPage::to_custom_id`], returns the prefix and the state
+    pub fn from_custom_id(id: &str) -> Option<(&str, Self)> {
+        let mut parts = id.splitn(5, ':');
+        let prefix = parts.next()?;
+        let page = parts.next()?.parse().ok()?;
+        let upcoming = match parts.next()? {
+            "0" => false,
+            "1" => true,
+            _ => return None,
+        };
+        let typ = match parts.next()? {
+            "" => None,
+            typ => Some(typ.parse().ok()?),
+        };
+        let query = match parts.next()? {
+            "" => None,
+            query => Some(query.to_string()),
+        };
+
+        Some((
+            prefix,
+            Self {
+                page,
+                upcoming,
+                typ,
+                query,
+            },
+        ))
+    }
+
+    /// The events matching the filters, sorted by their start
+    pub fn filter(&self, events: Vec<Event>) -> Vec<Event> {
+        let query = self.query.as_ref().map(|query| query.to_lowercase());
+        let mut events = events
+            .into_iter()
+            .filter(|event| self.typ.is_none_or(|typ| event.r#type == typ))
+            .filter(|event| match &query {
+                Some(query) => {
+                    event.name.to_lowercase().contains(query)
+                        || event.description.to_lowercase().contains(query)
+                }
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        events.sort_unstable_by_key(|event| event.start);
+        events
+    }
+
+    pub fn page_count(event_count: usize) -> usize {
+        event_count.div_ceil(Self::EVENTS_PER_PAGE).max(1)
+    }
+
+    /// The events on the current page, the page is clamped to the last page
+    pub fn page_of<'a>(&mut self, events: &'a [Event]) -> &'a [Event] {
+        self.page = self.page.min(Self::page_count(events.len()) - 1);
+        let start = self.page * Self::EVENTS_PER_PAGE;
+        let end = (start + Self::EVENTS_PER_PAGE).min(events.len());
+        &events[start..end]
+    }
+}
+
+/// The accept or deny button of a join request posted in the admin channel
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct JoinRequestButton {
+    pub accept: bool,
+    pub class: Uuid,
+    pub user: Uuid,
+}
+
+impl JoinRequestButton {
+    pub const PREFIX: &'static str = "joinrequest";
+
+    pub fn to_custom_id(self) -> String {
+        format!(
+            "{}:{}:{}:{}",
+            Self::PREFIX,
+            if self.accept { "accept" } else { "deny" },
+            self.class,
+            self.user
+        )
+    }
+
+    /// Parse a custom id created by [`JoinRequestButton::to_custom_id`]
+    pub fn from_custom_id(id: &str) -> Option<Self> {
+        let mut parts = id.split(':');
+        if parts.next()? != Self::PREFIX {
+            return None;
+        }
+        let accept = match parts.next()? {
+            "accept" => true,
+            "deny" => false,
+            _ => return None,
+        };
+        let class = parts.next()?.parse().ok()?;
+        let user = parts.next()?.parse().ok()?;
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            accept,
+            class,
+            user,
+        })
+    }
+}
+
+/// The mapped discord roles that a guild member gets and loses in the role sync
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RoleChange {
+    pub user: u64,
+    pub add: Vec<u64>,
+    pub remove: Vec<u64>,
+}
+
+/// Compares the roles of the guild members with the role mappings of the guild
+///
+/// The mapped discord roles of linked users are managed by CORS: members get the role of their
+/// class role, all other mapped roles are removed. Banned and former members lose all mapped roles.
+/// Users that the server doesn't report aren't linked and keep their roles, just like unmapped roles.
+pub fn role_changes(
+    mappings: &[RoleMapping],
+    class_members: &[DiscordMember],
+    guild_members: &[(u64, Vec<u64>)],
+) -> Vec<RoleChange> {
+    let mapped_role = |role| {
+        mappings
+            .iter()
+            .find(|mapping| mapping.role == role)
+            .and_then(|mapping| mapping.discord_role.parse::<u64>().ok())
+    };
+    let mapped_roles = mappings
+        .iter()
+        .filter_map(|mapping| mapping.discord_role.parse::<u64>().ok())
+        .collect::<Vec<_>>();
+
+    guild_members
+        .iter()
+        .filter_map(|(user, roles)| {
+            let member = class_members
+                .iter()
+                .find(|member| member.snowflake.parse() == Ok(*user))?;
+            let wanted = member.role.and_then(mapped_role);
+
+            let add = wanted
+                .filter(|role| !roles.contains(role))
+                .into_iter()
+                .collect::<Vec<_>>();
+            let remove = roles
+                .iter()
+                .copied()
+                .filter(|role| mapped_roles.contains(role) && Some(*role) != wanted)
+                .collect::<Vec<_>>();
+
+            if add.is_empty() && remove.is_empty() {
+                None
+            } else {
+                Some(RoleChange {
+                    user: *user,
+                    add,
+                    remove,
+                })
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod test {
+    use super::{EventPage, JoinRequestButton, RoleChange};
     use chrono::*;
-    use dto::{Lesson, Timetable};
+    use dto::{Event, EventType, Lesson, Timetable};
 
     fn monday_10am() -> DateTime<Utc> {
         Utc.datetime_from_str("2021-07-26 10:00:00", "%Y-%m-%d %H:%M:%S")
@@ -82,6 +335,69 @@ mod test {
         assert_eq!(timestamp, ten_am);
     }
 
+    #[test]
+    fn weekday_local_timezone() {
+        // sunday 23:30 UTC is already monday in Zurich
+        let day = Utc
+            .datetime_from_str("2021-07-25 23:30:00", "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .with_timezone(&chrono_tz::Europe::Zurich);
+
+        let (timestamp, weekday) = super::absolute_time_as_weekday(day);
+        assert_eq!(weekday, Weekday::Mon);
+        let one_thirty_am = 90 * 60 * 1000;
+        assert_eq!(timestamp, one_thirty_am);
+    }
+
+    #[test]
+    fn format_datetime_daylight_saving() {
+        let tz = super::parse_timezone("Europe/Zurich");
+        let summer = monday_10am().timestamp_millis();
+        let winter = Utc
+            .datetime_from_str("2021-12-06 10:00:00", "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .timestamp_millis();
+
+        assert_eq!(
+            super::format_datetime(summer, tz).to_string(),
+            "26.07.2021 12:00 CEST"
+        );
+        assert_eq!(
+            super::format_datetime(winter, tz).to_string(),
+            "06.12.2021 11:00 CET"
+        );
+    }
+
+    #[test]
+    fn invalid_timezone_fallback() {
+        assert_eq!(
+            super::parse_timezone("Mars/Olympus_Mons"),
+            chrono_tz::Europe::Zurich
+        );
+        assert_eq!(super::parse_timezone("Asia/Tokyo"), chrono_tz::Asia::Tokyo);
+    }
+
+    #[test]
+    fn parse_local_datetime() {
+        let tz = super::parse_timezone("Europe/Zurich");
+        let summer = monday_10am().timestamp_millis();
+        let midnight = Utc
+            .datetime_from_str("2021-12-05 23:00:00", "%Y-%m-%d %H:%M:%S")
+            .unwrap()
+            .timestamp_millis();
+
+        assert_eq!(
+            super::parse_local_datetime("26.07.2021 12:00", tz),
+            Some(summer)
+        );
+        assert_eq!(
+            super::parse_local_datetime(" 6.12.2021 ", tz),
+            Some(midnight)
+        );
+        assert_eq!(super::parse_local_datetime("28.03.2021 02:30", tz), None);
+        assert_eq!(super::parse_local_datetime("2021-07-26", tz), None);
+    }
+
     #[test]
     fn wielangenoch_no_lesson() {
         let timetable: Timetable = [vec![], vec![], vec![], vec![], vec![], vec![], vec![]];
@@ -148,4 +464,170 @@ mod test {
         assert_eq!(None, lesson);
         assert_eq!(Some(&target_lesson), next);
     }
+
+    fn event(name: &str, typ: EventType, start: i64) -> Event {
+        Event {
+            id: Default::default(),
+            r#type: typ,
+            name: name.to_string(),
+            start,
+            end: None,
+            description: "".to_string(),
+            notification: None,
+        }
+    }
+
+    #[test]
+    fn event_page_custom_id() {
+        let mut state = EventPage::new(true, Some(EventType::Exam), Some("a:b".to_string()));
+        state.page = 3;
+
+        let id = state.to_custom_id(EventPage::BUTTON_PREFIX);
+        assert_eq!(id, "events:3:1:exam:a:b");
+        assert_eq!(
+            EventPage::from_custom_id(&id),
+            Some((EventPage::BUTTON_PREFIX, state))
+        );
+
+        let state = EventPage::new(false, None, None);
+        let id = state.to_custom_id(EventPage::SELECT_PREFIX);
+        assert_eq!(
+            EventPage::from_custom_id(&id),
+            Some((EventPage::SELECT_PREFIX, state))
+        );
+        assert_eq!(EventPage::from_custom_id("events:x:0::"), None);
+    }
+
+    #[test]
+    fn join_request_custom_id() {
+        let button = JoinRequestButton {
+            accept: false,
+            class: "b1b1b1b1-0000-4000-8000-000000000001".parse().unwrap(),
+            user: "b1b1b1b1-0000-4000-8000-000000000002".parse().unwrap(),
+        };
+
+        let id = button.to_custom_id();
+        assert!(id.starts_with("joinrequest:deny:"));
+        assert!(id.len() <= 100);
+        assert_eq!(JoinRequestButton::from_custom_id(&id), Some(button));
+
+        let accept = JoinRequestButton {
+            accept: true,
+            ..button
+        };
+        assert_eq!(
+            JoinRequestButton::from_custom_id(&accept.to_custom_id()),
+            Some(accept)
+        );
+        assert_eq!(
+            JoinRequestButton::from_custom_id("joinrequest:maybe:a:b"),
+            None
+        );
+        assert_eq!(JoinRequestButton::from_custom_id("events:0:0::"), None);
+    }
+
+    #[test]
+    fn event_page_long_query() {
+        let state = EventPage::new(false, None, Some("ä".repeat(100)));
+        let id = state.to_custom_id(EventPage::BUTTON_PREFIX);
+        assert!(id.len() <= 100);
+        assert!(EventPage::from_custom_id(&id).is_some());
+    }
+
+    #[test]
+    fn event_page_pages() {
+        let events = (0..25)
+            .rev()
+            .map(|i| event(&format!("event {}", i), EventType::Homework, i))
+            .chain(std::iter::once(event("exam", EventType::Exam, 100)))
+            .collect::<Vec<_>>();
+
+        let mut state = EventPage::new(false, None, None);
+        let filtered = state.filter(events.clone());
+        assert_eq!(EventPage::page_count(filtered.len()), 3);
+        assert_eq!(state.page_of(&filtered)[0].start, 0);
+
+        state.page = 7;
+        let last = state.page_of(&filtered);
+        assert_eq!(state.page, 2);
+        assert_eq!(last.len(), 6);
+        assert_eq!(last[5].name, "exam");
+
+        let state = EventPage::new(false, Some(EventType::Exam), None);
+        assert_eq!(state.filter(events.clone()).len(), 1);
+        let state = EventPage::new(false, None, Some("EVENT 1".to_string()));
+        assert_eq!(state.filter(events).len(), 11);
+        assert_eq!(EventPage::page_count(0), 1);
+    }
+
+    #[test]
+    fn role_changes() {
+        use dto::{DiscordMember, MemberRole, RoleMapping};
+
+        let mappings = [
+            RoleMapping {
+                role: MemberRole::Admin,
+                discord_role: "10".to_string(),
+            },
+            RoleMapping {
+                role: MemberRole::Member,
+                discord_role: "20".to_string(),
+            },
+        ];
+        let member = |snowflake: &str, role| DiscordMember {
+            snowflake: snowflake.to_string(),
+            role,
+        };
+        let class_members = [
+            member("1", Some(MemberRole::Admin)),
+            member("2", Some(MemberRole::Member)),
+            member("3", Some(MemberRole::Banned)),
+            member("4", Some(MemberRole::Owner)),
+            // kicked or left
+            member("5", None),
+        ];
+        let guild_members = [
+            // promoted to admin
+            (1, vec![20, 99]),
+            // already correct
+            (2, vec![20]),
+            // banned
+            (3, vec![10, 20, 99]),
+            // owners have no mapping
+            (4, vec![10]),
+            // former member
+            (5, vec![20, 99]),
+            // not linked, the mapped role was given by hand
+            (6, vec![20]),
+            (7, vec![99]),
+        ];
+
+        assert_eq!(
+            super::role_changes(&mappings, &class_members, &guild_members),
+            vec![
+                RoleChange {
+                    user: 1,
+                    add: vec![10],
+                    remove: vec![20],
+                },
+                RoleChange {
+                    user: 3,
+                    add: vec![],
+                    remove: vec![10, 20],
+                },
+                RoleChange {
+                    user: 4,
+                    add: vec![],
+                    remove: vec![10],
+                },
+                RoleChange {
+                    user: 5,
+                    add: vec![],
+                    remove: vec![20],
+                },
+            ]
+        );
+
+        assert!(super::role_changes(&[], &class_members, &guild_members).is_empty());
+    }
 }
@@ -0,0 +1,185 @@
+//! Translations of the messages of the bot
+//!
+//! The messages are stored as Fluent files in `bot/locales`, German is the default language.
+//! Messages that are missing in a language fall back to German.
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use once_cell::sync::Lazy;
+use tracing::warn;
+use unic_langid::LanguageIdentifier;
+
+/// Translate a message, arguments are passed as `name = value`
+///
+/// `tr!(lang, "lesson-next", subject = "Math")`
+macro_rules! tr {
+    ($lang:expr, $id:expr) => {
+        $lang.translate($id, None)
+    };
+    ($lang:expr, $id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $lang.translate($id, Some(&args))
+    }};
+}
+
+type Bundle = FluentBundle<FluentResource>;
+
+static BUNDLES: Lazy<Vec<Bundle>> = Lazy::new(|| {
+    Language::ALL
+        .iter()
+        .map(|lang| create_bundle(*lang))
+        .collect()
+});
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Language {
+    German,
+    English,
+    French,
+}
+
+impl Language {
+    /// All languages in the same order as `dto::LANGUAGES`
+    pub const ALL: [Language; 3] = [Language::German, Language::English, Language::French];
+
+    pub fn code(self) -> &'static str {
+        dto::LANGUAGES[self as usize]
+    }
+
+    /// Parse a language code, region subtags like in the discord locale `en-US` are ignored
+    pub fn from_code(code: &str) -> Option<Self> {
+        let code = code.split('-').next()?;
+        Self::ALL.iter().copied().find(|lang| lang.code() == code)
+    }
+
+    /// The language setting of the guild wins over the locale of the user, German is the default
+    pub fn resolve(guild_language: Option<&str>, locale: Option<&str>) -> Self {
+        guild_language
+            .and_then(Self::from_code)
+            .or_else(|| locale.and_then(Self::from_code))
+            .unwrap_or(Language::German)
+    }
+
+    /// The locales that discord uses for this language
+    pub fn discord_locales(self) -> &'static [&'static str] {
+        match self {
+            Language::German => &["de"],
+            Language::English => &["en-US", "en-GB"],
+            Language::French => &["fr"],
+        }
+    }
+
+    /// Whether the language has its own translation of a message
+    pub fn has_message(self, id: &str) -> bool {
+        self.bundle().has_message(id)
+    }
+
+    /// Translate a message, use the `tr!` macro for messages with arguments
+    pub fn translate(self, id: &str, args: Option<&FluentArgs>) -> String {
+        let (bundle, message) = match self.bundle().get_message(id) {
+            Some(message) => (self.bundle(), message),
+            None => match Language::German.bundle().get_message(id) {
+                Some(message) => (Language::German.bundle(), message),
+                None => {
+                    warn!(%id, "Missing message");
+                    return id.to_string();
+                }
+            },
+        };
+
+        let pattern = match message.value() {
+            Some(pattern) => pattern,
+            None => return id.to_string(),
+        };
+
+        let mut errors = vec![];
+        let value = bundle.format_pattern(pattern, args, &mut errors);
+        if !errors.is_empty() {
+            warn!(%id, ?errors, "Could not format message");
+        }
+        value.into_owned()
+    }
+
+    fn bundle(self) -> &'static Bundle {
+        &BUNDLES[self as usize]
+    }
+}
+
+fn create_bundle(lang: Language) -> Bundle {
+    let source = match lang {
+        Language::German => include_str!("../locales/de.ftl"),
+        Language::English => include_str!("../locales/en.ftl"),
+        Language::French => include_str!("../locales/fr.ftl"),
+    };
+
+    let resource = FluentResource::try_new(source.to_string()).expect("Valid fluent file");
+    let id = lang
+        .code()
+        .parse::<LanguageIdentifier>()
+        .expect("Valid language identifier");
+
+    let mut bundle = FluentBundle::new_concurrent(vec![id]);
+    // the unicode isolation marks would break the mentions in messages
+    bundle.set_use_isolating(false);
+    bundle
+        .add_resource(resource)
+        .expect("No duplicate messages");
+    bundle
+}
+
+#[cfg(test)]
+mod test {
+    use super::Language;
+
+    #[test]
+    fn resolve_language() {
+        assert_eq!(Language::resolve(None, None), Language::German);
+        assert_eq!(Language::resolve(None, Some("en-US")), Language::English);
+        assert_eq!(Language::resolve(None, Some("ja")), Language::German);
+        assert_eq!(
+            Language::resolve(Some("fr"), Some("en-GB")),
+            Language::French
+        );
+        assert_eq!(
+            Language::resolve(Some("xx"), Some("en-GB")),
+            Language::English
+        );
+    }
+
+    #[test]
+    fn translate_with_args() {
+        assert_eq!(
+            tr!(Language::French, "lesson-next", subject = "Maths"),
+            "Prochaine leçon : Maths"
+        );
+        assert_eq!(
+            tr!(
+                Language::English,
+                "setting-channel-set-hint",
+                channel = "42"
+            ),
+            "Notifications are now sent in <#42>"
+        );
+    }
+
+    #[test]
+    fn missing_message() {
+        assert_eq!(tr!(Language::English, "not-a-message"), "not-a-message");
+    }
+
+    #[test]
+    fn all_catalogs_have_the_german_messages() {
+        let german = include_str!("../locales/de.ftl");
+        let ids = german
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.is_empty())
+            .filter_map(|line| line.split(" = ").next());
+
+        for id in ids {
+            for lang in Language::ALL.iter() {
+                assert!(lang.has_message(id), "{} missing in {:?}", id, lang);
+            }
+        }
+    }
+}
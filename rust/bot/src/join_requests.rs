@@ -0,0 +1,122 @@
+//! Posts new join requests in the admin channel of the guild, admins answer them with buttons
+
+use crate::error::{BotError, BotResult};
+use crate::functions::{from_utc_timestamp, JoinRequestButton};
+use crate::i18n::Language;
+use crate::requests::CorsClient;
+use dto::JoinRequestNotification;
+use serenity::builder::{CreateComponents, CreateEmbed};
+use serenity::model::id::ChannelId;
+use serenity::model::interactions::message_component::ButtonStyle;
+use serenity::CacheAndHttp;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+const LAST_JOIN_REQUESTS_PATH: &str = "last_join_requests.txt";
+
+pub async fn start_timer(arc: Arc<CacheAndHttp>, client: Arc<CorsClient>) {
+    tokio::time::sleep(Duration::from_secs(10)).await;
+
+    let mut interval = tokio::time::interval(Duration::from_secs(30));
+
+    loop {
+        interval.tick().await;
+        if let Err(why) = send_join_requests(&arc, &client).await {
+            error!(%why, "Error while sending join requests")
+        }
+    }
+}
+
+async fn send_join_requests(http: &CacheAndHttp, client: &CorsClient) -> BotResult<()> {
+    let last = match std::fs::read_to_string(LAST_JOIN_REQUESTS_PATH) {
+        Ok(str) => match str.trim().parse::<i64>() {
+            Ok(n) => n,
+            Err(_) => {
+                warn!("Invalid join request file");
+                chrono::Utc::now().timestamp_millis()
+            }
+        },
+        Err(_) => {
+            warn!("Could not find join request file");
+            chrono::Utc::now().timestamp_millis()
+        }
+    };
+
+    let requests = client.get_join_requests(last).await?;
+
+    std::fs::write(LAST_JOIN_REQUESTS_PATH, requests.time.to_string())
+        .map_err(|_| BotError::Other("could not write join request file"))?;
+
+    let sent_messages = requests
+        .requests
+        .into_iter()
+        .filter_map(|request| {
+            let channel = match request.channel.parse() {
+                Ok(channel) => ChannelId(channel),
+                Err(_) => {
+                    warn!(channel = %request.channel, "Invalid admin channel");
+                    return None;
+                }
+            };
+            let lang = Language::resolve(request.language.as_deref(), None);
+
+            Some(channel.send_message(&http.http, move |msg| {
+                msg.embed(|embed| join_request_embed(embed, lang, &request))
+                    .components(|components| join_request_components(components, lang, &request))
+            }))
+        })
+        .collect::<Vec<_>>();
+
+    futures::future::join_all(sent_messages)
+        .await
+        .iter()
+        .filter(|result| result.is_err())
+        .for_each(|err| debug!(?err, "Error when sending join request"));
+
+    Ok(())
+}
+
+fn join_request_embed<'a>(
+    embed: &'a mut CreateEmbed,
+    lang: Language,
+    request: &JoinRequestNotification,
+) -> &'a mut CreateEmbed {
+    embed
+        .title(tr!(
+            lang,
+            "join-request-title",
+            class = request.class_name.as_str()
+        ))
+        .description(tr!(
+            lang,
+            "join-request-description",
+            name = request.display_name.as_str()
+        ))
+        .timestamp(from_utc_timestamp(request.requested))
+}
+
+fn join_request_components<'a>(
+    components: &'a mut CreateComponents,
+    lang: Language,
+    request: &JoinRequestNotification,
+) -> &'a mut CreateComponents {
+    let button = |accept| JoinRequestButton {
+        accept,
+        class: request.class_id,
+        user: request.user_id,
+    };
+
+    components.create_action_row(|row| {
+        row.create_button(|b| {
+            b.custom_id(button(true).to_custom_id())
+                .label(tr!(lang, "join-request-accept"))
+                .style(ButtonStyle::Success)
+        })
+        .create_button(|b| {
+            b.custom_id(button(false).to_custom_id())
+                .label(tr!(lang, "join-request-deny"))
+                .style(ButtonStyle::Danger)
+        })
+    })
+}
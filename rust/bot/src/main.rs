@@ -4,18 +4,23 @@ use color_eyre::Report;
 use serenity::async_trait;
 use serenity::model::gateway::Ready;
 use serenity::model::interactions::Interaction;
-use serenity::model::prelude::*;
 use serenity::prelude::*;
 use tracing::{error, info};
 use tracing_subscriber::EnvFilter;
 
 use crate::requests::CorsClient;
 
+// the `tr!` macro has to be declared before the modules using it
+#[macro_use]
+mod i18n;
+
 mod commands;
 mod error;
 mod functions;
+mod join_requests;
 mod notifications;
 mod requests;
+mod role_sync;
 
 struct Handler;
 
@@ -33,14 +38,21 @@ impl EventHandler for Handler {
         commands::setup_slash_commands(&ctx).await;
     }
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
-        if interaction.kind == InteractionType::ApplicationCommand {
-            if let Some(InteractionData::ApplicationCommand(data)) = interaction.data.as_ref() {
-                if let Err(why) =
-                    commands::create_interaction_response(&ctx, data, &interaction).await
-                {
-                    error!("Error: {}", why);
-                }
+        let result = match interaction {
+            Interaction::ApplicationCommand(interaction) => {
+                commands::create_interaction_response(&ctx, &interaction).await
+            }
+            Interaction::Autocomplete(interaction) => {
+                commands::create_autocomplete_response(&ctx, &interaction).await
+            }
+            Interaction::MessageComponent(interaction) => {
+                commands::create_component_response(&ctx, &interaction).await
             }
+            _ => Ok(()),
+        };
+
+        if let Err(why) = result {
+            error!("Error: {}", why);
         }
     }
 }
@@ -76,6 +88,14 @@ async fn main() -> std::result::Result<(), Report> {
         client.cache_and_http.clone(),
         cors_client.clone(),
     ));
+    tokio::spawn(join_requests::start_timer(
+        client.cache_and_http.clone(),
+        cors_client.clone(),
+    ));
+    tokio::spawn(role_sync::start_timer(
+        client.cache_and_http.clone(),
+        cors_client.clone(),
+    ));
 
     client.start().await.expect("Could not create client");
 
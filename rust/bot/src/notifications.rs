@@ -1,6 +1,6 @@
-use crate::commands::format_datetime;
 use crate::error::{BotError, BotResult};
-use crate::functions::limit_length;
+use crate::functions::{format_datetime, limit_length, parse_timezone};
+use crate::i18n::Language;
 use crate::requests::CorsClient;
 use dto::Notification;
 use serenity::builder::CreateEmbed;
@@ -112,11 +112,17 @@ fn notification_embed<'a>(embed: &'a mut CreateEmbed, notif: &Notification) -> &
     const MAX_DESCRIPTION_LENGTH: usize = 1000;
 
     let description = limit_length(&notif.event.description, MAX_DESCRIPTION_LENGTH);
+    let lang = Language::resolve(notif.language.as_deref(), None);
+    let start = format_datetime(notif.event.start, parse_timezone(&notif.timezone)).to_string();
 
     embed
-        .title(format!("Benachrichtigung für {}", notif.event.name))
+        .title(tr!(
+            lang,
+            "notification-title",
+            name = notif.event.name.as_str()
+        ))
         .field(
-            format!("Start: {}", format_datetime(notif.event.start)),
+            tr!(lang, "notification-start", time = start),
             &description,
             false,
         )
@@ -1,13 +1,16 @@
 use once_cell::sync::Lazy;
 use reqwest::header::HeaderMap;
-use reqwest::{Client, StatusCode};
+use reqwest::{Client, Response};
 use serenity::model::id::UserId;
 use tracing::debug;
 use uuid::Uuid;
 
-use dto::{Class, Event, GetEventQueryParams, NotificationRes, Timetable};
+use dto::{
+    ApiError, Class, DiscordLinkRequest, DiscordMember, ErrorCode, Event, GetEventQueryParams,
+    JoinRequestRes, LinkCode, MemberAcceptDto, NotificationRes, SingleSnowflake, Timetable,
+};
 
-use crate::error::BotResult;
+use crate::error::{BotError, BotResult};
 
 static BASE_URL: Lazy<String> =
     Lazy::new(|| std::env::var("BACKEND_URL").unwrap_or("http://localhost:8080/api".to_string()));
@@ -16,6 +19,40 @@ pub struct CorsClient {
     client: reqwest::Client,
 }
 
+/// What happened when an admin answered a join request from discord
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum JoinRequestReply {
+    Done,
+    /// The discord user isn't linked or not an admin of the class
+    NoRights,
+    /// The request was already accepted or denied, or the user left
+    AlreadyHandled,
+}
+
+/// The response if it was successful, or the [`BotError`] of the error in its body
+async fn check(res: Response) -> BotResult<Response> {
+    let status = res.status();
+    if status.is_success() {
+        return Ok(res);
+    }
+
+    // for error responses that are not from the api, like from a proxy in front of it
+    let status_err = res.error_for_status_ref().err();
+    match res.json::<ApiError>().await {
+        Ok(error) => Err(BotError::from_api_error(status, error)),
+        Err(err) => Err(status_err.unwrap_or(err).into()),
+    }
+}
+
+/// Like [`check`], but `None` if the api doesn't know the requested thing
+async fn check_found(res: Response) -> BotResult<Option<Response>> {
+    match check(res).await {
+        Ok(res) => Ok(Some(res)),
+        Err(BotError::NotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
 impl CorsClient {
     pub fn from_token(token: String) -> Self {
         let mut headers = HeaderMap::new();
@@ -46,20 +83,66 @@ impl CorsClient {
             .await?;
         debug!(res = %res.status(), "Get notification response status");
 
-        let data = res.json::<NotificationRes>().await?;
+        let data = check(res).await?.json::<NotificationRes>().await?;
         Ok(data)
     }
 
+    pub async fn get_join_requests(&self, old_timestamp: i64) -> BotResult<JoinRequestRes> {
+        debug!(after = %old_timestamp, "Getting join requests");
+
+        let res = self
+            .client
+            .get(format!(
+                "{}/bot/join-requests?since={}",
+                *BASE_URL, old_timestamp
+            ))
+            .send()
+            .await?;
+        debug!(res = %res.status(), "Get join requests response status");
+
+        Ok(check(res).await?.json().await?)
+    }
+
+    /// Accept or deny a join request on behalf of the linked discord user
+    pub async fn reply_to_join_request(
+        &self,
+        class_id: Uuid,
+        user_id: Uuid,
+        accept: bool,
+        acting: UserId,
+    ) -> BotResult<JoinRequestReply> {
+        let res = self
+            .client
+            .post(format!(
+                "{}/classes/{}/requests/{}",
+                *BASE_URL, class_id, user_id
+            ))
+            .header("Discord-User", acting.to_string())
+            .json(&MemberAcceptDto { accept })
+            .send()
+            .await?;
+
+        debug!(status = %res.status(), "Reply to join request");
+        match check(res).await {
+            Ok(_) => Ok(JoinRequestReply::Done),
+            Err(BotError::NoPermission(_)) => Ok(JoinRequestReply::NoRights),
+            Err(BotError::Rejected(_) | BotError::NotFound) => Ok(JoinRequestReply::AlreadyHandled),
+            Err(err) => Err(err),
+        }
+    }
+
     pub async fn get_events(
         &self,
-        guild_id: u64,
+        class_id: Uuid,
         before: Option<i64>,
         after: Option<i64>,
     ) -> BotResult<Vec<Event>> {
-        let class_id = self.get_class(guild_id).await?.id;
-
-        let params = serde_url_params::to_string(&GetEventQueryParams { before, after })
-            .expect("Query params be valid");
+        let params = serde_url_params::to_string(&GetEventQueryParams {
+            before,
+            after,
+            day: None,
+        })
+        .expect("Query params be valid");
 
         let res = self
             .client
@@ -73,37 +156,98 @@ impl CorsClient {
         debug!(status = %res.status(), "Get events status");
         debug!(params = %params, "Get events sent params");
 
-        let events = res.json().await?;
+        let events = check(res).await?.json().await?;
         Ok(events)
     }
 
-    pub async fn get_timetable(&self, guild_id: u64) -> BotResult<Option<Timetable>> {
-        let class_id = self.get_class(guild_id).await?.id;
+    /// Create an event on behalf of the linked discord user, so their rights in the class are checked
+    pub async fn create_event(
+        &self,
+        class_id: Uuid,
+        event: &Event,
+        acting: UserId,
+    ) -> BotResult<Event> {
+        let res = self
+            .client
+            .post(format!("{}/classes/{}/events", *BASE_URL, class_id))
+            .header("Discord-User", acting.to_string())
+            .json(event)
+            .send()
+            .await?;
+
+        debug!(status = %res.status(), "Create event");
+        Ok(check(res).await?.json().await?)
+    }
+
+    /// Edit an event on behalf of the linked discord user
+    pub async fn edit_event(
+        &self,
+        class_id: Uuid,
+        event: &Event,
+        acting: UserId,
+    ) -> BotResult<Event> {
+        let res = self
+            .client
+            .put(format!(
+                "{}/classes/{}/events/{}",
+                *BASE_URL, class_id, event.id
+            ))
+            .header("Discord-User", acting.to_string())
+            .json(event)
+            .send()
+            .await?;
+
+        debug!(status = %res.status(), "Edit event");
+        Ok(check(res).await?.json().await?)
+    }
+
+    /// Delete an event on behalf of the linked discord user
+    pub async fn delete_event(
+        &self,
+        class_id: Uuid,
+        event_id: Uuid,
+        acting: UserId,
+    ) -> BotResult<()> {
+        let res = self
+            .client
+            .delete(format!(
+                "{}/classes/{}/events/{}",
+                *BASE_URL, class_id, event_id
+            ))
+            .header("Discord-User", acting.to_string())
+            .send()
+            .await?;
+
+        debug!(status = %res.status(), "Delete event");
+        check(res).await?;
+        Ok(())
+    }
 
+    pub async fn get_timetable(&self, class_id: Uuid) -> BotResult<Option<Timetable>> {
         let res = self
             .client
             .get(format!("{}/classes/{}/timetable", *BASE_URL, class_id))
             .send()
             .await?;
 
-        if res.status() == 404 {
-            Ok(None)
-        } else {
-            let timetable = res.json().await?;
-            Ok(Some(timetable))
+        match check_found(res).await? {
+            Some(res) => Ok(Some(res.json().await?)),
+            None => Ok(None),
         }
     }
 
-    pub async fn edit_guild_settings(&self, guild: &dto::Guild) -> BotResult<()> {
+    /// Change the guild settings on behalf of the linked discord user
+    pub async fn edit_guild_settings(&self, guild: &dto::Guild, acting: UserId) -> BotResult<()> {
         let res = self
             .client
             .put(format!("{}/bot/guilds", *BASE_URL))
+            .header("Discord-User", acting.to_string())
             .json(guild)
             .send()
             .await?;
 
         debug!(status = %res.status());
-        res.error_for_status()?;
+        check(res).await?;
         Ok(())
     }
 
@@ -115,11 +259,25 @@ impl CorsClient {
             .await?;
 
         debug!(status = %res.status());
-        if let StatusCode::NOT_FOUND = res.status() {
-            return Ok(None);
+        match check_found(res).await? {
+            Some(res) => Ok(Some(res.json().await?)),
+            None => Ok(None),
         }
+    }
 
-        Ok(Some(res.json().await?))
+    /// The linked members of the class of the guild, `None` if the guild has no class
+    pub async fn get_guild_members(&self, guild_id: u64) -> BotResult<Option<Vec<DiscordMember>>> {
+        let res = self
+            .client
+            .get(format!("{}/bot/guilds/{}/members", *BASE_URL, guild_id))
+            .send()
+            .await?;
+
+        debug!(status = %res.status(), "Get guild members");
+        match check_found(res).await? {
+            Some(res) => Ok(Some(res.json().await?)),
+            None => Ok(None),
+        }
     }
 
     pub async fn get_member(&self, id: UserId, class_id: Uuid) -> BotResult<Option<dto::Member>> {
@@ -131,11 +289,10 @@ impl CorsClient {
             .await?;
 
         debug!(status = %res.status(), "Get user");
-        if let StatusCode::NOT_FOUND = res.status() {
-            return Ok(None);
-        }
-
-        let user = res.json::<dto::User>().await?;
+        let user = match check_found(res).await? {
+            Some(res) => res.json::<dto::User>().await?,
+            None => return Ok(None),
+        };
         let res2 = self
             .client
             .get(format!(
@@ -146,12 +303,57 @@ impl CorsClient {
             .await?;
 
         debug!(status = %res2.status(), "Get member");
-        if let StatusCode::NOT_FOUND = res2.status() {
-            return Ok(None);
+        match check_found(res2).await? {
+            Some(res) => Ok(Some(res.json::<dto::Member>().await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn create_link_code(&self, id: UserId) -> BotResult<LinkCode> {
+        let res = self
+            .client
+            .post(format!("{}/bot/link-codes", *BASE_URL))
+            .json(&SingleSnowflake {
+                snowflake: id.to_string(),
+            })
+            .send()
+            .await?;
+
+        debug!(status = %res.status(), "Create link code");
+        Ok(check(res).await?.json().await?)
+    }
+
+    /// Link the discord user with the CORS account that created the code,
+    /// returns `false` if the code is invalid
+    pub async fn link_with_code(&self, id: UserId, code: &str) -> BotResult<bool> {
+        let res = self
+            .client
+            .post(format!("{}/bot/link", *BASE_URL))
+            .json(&DiscordLinkRequest {
+                code: code.to_string(),
+                snowflake: id.to_string(),
+            })
+            .send()
+            .await?;
+
+        debug!(status = %res.status(), "Link with code");
+        match check(res).await {
+            Ok(_) => Ok(true),
+            Err(BotError::Rejected(ErrorCode::InvalidLinkCode)) => Ok(false),
+            Err(err) => Err(err),
         }
-        let member = res2.json::<dto::Member>().await?;
+    }
+
+    /// Unlink the discord user, returns `false` if the user wasn't linked
+    pub async fn unlink(&self, id: UserId) -> BotResult<bool> {
+        let res = self
+            .client
+            .delete(format!("{}/users/discord/{}/link", *BASE_URL, id.0))
+            .send()
+            .await?;
 
-        Ok(Some(member))
+        debug!(status = %res.status(), "Unlink");
+        Ok(check_found(res).await?.is_some())
     }
 
     pub async fn get_class(&self, guild_id: u64) -> BotResult<Class> {
@@ -161,7 +363,7 @@ impl CorsClient {
             .send()
             .await?;
         debug!(status = %res.status());
-        let class = res.json::<dto::Class>().await?;
+        let class = check(res).await?.json::<dto::Class>().await?;
         Ok(class)
     }
 }
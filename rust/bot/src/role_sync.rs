@@ -0,0 +1,122 @@
+//! Keeps the discord roles of linked class members in sync with their roles in the class
+
+use crate::error::BotResult;
+use crate::functions::{role_changes, RoleChange};
+use crate::requests::CorsClient;
+use serenity::http::Http;
+use serenity::model::guild::Member;
+use serenity::model::id::{GuildId, RoleId, UserId};
+use serenity::model::misc::Mentionable;
+use serenity::CacheAndHttp;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error, warn};
+
+const SYNC_INTERVAL_SECS: u64 = 15 * 60;
+/// The maximum amount of members discord returns at once
+const MEMBERS_PAGE_SIZE: u64 = 1000;
+
+pub async fn start_timer(arc: Arc<CacheAndHttp>, client: Arc<CorsClient>) {
+    // wait for the guilds to be cached
+    tokio::time::sleep(Duration::from_secs(30)).await;
+
+    let mut interval = tokio::time::interval(Duration::from_secs(SYNC_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+        for guild_id in arc.cache.guilds().await {
+            if let Err(why) = sync_guild(&arc.http, &client, guild_id).await {
+                error!(%why, %guild_id, "Error while syncing roles")
+            }
+        }
+    }
+}
+
+/// The role changes the sync would make, `None` if the guild has no class or no role mappings
+pub async fn plan_guild(
+    http: &Http,
+    client: &CorsClient,
+    guild_id: GuildId,
+) -> BotResult<Option<Vec<RoleChange>>> {
+    let guild = match client.get_guild(guild_id.0).await? {
+        Some(guild) if !guild.role_mappings.is_empty() => guild,
+        _ => return Ok(None),
+    };
+    let class_members = match client.get_guild_members(guild_id.0).await? {
+        Some(members) => members,
+        None => return Ok(None),
+    };
+
+    let guild_members = all_members(http, guild_id)
+        .await?
+        .into_iter()
+        // bots are never class members
+        .filter(|member| !member.user.bot)
+        .map(|member| {
+            let roles = member.roles.iter().map(|role| role.0).collect();
+            (member.user.id.0, roles)
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Some(role_changes(
+        &guild.role_mappings,
+        &class_members,
+        &guild_members,
+    )))
+}
+
+/// A line of the dry run report, e.g. `@user: +@Admin -@Member`
+pub fn format_change(change: &RoleChange) -> String {
+    let added = change
+        .add
+        .iter()
+        .map(|role| format!(" +{}", RoleId(*role).mention()));
+    let removed = change
+        .remove
+        .iter()
+        .map(|role| format!(" -{}", RoleId(*role).mention()));
+
+    format!("{}:", UserId(change.user).mention()) + &added.chain(removed).collect::<String>()
+}
+
+async fn sync_guild(http: &Http, client: &CorsClient, guild_id: GuildId) -> BotResult<()> {
+    let changes = match plan_guild(http, client, guild_id).await? {
+        Some(changes) => changes,
+        None => return Ok(()),
+    };
+    debug!(%guild_id, changes = changes.len(), "sync roles");
+
+    // a single role that the bot can't manage shouldn't stop the rest of the sync
+    for change in changes {
+        for role in &change.add {
+            if let Err(why) = http.add_member_role(guild_id.0, change.user, *role).await {
+                warn!(%why, %guild_id, user = change.user, role, "Could not add role");
+            }
+        }
+        for role in &change.remove {
+            if let Err(why) = http
+                .remove_member_role(guild_id.0, change.user, *role)
+                .await
+            {
+                warn!(%why, %guild_id, user = change.user, role, "Could not remove role");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn all_members(http: &Http, guild_id: GuildId) -> BotResult<Vec<Member>> {
+    let mut members = Vec::<Member>::new();
+    loop {
+        let after = members.last().map(|member| member.user.id);
+        let page = guild_id
+            .members(http, Some(MEMBERS_PAGE_SIZE), after)
+            .await?;
+        let last_page = (page.len() as u64) < MEMBERS_PAGE_SIZE;
+        members.extend(page);
+        if last_page {
+            return Ok(members);
+        }
+    }
+}
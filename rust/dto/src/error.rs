@@ -0,0 +1,149 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+/// The body of every error response of the api
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiError {
+    pub code: ErrorCode,
+    /// A description for developers, in english
+    pub message: String,
+    /// More about the error, like `retryAfter` in seconds for `too-many-requests`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+    /// The id of the request in the logs of the server, also in the `X-Request-Id` header
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+macro_rules! error_codes {
+    ($($variant:ident => $code:literal: $message:literal,)*) => {
+        /// The stable code of an api error, the same as the `code` string in the json
+        ///
+        /// Codes of newer servers are deserialized as [`ErrorCode::Unknown`].
+        ///
+        /// ```
+        /// use dto::ErrorCode;
+        ///
+        /// assert_eq!("invalid-token", ErrorCode::InvalidToken.as_str());
+        /// assert_eq!(Ok(ErrorCode::NotFound), "not-found".parse());
+        /// assert_eq!(Err(()), "hugo".parse::<ErrorCode>());
+        /// ```
+        #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize)]
+        pub enum ErrorCode {
+            $(
+                #[serde(rename = $code)]
+                $variant,
+            )*
+            #[serde(other, rename = "unknown")]
+            Unknown,
+        }
+
+        impl ErrorCode {
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $code,)*
+                    ErrorCode::Unknown => "unknown",
+                }
+            }
+
+            /// The default message of the code
+            pub fn message(&self) -> &'static str {
+                match self {
+                    $(ErrorCode::$variant => $message,)*
+                    ErrorCode::Unknown => "Unknown error",
+                }
+            }
+        }
+
+        impl std::str::FromStr for ErrorCode {
+            type Err = ();
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($code => Ok(ErrorCode::$variant),)*
+                    _ => Err(()),
+                }
+            }
+        }
+    };
+}
+
+error_codes! {
+    // general
+    NotFound => "not-found": "The resource does not exist",
+    InvalidRequest => "invalid-request": "The request body, path or query is invalid",
+    InvalidUuid => "invalid-uuid": "An id is not a valid uuid",
+    AlreadyExists => "already-exists": "The resource already exists",
+    DoesNotExist => "does-not-exist": "A referenced resource does not exist",
+    TooManyRequests => "too-many-requests": "Too many requests, try again later",
+    InternalError => "internal-error": "Internal server error",
+
+    // auth
+    InvalidEmailPassword => "invalid-email-password": "The email or the password is wrong",
+    WrongPassword => "wrong-password": "The password is wrong",
+    NoToken => "no-token": "The request has no token",
+    InvalidToken => "invalid-token": "The token is invalid",
+    TokenExpired => "token-expired": "The token is expired",
+    OldToken => "old-token": "The refresh token is outdated, log in again",
+    WrongTokenKind => "wrong-token-kind": "The token can't be used for this route",
+    TokenReused => "token-reused": "The refresh token was already used, the session is ended",
+    SessionRevoked => "session-revoked": "The session was ended",
+    NoSession => "no-session": "The token belongs to no session",
+    TokenReadOnly => "token-read-only": "The access token can only read",
+    TokenNoAccess => "token-no-access": "The access token has no scope for the class",
+    InvalidScope => "invalid-scope": "The scope of the access token is invalid",
+    InvalidExpiry => "invalid-expiry": "The expiry of the access token is invalid",
+    InvalidTokenName => "invalid-token-name": "The name of the access token is invalid",
+    BotOnly => "bot-only": "Only the bot can use this route",
+    InvalidApiKey => "invalid-api-key": "The api key is unknown or revoked",
+    MissingScope => "missing-scope": "The api key lacks the scope",
+    InvalidChallenge => "invalid-challenge": "The two-factor challenge is invalid or expired",
+    InvalidCode => "invalid-code": "The two-factor code is wrong",
+    TwoFactorRequired => "two-factor-required": "The class requires two-factor authentication",
+    TwoFactorEnabled => "two-factor-enabled": "Two-factor authentication is already enabled",
+    TwoFactorNotEnabled => "two-factor-not-enabled": "Two-factor authentication is not enabled",
+    NoEnrollment => "no-enrollment": "No two-factor enrollment was started",
+    InvalidResetToken => "invalid-reset-token": "The password reset token is invalid",
+    InvalidVerificationToken => "invalid-verification-token": "The email verification token is invalid",
+    AlreadyVerified => "already-verified": "The email is already verified",
+    EmailNotVerified => "email-not-verified": "The email must be verified first",
+    PasswordTooShort => "password-too-short": "The password is too short",
+    PasswordTooLong => "password-too-long": "The password is too long",
+    PasswordNeedsLetter => "password-needs-letter": "The password needs a letter",
+    PasswordNeedsDigit => "password-needs-digit": "The password needs a digit",
+    InvalidEmail => "invalid-email": "The email is invalid",
+    SignupDisabled => "signup-disabled": "New accounts can't be created on this server",
+    DeletionNotConfirmed => "deletion-not-confirmed": "The owned classes of the account must be confirmed",
+
+    // discord
+    InvalidOauthCode => "invalid-oauth-code": "The discord oauth code is invalid",
+    DiscordEmailMissing => "discord-email-missing": "The discord account has no verified email",
+    EmailLinkedWithOtherDiscord => "email-linked-with-other-discord": "The email is linked with another discord account",
+    EmailAccountNotVerified => "email-account-not-verified": "An account with the email exists but its email isn't verified, log in and link discord there",
+    InvalidSnowflake => "invalid-snowflake": "The discord id is invalid",
+    InvalidLinkCode => "invalid-link-code": "The link code is invalid or expired",
+    InvalidDiscordUser => "invalid-discord-user": "The Discord-User header is invalid",
+    InvalidRoleMapping => "invalid-role-mapping": "The role mapping is invalid",
+    InvalidLanguage => "invalid-language": "The language is not supported",
+
+    // classes
+    NoAdmin => "no-admin": "Only admins can do this",
+    NoAccess => "no-access": "The user is not a member of the class",
+    NotEnoughPermissions => "not-enough-permissions": "The role of the user is too low",
+    NoOwner => "no-owner": "Only the owner can do this",
+    MustHaveOwner => "must-have-owner": "The class must keep an owner",
+    Banned => "banned": "The user is banned from the class",
+    MemberNotPending => "member-not-pending": "The member has no pending join request",
+    NoClassId => "no-class-id": "The request has no class id",
+    InvalidTimezone => "invalid-timezone": "The timezone is unknown",
+    InvalidDay => "invalid-day": "The day is not a date like 2021-08-05",
+    UnsupportedArchiveVersion => "unsupported-archive-version": "The class archive is from a newer server",
+    ClassArchived => "class-archived": "The class is archived and can only be read",
+}
+
+impl Display for ErrorCode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
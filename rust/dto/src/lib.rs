@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::cmp::Ordering;
+use std::str::FromStr;
+
+mod error;
+
+pub use error::{ApiError, ErrorCode};
 
 /// A UTC Unix timestamp in seconds
 type Timestamp = i64;
@@ -13,6 +18,22 @@ type Uuid = uuid::Uuid;
 /// A discord Snowflake id
 type Snowflake = String;
 
+/// The name of a timezone in the IANA timezone database, for example `Europe/Zurich`
+type TimezoneName = String;
+
+/// The timezone of classes that don't set their own timezone
+pub const DEFAULT_TIMEZONE: &str = "Europe/Zurich";
+
+fn default_timezone() -> TimezoneName {
+    DEFAULT_TIMEZONE.to_string()
+}
+
+/// The code of a language the bot can answer in, for example `fr`
+type LanguageCode = String;
+
+/// All languages the bot has translations for, the first one is the default
+pub const LANGUAGES: [&str; 3] = ["de", "en", "fr"];
+
 /// A class event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Event {
@@ -56,6 +77,28 @@ impl EventType {
     }
 }
 
+/// Parse an event type from its string representation
+///
+/// ```
+/// use dto::EventType;
+///
+/// assert_eq!(Ok(EventType::Exam), "exam".parse());
+/// assert_eq!(Err(()), "hugo".parse::<EventType>());
+/// ```
+impl FromStr for EventType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "homework" => Ok(EventType::Homework),
+            "exam" => Ok(EventType::Exam),
+            "holidays" => Ok(EventType::Holidays),
+            "other" => Ok(EventType::Other),
+            _ => Err(()),
+        }
+    }
+}
+
 /// A Class
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -69,6 +112,16 @@ pub struct Class {
     pub description: String,
     #[serde(default)]
     pub discord_id: Option<String>,
+    /// Requests without a timezone keep the current timezone of the class
+    #[serde(default)]
+    pub timezone: Option<TimezoneName>,
+    /// Whether members with rights need two-factor authentication,
+    /// requests without it keep the current setting
+    #[serde(default)]
+    pub require_two_factor: Option<bool>,
+    /// Archived classes can only be read, set by the server, ignored in requests
+    #[serde(default)]
+    pub archived: bool,
 }
 
 /// A User
@@ -82,6 +135,12 @@ pub struct User {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub classes: Option<Vec<Class>>,
+    /// Set by the server, ignored in requests
+    #[serde(default, rename = "emailVerified")]
+    pub email_verified: bool,
+    /// Set by the server for the own user, ignored in requests
+    #[serde(default, rename = "twoFactorEnabled")]
+    pub two_factor_enabled: bool,
 }
 
 /// The user for the `POST /users` route, with a password
@@ -153,7 +212,7 @@ pub struct Lesson {
 
 impl PartialOrd for Lesson {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.start.partial_cmp(&other.start)
+        Some(self.cmp(other))
     }
 }
 
@@ -176,6 +235,26 @@ pub struct LoginResponse {
     pub expires: Timestamp,
 }
 
+/// A public key that verifies the JWTs, see RFC 7517
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(rename = "use")]
+    pub use_: String,
+    pub alg: String,
+    pub kid: String,
+    /// The modulus of the RSA key, base64url
+    pub n: String,
+    /// The exponent of the RSA key, base64url
+    pub e: String,
+}
+
+/// Response of /.well-known/jwks.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Jwks {
+    pub keys: Vec<Jwk>,
+}
+
 /// Request body of /classes/{uuid}/requests/{uuid}
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MemberAcceptDto {
@@ -190,6 +269,18 @@ pub struct UserLogin {
     pub password: String,
 }
 
+/// Request body of POST /login/discord, the code is the OAuth2 authorization code from discord
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscordLoginRequest {
+    pub code: String,
+}
+
+/// Query params of GET /login/discord, the state is passed through to the redirect
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscordAuthorizeQuery {
+    pub state: Option<String>,
+}
+
 /// Response body of POST /users
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserPostResponse {
@@ -202,10 +293,235 @@ pub struct SingleSnowflake {
     pub snowflake: Snowflake,
 }
 
+/// A one-time code to link a CORS account with a discord account
+///
+/// Codes created by the bot are entered on the website and the other way around
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCode {
+    pub code: String,
+    pub expires: Timestamp,
+}
+
+/// Redeem a link code created on the website for a discord account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscordLinkRequest {
+    pub code: String,
+    pub snowflake: Snowflake,
+}
+
+/// Redeem a link code created by the bot for the own account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserLinkRequest {
+    pub code: String,
+}
+
+/// The access of a personal access token to a class, tokens can only read unless `write` is set
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessTokenScope {
+    pub class: Uuid,
+    pub write: bool,
+}
+
+/// A personal access token of a user, the token itself is only returned when it's created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub id: Uuid,
+    pub name: String,
+    pub created: Timestamp,
+    pub expires: Option<Timestamp>,
+    pub scopes: Vec<AccessTokenScope>,
+}
+
+/// Request body of POST /users/me/tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostAccessToken {
+    pub name: String,
+    pub expires: Option<Timestamp>,
+    pub scopes: Vec<AccessTokenScope>,
+}
+
+/// # IMPORTANT: never log the token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessTokenPostResponse {
+    pub token: String,
+    pub access_token: AccessToken,
+}
+
+/// A login of a user on a device
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Session {
+    pub id: Uuid,
+    pub device: String,
+    pub created: Timestamp,
+    pub last_used: Timestamp,
+    /// Whether this is the session of the token of the request
+    pub current: bool,
+}
+
+/// The version of [`UserExport`], it changes when fields are removed or change their meaning
+pub const USER_EXPORT_VERSION: u32 = 1;
+
+/// All personal data of a user, response of GET /users/me/export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserExport {
+    pub version: u32,
+    pub exported: Timestamp,
+    pub user: User,
+    pub discord_id: Option<Snowflake>,
+    pub memberships: Vec<Membership>,
+    pub owned_classes: Vec<ClassData>,
+    pub access_tokens: Vec<AccessToken>,
+    pub sessions: Vec<Session>,
+}
+
+/// The membership of a user in a class, or an invite to one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Membership {
+    pub class: Uuid,
+    pub class_name: String,
+    pub display_name: String,
+    pub role: MemberRole,
+}
+
+/// A class with its events and its timetable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassData {
+    pub class: Class,
+    pub events: Vec<Event>,
+    pub timetable: Option<Timetable>,
+}
+
+/// What happens to a class the user owns when they delete their account
+///
+/// Response of GET /users/me/deletion
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnedClassDeletion {
+    pub class: Uuid,
+    pub name: String,
+    /// The admin that becomes the owner with [`OwnedClassesAction::Transfer`],
+    /// without one the class is deleted
+    pub next_owner: Option<Member>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OwnedClassesAction {
+    /// Every class goes to its next owner, classes without one are deleted
+    #[default]
+    Transfer,
+    Delete,
+}
+
+/// Request body of DELETE /users/me, only needed if the user owns classes
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteUserRequest {
+    #[serde(default)]
+    pub owned_classes: OwnedClassesAction,
+    /// The ids of all owned classes, to confirm what happens to them
+    #[serde(default)]
+    pub confirm: Vec<Uuid>,
+}
+
+/// The version of [`ClassArchive`], it changes when fields are removed or change their meaning
+pub const CLASS_ARCHIVE_VERSION: u32 = 1;
+
+/// A class with everything in it, to back it up or to move it to another server
+///
+/// Response of GET /classes/{classid}/export, request of POST /classes/import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassArchive {
+    pub version: u32,
+    #[serde(default)]
+    pub exported: Timestamp,
+    /// Without members, they are in `members`
+    pub class: Class,
+    pub members: Vec<ArchivedMember>,
+    #[serde(default)]
+    pub events: Vec<Event>,
+    #[serde(default)]
+    pub timetable: Option<Timetable>,
+    /// The settings of the linked guild, an import doesn't link it
+    #[serde(default)]
+    pub guild: Option<Guild>,
+}
+
+/// A member in a [`ClassArchive`], users are found by their email on the other server
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchivedMember {
+    pub email: String,
+    pub display_name: String,
+    pub role: MemberRole,
+}
+
+/// Response of POST /classes/import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassImportResponse {
+    pub class: Class,
+    /// The amount of members that were invited, they join when they accept the invite
+    pub invited: usize,
+}
+
+/// Request of POST /classes/{classid}/clone, for the next school year
+///
+/// Nothing but the name and the description is copied without the options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloneClassRequest {
+    pub name: String,
+    /// The description of the source class without it
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Copies the members and the bans, pending join requests are not copied
+    #[serde(default)]
+    pub copy_members: bool,
+    /// Copied members are all members, the admins have to be set again
+    #[serde(default)]
+    pub reset_roles: bool,
+    #[serde(default)]
+    pub copy_timetable: bool,
+    /// Copies the events of `event_types`, they are shifted by `shift_weeks`
+    ///
+    /// Events don't repeat, every event of the types is copied, including the old homework and exams
+    #[serde(default)]
+    pub copy_events: bool,
+    /// The types of the copied events, all types if there are none, e.g. only the holidays
+    #[serde(default)]
+    pub event_types: Vec<EventType>,
+    #[serde(default)]
+    pub shift_weeks: i64,
+    #[serde(default)]
+    pub discord: DiscordLink,
+    /// Archives the source class, it can only be read afterwards
+    #[serde(default)]
+    pub archive_source: bool,
+}
+
+/// What happens to the discord guild of a cloned class
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiscordLink {
+    /// The guild stays with the source class
+    #[default]
+    Keep,
+    /// The guild and its settings move to the clone
+    Move,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GetEventQueryParams {
     pub before: Option<i64>,
     pub after: Option<i64>,
+    /// The events of a day in the timezone of the class, as `YYYY-MM-DD`
+    pub day: Option<String>,
 }
 
 /// # IMPORTANT: never log the password
@@ -216,6 +532,59 @@ pub struct ChangePasswordReq {
     pub old_password: String,
 }
 
+/// The response of a login with two-factor authentication, the login is completed with
+/// the challenge and a code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginChallenge {
+    pub challenge: Uuid,
+    pub expires: Timestamp,
+}
+
+/// # IMPORTANT: never log the code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorLogin {
+    pub challenge: Uuid,
+    pub code: String,
+}
+
+/// # IMPORTANT: never log the code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorCode {
+    pub code: String,
+}
+
+/// The secret for the authenticator app, `uri` is the `otpauth://` uri for a QR code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TwoFactorEnrollment {
+    pub secret: String,
+    pub uri: String,
+}
+
+/// Single use codes for logins without the authenticator, only shown once
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryCodes {
+    pub recovery_codes: Vec<String>,
+}
+
+/// # IMPORTANT: never log the token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VerifyEmail {
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordResetRequest {
+    pub email: String,
+}
+
+/// # IMPORTANT: never log the token or the password
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PasswordResetConfirm {
+    pub token: String,
+    pub password: String,
+}
+
 /// A single notification that should be sent out by the bot
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Notification {
@@ -224,6 +593,12 @@ pub struct Notification {
     pub channel: Snowflake,
     pub role_ping: Option<Snowflake>,
     pub everyone_ping: bool,
+    /// The timezone of the class of the event
+    #[serde(default = "default_timezone")]
+    pub timezone: TimezoneName,
+    /// The language of the guild, `None` if the guild didn't choose one
+    #[serde(default)]
+    pub language: Option<LanguageCode>,
 }
 
 /// The response for the notifications route
@@ -238,6 +613,26 @@ pub struct NotificationQueryParams {
     pub since: i64,
 }
 
+/// A new join request of a class, posted in the admin channel of the guild
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JoinRequestNotification {
+    pub class_id: Uuid,
+    pub class_name: String,
+    pub user_id: Uuid,
+    pub display_name: String,
+    pub requested: Timestamp,
+    pub channel: Snowflake,
+    pub language: Option<LanguageCode>,
+}
+
+/// The response for the join requests route, works like [`NotificationRes`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JoinRequestRes {
+    pub requests: Vec<JoinRequestNotification>,
+    pub time: Timestamp,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Guild {
@@ -245,4 +640,73 @@ pub struct Guild {
     pub notif_channel: Option<Snowflake>,
     pub notif_ping_role: Option<Snowflake>,
     pub notif_ping_everyone: bool,
+    #[serde(default)]
+    pub notif_routes: Vec<NotificationRoute>,
+    /// The discord roles that the bot keeps in sync with the roles of the class members
+    #[serde(default)]
+    pub role_mappings: Vec<RoleMapping>,
+    /// The language the bot answers in, one of [`LANGUAGES`]
+    #[serde(default)]
+    pub language: Option<LanguageCode>,
+    /// The channel that new join requests are posted in, with buttons to accept or deny them
+    #[serde(default)]
+    pub admin_channel: Option<Snowflake>,
+}
+
+/// Overrides the notification settings of a guild for a single event type
+///
+/// Every field that is `None` falls back to the default setting of the guild
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationRoute {
+    pub r#type: EventType,
+    #[serde(default)]
+    pub channel: Option<Snowflake>,
+    #[serde(default)]
+    pub role_ping: Option<Snowflake>,
+    #[serde(default)]
+    pub everyone_ping: Option<bool>,
+}
+
+/// Maps a role of the class to a discord role
+///
+/// Linked members with the role get the discord role, other linked members and former members lose
+/// it
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoleMapping {
+    pub role: MemberRole,
+    pub discord_role: Snowflake,
+}
+
+/// A member of the class of a guild that is linked with discord
+///
+/// Response of /bot/guilds/{snowflake}/members
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscordMember {
+    pub snowflake: Snowflake,
+    /// `None` for former members that were kicked or left the class
+    pub role: Option<MemberRole>,
+}
+
+impl Guild {
+    /// Get the notification route for an event type, creating an empty one if there is none
+    pub fn notif_route_mut(&mut self, typ: EventType) -> &mut NotificationRoute {
+        match self
+            .notif_routes
+            .iter()
+            .position(|route| route.r#type == typ)
+        {
+            Some(index) => &mut self.notif_routes[index],
+            None => {
+                self.notif_routes.push(NotificationRoute {
+                    r#type: typ,
+                    channel: None,
+                    role_ping: None,
+                    everyone_ping: None,
+                });
+                self.notif_routes.last_mut().expect("just pushed")
+            }
+        }
+    }
 }
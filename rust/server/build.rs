@@ -0,0 +1,4 @@
+// the migrations are embedded into the binary, new ones must rebuild it
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
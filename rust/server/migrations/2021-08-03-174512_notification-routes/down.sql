@@ -0,0 +1 @@
+DROP TABLE guild_notif_routes;
\ No newline at end of file
@@ -0,0 +1,17 @@
+CREATE TABLE guild_notif_routes
+(
+    guild               VARCHAR(20) NOT NULL,
+    e_type              INT         NOT NULL,
+    notif_channel       VARCHAR(20) NULL,
+    notif_ping_role     VARCHAR(20) NULL,
+    notif_ping_everyone BOOLEAN     NULL,
+    PRIMARY KEY (guild, e_type),
+    CONSTRAINT notif_route_guild_fk
+        FOREIGN KEY (guild)
+            REFERENCES guilds (id)
+            ON DELETE CASCADE,
+    CONSTRAINT notif_route_type_fk
+        FOREIGN KEY (e_type)
+            REFERENCES event_types (id)
+            ON DELETE CASCADE
+);
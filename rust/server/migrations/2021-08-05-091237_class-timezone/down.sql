@@ -0,0 +1,2 @@
+ALTER TABLE classes
+    DROP COLUMN timezone;
\ No newline at end of file
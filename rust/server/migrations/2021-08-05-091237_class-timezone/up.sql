@@ -0,0 +1,2 @@
+ALTER TABLE classes
+    ADD COLUMN timezone VARCHAR(50) NOT NULL DEFAULT 'Europe/Zurich';
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE guilds
+    DROP COLUMN language;
\ No newline at end of file
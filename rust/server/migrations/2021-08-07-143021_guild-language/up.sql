@@ -0,0 +1,2 @@
+ALTER TABLE guilds
+    ADD COLUMN language VARCHAR(10);
\ No newline at end of file
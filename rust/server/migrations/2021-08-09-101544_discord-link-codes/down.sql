@@ -0,0 +1 @@
+DROP TABLE discord_link_codes;
\ No newline at end of file
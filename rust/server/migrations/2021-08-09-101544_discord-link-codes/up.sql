@@ -0,0 +1,11 @@
+-- one-time codes to link a user with a discord account
+-- a code is either created by the bot for a discord account or by the website for a user,
+-- and is redeemed on the other side
+CREATE TABLE discord_link_codes
+(
+    code       VARCHAR(8) PRIMARY KEY,
+    user_id    UUID REFERENCES users ON DELETE CASCADE,
+    discord_id VARCHAR(20),
+    expires    TIMESTAMP NOT NULL,
+    CHECK ((user_id IS NULL) != (discord_id IS NULL))
+);
@@ -0,0 +1 @@
+DROP TABLE guild_role_mappings;
\ No newline at end of file
@@ -0,0 +1,15 @@
+CREATE TABLE guild_role_mappings
+(
+    guild        VARCHAR(20) NOT NULL,
+    member_role  INT         NOT NULL,
+    discord_role VARCHAR(20) NOT NULL,
+    PRIMARY KEY (guild, member_role),
+    CONSTRAINT role_mapping_guild_fk
+        FOREIGN KEY (guild)
+            REFERENCES guilds (id)
+            ON DELETE CASCADE,
+    CONSTRAINT role_mapping_role_fk
+        FOREIGN KEY (member_role)
+            REFERENCES member_roles (id)
+            ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+DROP TABLE join_requests;
+
+ALTER TABLE guilds
+    DROP COLUMN admin_channel;
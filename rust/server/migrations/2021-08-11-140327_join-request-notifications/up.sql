@@ -0,0 +1,14 @@
+ALTER TABLE guilds
+    ADD COLUMN admin_channel VARCHAR(20) NULL;
+
+CREATE TABLE join_requests
+(
+    "user"    UUID      NOT NULL,
+    class     UUID      NOT NULL,
+    requested TIMESTAMP NOT NULL,
+    PRIMARY KEY ("user", class),
+    CONSTRAINT join_request_member_fk
+        FOREIGN KEY ("user", class)
+            REFERENCES members ("user", class)
+            ON DELETE CASCADE
+);
@@ -0,0 +1,9 @@
+-- named keys for the bot and other services, only the sha256 hash of the key is stored
+CREATE TABLE api_keys
+(
+    id       UUID PRIMARY KEY,
+    name     VARCHAR(50) NOT NULL UNIQUE,
+    key_hash BYTEA       NOT NULL UNIQUE,
+    scopes   TEXT[]      NOT NULL,
+    created  TIMESTAMP   NOT NULL DEFAULT now()
+);
@@ -0,0 +1,20 @@
+-- tokens that users create to use the api from scripts, only the sha256 hash of the token is stored
+CREATE TABLE access_tokens
+(
+    id         UUID PRIMARY KEY,
+    user_id    UUID        NOT NULL REFERENCES users ON DELETE CASCADE,
+    name       VARCHAR(50) NOT NULL,
+    token_hash BYTEA       NOT NULL UNIQUE,
+    created    TIMESTAMP   NOT NULL DEFAULT now(),
+    expires    TIMESTAMP,
+    UNIQUE (user_id, name)
+);
+
+-- the classes a token can access, read only unless write is set
+CREATE TABLE access_token_scopes
+(
+    token UUID    NOT NULL REFERENCES access_tokens ON DELETE CASCADE,
+    class UUID    NOT NULL REFERENCES classes ON DELETE CASCADE,
+    write BOOLEAN NOT NULL,
+    PRIMARY KEY (token, class)
+);
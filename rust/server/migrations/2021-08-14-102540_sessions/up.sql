@@ -0,0 +1,11 @@
+-- every login is a session, its refresh token is rotated on every use
+-- generation is the generation of the current refresh token, older ones are reused tokens
+CREATE TABLE sessions
+(
+    id         UUID PRIMARY KEY,
+    user_id    UUID         NOT NULL REFERENCES users ON DELETE CASCADE,
+    device     VARCHAR(200) NOT NULL,
+    created    TIMESTAMP    NOT NULL DEFAULT now(),
+    last_used  TIMESTAMP    NOT NULL DEFAULT now(),
+    generation INT          NOT NULL DEFAULT 0
+);
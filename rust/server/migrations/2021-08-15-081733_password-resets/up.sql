@@ -0,0 +1,7 @@
+-- tokens from the password reset emails, only the hash of the token is stored
+CREATE TABLE password_resets
+(
+    token_hash BYTEA PRIMARY KEY,
+    user_id    UUID      NOT NULL REFERENCES users ON DELETE CASCADE,
+    expires    TIMESTAMP NOT NULL
+);
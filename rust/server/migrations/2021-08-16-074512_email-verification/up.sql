@@ -0,0 +1,15 @@
+-- the accounts from before the verification keep working like before
+ALTER TABLE users
+    ADD COLUMN email_verified BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE users
+SET email_verified = TRUE;
+
+-- tokens from the verification emails, only the hash of the token is stored
+-- the token only verifies the email that it was sent to
+CREATE TABLE email_verifications
+(
+    token_hash BYTEA PRIMARY KEY,
+    user_id    UUID        NOT NULL REFERENCES users ON DELETE CASCADE,
+    email      VARCHAR(50) NOT NULL,
+    expires    TIMESTAMP   NOT NULL
+);
@@ -0,0 +1,6 @@
+ALTER TABLE classes
+    DROP COLUMN require_two_factor;
+
+DROP TABLE login_challenges;
+DROP TABLE recovery_codes;
+DROP TABLE two_factor;
@@ -0,0 +1,29 @@
+-- the totp secret of a user, only used for logins after the first code was verified
+-- last_step is the last used time step, so that a code can't be used twice
+CREATE TABLE two_factor
+(
+    user_id   UUID PRIMARY KEY REFERENCES users ON DELETE CASCADE,
+    secret    BYTEA   NOT NULL,
+    enabled   BOOLEAN NOT NULL DEFAULT FALSE,
+    last_step BIGINT  NOT NULL DEFAULT 0
+);
+
+-- single use codes for a login without the authenticator, only their hash is stored
+CREATE TABLE recovery_codes
+(
+    user_id   UUID  NOT NULL REFERENCES users ON DELETE CASCADE,
+    code_hash BYTEA NOT NULL,
+    PRIMARY KEY (user_id, code_hash)
+);
+
+-- logins that checked the password and wait for the second factor
+CREATE TABLE login_challenges
+(
+    id       UUID PRIMARY KEY,
+    user_id  UUID      NOT NULL REFERENCES users ON DELETE CASCADE,
+    expires  TIMESTAMP NOT NULL,
+    attempts INT       NOT NULL DEFAULT 0
+);
+
+ALTER TABLE classes
+    ADD COLUMN require_two_factor BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,2 @@
+ALTER TABLE classes
+    DROP COLUMN archived;
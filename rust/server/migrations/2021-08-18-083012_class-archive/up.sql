@@ -0,0 +1,3 @@
+-- archived classes can only be read, for example the class of the last school year
+ALTER TABLE classes
+    ADD COLUMN archived BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,9 @@
+-- members of imported classes, the user with the email can accept or decline the invite
+CREATE TABLE class_invites
+(
+    class        UUID        NOT NULL REFERENCES classes ON DELETE CASCADE,
+    email        VARCHAR(50) NOT NULL,
+    display_name VARCHAR(50) NOT NULL,
+    role         INT         NOT NULL REFERENCES member_roles ON DELETE CASCADE,
+    PRIMARY KEY (class, email)
+);
@@ -0,0 +1 @@
+DROP TABLE former_members;
@@ -0,0 +1,7 @@
+-- users that were kicked from a class or left it, the bot removes their mapped discord roles
+CREATE TABLE former_members
+(
+    "user" UUID NOT NULL REFERENCES users ON DELETE CASCADE,
+    class  UUID NOT NULL REFERENCES classes ON DELETE CASCADE,
+    PRIMARY KEY ("user", class)
+);
@@ -0,0 +1,108 @@
+use super::Pool;
+use crate::diesel::{QueryDsl, RunQueryDsl};
+use crate::error::{ServiceErr, ServiceResult};
+use crate::models::{AccessToken, AccessTokenScope};
+use crate::schema::access_tokens::dsl::*;
+use crate::token;
+use diesel::{delete, insert_into, BoolExpressionMethods, Connection, ExpressionMethods};
+use uuid::Uuid;
+
+/// All personal access tokens start with this, so they can't be confused with a JWT or api key
+pub const ACCESS_TOKEN_PREFIX: &str = "corspat_";
+const ACCESS_TOKEN_LENGTH: usize = 40;
+
+pub type AccessTokenData = (AccessToken, Vec<AccessTokenScope>);
+
+/// Creates a token for the user, returns the token, which can't be retrieved again
+pub fn create_access_token(
+    db: &Pool,
+    user: Uuid,
+    token_name: &str,
+    token_expires: Option<chrono::NaiveDateTime>,
+    scopes: Vec<(Uuid, bool)>,
+) -> ServiceResult<(AccessTokenData, String)> {
+    use crate::schema::access_token_scopes::dsl::access_token_scopes;
+    let conn = db.get()?;
+
+    let (token, hash) = token::generate(ACCESS_TOKEN_PREFIX, ACCESS_TOKEN_LENGTH);
+
+    let data = conn.transaction::<_, ServiceErr, _>(|| {
+        let access_token = insert_into(access_tokens)
+            .values((
+                id.eq(Uuid::new_v4()),
+                user_id.eq(user),
+                name.eq(token_name),
+                token_hash.eq(hash),
+                expires.eq(token_expires),
+            ))
+            .get_result::<AccessToken>(&conn)?;
+
+        let scopes = scopes
+            .into_iter()
+            .map(|(class, write)| AccessTokenScope {
+                token: access_token.id,
+                class,
+                write,
+            })
+            .collect::<Vec<_>>();
+        let scopes = insert_into(access_token_scopes)
+            .values(&scopes)
+            .get_results(&conn)?;
+
+        Ok((access_token, scopes))
+    })?;
+
+    Ok((data, token))
+}
+
+/// Get a token that didn't expire yet
+pub fn get_access_token(db: &Pool, token: &str) -> ServiceResult<AccessTokenData> {
+    use crate::schema::access_token_scopes::dsl as scopes;
+    let conn = db.get()?;
+
+    let now = chrono::Utc::now().naive_utc();
+    let access_token = access_tokens
+        .filter(
+            token_hash
+                .eq(token::hash(token))
+                .and(expires.is_null().or(expires.gt(now))),
+        )
+        .get_result::<AccessToken>(&conn)?;
+    let token_scopes = scopes::access_token_scopes
+        .filter(scopes::token.eq(access_token.id))
+        .load(&conn)?;
+
+    Ok((access_token, token_scopes))
+}
+
+pub fn get_access_tokens(db: &Pool, user: Uuid) -> ServiceResult<Vec<AccessTokenData>> {
+    use crate::schema::access_token_scopes::dsl as scopes;
+    let conn = db.get()?;
+
+    let tokens = access_tokens
+        .filter(user_id.eq(user))
+        .order_by(created)
+        .load::<AccessToken>(&conn)?;
+    let token_scopes = scopes::access_token_scopes
+        .filter(scopes::token.eq_any(tokens.iter().map(|token| token.id).collect::<Vec<_>>()))
+        .load::<AccessTokenScope>(&conn)?;
+
+    Ok(tokens
+        .into_iter()
+        .map(|token| {
+            let scopes = token_scopes
+                .iter()
+                .filter(|scope| scope.token == token.id)
+                .cloned()
+                .collect();
+            (token, scopes)
+        })
+        .collect())
+}
+
+/// Revokes a token of the user, returns the amount of deleted tokens
+pub fn delete_access_token(db: &Pool, user: Uuid, token_id: Uuid) -> ServiceResult<usize> {
+    let conn = db.get()?;
+
+    Ok(delete(access_tokens.filter(id.eq(token_id).and(user_id.eq(user)))).execute(&conn)?)
+}
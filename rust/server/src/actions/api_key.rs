@@ -0,0 +1,55 @@
+use super::Pool;
+use crate::diesel::{QueryDsl, RunQueryDsl};
+use crate::error::ServiceResult;
+use crate::models::ApiKey;
+use crate::schema::api_keys::dsl::*;
+use crate::token;
+use diesel::{delete, insert_into, ExpressionMethods};
+use uuid::Uuid;
+
+/// All keys start with this, so they can't be confused with a JWT
+pub const API_KEY_PREFIX: &str = "cors_";
+const API_KEY_LENGTH: usize = 40;
+
+/// Creates a new key, returns the key, which can't be retrieved again
+pub fn create_api_key(
+    db: &Pool,
+    key_name: &str,
+    key_scopes: Vec<String>,
+) -> ServiceResult<(ApiKey, String)> {
+    let conn = db.get()?;
+
+    let (key, hash) = token::generate(API_KEY_PREFIX, API_KEY_LENGTH);
+
+    let api_key = insert_into(api_keys)
+        .values((
+            id.eq(Uuid::new_v4()),
+            name.eq(key_name),
+            key_hash.eq(hash),
+            scopes.eq(key_scopes),
+        ))
+        .get_result(&conn)?;
+
+    Ok((api_key, key))
+}
+
+pub fn get_api_key(db: &Pool, key: &str) -> ServiceResult<ApiKey> {
+    let conn = db.get()?;
+
+    Ok(api_keys
+        .filter(key_hash.eq(token::hash(key)))
+        .get_result(&conn)?)
+}
+
+pub fn get_api_keys(db: &Pool) -> ServiceResult<Vec<ApiKey>> {
+    let conn = db.get()?;
+
+    Ok(api_keys.order_by(name).load(&conn)?)
+}
+
+/// Revokes a key, returns the amount of deleted keys
+pub fn delete_api_key(db: &Pool, key_name: &str) -> ServiceResult<usize> {
+    let conn = db.get()?;
+
+    Ok(delete(api_keys.filter(name.eq(key_name))).execute(&conn)?)
+}
@@ -2,12 +2,17 @@ use crate::actions::Pool;
 use crate::diesel::{QueryDsl, RunQueryDsl};
 use crate::error::{ServiceErr, ServiceResult};
 use crate::models::{
-    Class, Guild, Member, MemberRole, NewClass, NewGuild, NewMember, Timetable, User,
+    Class, ClassInvite, Guild, JoinRequest, Member, MemberRole, NewClass, NewClassInvite, NewEvent,
+    NewGuild, NewMember, NewNotifRoute, NewRoleMapping, NewTimetable, NotifRoute, RoleMapping,
+    Timetable, User,
 };
 use crate::schema::classes::dsl::*;
+use diesel::sql_types::{Nullable, VarChar};
 use diesel::{
-    delete, insert_into, update, BoolExpressionMethods, ExpressionMethods, SaveChangesDsl,
+    delete, insert_into, update, BoolExpressionMethods, Connection, ExpressionMethods, JoinOnDsl,
+    NullableExpressionMethods, OptionalExtension, PgConnection, SaveChangesDsl,
 };
+use dto::ErrorCode;
 use uuid::Uuid;
 
 pub fn insert_class(db: &Pool, new_class: NewClass) -> ServiceResult<Class> {
@@ -16,8 +21,155 @@ pub fn insert_class(db: &Pool, new_class: NewClass) -> ServiceResult<Class> {
     Ok(insert_into(classes).values(&new_class).get_result(&conn)?)
 }
 
+/// A class with everything in it, inserted at once by [`import_class`]
+pub struct ClassImport<'a> {
+    pub class: NewClass<'a>,
+    pub require_two_factor: bool,
+    pub members: Vec<NewMember<'a>>,
+    /// The members of an archive, they join when they accept
+    pub invites: Vec<NewClassInvite<'a>>,
+    pub events: Vec<NewEvent<'a>>,
+    pub timetable: Option<&'a str>,
+}
+
+/// Inserts the class with everything in it
+pub fn import_class(db: &Pool, import: ClassImport) -> ServiceResult<Class> {
+    let conn = db.get()?;
+
+    Ok(conn.transaction(|| insert_class_import(&conn, import))?)
+}
+
+/// Inserts the clone of the source class like [`import_class`]
+///
+/// A clone with the guild of the source moves the guild with its settings, the source loses the link.
+pub fn clone_class(
+    db: &Pool,
+    source: Uuid,
+    clone: ClassImport,
+    archive_source: bool,
+) -> ServiceResult<Class> {
+    use crate::schema::guild_notif_routes::dsl::{guild, guild_notif_routes};
+    use crate::schema::guild_role_mappings::dsl as mappings;
+    use crate::schema::guilds::dsl::guilds;
+    let conn = db.get()?;
+
+    Ok(conn.transaction::<_, diesel::result::Error, _>(|| {
+        // the guild references the class, it is deleted and inserted again for the clone
+        let moved = match clone.class.discord_id {
+            Some(guild_id) => {
+                let settings: Guild = guilds.find(guild_id).get_result(&conn)?;
+                let routes: Vec<NotifRoute> =
+                    guild_notif_routes.filter(guild.eq(guild_id)).load(&conn)?;
+                let role_mappings: Vec<RoleMapping> = mappings::guild_role_mappings
+                    .filter(mappings::guild.eq(guild_id))
+                    .load(&conn)?;
+                delete(guilds.find(guild_id)).execute(&conn)?;
+                update(classes.find(source))
+                    .set(discord_id.eq(None::<&str>))
+                    .execute(&conn)?;
+                Some((settings, routes, role_mappings))
+            }
+            None => None,
+        };
+        if archive_source {
+            update(classes.find(source))
+                .set(archived.eq(true))
+                .execute(&conn)?;
+        }
+
+        let class = insert_class_import(&conn, clone)?;
+        if let Some((settings, routes, role_mappings)) = moved {
+            insert_into(guilds)
+                .values(NewGuild {
+                    id: &settings.id,
+                    notif_channel: settings.notif_channel.as_deref(),
+                    notif_ping_role: settings.notif_ping_role.as_deref(),
+                    notif_ping_everyone: settings.notif_ping_everyone,
+                    language: settings.language.as_deref(),
+                    admin_channel: settings.admin_channel.as_deref(),
+                })
+                .execute(&conn)?;
+            let routes = routes
+                .iter()
+                .map(|route| NewNotifRoute {
+                    guild: &route.guild,
+                    e_type: route.e_type,
+                    notif_channel: route.notif_channel.as_deref(),
+                    notif_ping_role: route.notif_ping_role.as_deref(),
+                    notif_ping_everyone: route.notif_ping_everyone,
+                })
+                .collect::<Vec<_>>();
+            insert_into(guild_notif_routes)
+                .values(&routes)
+                .execute(&conn)?;
+            let role_mappings = role_mappings
+                .iter()
+                .map(|mapping| NewRoleMapping {
+                    guild: &mapping.guild,
+                    member_role: mapping.member_role,
+                    discord_role: &mapping.discord_role,
+                })
+                .collect::<Vec<_>>();
+            insert_into(mappings::guild_role_mappings)
+                .values(&role_mappings)
+                .execute(&conn)?;
+        }
+        Ok(class)
+    })?)
+}
+
+fn insert_class_import(
+    conn: &PgConnection,
+    import: ClassImport,
+) -> Result<Class, diesel::result::Error> {
+    use crate::schema::class_invites::dsl::class_invites;
+    use crate::schema::events::dsl::events;
+    use crate::schema::members::dsl::members;
+    use crate::schema::timetables::dsl::timetables;
+
+    let class: Class = insert_into(classes)
+        .values((
+            &import.class,
+            require_two_factor.eq(import.require_two_factor),
+        ))
+        .get_result(conn)?;
+    insert_into(members).values(&import.members).execute(conn)?;
+    insert_into(class_invites)
+        .values(&import.invites)
+        .execute(conn)?;
+    insert_into(events).values(&import.events).execute(conn)?;
+    if let Some(timetable) = import.timetable {
+        insert_into(timetables)
+            .values(NewTimetable {
+                class: &class.id,
+                timetable,
+            })
+            .execute(conn)?;
+    }
+    Ok(class)
+}
+
 pub type ClassMemberData = (Class, Vec<(Member, User)>);
 
+/// An owned class with the admin that gets it when the owner deletes their account
+pub type OwnedClassDeletion = (Class, Option<(Member, User)>);
+
+pub fn get_classes(db: &Pool) -> ServiceResult<Vec<Class>> {
+    let conn = db.get()?;
+
+    Ok(classes.order_by((name, id)).load(&conn)?)
+}
+
+/// The classes of an owner, the owner can't be deleted before they have another owner
+pub fn get_owned_classes(db: &Pool, user_id: Uuid) -> ServiceResult<Vec<Class>> {
+    let conn = db.get()?;
+
+    Ok(classes
+        .filter(owner.eq(user_id))
+        .order_by((name, id))
+        .load(&conn)?)
+}
+
 pub fn get_class(db: &Pool, class_id: Uuid) -> ServiceResult<Option<ClassMemberData>> {
     use crate::schema::members::dsl::{display_name, members, role};
     use crate::schema::users::dsl::users;
@@ -49,6 +201,122 @@ pub fn get_classes_by_user(db: &Pool, user_id: Uuid) -> ServiceResult<Vec<Class>
     Ok(class_vec.into_iter().map(|(class, _)| class).collect())
 }
 
+/// All classes the user is in with the membership, including pending join requests and bans
+pub fn get_memberships(db: &Pool, user_id: Uuid) -> ServiceResult<Vec<(Class, Member)>> {
+    use crate::schema::members::dsl::{members, user as member_user};
+    let conn = db.get()?;
+
+    Ok(classes
+        .inner_join(members)
+        .filter(member_user.eq(user_id))
+        .order_by(name)
+        .load(&conn)?)
+}
+
+/// The invites for the email with their class
+pub fn get_invites(db: &Pool, user_email: &str) -> ServiceResult<Vec<(ClassInvite, Class)>> {
+    use crate::schema::class_invites::dsl::{class_invites, email};
+    let conn = db.get()?;
+
+    Ok(class_invites
+        .inner_join(classes)
+        .filter(email.eq(user_email))
+        .order_by(name)
+        .load(&conn)?)
+}
+
+/// The user joins the class with the display name and the role of the invite.
+/// A join request of the user is replaced, members and banned users stay what they are
+pub fn accept_invite(db: &Pool, user: &User, class_id: Uuid) -> ServiceResult<Member> {
+    use crate::schema::class_invites::dsl as invites;
+    use crate::schema::join_requests::dsl as requests;
+    use crate::schema::members::dsl as members;
+    let conn = db.get()?;
+
+    conn.transaction(|| {
+        let invite = delete(
+            invites::class_invites.filter(
+                invites::class
+                    .eq(class_id)
+                    .and(invites::email.eq(&user.email)),
+            ),
+        )
+        .get_result::<ClassInvite>(&conn)
+        .optional()?
+        .ok_or(ServiceErr::NotFound)?;
+
+        let existing = members::members
+            .find((user.id, class_id))
+            .get_result::<Member>(&conn)
+            .optional()?;
+        match existing {
+            Some(member) if member.role == MemberRole::BANNED => {
+                Err(ServiceErr::Unauthorized(ErrorCode::Banned))
+            }
+            Some(member) if member.role != MemberRole::PENDING => Ok(member),
+            _ => {
+                delete(
+                    requests::join_requests
+                        .filter(requests::class.eq(class_id).and(requests::user.eq(user.id))),
+                )
+                .execute(&conn)?;
+                Ok(insert_into(members::members)
+                    .values(NewMember {
+                        user: user.id,
+                        class: class_id,
+                        display_name: &invite.display_name,
+                        role: invite.role,
+                    })
+                    .on_conflict((members::user, members::class))
+                    .do_update()
+                    .set((
+                        members::display_name.eq(&invite.display_name),
+                        members::role.eq(invite.role),
+                    ))
+                    .get_result(&conn)?)
+            }
+        }
+    })
+}
+
+pub fn decline_invite(db: &Pool, user_email: &str, class_id: Uuid) -> ServiceResult<usize> {
+    use crate::schema::class_invites::dsl::{class, class_invites, email};
+    let conn = db.get()?;
+
+    Ok(
+        delete(class_invites.filter(class.eq(class_id).and(email.eq(user_email))))
+            .execute(&conn)?,
+    )
+}
+
+/// The admin that gets the class when the owner deletes their account, the first by display name
+pub fn get_next_owner(db: &Pool, class_id: Uuid) -> ServiceResult<Option<(Member, User)>> {
+    use crate::schema::members::dsl::{class, display_name, members, role};
+    use crate::schema::users::dsl::users;
+    let conn = db.get()?;
+
+    Ok(members
+        .inner_join(users)
+        .filter(class.eq(class_id).and(role.eq(MemberRole::ADMIN)))
+        .order_by(display_name)
+        .first(&conn)
+        .optional()?)
+}
+
+/// The classes of the user with their next owners, see [`get_next_owner`]
+pub fn get_owned_class_deletions(
+    db: &Pool,
+    user_id: Uuid,
+) -> ServiceResult<Vec<OwnedClassDeletion>> {
+    get_owned_classes(db, user_id)?
+        .into_iter()
+        .map(|class| {
+            let next_owner = get_next_owner(db, class.id)?;
+            Ok((class, next_owner))
+        })
+        .collect()
+}
+
 pub fn get_pending_members(db: &Pool, class_id: Uuid) -> ServiceResult<Vec<Member>> {
     use crate::schema::members::dsl::{class, members, role};
     let conn = db.get()?;
@@ -69,6 +337,52 @@ pub fn get_banned_members(db: &Pool, class_id: Uuid) -> ServiceResult<Vec<(Membe
         .load(&conn)?)
 }
 
+/// All members of the class, including the pending and banned ones
+pub fn get_all_members(db: &Pool, class_id: Uuid) -> ServiceResult<Vec<(Member, User)>> {
+    use crate::schema::members::dsl::{class, display_name, members, role};
+    use crate::schema::users::dsl::users;
+    let conn = db.get()?;
+
+    Ok(members
+        .inner_join(users)
+        .filter(class.eq(class_id))
+        .order_by((role, display_name))
+        .load(&conn)?)
+}
+
+/// Makes the user the owner of the class, the previous owner stays as an admin.
+/// A user that isn't a member yet joins with the display name.
+pub fn transfer_ownership(
+    db: &Pool,
+    class_id: Uuid,
+    new_owner: Uuid,
+    new_display_name: &str,
+) -> ServiceResult<Class> {
+    use crate::schema::members::dsl::{class, members, role, user};
+    let conn = db.get()?;
+
+    Ok(conn.transaction::<_, diesel::result::Error, _>(|| {
+        update(members.filter(class.eq(class_id).and(role.eq(MemberRole::OWNER))))
+            .set(role.eq(MemberRole::ADMIN))
+            .execute(&conn)?;
+        insert_into(members)
+            .values(NewMember {
+                user: new_owner,
+                class: class_id,
+                display_name: new_display_name,
+                role: MemberRole::OWNER,
+            })
+            .on_conflict((user, class))
+            .do_update()
+            .set(role.eq(MemberRole::OWNER))
+            .execute(&conn)?;
+
+        update(classes.filter(id.eq(class_id)))
+            .set(owner.eq(new_owner))
+            .get_result(&conn)
+    })?)
+}
+
 pub fn update_member(db: &Pool, member: NewMember) -> ServiceResult<Member> {
     let conn = db.get()?;
 
@@ -82,6 +396,74 @@ pub fn create_member(db: &Pool, member: NewMember) -> ServiceResult<Member> {
     Ok(insert_into(members).values(&member).get_result(&conn)?)
 }
 
+/// Creates a pending member and remembers the time of the request for the bot
+pub fn create_join_request(db: &Pool, member: NewMember) -> ServiceResult<Member> {
+    use crate::schema::join_requests::dsl::join_requests;
+    use crate::schema::members::dsl::members;
+    let conn = db.get()?;
+
+    conn.transaction::<_, ServiceErr, _>(|| {
+        let member = insert_into(members)
+            .values(&member)
+            .get_result::<Member>(&conn)?;
+        insert_into(join_requests)
+            .values(JoinRequest {
+                user: member.user,
+                class: member.class,
+                requested: chrono::Utc::now().naive_utc(),
+            })
+            .execute(&conn)?;
+        Ok(member)
+    })
+}
+
+pub fn delete_join_request(db: &Pool, user_id: Uuid, class_id: Uuid) -> ServiceResult<usize> {
+    use crate::schema::join_requests::dsl::{class, join_requests, user};
+    let conn = db.get()?;
+
+    Ok(delete(join_requests.filter(class.eq(class_id).and(user.eq(user_id)))).execute(&conn)?)
+}
+
+pub type JoinRequests = (
+    chrono::NaiveDateTime,
+    Vec<(JoinRequest, Member, Class, Guild)>,
+);
+
+/// Get the join requests since the last time, only of guilds with an admin channel
+pub fn get_join_request_notifications(
+    db: &Pool,
+    since: chrono::NaiveDateTime,
+) -> ServiceResult<JoinRequests> {
+    use crate::schema::guilds::dsl as guilds;
+    use crate::schema::join_requests::dsl as requests;
+    use crate::schema::members::dsl as members;
+
+    sql_function!(fn coalesce(a: Nullable<VarChar>, b: VarChar) -> VarChar);
+
+    let conn = db.get()?;
+    let current_time = chrono::Utc::now().naive_utc();
+
+    let join_requests = requests::join_requests
+        .inner_join(
+            members::members.on(members::user
+                .eq(requests::user)
+                .and(members::class.eq(requests::class))),
+        )
+        .inner_join(classes.on(id.eq(requests::class)))
+        .inner_join(guilds::guilds.on(coalesce(discord_id, "").eq(guilds::id)))
+        .filter(
+            requests::requested
+                .gt(since)
+                .and(requests::requested.le(current_time))
+                .and(members::role.eq(MemberRole::PENDING))
+                .and(guilds::admin_channel.is_not_null()),
+        )
+        .order_by(requests::requested)
+        .load(&conn)?;
+
+    Ok((current_time, join_requests))
+}
+
 pub fn update_class(db: &Pool, new_class: NewClass) -> ServiceResult<Class> {
     let conn = db.get()?;
 
@@ -103,6 +485,54 @@ pub fn set_discord_id_class(db: &Pool, class_id: Uuid, d_id: Option<&str>) -> Se
         .get_result(&conn)?)
 }
 
+pub fn is_two_factor_required(db: &Pool, class_id: Uuid) -> ServiceResult<bool> {
+    let conn = db.get()?;
+
+    Ok(classes
+        .find(class_id)
+        .select(require_two_factor)
+        .get_result(&conn)?)
+}
+
+pub fn set_two_factor_required(db: &Pool, class_id: Uuid, required: bool) -> ServiceResult<Class> {
+    let conn = db.get()?;
+
+    Ok(update(classes)
+        .filter(id.eq(class_id))
+        .set(require_two_factor.eq(required))
+        .get_result(&conn)?)
+}
+
+pub fn is_archived(db: &Pool, class_id: Uuid) -> ServiceResult<bool> {
+    let conn = db.get()?;
+
+    Ok(classes.find(class_id).select(archived).get_result(&conn)?)
+}
+
+pub fn set_archived(db: &Pool, class_id: Uuid, is_archived: bool) -> ServiceResult<Class> {
+    let conn = db.get()?;
+
+    Ok(update(classes)
+        .filter(id.eq(class_id))
+        .set(archived.eq(is_archived))
+        .get_result(&conn)?)
+}
+
+pub fn get_timezone_class(db: &Pool, class_id: Uuid) -> ServiceResult<String> {
+    let conn = db.get()?;
+
+    Ok(classes.find(class_id).select(timezone).get_result(&conn)?)
+}
+
+pub fn set_timezone_class(db: &Pool, class_id: Uuid, tz: &str) -> ServiceResult<Class> {
+    let conn = db.get()?;
+
+    Ok(update(classes)
+        .filter(id.eq(class_id))
+        .set(timezone.eq(tz))
+        .get_result(&conn)?)
+}
+
 pub fn get_class_by_discord(db: &Pool, class_id: &str) -> ServiceResult<Class> {
     let conn = db.get()?;
 
@@ -125,11 +555,26 @@ pub fn get_member(db: &Pool, user_id: Uuid, class_id: Uuid) -> ServiceResult<(Me
         .get_result(&conn)?)
 }
 
+/// Removes a member from the class, the user is remembered as a former member for the role sync
 pub fn delete_member(db: &Pool, user_id: Uuid, class_id: Uuid) -> ServiceResult<usize> {
+    use crate::schema::former_members;
     use crate::schema::members::dsl::{class, members, user};
     let conn = db.get()?;
 
-    Ok(delete(members.filter(class.eq(class_id).and(user.eq(user_id)))).execute(&conn)?)
+    conn.transaction::<_, ServiceErr, _>(|| {
+        let deleted =
+            delete(members.filter(class.eq(class_id).and(user.eq(user_id)))).execute(&conn)?;
+        if deleted > 0 {
+            insert_into(former_members::table)
+                .values((
+                    former_members::user.eq(user_id),
+                    former_members::class.eq(class_id),
+                ))
+                .on_conflict_do_nothing()
+                .execute(&conn)?;
+        }
+        Ok(deleted)
+    })
 }
 
 pub fn delete_class(db: &Pool, class_id: Uuid) -> ServiceResult<usize> {
@@ -191,6 +636,133 @@ pub fn get_guild_settings(db: &Pool, guild_id: &str) -> ServiceResult<Guild> {
     Ok(guilds.find(guild_id).get_result(&conn)?)
 }
 
+/// All guilds with their class
+pub fn get_guilds(db: &Pool) -> ServiceResult<Vec<(Guild, Class)>> {
+    use crate::schema::guilds::dsl as guilds;
+
+    let conn = db.get()?;
+    Ok(guilds::guilds
+        .inner_join(classes.on(discord_id.eq(guilds::id.nullable())))
+        .order_by(guilds::id)
+        .load(&conn)?)
+}
+
+/// Unlinks the guild from its class and deletes its settings, returns the amount of deleted guilds
+pub fn unlink_guild(db: &Pool, guild_id: &str) -> ServiceResult<usize> {
+    use crate::schema::guilds::dsl::guilds;
+
+    let conn = db.get()?;
+    Ok(conn.transaction::<_, diesel::result::Error, _>(|| {
+        // the guild references the discord id of the class, so it goes first
+        let deleted = delete(guilds.find(guild_id)).execute(&conn)?;
+        update(classes.filter(discord_id.eq(guild_id)))
+            .set(discord_id.eq(None::<&str>))
+            .execute(&conn)?;
+        Ok(deleted)
+    })?)
+}
+
+pub fn get_notif_routes(db: &Pool, guild_id: &str) -> ServiceResult<Vec<NotifRoute>> {
+    use crate::schema::guild_notif_routes::dsl::{e_type, guild, guild_notif_routes};
+
+    let conn = db.get()?;
+    Ok(guild_notif_routes
+        .filter(guild.eq(guild_id))
+        .order_by(e_type)
+        .load(&conn)?)
+}
+
+/// Replaces all notification routes of a guild
+pub fn set_notif_routes(
+    db: &Pool,
+    guild_id: &str,
+    routes: Vec<NewNotifRoute>,
+) -> ServiceResult<Vec<NotifRoute>> {
+    use crate::schema::guild_notif_routes::dsl::{e_type, guild, guild_notif_routes};
+
+    let conn = db.get()?;
+    Ok(conn.transaction::<_, diesel::result::Error, _>(|| {
+        delete(guild_notif_routes.filter(guild.eq(guild_id))).execute(&conn)?;
+        insert_into(guild_notif_routes)
+            .values(&routes)
+            .execute(&conn)?;
+
+        guild_notif_routes
+            .filter(guild.eq(guild_id))
+            .order_by(e_type)
+            .load(&conn)
+    })?)
+}
+
+pub fn get_role_mappings(db: &Pool, guild_id: &str) -> ServiceResult<Vec<RoleMapping>> {
+    use crate::schema::guild_role_mappings::dsl::{guild, guild_role_mappings, member_role};
+
+    let conn = db.get()?;
+    Ok(guild_role_mappings
+        .filter(guild.eq(guild_id))
+        .order_by(member_role)
+        .load(&conn)?)
+}
+
+/// Replaces all role mappings of a guild
+pub fn set_role_mappings(
+    db: &Pool,
+    guild_id: &str,
+    mappings: Vec<NewRoleMapping>,
+) -> ServiceResult<Vec<RoleMapping>> {
+    use crate::schema::guild_role_mappings::dsl::{guild, guild_role_mappings, member_role};
+
+    let conn = db.get()?;
+    Ok(conn.transaction::<_, diesel::result::Error, _>(|| {
+        delete(guild_role_mappings.filter(guild.eq(guild_id))).execute(&conn)?;
+        insert_into(guild_role_mappings)
+            .values(&mappings)
+            .execute(&conn)?;
+
+        guild_role_mappings
+            .filter(guild.eq(guild_id))
+            .order_by(member_role)
+            .load(&conn)
+    })?)
+}
+
+/// The discord ids and roles of all members of the class of a guild that are linked with discord
+/// The linked members of the class of a guild and their role, and the linked former members
+/// without a role
+pub fn get_discord_members(db: &Pool, guild_id: &str) -> ServiceResult<Vec<(String, Option<i32>)>> {
+    use crate::schema::former_members;
+    use crate::schema::members;
+    use crate::schema::users;
+
+    // an unlinked guild has no members, not an empty list
+    let class = get_class_by_discord(db, guild_id)?;
+    let conn = db.get()?;
+
+    let linked = members::table
+        .inner_join(users::table)
+        .filter(members::class.eq(class.id))
+        .select((users::discord_id, members::role.nullable()))
+        .load::<(Option<String>, Option<i32>)>(&conn)?;
+    // users that joined again are in the members
+    let former = former_members::table
+        .inner_join(users::table)
+        .left_join(
+            members::table.on(members::user
+                .eq(former_members::user)
+                .and(members::class.eq(former_members::class))),
+        )
+        .filter(former_members::class.eq(class.id))
+        .filter(members::user.is_null())
+        .select(users::discord_id)
+        .load::<Option<String>>(&conn)?;
+
+    Ok(linked
+        .into_iter()
+        .chain(former.into_iter().map(|snowflake| (snowflake, None)))
+        .filter_map(|(snowflake, role)| snowflake.map(|snowflake| (snowflake, role)))
+        .collect())
+}
+
 pub fn map_class_join_members(vec: Vec<(Class, (Member, User))>) -> Option<ClassMemberData> {
     match vec
         .into_iter()
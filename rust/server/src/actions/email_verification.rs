@@ -0,0 +1,63 @@
+use super::Pool;
+use crate::diesel::{QueryDsl, RunQueryDsl};
+use crate::error::{ServiceErr, ServiceResult};
+use crate::models::User;
+use crate::schema::email_verifications::dsl::*;
+use crate::schema::users;
+use crate::token;
+use diesel::{delete, insert_into, update, BoolExpressionMethods, Connection, ExpressionMethods};
+use uuid::Uuid;
+
+const VERIFICATION_TOKEN_LENGTH: usize = 40;
+
+/// Creates a token that verifies `address` for the user, older tokens of the user stop working.
+/// Returns the token, which can't be retrieved again
+pub fn create_email_verification(
+    db: &Pool,
+    user: Uuid,
+    address: &str,
+    lifetime: chrono::Duration,
+) -> ServiceResult<String> {
+    let conn = db.get()?;
+
+    let (token, hash) = token::generate("", VERIFICATION_TOKEN_LENGTH);
+
+    conn.transaction::<_, ServiceErr, _>(|| {
+        delete(email_verifications.filter(user_id.eq(user))).execute(&conn)?;
+        insert_into(email_verifications)
+            .values((
+                token_hash.eq(hash),
+                user_id.eq(user),
+                email.eq(address),
+                expires.eq(chrono::Utc::now().naive_utc() + lifetime),
+            ))
+            .execute(&conn)?;
+        Ok(())
+    })?;
+
+    Ok(token)
+}
+
+/// Uses up an unexpired token and marks the email of its user as verified.
+/// Fails with `NotFound` if the user changed the email since the token was sent
+pub fn redeem_email_verification(db: &Pool, token: &str) -> ServiceResult<User> {
+    let conn = db.get()?;
+
+    conn.transaction(|| {
+        let (user, address) = delete(
+            email_verifications.filter(
+                token_hash
+                    .eq(token::hash(token))
+                    .and(expires.gt(chrono::Utc::now().naive_utc())),
+            ),
+        )
+        .returning((user_id, email))
+        .get_result::<(Uuid, String)>(&conn)?;
+
+        Ok(
+            update(users::table.filter(users::id.eq(user).and(users::email.eq(address))))
+                .set(users::email_verified.eq(true))
+                .get_result(&conn)?,
+        )
+    })
+}
@@ -1,6 +1,6 @@
 use crate::actions::Pool;
 use crate::diesel::{QueryDsl, RunQueryDsl};
-use crate::models::{Class, Event, Guild, NewEvent};
+use crate::models::{Class, Event, Guild, NewEvent, NotifRoute};
 
 use crate::error::ServiceResult;
 use crate::schema::events::dsl::*;
@@ -69,6 +69,28 @@ pub fn get_events_by_class_filtered_both(
     Ok(vec)
 }
 
+/// The events that overlap the time from `from` until `until`
+pub fn get_events_by_class_between(
+    db: &Pool,
+    class_id: Uuid,
+    from: chrono::NaiveDateTime,
+    until: chrono::NaiveDateTime,
+) -> ServiceResult<Vec<Event>> {
+    let conn = db.get()?;
+
+    sql_function!(fn coalesce(a: Nullable<Timestamp>, b: Timestamp) -> Timestamp);
+
+    let vec: Vec<Event> = events
+        .filter(
+            class
+                .eq(class_id)
+                .and(start.lt(until).and(coalesce(end, start).ge(from))),
+        )
+        .load(&conn)?;
+
+    Ok(vec)
+}
+
 pub fn get_event_by_id(db: &Pool, event_id: Uuid) -> ServiceResult<Event> {
     let conn = db.get()?;
 
@@ -93,10 +115,15 @@ pub fn delete_event(db: &Pool, event_id: Uuid) -> ServiceResult<usize> {
     Ok(delete(events).filter(id.eq(event_id)).execute(&conn)?)
 }
 
-pub type Notifications = (chrono::NaiveDateTime, Vec<(Event, (Class, Guild))>);
+pub type Notifications = (
+    chrono::NaiveDateTime,
+    Vec<(Event, (Class, Guild), Option<NotifRoute>)>,
+);
 
+/// Get all due notifications, together with the notification route for the type of the event
 pub fn get_notifications(db: &Pool, since: chrono::NaiveDateTime) -> ServiceResult<Notifications> {
     use crate::schema::classes::dsl::{classes, discord_id};
+    use crate::schema::guild_notif_routes::dsl as routes;
     use crate::schema::guilds::dsl::{guilds, id as gid, notif_channel};
 
     let conn = db.get()?;
@@ -104,15 +131,22 @@ pub fn get_notifications(db: &Pool, since: chrono::NaiveDateTime) -> ServiceResu
     let current_time = chrono::Utc::now().naive_utc();
 
     sql_function!(fn coalesce(a: Nullable<VarChar>, b: VarChar) -> VarChar);
+    sql_function! {
+        #[sql_name = "coalesce"]
+        fn coalesce_nullable(a: Nullable<VarChar>, b: Nullable<VarChar>) -> Nullable<VarChar>;
+    }
 
     let notifications = events
         .inner_join(classes.inner_join(guilds.on(coalesce(discord_id, "").eq(gid))))
+        .left_join(
+            routes::guild_notif_routes.on(routes::guild.eq(gid).and(routes::e_type.eq(e_type))),
+        )
         .filter(
             notification
                 .is_not_null()
                 .and(notification.lt(current_time))
                 .and(notification.gt(since))
-                .and(notif_channel.is_not_null()),
+                .and(coalesce_nullable(routes::notif_channel, notif_channel).is_not_null()),
         )
         .load(&conn)?;
 
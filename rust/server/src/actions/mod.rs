@@ -1,28 +1,61 @@
+use crate::config::DatabaseConfig;
 use diesel::r2d2::ConnectionManager;
 use diesel::PgConnection;
 
+pub mod access_token;
+pub mod api_key;
 pub mod class;
+pub mod email_verification;
 pub mod event;
+pub mod password_reset;
+pub mod session;
+pub mod two_factor;
 pub mod user;
 
 type Connection = ConnectionManager<PgConnection>;
 pub type Pool = r2d2::Pool<Connection>;
 
+/// Connects to the database in the `DATABASE_URL` env var
+pub fn create_pool(config: &DatabaseConfig) -> Result<Pool, color_eyre::Report> {
+    let database_url = std::env::var("DATABASE_URL")
+        .map_err(|_| color_eyre::eyre::eyre!("DATABASE_URL must be set"))?;
+
+    let manager = ConnectionManager::<PgConnection>::new(database_url);
+    Ok(r2d2::Pool::builder()
+        .max_size(config.max_connections)
+        .min_idle(config.min_idle)
+        .connection_timeout(config.connection_timeout())
+        .build(manager)?)
+}
+
 #[cfg(test)]
 mod test {
     use diesel::r2d2::ConnectionManager;
     use diesel::PgConnection;
 
+    use super::access_token::*;
+    use super::api_key::*;
     use super::class::*;
+    use super::email_verification::*;
+    use super::password_reset::*;
+    use super::session;
+    use super::two_factor;
     use super::user::*;
     use crate::actions::event::{
-        get_events_by_class, get_events_by_class_filtered_after,
-        get_events_by_class_filtered_before, get_events_by_class_filtered_both, insert_event,
+        get_events_by_class, get_events_by_class_between, get_events_by_class_filtered_after,
+        get_events_by_class_filtered_before, get_events_by_class_filtered_both, get_notifications,
+        insert_event,
     };
     use crate::actions::Pool;
+    use crate::error::ServiceErr;
     use crate::models;
-    use crate::models::{Class, NewClass, NewEvent, NewMember, NewUser, User};
+    use crate::models::conversion::IntoDto;
+    use crate::models::{
+        Class, NewClass, NewClassInvite, NewEvent, NewGuild, NewMember, NewNotifRoute,
+        NewRoleMapping, NewUser, User,
+    };
     use chrono::NaiveDateTime;
+    use dto::ErrorCode;
     use dto::{Lesson, Timetable};
 
     fn get_pool() -> Pool {
@@ -48,6 +81,7 @@ mod test {
                 description: "test",
                 discord_id: None,
                 token_version: 0,
+                email_verified: false,
             },
         )
         .unwrap();
@@ -68,6 +102,7 @@ mod test {
                 name: "testklasse",
                 description: "",
                 discord_id: Some("4387208542528543"),
+                timezone: dto::DEFAULT_TIMEZONE,
             },
         )
         .unwrap();
@@ -76,6 +111,7 @@ mod test {
         assert_eq!(&*class.name, "testklasse");
         assert_eq!(class.description, "");
         assert_eq!(class.discord_id, Some("4387208542528543".to_string()));
+        assert_eq!(class.timezone, dto::DEFAULT_TIMEZONE);
 
         let class_by_discord = get_class_by_discord(&db, "4387208542528543").unwrap();
         assert_eq!(class_by_discord.id, class.id);
@@ -167,6 +203,7 @@ mod test {
                 description: "test",
                 discord_id: None,
                 token_version: 0,
+                email_verified: false,
             },
         )
         .unwrap();
@@ -174,13 +211,13 @@ mod test {
         assert_eq!(user.email, "test_password");
 
         let wrong_password = validate_user_password(&db, "test_password", "pass_wrt").unwrap();
-        assert!(matches!(wrong_password, None));
+        assert!(wrong_password.is_none());
 
         let wrong_email = validate_user_password(&db, "test_passwrd", "pass_wort").unwrap();
-        assert!(matches!(wrong_email, None));
+        assert!(wrong_email.is_none());
 
         let correct = validate_user_password(&db, "test_password", "pass_wort").unwrap();
-        assert!(matches!(correct, Some(_)));
+        assert!(correct.is_some());
 
         let validated_user = correct.unwrap();
         assert_eq!(validated_user.id, user.id);
@@ -188,13 +225,65 @@ mod test {
         delete_user(&db, user.id).unwrap();
     }
 
+    #[test]
+    fn legacy_password_upgrade() {
+        use crate::diesel::{QueryDsl, RunQueryDsl};
+        use crate::schema::users;
+        use diesel::sql_types::{Integer, Text};
+        use diesel::ExpressionMethods;
+
+        sql_function!(fn crypt(pwd: Text, salt: Text) -> Text);
+        sql_function!(fn gen_salt(kind: Text, number: Integer) -> Text);
+
+        let db = get_pool();
+        let user = insert_user(
+            &db,
+            NewUser {
+                id: uuid::Uuid::new_v4(),
+                email: &uuid::Uuid::new_v4().to_string(),
+                password: "pass_wort",
+                description: "test",
+                discord_id: None,
+                token_version: 0,
+                email_verified: false,
+            },
+        )
+        .unwrap();
+        assert!(user.password.starts_with("$argon2id$"));
+
+        // the hashes from before, made by pgcrypto
+        let conn = db.get().unwrap();
+        diesel::update(users::table.filter(users::id.eq(user.id)))
+            .set(users::password.eq(crypt("pass_wort", gen_salt("bf", 8))))
+            .execute(&conn)
+            .unwrap();
+
+        assert!(validate_user_password(&db, &user.email, "pass_wrt")
+            .unwrap()
+            .is_none());
+        assert!(get_user_by_id(&db, user.id)
+            .unwrap()
+            .password
+            .starts_with("$2a$"));
+
+        let upgraded = validate_user_password(&db, &user.email, "pass_wort")
+            .unwrap()
+            .unwrap();
+        assert!(upgraded.password.starts_with("$argon2id$"));
+        assert!(validate_user_password(&db, &user.email, "pass_wort")
+            .unwrap()
+            .is_some());
+
+        delete_user(&db, user.id).unwrap();
+    }
+
     #[test]
     fn event_filter() {
         let db = get_pool();
 
         let (owner, class) = insert_class_user(&db);
 
-        let events = vec![
+        let events = [
             insert_event(
                 &db,
                 NewEvent {
@@ -293,13 +382,930 @@ mod test {
         .unwrap();
         assert_eq!(events.len(), 3);
 
+        // events that start at the beginning of the time are included
+        let events = get_events_by_class_between(
+            &db,
+            class.id,
+            NaiveDateTime::from_timestamp(2000, 0),
+            NaiveDateTime::from_timestamp(2001, 0),
+        )
+        .unwrap();
+        assert_eq!(events.len(), 3);
+
+        let events = get_events_by_class_between(
+            &db,
+            class.id,
+            NaiveDateTime::from_timestamp(2001, 0),
+            NaiveDateTime::from_timestamp(3000, 0),
+        )
+        .unwrap();
+        assert_eq!(events.len(), 1);
+
+        delete_class(&db, class.id).unwrap();
+        delete_user(&db, owner.id).unwrap();
+    }
+
+    #[test]
+    fn link_discord_with_code() {
+        let db = get_pool();
+
+        let (owner, class) = insert_class_user(&db);
+        let snowflake = "8123456789012345";
+        let valid_for = chrono::Duration::minutes(10);
+
+        // created by the bot and entered on the website
+        let code = create_link_code(&db, None, Some(snowflake), valid_for).unwrap();
+        assert!(matches!(
+            redeem_link_code(&db, &code.code, None, Some(snowflake)),
+            Err(ServiceErr::BadRequest(ErrorCode::InvalidLinkCode))
+        ));
+        let user = redeem_link_code(&db, &code.code.to_lowercase(), Some(owner.id), None).unwrap();
+        assert_eq!(user.discord_id.as_deref(), Some(snowflake));
+        assert!(redeem_link_code(&db, &code.code, Some(owner.id), None).is_err());
+
+        set_discord_id_user(&db, owner.id, None).unwrap();
+
+        // created on the website and entered in discord, older codes are replaced
+        let old = create_link_code(&db, Some(owner.id), None, valid_for).unwrap();
+        let code = create_link_code(&db, Some(owner.id), None, valid_for).unwrap();
+        assert!(redeem_link_code(&db, &old.code, None, Some(snowflake)).is_err());
+        let user = redeem_link_code(&db, &code.code, None, Some(snowflake)).unwrap();
+        assert_eq!(user.discord_id.as_deref(), Some(snowflake));
+
+        let expired =
+            create_link_code(&db, None, Some(snowflake), chrono::Duration::minutes(-1)).unwrap();
+        assert!(redeem_link_code(&db, &expired.code, Some(owner.id), None).is_err());
+
         delete_class(&db, class.id).unwrap();
         delete_user(&db, owner.id).unwrap();
     }
 
+    #[test]
+    fn discord_login_creates_and_merges_users() {
+        let db = get_pool();
+
+        let (owner, class) = insert_class_user(&db);
+        let merged_snowflake = "8223456789012345";
+        let new_snowflake = "8323456789012345";
+
+        // an unverified email may belong to someone else
+        assert!(matches!(
+            login_with_discord(&db, merged_snowflake, Some(&owner.email), true),
+            Err(ServiceErr::Conflict(ErrorCode::EmailAccountNotVerified))
+        ));
+        set_email_verified(&db, owner.id, true).unwrap();
+
+        // the verified email of an existing account merges it
+        let merged = login_with_discord(&db, merged_snowflake, Some(&owner.email), true).unwrap();
+        assert_eq!(merged.id, owner.id);
+        assert_eq!(merged.discord_id.as_deref(), Some(merged_snowflake));
+        assert_eq!(merged.token_version, owner.token_version + 1);
+
+        // the next login finds the user by discord
+        let again = login_with_discord(&db, merged_snowflake, None, true).unwrap();
+        assert_eq!(again.id, owner.id);
+        assert_eq!(again.token_version, merged.token_version);
+
+        assert!(matches!(
+            login_with_discord(&db, new_snowflake, Some(&owner.email), true),
+            Err(ServiceErr::Conflict(ErrorCode::EmailLinkedWithOtherDiscord))
+        ));
+        assert!(matches!(
+            login_with_discord(&db, new_snowflake, None, true),
+            Err(ServiceErr::BadRequest(ErrorCode::DiscordEmailMissing))
+        ));
+
+        let new_email = uuid::Uuid::new_v4().to_string();
+        assert!(matches!(
+            login_with_discord(&db, new_snowflake, Some(&new_email), false),
+            Err(ServiceErr::Forbidden(ErrorCode::SignupDisabled))
+        ));
+        let created = login_with_discord(&db, new_snowflake, Some(&new_email), true).unwrap();
+        assert_ne!(created.id, owner.id);
+        assert_eq!(created.email, new_email);
+        assert_eq!(created.discord_id.as_deref(), Some(new_snowflake));
+
+        delete_user(&db, created.id).unwrap();
+        delete_class(&db, class.id).unwrap();
+        delete_user(&db, owner.id).unwrap();
+    }
+
+    #[test]
+    fn transfer_ownership_and_unlink_guild() {
+        let db = get_pool();
+
+        let (owner, class) = insert_class_user(&db);
+        let (new_owner, other_class) = insert_class_user(&db);
+        create_member(
+            &db,
+            NewMember {
+                user: owner.id,
+                class: class.id,
+                display_name: "Owner",
+                role: models::MemberRole::OWNER,
+            },
+        )
+        .unwrap();
+
+        let transferred = transfer_ownership(&db, class.id, new_owner.id, "New").unwrap();
+        assert_eq!(transferred.owner, new_owner.id);
+        let (old, _) = get_member(&db, owner.id, class.id).unwrap();
+        assert_eq!(old.role, models::MemberRole::ADMIN);
+        let (new, _) = get_member(&db, new_owner.id, class.id).unwrap();
+        assert_eq!(new.role, models::MemberRole::OWNER);
+        assert_eq!(new.display_name, "New");
+        assert!(get_owned_classes(&db, owner.id).unwrap().is_empty());
+        assert_eq!(get_owned_classes(&db, new_owner.id).unwrap().len(), 2);
+        assert_eq!(get_all_members(&db, class.id).unwrap().len(), 2);
+
+        let guild_id = "7312984652392";
+        set_discord_id_class(&db, class.id, Some(guild_id)).unwrap();
+        insert_guild(
+            &db,
+            NewGuild {
+                id: guild_id,
+                notif_channel: None,
+                notif_ping_role: None,
+                notif_ping_everyone: false,
+                language: None,
+                admin_channel: None,
+            },
+        )
+        .unwrap();
+        assert!(get_guilds(&db)
+            .unwrap()
+            .iter()
+            .any(|(guild, guild_class)| guild.id == guild_id && guild_class.id == class.id));
+
+        assert_eq!(unlink_guild(&db, guild_id).unwrap(), 1);
+        assert!(matches!(
+            get_class_by_discord(&db, guild_id),
+            Err(ServiceErr::NotFound)
+        ));
+        assert_eq!(unlink_guild(&db, guild_id).unwrap(), 0);
+
+        delete_class(&db, class.id).unwrap();
+        delete_class(&db, other_class.id).unwrap();
+        delete_user(&db, owner.id).unwrap();
+        delete_user(&db, new_owner.id).unwrap();
+    }
+
+    #[test]
+    fn delete_user_with_owned_classes() {
+        let db = get_pool();
+
+        let (owner, class) = insert_class_user(&db);
+        let (anna, anna_class) = insert_class_user(&db);
+        let (bertha, bertha_class) = insert_class_user(&db);
+        let lonely = insert_class(
+            &db,
+            NewClass {
+                id: uuid::Uuid::new_v4(),
+                owner: owner.id,
+                name: "einsam",
+                description: "",
+                discord_id: None,
+                timezone: "Europe/Zurich",
+            },
+        )
+        .unwrap();
+        for (user, display_name, role) in [
+            (&owner, "Owner", models::MemberRole::OWNER),
+            (&bertha, "Bertha", models::MemberRole::ADMIN),
+            (&anna, "Anna", models::MemberRole::ADMIN),
+        ] {
+            create_member(
+                &db,
+                NewMember {
+                    user: user.id,
+                    class: class.id,
+                    display_name,
+                    role,
+                },
+            )
+            .unwrap();
+        }
+
+        let memberships = get_memberships(&db, owner.id).unwrap();
+        assert_eq!(memberships.len(), 1);
+        assert_eq!(memberships[0].1.role, models::MemberRole::OWNER);
+
+        let deletions = get_owned_class_deletions(&db, owner.id).unwrap();
+        assert_eq!(deletions.len(), 2);
+        for (owned, next_owner) in &deletions {
+            match owned.id == class.id {
+                true => assert_eq!(next_owner.as_ref().unwrap().0.user, anna.id),
+                false => assert!(next_owner.is_none()),
+            }
+        }
+
+        let owned = [(class.id, Some(anna.id)), (lonely.id, None)];
+        assert_eq!(delete_user_with_classes(&db, owner.id, &owned).unwrap(), 1);
+        let (new_owner, _) = get_member(&db, anna.id, class.id).unwrap();
+        assert_eq!(new_owner.role, models::MemberRole::OWNER);
+        assert_eq!(get_owned_classes(&db, anna.id).unwrap().len(), 2);
+        assert_eq!(get_all_members(&db, class.id).unwrap().len(), 2);
+        assert!(get_classes(&db).unwrap().iter().all(|c| c.id != lonely.id));
+        assert!(matches!(
+            get_user_by_id(&db, owner.id),
+            Err(ServiceErr::NotFound)
+        ));
+
+        delete_class(&db, class.id).unwrap();
+        delete_class(&db, anna_class.id).unwrap();
+        delete_class(&db, bertha_class.id).unwrap();
+        delete_user(&db, anna.id).unwrap();
+        delete_user(&db, bertha.id).unwrap();
+    }
+
+    #[test]
+    fn import_class_with_invites() {
+        let db = get_pool();
+
+        let (owner, owner_class) = insert_class_user(&db);
+        let (member, member_class) = insert_class_user(&db);
+
+        let start = NaiveDateTime::from_timestamp(1_700_000_000, 0);
+        let import = |class_id: uuid::Uuid| ClassImport {
+            class: NewClass {
+                id: class_id,
+                owner: owner.id,
+                name: "importiert",
+                description: "",
+                discord_id: None,
+                timezone: "Europe/Zurich",
+            },
+            require_two_factor: false,
+            members: vec![NewMember {
+                user: owner.id,
+                class: class_id,
+                display_name: "Owner",
+                role: models::MemberRole::OWNER,
+            }],
+            invites: vec![NewClassInvite {
+                class: class_id,
+                email: &member.email,
+                display_name: "Member",
+                role: models::MemberRole::ADMIN,
+            }],
+            events: vec![NewEvent {
+                id: uuid::Uuid::new_v4(),
+                class: class_id,
+                e_type: 2,
+                name: "Mathe",
+                start: &start,
+                end: None,
+                description: "",
+                notification: None,
+            }],
+            timetable: Some("[[],[],[],[],[],[],[]]"),
+        };
+
+        let class = import_class(&db, import(uuid::Uuid::new_v4())).unwrap();
+        assert_eq!(class.discord_id, None);
+        assert_eq!(get_all_members(&db, class.id).unwrap().len(), 1);
+        assert_eq!(get_invites(&db, &member.email).unwrap().len(), 1);
+        assert_eq!(get_events_by_class(&db, class.id).unwrap().len(), 1);
+        assert!(get_timetable(&db, class.id).is_ok());
+        let other_class = import_class(&db, import(uuid::Uuid::new_v4())).unwrap();
+
+        // the invited user joins when they accept
+        let joined = accept_invite(&db, &member, class.id).unwrap();
+        assert_eq!(joined.role, models::MemberRole::ADMIN);
+        assert_eq!(joined.display_name, "Member");
+        assert!(matches!(
+            accept_invite(&db, &member, class.id),
+            Err(ServiceErr::NotFound)
+        ));
+        assert_eq!(
+            decline_invite(&db, &member.email, other_class.id).unwrap(),
+            1
+        );
+        assert!(get_invites(&db, &member.email).unwrap().is_empty());
+
+        delete_class(&db, class.id).unwrap();
+        delete_class(&db, other_class.id).unwrap();
+        delete_class(&db, owner_class.id).unwrap();
+        delete_class(&db, member_class.id).unwrap();
+        delete_user(&db, owner.id).unwrap();
+        delete_user(&db, member.id).unwrap();
+    }
+
+    #[test]
+    fn clone_class_moves_guild() {
+        let db = get_pool();
+
+        let (owner, source) = insert_class_user(&db);
+        let guild_id = "7312984652394";
+        set_discord_id_class(&db, source.id, Some(guild_id)).unwrap();
+        insert_guild(
+            &db,
+            NewGuild {
+                id: guild_id,
+                notif_channel: Some("100"),
+                notif_ping_role: None,
+                notif_ping_everyone: false,
+                language: None,
+                admin_channel: None,
+            },
+        )
+        .unwrap();
+        set_role_mappings(
+            &db,
+            guild_id,
+            vec![NewRoleMapping {
+                guild: guild_id,
+                member_role: models::MemberRole::ADMIN,
+                discord_role: "400",
+            }],
+        )
+        .unwrap();
+        assert!(!is_archived(&db, source.id).unwrap());
+
+        let clone_id = uuid::Uuid::new_v4();
+        let clone = clone_class(
+            &db,
+            source.id,
+            ClassImport {
+                class: NewClass {
+                    id: clone_id,
+                    owner: owner.id,
+                    name: "geklont",
+                    description: "",
+                    discord_id: Some(guild_id),
+                    timezone: "Europe/Zurich",
+                },
+                require_two_factor: false,
+                members: vec![NewMember {
+                    user: owner.id,
+                    class: clone_id,
+                    display_name: "Owner",
+                    role: models::MemberRole::OWNER,
+                }],
+                invites: vec![],
+                events: vec![],
+                timetable: None,
+            },
+            true,
+        )
+        .unwrap();
+        assert_eq!(clone.discord_id.as_deref(), Some(guild_id));
+        assert!(!clone.archived);
+        // the settings stay with the guild
+        assert_eq!(get_role_mappings(&db, guild_id).unwrap().len(), 1);
+        assert_eq!(
+            get_guild_settings(&db, guild_id)
+                .unwrap()
+                .notif_channel
+                .as_deref(),
+            Some("100")
+        );
+
+        assert!(is_archived(&db, source.id).unwrap());
+        let source = set_archived(&db, source.id, false).unwrap();
+        assert_eq!(source.discord_id, None);
+        assert!(!source.archived);
+
+        delete_class(&db, source.id).unwrap();
+        delete_class(&db, clone.id).unwrap();
+        delete_user(&db, owner.id).unwrap();
+    }
+
+    #[test]
+    fn notification_routes() {
+        let db = get_pool();
+
+        let (owner, class) = insert_class_user(&db);
+        let guild_id = "7312984652391";
+        set_discord_id_class(&db, class.id, Some(guild_id)).unwrap();
+        insert_guild(
+            &db,
+            NewGuild {
+                id: guild_id,
+                notif_channel: Some("100"),
+                notif_ping_role: Some("200"),
+                notif_ping_everyone: false,
+                language: Some("fr"),
+                admin_channel: None,
+            },
+        )
+        .unwrap();
+
+        let routes = set_notif_routes(
+            &db,
+            guild_id,
+            vec![NewNotifRoute {
+                guild: guild_id,
+                e_type: dto::EventType::Exam as i32,
+                notif_channel: Some("101"),
+                notif_ping_role: None,
+                notif_ping_everyone: Some(true),
+            }],
+        )
+        .unwrap();
+        assert_eq!(routes.len(), 1);
+
+        let since = chrono::Utc::now().naive_utc() - chrono::Duration::minutes(1);
+        let due = chrono::Utc::now().naive_utc() - chrono::Duration::seconds(10);
+        for (e_type, name) in &[(1, "homework"), (2, "exam")] {
+            insert_event(
+                &db,
+                NewEvent {
+                    id: uuid::Uuid::new_v4(),
+                    class: class.id,
+                    e_type: *e_type,
+                    name,
+                    start: &due,
+                    end: None,
+                    description: "",
+                    notification: Some(&due),
+                },
+            )
+            .unwrap();
+        }
+
+        let (_, notifications) = get_notifications(&db, since).unwrap();
+        let notifications: Vec<dto::Notification> = notifications
+            .into_iter()
+            .filter(|(event, _, _)| event.class == class.id)
+            .map(IntoDto::into_dto)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(notifications.len(), 2);
+
+        let homework = notifications
+            .iter()
+            .find(|notif| notif.event.name == "homework")
+            .unwrap();
+        assert_eq!(homework.channel, "100");
+        assert_eq!(homework.role_ping, Some("200".to_string()));
+        assert!(!homework.everyone_ping);
+
+        let exam = notifications
+            .iter()
+            .find(|notif| notif.event.name == "exam")
+            .unwrap();
+        assert_eq!(exam.channel, "101");
+        assert_eq!(exam.timezone, "America/New_York");
+        assert_eq!(exam.language.as_deref(), Some("fr"));
+        assert_eq!(exam.role_ping, Some("200".to_string()));
+        assert!(exam.everyone_ping);
+
+        let routes = set_notif_routes(&db, guild_id, vec![]).unwrap();
+        assert!(routes.is_empty());
+
+        delete_class(&db, class.id).unwrap();
+        delete_user(&db, owner.id).unwrap();
+    }
+
+    #[test]
+    fn role_mappings_and_discord_members() {
+        let db = get_pool();
+
+        let (owner, class) = insert_class_user(&db);
+        let (banned, other_class) = insert_class_user(&db);
+        let guild_id = "7412984652391";
+        set_discord_id_class(&db, class.id, Some(guild_id)).unwrap();
+        insert_guild(
+            &db,
+            NewGuild {
+                id: guild_id,
+                notif_channel: None,
+                notif_ping_role: None,
+                notif_ping_everyone: false,
+                language: None,
+                admin_channel: None,
+            },
+        )
+        .unwrap();
+
+        let mappings = set_role_mappings(
+            &db,
+            guild_id,
+            vec![
+                NewRoleMapping {
+                    guild: guild_id,
+                    member_role: 2,
+                    discord_role: "301",
+                },
+                NewRoleMapping {
+                    guild: guild_id,
+                    member_role: 1,
+                    discord_role: "300",
+                },
+            ],
+        )
+        .unwrap();
+        assert_eq!(mappings.len(), 2);
+        let mappings = get_role_mappings(&db, guild_id).unwrap();
+        assert_eq!(mappings[0].member_role, 1);
+        assert_eq!(mappings[0].discord_role, "300");
+
+        for (user, role) in &[(&owner, 0), (&banned, 4)] {
+            create_member(
+                &db,
+                NewMember {
+                    user: user.id,
+                    class: class.id,
+                    display_name: "member",
+                    role: *role,
+                },
+            )
+            .unwrap();
+        }
+
+        // only linked members are returned
+        set_discord_id_user(&db, banned.id, Some("8423456789012345")).unwrap();
+        let members = get_discord_members(&db, guild_id).unwrap();
+        assert_eq!(members, vec![("8423456789012345".to_string(), Some(4))]);
+
+        // kicked members are reported without a role until they join again
+        delete_member(&db, banned.id, class.id).unwrap();
+        let members = get_discord_members(&db, guild_id).unwrap();
+        assert_eq!(members, vec![("8423456789012345".to_string(), None)]);
+        create_member(
+            &db,
+            NewMember {
+                user: banned.id,
+                class: class.id,
+                display_name: "member",
+                role: 2,
+            },
+        )
+        .unwrap();
+        let members = get_discord_members(&db, guild_id).unwrap();
+        assert_eq!(members, vec![("8423456789012345".to_string(), Some(2))]);
+
+        assert!(matches!(
+            get_discord_members(&db, "7512984652391"),
+            Err(ServiceErr::NotFound)
+        ));
+
+        delete_class(&db, class.id).unwrap();
+        assert!(get_role_mappings(&db, guild_id).unwrap().is_empty());
+        delete_class(&db, other_class.id).unwrap();
+        delete_user(&db, owner.id).unwrap();
+        delete_user(&db, banned.id).unwrap();
+    }
+
+    #[test]
+    fn join_request_notifications() {
+        let db = get_pool();
+
+        let (owner, class) = insert_class_user(&db);
+        let (requester, other_class) = insert_class_user(&db);
+        let guild_id = "7612984652391";
+        set_discord_id_class(&db, class.id, Some(guild_id)).unwrap();
+        insert_guild(
+            &db,
+            NewGuild {
+                id: guild_id,
+                notif_channel: None,
+                notif_ping_role: None,
+                notif_ping_everyone: false,
+                language: None,
+                admin_channel: None,
+            },
+        )
+        .unwrap();
+
+        let since = chrono::Utc::now().naive_utc() - chrono::Duration::minutes(1);
+        create_join_request(
+            &db,
+            NewMember {
+                user: requester.id,
+                class: class.id,
+                display_name: "requester",
+                role: 3,
+            },
+        )
+        .unwrap();
+
+        let of_class = |since| {
+            get_join_request_notifications(&db, since)
+                .unwrap()
+                .1
+                .into_iter()
+                .filter(|(request, ..)| request.class == class.id)
+                .collect::<Vec<_>>()
+        };
+
+        // guilds without an admin channel don't get the requests
+        assert!(of_class(since).is_empty());
+
+        change_guild_settings(
+            &db,
+            NewGuild {
+                id: guild_id,
+                notif_channel: None,
+                notif_ping_role: None,
+                notif_ping_everyone: false,
+                language: None,
+                admin_channel: Some("500"),
+            },
+        )
+        .unwrap();
+
+        let requests = of_class(since);
+        assert_eq!(requests.len(), 1);
+        let notification: dto::JoinRequestNotification =
+            requests.into_iter().next().unwrap().into_dto().unwrap();
+        assert_eq!(notification.user_id, requester.id);
+        assert_eq!(notification.display_name, "requester");
+        assert_eq!(notification.channel, "500");
+
+        assert!(of_class(chrono::Utc::now().naive_utc()).is_empty());
+
+        assert_eq!(delete_join_request(&db, requester.id, class.id).unwrap(), 1);
+        assert!(of_class(since).is_empty());
+
+        delete_class(&db, class.id).unwrap();
+        delete_class(&db, other_class.id).unwrap();
+        delete_user(&db, owner.id).unwrap();
+        delete_user(&db, requester.id).unwrap();
+    }
+
+    #[test]
+    fn api_keys() {
+        let db = get_pool();
+
+        let name = uuid::Uuid::new_v4().to_string();
+        let (api_key, key) = create_api_key(&db, &name, vec!["bot:guilds".to_string()]).unwrap();
+        assert!(key.starts_with(API_KEY_PREFIX));
+        assert_eq!(api_key.scopes, vec!["bot:guilds".to_string()]);
+        // only the hash is stored
+        assert_ne!(api_key.key_hash, key.as_bytes());
+
+        assert_eq!(get_api_key(&db, &key).unwrap().id, api_key.id);
+        assert!(matches!(
+            get_api_key(&db, &format!("{}x", key)),
+            Err(ServiceErr::NotFound)
+        ));
+        assert!(matches!(
+            create_api_key(&db, &name, vec![]),
+            Err(ServiceErr::Conflict(_))
+        ));
+        assert!(get_api_keys(&db)
+            .unwrap()
+            .iter()
+            .any(|key| key.id == api_key.id));
+
+        assert_eq!(delete_api_key(&db, &name).unwrap(), 1);
+        assert!(matches!(get_api_key(&db, &key), Err(ServiceErr::NotFound)));
+    }
+
+    #[test]
+    fn access_tokens() {
+        let db = get_pool();
+
+        let (user, class) = insert_class_user(&db);
+        let ((token, scopes), secret) =
+            create_access_token(&db, user.id, "export", None, vec![(class.id, false)]).unwrap();
+        assert!(secret.starts_with(ACCESS_TOKEN_PREFIX));
+        assert_eq!(scopes.len(), 1);
+        assert!(!scopes[0].write);
+
+        let (found, found_scopes) = get_access_token(&db, &secret).unwrap();
+        assert_eq!(found.id, token.id);
+        assert_eq!(found.user_id, user.id);
+        assert_eq!(found_scopes[0].class, class.id);
+
+        // the names are unique per user
+        assert!(matches!(
+            create_access_token(&db, user.id, "export", None, vec![]),
+            Err(ServiceErr::Conflict(_))
+        ));
+
+        let expired = chrono::Utc::now().naive_utc() - chrono::Duration::minutes(1);
+        let (_, expired_secret) =
+            create_access_token(&db, user.id, "old", Some(expired), vec![]).unwrap();
+        assert!(matches!(
+            get_access_token(&db, &expired_secret),
+            Err(ServiceErr::NotFound)
+        ));
+
+        let tokens = get_access_tokens(&db, user.id).unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].0.name, "export");
+        assert_eq!(tokens[0].1.len(), 1);
+        assert!(tokens[1].1.is_empty());
+
+        let (other, other_class) = insert_class_user(&db);
+        assert_eq!(delete_access_token(&db, other.id, token.id).unwrap(), 0);
+        assert_eq!(delete_access_token(&db, user.id, token.id).unwrap(), 1);
+        assert!(matches!(
+            get_access_token(&db, &secret),
+            Err(ServiceErr::NotFound)
+        ));
+
+        delete_class(&db, class.id).unwrap();
+        delete_class(&db, other_class.id).unwrap();
+        delete_user(&db, user.id).unwrap();
+        delete_user(&db, other.id).unwrap();
+    }
+
+    #[test]
+    fn sessions() {
+        let db = get_pool();
+
+        let (user, class) = insert_class_user(&db);
+        let idle = chrono::Duration::days(30);
+        let first = session::create_session(&db, user.id, &"a".repeat(300), idle).unwrap();
+        assert_eq!(first.device.len(), 200);
+        assert_eq!(first.generation, 0);
+        let second = session::create_session(&db, user.id, "phone", idle).unwrap();
+
+        let rotated = session::rotate_session(&db, first.id, 0).unwrap();
+        assert_eq!(rotated.generation, 1);
+        assert!(rotated.last_used >= first.last_used);
+        let rotated = session::rotate_session(&db, first.id, 1).unwrap();
+        assert_eq!(rotated.generation, 2);
+
+        // an old refresh token revokes the session
+        assert!(matches!(
+            session::rotate_session(&db, first.id, 1),
+            Err(ServiceErr::Unauthorized(ErrorCode::TokenReused))
+        ));
+        assert!(matches!(
+            session::rotate_session(&db, first.id, 2),
+            Err(ServiceErr::Unauthorized(ErrorCode::SessionRevoked))
+        ));
+
+        let sessions = session::get_sessions(&db, user.id).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].id, second.id);
+
+        let (other, other_class) = insert_class_user(&db);
+        assert_eq!(
+            session::delete_session(&db, other.id, second.id).unwrap(),
+            0
+        );
+        session::create_session(&db, user.id, "laptop", idle).unwrap();
+        assert_eq!(session::delete_sessions(&db, user.id).unwrap(), 2);
+        assert!(session::get_sessions(&db, user.id).unwrap().is_empty());
+
+        delete_class(&db, class.id).unwrap();
+        delete_class(&db, other_class.id).unwrap();
+        delete_user(&db, user.id).unwrap();
+        delete_user(&db, other.id).unwrap();
+    }
+
+    #[test]
+    fn password_resets() {
+        let db = get_pool();
+
+        let (user, class) = insert_class_user(&db);
+        let hour = chrono::Duration::hours(1);
+        let old = create_password_reset(&db, user.id, hour).unwrap();
+        let token = create_password_reset(&db, user.id, hour).unwrap();
+        assert_ne!(old, token);
+
+        // only the newest token works, and only once
+        assert!(matches!(
+            redeem_password_reset(&db, &old),
+            Err(ServiceErr::NotFound)
+        ));
+        assert_eq!(redeem_password_reset(&db, &token).unwrap(), user.id);
+        assert!(matches!(
+            redeem_password_reset(&db, &token),
+            Err(ServiceErr::NotFound)
+        ));
+
+        let expired = create_password_reset(&db, user.id, -hour).unwrap();
+        assert!(matches!(
+            redeem_password_reset(&db, &expired),
+            Err(ServiceErr::NotFound)
+        ));
+
+        assert_eq!(
+            get_user_by_email(&db, &user.email).unwrap().unwrap().id,
+            user.id
+        );
+        assert!(get_user_by_email(&db, "nobody@example.com")
+            .unwrap()
+            .is_none());
+
+        delete_class(&db, class.id).unwrap();
+        delete_user(&db, user.id).unwrap();
+    }
+
+    #[test]
+    fn email_verifications() {
+        let db = get_pool();
+
+        let (user, class) = insert_class_user(&db);
+        assert!(!user.email_verified);
+        let day = chrono::Duration::days(1);
+
+        let token = create_email_verification(&db, user.id, &user.email, day).unwrap();
+        let verified = redeem_email_verification(&db, &token).unwrap();
+        assert!(verified.email_verified);
+        assert!(matches!(
+            redeem_email_verification(&db, &token),
+            Err(ServiceErr::NotFound)
+        ));
+
+        // keeping the email keeps the verification
+        let updated = update_user(&db, verified.clone()).unwrap();
+        assert!(updated.email_verified);
+
+        // a token for the old email doesn't verify the new one
+        let old_token = create_email_verification(&db, user.id, &user.email, day).unwrap();
+        let changed = update_user(
+            &db,
+            User {
+                email: format!("new{}", user.email),
+                ..verified
+            },
+        )
+        .unwrap();
+        assert!(!changed.email_verified);
+        assert!(matches!(
+            redeem_email_verification(&db, &old_token),
+            Err(ServiceErr::NotFound)
+        ));
+
+        let expired = create_email_verification(&db, user.id, &changed.email, -day).unwrap();
+        assert!(matches!(
+            redeem_email_verification(&db, &expired),
+            Err(ServiceErr::NotFound)
+        ));
+
+        delete_class(&db, class.id).unwrap();
+        delete_user(&db, user.id).unwrap();
+    }
+
+    #[test]
+    fn two_factor_authentication() {
+        let db = get_pool();
+
+        let (user, class) = insert_class_user(&db);
+        let secret = crate::totp::generate_secret();
+        let code = || {
+            crate::totp::code_at(
+                &secret,
+                crate::totp::time_step(chrono::Utc::now().timestamp()),
+            )
+        };
+
+        two_factor::start_enrollment(&db, user.id, &secret).unwrap();
+        assert!(!two_factor::is_two_factor_enabled(&db, user.id).unwrap());
+        // codes of unfinished enrollments can't be used for logins
+        assert!(!two_factor::verify_code(&db, user.id, &code()).unwrap());
+
+        let first_code = code();
+        assert!(two_factor::verify_totp(&db, user.id, &first_code).unwrap());
+        // a code can only be used once
+        assert!(!two_factor::verify_totp(&db, user.id, &first_code).unwrap());
+
+        let recovery_codes = two_factor::enable_two_factor(&db, user.id).unwrap();
+        assert_eq!(recovery_codes.len(), 10);
+        assert!(two_factor::is_two_factor_enabled(&db, user.id).unwrap());
+        assert!(matches!(
+            two_factor::start_enrollment(&db, user.id, &secret),
+            Err(ServiceErr::Conflict(ErrorCode::TwoFactorEnabled))
+        ));
+
+        let recovery_code = recovery_codes[0].to_uppercase();
+        assert!(two_factor::verify_code(&db, user.id, &recovery_code).unwrap());
+        assert!(!two_factor::verify_code(&db, user.id, &recovery_code).unwrap());
+        assert!(!two_factor::verify_code(&db, user.id, "aaaaa-aaaaa").unwrap());
+
+        let challenge =
+            two_factor::create_login_challenge(&db, user.id, chrono::Duration::minutes(5)).unwrap();
+        assert_eq!(
+            two_factor::get_login_challenge(&db, challenge.id)
+                .unwrap()
+                .user_id,
+            user.id
+        );
+        two_factor::fail_login_challenge(&db, challenge.id, 2).unwrap();
+        assert!(two_factor::get_login_challenge(&db, challenge.id).is_ok());
+        two_factor::fail_login_challenge(&db, challenge.id, 2).unwrap();
+        assert!(matches!(
+            two_factor::get_login_challenge(&db, challenge.id),
+            Err(ServiceErr::NotFound)
+        ));
+
+        assert!(!is_two_factor_required(&db, class.id).unwrap());
+        assert!(
+            set_two_factor_required(&db, class.id, true)
+                .unwrap()
+                .require_two_factor
+        );
+        assert!(is_two_factor_required(&db, class.id).unwrap());
+
+        two_factor::disable_two_factor(&db, user.id).unwrap();
+        assert!(two_factor::get_two_factor(&db, user.id).unwrap().is_none());
+        assert!(!two_factor::verify_code(&db, user.id, &recovery_codes[1]).unwrap());
+
+        delete_class(&db, class.id).unwrap();
+        delete_user(&db, user.id).unwrap();
+    }
+
     fn insert_class_user(db: &Pool) -> (User, Class) {
         let user = insert_user(
-            &db,
+            db,
             NewUser {
                 id: uuid::Uuid::new_v4(),
                 email: &uuid::Uuid::new_v4().to_string(),
@@ -307,18 +1313,20 @@ mod test {
                 description: "test",
                 discord_id: None,
                 token_version: 0,
+                email_verified: false,
             },
         )
         .unwrap();
 
         let class = insert_class(
-            &db,
+            db,
             NewClass {
                 id: uuid::Uuid::new_v4(),
                 owner: user.id,
                 name: "testklasse",
                 description: "",
                 discord_id: None,
+                timezone: "America/New_York",
             },
         )
         .unwrap();
@@ -0,0 +1,55 @@
+use super::Pool;
+use crate::diesel::{QueryDsl, RunQueryDsl};
+use crate::error::{ServiceErr, ServiceResult};
+use crate::schema::password_resets::dsl::*;
+use crate::token;
+use diesel::{delete, insert_into, BoolExpressionMethods, Connection, ExpressionMethods};
+use uuid::Uuid;
+
+const RESET_TOKEN_LENGTH: usize = 40;
+
+/// Creates a reset token for the user that is valid for `lifetime`, older tokens of the user
+/// stop working. Returns the token, which can't be retrieved again
+pub fn create_password_reset(
+    db: &Pool,
+    user: Uuid,
+    lifetime: chrono::Duration,
+) -> ServiceResult<String> {
+    let conn = db.get()?;
+
+    let (token, hash) = token::generate("", RESET_TOKEN_LENGTH);
+
+    conn.transaction::<_, ServiceErr, _>(|| {
+        delete(password_resets.filter(user_id.eq(user))).execute(&conn)?;
+        insert_into(password_resets)
+            .values((
+                token_hash.eq(hash),
+                user_id.eq(user),
+                expires.eq(chrono::Utc::now().naive_utc() + lifetime),
+            ))
+            .execute(&conn)?;
+        Ok(())
+    })?;
+
+    Ok(token)
+}
+
+/// Uses up an unexpired reset token and returns its user, all reset tokens of the user are removed
+pub fn redeem_password_reset(db: &Pool, token: &str) -> ServiceResult<Uuid> {
+    let conn = db.get()?;
+
+    conn.transaction(|| {
+        let user = delete(
+            password_resets.filter(
+                token_hash
+                    .eq(token::hash(token))
+                    .and(expires.gt(chrono::Utc::now().naive_utc())),
+            ),
+        )
+        .returning(user_id)
+        .get_result::<Uuid>(&conn)?;
+
+        delete(password_resets.filter(user_id.eq(user))).execute(&conn)?;
+        Ok(user)
+    })
+}
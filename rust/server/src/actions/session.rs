@@ -0,0 +1,92 @@
+use super::Pool;
+use crate::diesel::{QueryDsl, RunQueryDsl};
+use crate::error::{ServiceErr, ServiceResult};
+use crate::models::Session;
+use crate::schema::sessions::dsl::*;
+use diesel::{delete, insert_into, update, BoolExpressionMethods, ExpressionMethods};
+use dto::ErrorCode;
+use uuid::Uuid;
+
+/// The maximum length of the device of a session, longer user agents are cut off
+const MAX_DEVICE_LENGTH: usize = 200;
+
+/// Creates a session for a login, sessions that weren't used for `max_idle` are removed
+pub fn create_session(
+    db: &Pool,
+    user: Uuid,
+    session_device: &str,
+    max_idle: chrono::Duration,
+) -> ServiceResult<Session> {
+    let conn = db.get()?;
+
+    let now = chrono::Utc::now().naive_utc();
+    delete(sessions.filter(last_used.lt(now - max_idle))).execute(&conn)?;
+
+    let session_device = session_device
+        .chars()
+        .take(MAX_DEVICE_LENGTH)
+        .collect::<String>();
+
+    Ok(insert_into(sessions)
+        .values((
+            id.eq(Uuid::new_v4()),
+            user_id.eq(user),
+            device.eq(session_device),
+            created.eq(now),
+            last_used.eq(now),
+        ))
+        .get_result(&conn)?)
+}
+
+pub fn get_sessions(db: &Pool, user: Uuid) -> ServiceResult<Vec<Session>> {
+    let conn = db.get()?;
+
+    Ok(sessions
+        .filter(user_id.eq(user))
+        .order_by(last_used.desc())
+        .load(&conn)?)
+}
+
+/// Moves the session to the next refresh token generation
+///
+/// Using an older refresh token means that it was stolen, the session is revoked then
+pub fn rotate_session(
+    db: &Pool,
+    session_id: Uuid,
+    token_generation: i32,
+) -> ServiceResult<Session> {
+    let conn = db.get()?;
+
+    let rotated = update(sessions.filter(id.eq(session_id).and(generation.eq(token_generation))))
+        .set((
+            generation.eq(generation + 1),
+            last_used.eq(chrono::Utc::now().naive_utc()),
+        ))
+        .get_result::<Session>(&conn);
+
+    match rotated {
+        Ok(session) => Ok(session),
+        Err(diesel::result::Error::NotFound) => {
+            let revoked = delete(sessions.filter(id.eq(session_id))).execute(&conn)?;
+            Err(ServiceErr::Unauthorized(match revoked {
+                0 => ErrorCode::SessionRevoked,
+                _ => ErrorCode::TokenReused,
+            }))
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Deletes a session of the user, returns the amount of deleted sessions
+pub fn delete_session(db: &Pool, user: Uuid, session_id: Uuid) -> ServiceResult<usize> {
+    let conn = db.get()?;
+
+    Ok(delete(sessions.filter(id.eq(session_id).and(user_id.eq(user)))).execute(&conn)?)
+}
+
+/// Deletes all sessions of the user, for example after a password change
+pub fn delete_sessions(db: &Pool, user: Uuid) -> ServiceResult<usize> {
+    let conn = db.get()?;
+
+    Ok(delete(sessions.filter(user_id.eq(user))).execute(&conn)?)
+}
@@ -0,0 +1,230 @@
+use super::Pool;
+use crate::diesel::{QueryDsl, RunQueryDsl};
+use crate::error::{ServiceErr, ServiceResult};
+use crate::models::{LoginChallenge, TwoFactor};
+use crate::schema::login_challenges;
+use crate::schema::recovery_codes;
+use crate::schema::two_factor::dsl::*;
+use crate::token;
+use crate::totp;
+use diesel::{
+    delete, insert_into, update, BoolExpressionMethods, Connection, ExpressionMethods,
+    OptionalExtension,
+};
+use dto::ErrorCode;
+use uuid::Uuid;
+
+/// The characters of recovery codes, without the ones that are easy to confuse like `0` and `o`
+const RECOVERY_CODE_CHARS: &[u8] = b"abcdefghjkmnpqrstuvwxyz23456789";
+const RECOVERY_CODE_COUNT: usize = 10;
+/// A code is split into two groups of this length, `xxxxx-xxxxx`
+const RECOVERY_CODE_GROUP_LENGTH: usize = 5;
+
+pub fn get_two_factor(db: &Pool, user: Uuid) -> ServiceResult<Option<TwoFactor>> {
+    let conn = db.get()?;
+
+    Ok(two_factor
+        .filter(user_id.eq(user))
+        .get_result(&conn)
+        .optional()?)
+}
+
+pub fn is_two_factor_enabled(db: &Pool, user: Uuid) -> ServiceResult<bool> {
+    Ok(get_two_factor(db, user)?.is_some_and(|factor| factor.enabled))
+}
+
+/// Stores a new secret that is enabled after the first code was verified,
+/// an unfinished enrollment is replaced
+pub fn start_enrollment(db: &Pool, user: Uuid, new_secret: &[u8]) -> ServiceResult<()> {
+    let conn = db.get()?;
+
+    conn.transaction(|| {
+        let existing = two_factor
+            .filter(user_id.eq(user))
+            .get_result::<TwoFactor>(&conn)
+            .optional()?;
+        if existing.is_some_and(|factor| factor.enabled) {
+            return Err(ServiceErr::Conflict(ErrorCode::TwoFactorEnabled));
+        }
+
+        delete(two_factor.filter(user_id.eq(user))).execute(&conn)?;
+        insert_into(two_factor)
+            .values((user_id.eq(user), secret.eq(new_secret)))
+            .execute(&conn)?;
+        Ok(())
+    })
+}
+
+/// Checks a totp code of the user, a code can only be used once.
+/// Also works for unfinished enrollments
+pub fn verify_totp(db: &Pool, user: Uuid, code: &str) -> ServiceResult<bool> {
+    let factor = match get_two_factor(db, user)? {
+        Some(factor) => factor,
+        None => return Ok(false),
+    };
+    let step = match totp::verify(
+        &factor.secret,
+        code,
+        chrono::Utc::now().timestamp(),
+        factor.last_step,
+    ) {
+        Some(step) => step,
+        None => return Ok(false),
+    };
+
+    // only one of two concurrent requests with the same code can move the step forward
+    let conn = db.get()?;
+    let updated = update(two_factor.filter(user_id.eq(user).and(last_step.lt(step))))
+        .set(last_step.eq(step))
+        .execute(&conn)?;
+    Ok(updated == 1)
+}
+
+/// Checks the code for the login of a user with two-factor authentication,
+/// either a totp code or a recovery code, which is used up
+pub fn verify_code(db: &Pool, user: Uuid, code: &str) -> ServiceResult<bool> {
+    if !is_two_factor_enabled(db, user)? {
+        return Ok(false);
+    }
+    Ok(verify_totp(db, user, code)? || use_recovery_code(db, user, code)?)
+}
+
+/// Enables the two-factor authentication of the user, returns the recovery codes
+pub fn enable_two_factor(db: &Pool, user: Uuid) -> ServiceResult<Vec<String>> {
+    let conn = db.get()?;
+
+    let codes = generate_recovery_codes();
+    conn.transaction::<_, ServiceErr, _>(|| {
+        update(two_factor.filter(user_id.eq(user)))
+            .set(enabled.eq(true))
+            .execute(&conn)?;
+        store_recovery_codes(&conn, user, &codes)
+    })?;
+
+    Ok(codes)
+}
+
+/// Replaces the recovery codes of the user
+pub fn regenerate_recovery_codes(db: &Pool, user: Uuid) -> ServiceResult<Vec<String>> {
+    let conn = db.get()?;
+
+    let codes = generate_recovery_codes();
+    conn.transaction(|| store_recovery_codes(&conn, user, &codes))?;
+
+    Ok(codes)
+}
+
+pub fn disable_two_factor(db: &Pool, user: Uuid) -> ServiceResult<()> {
+    let conn = db.get()?;
+
+    conn.transaction(|| {
+        delete(recovery_codes::table.filter(recovery_codes::user_id.eq(user))).execute(&conn)?;
+        delete(two_factor.filter(user_id.eq(user))).execute(&conn)?;
+        Ok(())
+    })
+}
+
+fn store_recovery_codes(
+    conn: &diesel::PgConnection,
+    user: Uuid,
+    codes: &[String],
+) -> ServiceResult<()> {
+    delete(recovery_codes::table.filter(recovery_codes::user_id.eq(user))).execute(conn)?;
+    for code in codes {
+        insert_into(recovery_codes::table)
+            .values((
+                recovery_codes::user_id.eq(user),
+                recovery_codes::code_hash.eq(token::hash(code)),
+            ))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+fn use_recovery_code(db: &Pool, user: Uuid, code: &str) -> ServiceResult<bool> {
+    let conn = db.get()?;
+
+    let code = normalize_recovery_code(code);
+    let deleted = delete(
+        recovery_codes::table.filter(
+            recovery_codes::user_id
+                .eq(user)
+                .and(recovery_codes::code_hash.eq(token::hash(&code))),
+        ),
+    )
+    .execute(&conn)?;
+    Ok(deleted == 1)
+}
+
+fn generate_recovery_codes() -> Vec<String> {
+    let group = || token::random_string(RECOVERY_CODE_CHARS, RECOVERY_CODE_GROUP_LENGTH);
+
+    (0..RECOVERY_CODE_COUNT)
+        .map(|_| format!("{}-{}", group(), group()))
+        .collect()
+}
+
+/// Recovery codes are typed by hand, so the case and the whitespace don't matter
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Creates a challenge that waits for the second factor of a login
+pub fn create_login_challenge(
+    db: &Pool,
+    user: Uuid,
+    lifetime: chrono::Duration,
+) -> ServiceResult<LoginChallenge> {
+    use crate::schema::login_challenges::dsl::*;
+    let conn = db.get()?;
+
+    let now = chrono::Utc::now().naive_utc();
+    delete(login_challenges.filter(expires.lt(now))).execute(&conn)?;
+
+    Ok(insert_into(login_challenges)
+        .values((
+            id.eq(Uuid::new_v4()),
+            user_id.eq(user),
+            expires.eq(now + lifetime),
+        ))
+        .get_result(&conn)?)
+}
+
+/// Gets an unexpired challenge
+pub fn get_login_challenge(db: &Pool, challenge: Uuid) -> ServiceResult<LoginChallenge> {
+    use crate::schema::login_challenges::dsl::*;
+    let conn = db.get()?;
+
+    Ok(login_challenges
+        .filter(
+            id.eq(challenge)
+                .and(expires.gt(chrono::Utc::now().naive_utc())),
+        )
+        .get_result(&conn)?)
+}
+
+/// Counts a wrong code, the challenge is removed after `max_attempts` wrong codes
+pub fn fail_login_challenge(db: &Pool, challenge: Uuid, max_attempts: i32) -> ServiceResult<()> {
+    use crate::schema::login_challenges::dsl::*;
+    let conn = db.get()?;
+
+    let challenge = update(login_challenges.filter(id.eq(challenge)))
+        .set(attempts.eq(attempts + 1))
+        .get_result::<LoginChallenge>(&conn)?;
+    if challenge.attempts >= max_attempts {
+        delete(login_challenges.filter(id.eq(challenge.id))).execute(&conn)?;
+    }
+    Ok(())
+}
+
+pub fn delete_login_challenge(db: &Pool, challenge: Uuid) -> ServiceResult<usize> {
+    let conn = db.get()?;
+
+    Ok(
+        delete(login_challenges::table.filter(login_challenges::id.eq(challenge)))
+            .execute(&conn)?,
+    )
+}
@@ -1,36 +1,73 @@
 use super::Pool;
 use crate::diesel::{QueryDsl, RunQueryDsl};
 use crate::error::{ServiceErr, ServiceResult};
-use crate::models::{NewUser, User};
+use crate::models::{LinkCode, MemberRole, NewUser, User};
+use crate::password::{self, Verified};
 use crate::schema::users::dsl::*;
-use diesel::sql_types::{Integer, Text};
-use diesel::{delete, insert_into, update, BoolExpressionMethods, ExpressionMethods};
+use crate::token;
+use diesel::{
+    delete, insert_into, update, BoolExpressionMethods, Connection, ExpressionMethods,
+    OptionalExtension, PgTextExpressionMethods,
+};
+use dto::ErrorCode;
 use uuid::Uuid;
 
-sql_function!(fn crypt(pwd: Text, salt: Text) -> Text);
-sql_function!(fn gen_salt(kind: Text, number: Integer) -> Text);
+/// The characters of link codes, without the ones that are easy to confuse like `0` and `O`
+const LINK_CODE_CHARS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const LINK_CODE_LENGTH: usize = 8;
 
 pub fn get_user_by_id(db: &Pool, user_id: Uuid) -> ServiceResult<User> {
     let conn = db.get()?;
     Ok(users.find(user_id).get_result::<User>(&conn)?)
 }
 
+/// Checks the password of the user, an outdated hash is replaced after the right password
 pub fn validate_user_password(
     db: &Pool,
     u_email: &str,
     u_password: &str,
 ) -> ServiceResult<Option<User>> {
+    let user = match get_user_by_email(db, u_email)? {
+        Some(user) => user,
+        None => {
+            // unknown emails shouldn't be faster than wrong passwords
+            password::dummy_verify(u_password);
+            return Ok(None);
+        }
+    };
+
+    match password::verify(u_password, &user.password) {
+        Verified::Invalid => Ok(None),
+        Verified::Valid => Ok(Some(user)),
+        Verified::NeedsRehash => {
+            let conn = db.get()?;
+            Ok(Some(
+                update(users.filter(id.eq(user.id)))
+                    .set(password.eq(password::hash(u_password)?))
+                    .get_result(&conn)?,
+            ))
+        }
+    }
+}
+
+/// All users, or the ones whose email contains `search`
+pub fn get_users(db: &Pool, search: Option<&str>) -> ServiceResult<Vec<User>> {
     let conn = db.get()?;
 
-    let found_user: Vec<User> = users
-        .filter(
-            email
-                .eq(u_email)
-                .and(password.eq(crypt(u_password, password))),
-        )
-        .get_results::<User>(&conn)?;
+    let mut query = users.order_by(email).into_boxed();
+    if let Some(search) = search {
+        query = query.filter(email.ilike(format!("%{}%", search)));
+    }
+    Ok(query.load(&conn)?)
+}
+
+pub fn get_user_by_email(db: &Pool, u_email: &str) -> ServiceResult<Option<User>> {
+    let conn = db.get()?;
 
-    Ok(found_user.into_iter().next())
+    Ok(users
+        .filter(email.eq(u_email))
+        .get_result::<User>(&conn)
+        .optional()?)
 }
 
 pub fn insert_user(db: &Pool, user: NewUser) -> ServiceResult<User> {
@@ -40,7 +77,7 @@ pub fn insert_user(db: &Pool, user: NewUser) -> ServiceResult<User> {
         .values((
             id.eq(user.id),
             email.eq(user.email),
-            password.eq(crypt(user.password, gen_salt("bf", 8))),
+            password.eq(password::hash(user.password)?),
             description.eq(user.description),
         ))
         .get_result(&conn)?)
@@ -52,11 +89,58 @@ pub fn delete_user(db: &Pool, user_id: Uuid) -> ServiceResult<usize> {
     Ok(delete(users).filter(id.eq(user_id)).execute(&conn)?)
 }
 
+/// Deletes the user, the owned classes go to their new owner first or are deleted without one
+pub fn delete_user_with_classes(
+    db: &Pool,
+    user_id: Uuid,
+    owned: &[(Uuid, Option<Uuid>)],
+) -> ServiceResult<usize> {
+    use crate::schema::classes::dsl as classes;
+    use crate::schema::members::dsl as members;
+    let conn = db.get()?;
+
+    conn.transaction::<_, ServiceErr, _>(|| {
+        for (class_id, new_owner) in owned {
+            match new_owner {
+                Some(new_owner) => {
+                    update(
+                        members::members
+                            .filter(members::class.eq(class_id).and(members::user.eq(new_owner))),
+                    )
+                    .set(members::role.eq(MemberRole::OWNER))
+                    .execute(&conn)?;
+                    update(classes::classes.find(class_id))
+                        .set(classes::owner.eq(new_owner))
+                        .execute(&conn)?;
+                }
+                None => {
+                    delete(classes::classes.find(class_id)).execute(&conn)?;
+                }
+            }
+        }
+
+        Ok(delete(users.find(user_id)).execute(&conn)?)
+    })
+}
+
+/// Updates the description and the email, a new email has to be verified again
 pub fn update_user(db: &Pool, user: User) -> ServiceResult<User> {
     let conn = db.get()?;
 
     Ok(update(users.filter(id.eq(user.id)))
-        .set((description.eq(user.description), (email.eq(user.email))))
+        .set((
+            description.eq(user.description),
+            email_verified.eq(email_verified.and(email.eq(user.email.clone()))),
+            email.eq(user.email),
+        ))
+        .get_result(&conn)?)
+}
+
+pub fn set_email_verified(db: &Pool, user_id: Uuid, verified: bool) -> ServiceResult<User> {
+    let conn = db.get()?;
+
+    Ok(update(users.filter(id.eq(user_id)))
+        .set(email_verified.eq(verified))
         .get_result(&conn)?)
 }
 
@@ -72,7 +156,7 @@ pub fn change_user_password(db: &Pool, user: User) -> ServiceResult<User> {
     let conn = db.get()?;
 
     Ok(update(users.filter(id.eq(user.id)))
-        .set(password.eq(crypt(user.password, gen_salt("bf", 8))))
+        .set(password.eq(password::hash(&user.password)?))
         .get_result(&conn)?)
 }
 
@@ -95,3 +179,162 @@ pub fn get_user_by_discord(db: &Pool, user_id: &str) -> ServiceResult<User> {
         .next()
         .ok_or(ServiceErr::NotFound)
 }
+
+/// Create a new link code for a user or a discord account, older codes of the owner are replaced
+pub fn create_link_code(
+    db: &Pool,
+    owner: Option<Uuid>,
+    owner_discord: Option<&str>,
+    valid_for: chrono::Duration,
+) -> ServiceResult<LinkCode> {
+    use crate::schema::discord_link_codes::dsl as codes;
+
+    let new_code = LinkCode {
+        code: token::random_string(LINK_CODE_CHARS, LINK_CODE_LENGTH),
+        user_id: owner,
+        discord_id: owner_discord.map(ToString::to_string),
+        expires: chrono::Utc::now().naive_utc() + valid_for,
+    };
+
+    let conn = db.get()?;
+    conn.transaction::<_, ServiceErr, _>(|| {
+        let now = chrono::Utc::now().naive_utc();
+        delete(codes::discord_link_codes.filter(codes::expires.le(now))).execute(&conn)?;
+        match (owner, owner_discord) {
+            (Some(owner), _) => {
+                delete(codes::discord_link_codes.filter(codes::user_id.eq(owner))).execute(&conn)?
+            }
+            (_, Some(owner)) => {
+                delete(codes::discord_link_codes.filter(codes::discord_id.eq(owner)))
+                    .execute(&conn)?
+            }
+            (None, None) => return Err(ServiceErr::BadRequest(ErrorCode::InvalidLinkCode)),
+        };
+
+        Ok(insert_into(codes::discord_link_codes)
+            .values(&new_code)
+            .get_result(&conn)?)
+    })
+}
+
+/// Redeem a link code and link the user with the discord account
+///
+/// The code has to be created by the other side, so `user` is set for codes of the bot
+/// and `discord` for codes of the website. Every code can only be used once.
+pub fn redeem_link_code(
+    db: &Pool,
+    link_code: &str,
+    user: Option<Uuid>,
+    discord: Option<&str>,
+) -> ServiceResult<User> {
+    use crate::schema::discord_link_codes::dsl as codes;
+
+    let conn = db.get()?;
+    conn.transaction::<_, ServiceErr, _>(|| {
+        let now = chrono::Utc::now().naive_utc();
+        let link = delete(
+            codes::discord_link_codes
+                .filter(codes::code.eq(link_code.to_uppercase()))
+                .filter(codes::expires.gt(now)),
+        )
+        .get_result::<LinkCode>(&conn)
+        .optional()?;
+
+        let (user, discord) = match (link, user, discord) {
+            (
+                Some(LinkCode {
+                    discord_id: Some(discord),
+                    ..
+                }),
+                Some(user),
+                None,
+            ) => (user, discord),
+            (
+                Some(LinkCode {
+                    user_id: Some(user),
+                    ..
+                }),
+                None,
+                Some(discord),
+            ) => (user, discord.to_string()),
+            _ => return Err(ServiceErr::BadRequest(ErrorCode::InvalidLinkCode)),
+        };
+
+        // a discord account can only be linked with one user
+        update(users.filter(discord_id.eq(&discord)))
+            .set(discord_id.eq(None::<String>))
+            .execute(&conn)?;
+
+        Ok(update(users.find(user))
+            .set(discord_id.eq(Some(discord)))
+            .get_result(&conn)?)
+    })
+}
+
+/// Get the user of a discord login, the user is created if neither the discord account nor
+/// the email is known yet
+///
+/// An existing account with the same verified email is merged by linking it with the discord
+/// account. The old tokens of the account are invalidated in that case. Accounts with an
+/// unverified email are not merged, anyone could have signed up with that email.
+/// `signup` allows creating a new user for an unknown email
+pub fn login_with_discord(
+    db: &Pool,
+    snowflake: &str,
+    discord_email: Option<&str>,
+    signup: bool,
+) -> ServiceResult<User> {
+    let conn = db.get()?;
+    conn.transaction::<_, ServiceErr, _>(|| {
+        if let Some(user) = users
+            .filter(discord_id.eq(snowflake))
+            .get_result::<User>(&conn)
+            .optional()?
+        {
+            return Ok(user);
+        }
+
+        let discord_email =
+            discord_email.ok_or(ServiceErr::BadRequest(ErrorCode::DiscordEmailMissing))?;
+
+        let existing = users
+            .filter(email.eq(discord_email))
+            .get_result::<User>(&conn)
+            .optional()?;
+
+        match existing {
+            Some(User {
+                discord_id: Some(_),
+                ..
+            }) => Err(ServiceErr::Conflict(ErrorCode::EmailLinkedWithOtherDiscord)),
+            Some(User {
+                email_verified: false,
+                ..
+            }) => Err(ServiceErr::Conflict(ErrorCode::EmailAccountNotVerified)),
+            // discord verified the email
+            Some(user) => Ok(update(users.find(user.id))
+                .set((
+                    discord_id.eq(snowflake),
+                    token_version.eq(token_version + 1),
+                    email_verified.eq(true),
+                ))
+                .get_result(&conn)?),
+            None if !signup => Err(ServiceErr::Forbidden(ErrorCode::SignupDisabled)),
+            None => {
+                // the account has no usable password, it can only log in with discord
+                let (random_password, _) = token::generate("", 32);
+
+                Ok(insert_into(users)
+                    .values((
+                        id.eq(Uuid::new_v4()),
+                        email.eq(discord_email),
+                        password.eq(password::hash(&random_password)?),
+                        description.eq(""),
+                        discord_id.eq(snowflake),
+                        email_verified.eq(true),
+                    ))
+                    .get_result(&conn)?)
+            }
+        }
+    })
+}
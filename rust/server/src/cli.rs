@@ -0,0 +1,47 @@
+//! Commands for the database of the server, `server <command>` runs a command instead of starting
+//! the server. Everything else is done with `cors-admin`.
+
+use color_eyre::eyre::bail;
+use color_eyre::Report;
+use server::actions::Pool;
+use server::migrations;
+
+const USAGE: &str = "usage:
+    server [--migrate]  starts the server, --migrate runs the pending migrations first
+    server migrate status
+    server migrate run";
+
+pub fn run(db: &Pool, args: &[String]) -> Result<(), Report> {
+    let args = args.iter().map(String::as_str).collect::<Vec<_>>();
+
+    match args.as_slice() {
+        ["migrate", "status"] => migration_status(db),
+        ["migrate", "run"] => run_migrations(db),
+        _ => bail!("{}", USAGE),
+    }
+}
+
+fn migration_status(db: &Pool) -> Result<(), Report> {
+    let status = migrations::status(&*db.get()?)?;
+    for version in &status.applied {
+        println!("{}\tapplied", version);
+    }
+    for version in &status.pending {
+        println!("{}\tpending", version);
+    }
+    for version in &status.unknown {
+        println!("{}\tunknown, applied by a newer server", version);
+    }
+    Ok(())
+}
+
+fn run_migrations(db: &Pool) -> Result<(), Report> {
+    let ran = migrations::run(&*db.get()?)?;
+    for version in &ran {
+        println!("Ran migration {}", version);
+    }
+    if ran.is_empty() {
+        println!("No migrations are pending");
+    }
+    Ok(())
+}
@@ -0,0 +1,739 @@
+//! The configuration of the server
+//!
+//! It's read from the TOML file in `CONFIG_FILE`, or `config.toml` if it exists, and env vars
+//! override single values of it. `config.example.toml` has all values with their defaults.
+//! The config is checked at startup, every problem with it is reported before the server exits.
+
+use crate::handlers::oauth::DISCORD_API_URL;
+use crate::password::PasswordPolicy;
+use actix_web::http::Uri;
+use serde::Deserialize;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing_subscriber::EnvFilter;
+
+const DEFAULT_CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub jwt: JwtConfig,
+    pub database: DatabaseConfig,
+    pub log: LogConfig,
+    pub features: Features,
+    pub rate_limit: RateLimitConfig,
+    pub password: PasswordPolicy,
+    pub mail: MailConfig,
+    pub discord: DiscordConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// The address the server listens on, like `0.0.0.0:8080`
+    pub listen: String,
+    /// The origins of the websites that can use the api, a single `*` allows all
+    pub allowed_origins: Vec<String>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            listen: "0.0.0.0:8080".to_string(),
+            allowed_origins: vec!["https://cors-school.com".to_string()],
+        }
+    }
+}
+
+/// How long the tokens work and the keys they are signed with
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct JwtConfig {
+    pub access_token_minutes: i64,
+    /// Also how long a session can be unused before it ends
+    pub refresh_token_days: i64,
+    /// The path of the RSA private key that signs the tokens
+    pub signing_key: Option<String>,
+    /// Paths of older keys that only verify tokens, for key rotations
+    pub verification_keys: Vec<String>,
+    /// The old HS512 secret, signs the tokens if there is no `signing_key`
+    pub secret: Option<String>,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        Self {
+            access_token_minutes: 60,
+            refresh_token_days: 30,
+            signing_key: None,
+            verification_keys: Vec::new(),
+            secret: None,
+        }
+    }
+}
+
+impl JwtConfig {
+    pub fn access_lifetime(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.access_token_minutes)
+    }
+
+    pub fn refresh_lifetime(&self) -> chrono::Duration {
+        chrono::Duration::days(self.refresh_token_days)
+    }
+}
+
+/// The connection pool, the database itself is in the `DATABASE_URL` env var
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub max_connections: u32,
+    /// Connections that are kept open when they are unused, `max_connections` if it's not set
+    pub min_idle: Option<u32>,
+    /// How long a request waits for a free connection
+    pub connection_timeout_seconds: u64,
+    /// Run the pending migrations at startup, like the `--migrate` flag
+    pub migrate: bool,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: 10,
+            min_idle: None,
+            connection_timeout_seconds: 30,
+            migrate: false,
+        }
+    }
+}
+
+impl DatabaseConfig {
+    pub fn connection_timeout(&self) -> Duration {
+        Duration::from_secs(self.connection_timeout_seconds)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    Full,
+    Compact,
+    Pretty,
+    /// A json object on every line, for log collectors
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "full" => Ok(LogFormat::Full),
+            "compact" => Ok(LogFormat::Compact),
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LogConfig {
+    pub format: LogFormat,
+    /// Which logs are written, in the syntax of `RUST_LOG`, like `info,server=debug`
+    pub filter: String,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            format: LogFormat::Full,
+            filter: "debug".to_string(),
+        }
+    }
+}
+
+/// The throttling of the auth routes
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Requests of an ip in every window
+    pub requests: u32,
+    pub window_seconds: u64,
+    /// Wrong passwords or codes until the account is locked
+    pub lockout_failures: u32,
+    /// How long the failures are counted and the account stays locked
+    pub lockout_seconds: u64,
+    /// Takes the ip from the `Forwarded` and `X-Forwarded-For` headers, only behind a proxy
+    pub trust_proxy: bool,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            requests: 20,
+            window_seconds: 60,
+            lockout_failures: 5,
+            lockout_seconds: 15 * 60,
+            trust_proxy: false,
+        }
+    }
+}
+
+/// Emails like password resets
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MailConfig {
+    /// An smtp server without authentication like `localhost:1025`, mails are only logged without it
+    pub smtp_address: Option<String>,
+    pub from: String,
+    /// The website, links in mails point to it
+    pub site_url: String,
+}
+
+impl Default for MailConfig {
+    fn default() -> Self {
+        Self {
+            smtp_address: None,
+            from: "noreply@cors-school.com".to_string(),
+            site_url: "https://cors-school.com".to_string(),
+        }
+    }
+}
+
+/// The OAuth2 application of the login with discord, the login is disabled without it
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiscordConfig {
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+    /// The page of the website that receives the code
+    pub redirect_uri: Option<String>,
+    /// Can point to a mock provider
+    pub api_url: String,
+}
+
+impl Default for DiscordConfig {
+    fn default() -> Self {
+        Self {
+            client_id: None,
+            client_secret: None,
+            redirect_uri: None,
+            api_url: DISCORD_API_URL.to_string(),
+        }
+    }
+}
+
+/// Parts of the api that can be turned off
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Features {
+    /// New accounts, with `POST /users` or the first login with discord
+    pub signup: bool,
+    /// The login with discord, which also needs the `[discord]` application
+    pub discord_login: bool,
+    /// Users can only create and join classes after verifying their email
+    pub require_verified_email: bool,
+}
+
+impl Default for Features {
+    fn default() -> Self {
+        Self {
+            signup: true,
+            discord_login: true,
+            require_verified_email: false,
+        }
+    }
+}
+
+impl Config {
+    /// Reads the config file and the env vars
+    pub fn load() -> Result<Self, String> {
+        let mut config = match env::var("CONFIG_FILE") {
+            Ok(path) => Self::from_file(Path::new(&path))?,
+            Err(_) if Path::new(DEFAULT_CONFIG_FILE).exists() => {
+                Self::from_file(Path::new(DEFAULT_CONFIG_FILE))?
+            }
+            Err(_) => Self::default(),
+        };
+
+        let mut errors = config.apply_overrides(|name| env::var(name).ok());
+        errors.extend(config.validate());
+        match errors.is_empty() {
+            true => Ok(config),
+            false => Err(format!("invalid config:\n  {}", errors.join("\n  "))),
+        }
+    }
+
+    fn from_file(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("could not read the config file {}: {}", path.display(), err))?;
+        toml::from_str(&content)
+            .map_err(|err| format!("invalid config file {}: {}", path.display(), err))
+    }
+
+    /// Sets the values of the env vars that `var` returns, returns the values that are invalid
+    fn apply_overrides(&mut self, var: impl Fn(&str) -> Option<String>) -> Vec<String> {
+        let mut overrides = Overrides {
+            var: &var,
+            errors: Vec::new(),
+        };
+
+        overrides.set("LISTEN_ADDRESS", &mut self.server.listen, |value| {
+            Ok(value.to_string())
+        });
+        overrides.set(
+            "ALLOWED_ORIGINS",
+            &mut self.server.allowed_origins,
+            |value| {
+                Ok(value
+                    .split(',')
+                    .map(|origin| origin.trim().to_string())
+                    .collect())
+            },
+        );
+        overrides.set(
+            "JWT_ACCESS_TOKEN_MINUTES",
+            &mut self.jwt.access_token_minutes,
+            number,
+        );
+        overrides.set(
+            "JWT_REFRESH_TOKEN_DAYS",
+            &mut self.jwt.refresh_token_days,
+            number,
+        );
+        overrides.set(
+            "DATABASE_MAX_CONNECTIONS",
+            &mut self.database.max_connections,
+            number,
+        );
+        overrides.set("DATABASE_MIN_IDLE", &mut self.database.min_idle, |value| {
+            number(value).map(Some)
+        });
+        overrides.set(
+            "DATABASE_CONNECTION_TIMEOUT_SECONDS",
+            &mut self.database.connection_timeout_seconds,
+            number,
+        );
+        overrides.set("JWT_SIGNING_KEY", &mut self.jwt.signing_key, |value| {
+            Ok(Some(value.to_string()))
+        });
+        overrides.set(
+            "JWT_VERIFICATION_KEYS",
+            &mut self.jwt.verification_keys,
+            |value| {
+                Ok(value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .map(str::to_string)
+                    .collect())
+            },
+        );
+        overrides.set("JWT_SECRET", &mut self.jwt.secret, |value| {
+            Ok(Some(value.to_string()))
+        });
+        overrides.set("DATABASE_MIGRATE", &mut self.database.migrate, flag);
+        overrides.set("LOG_FORMAT", &mut self.log.format, |value| {
+            value
+                .parse()
+                .map_err(|_| "is not full, compact, pretty or json".to_string())
+        });
+        overrides.set("RUST_LOG", &mut self.log.filter, |value| {
+            Ok(value.to_string())
+        });
+        overrides.set("FEATURE_SIGNUP", &mut self.features.signup, flag);
+        overrides.set(
+            "FEATURE_DISCORD_LOGIN",
+            &mut self.features.discord_login,
+            flag,
+        );
+        overrides.set(
+            "REQUIRE_VERIFIED_EMAIL",
+            &mut self.features.require_verified_email,
+            flag,
+        );
+        overrides.set("RATE_LIMIT_REQUESTS", &mut self.rate_limit.requests, number);
+        overrides.set(
+            "RATE_LIMIT_WINDOW_SECONDS",
+            &mut self.rate_limit.window_seconds,
+            number,
+        );
+        overrides.set(
+            "LOCKOUT_FAILURES",
+            &mut self.rate_limit.lockout_failures,
+            number,
+        );
+        overrides.set(
+            "LOCKOUT_SECONDS",
+            &mut self.rate_limit.lockout_seconds,
+            number,
+        );
+        overrides.set(
+            "RATE_LIMIT_TRUST_PROXY",
+            &mut self.rate_limit.trust_proxy,
+            flag,
+        );
+        overrides.set("PASSWORD_MIN_LENGTH", &mut self.password.min_length, number);
+        overrides.set("PASSWORD_MAX_LENGTH", &mut self.password.max_length, number);
+        overrides.set(
+            "PASSWORD_REQUIRE_LETTER",
+            &mut self.password.require_letter,
+            flag,
+        );
+        overrides.set(
+            "PASSWORD_REQUIRE_DIGIT",
+            &mut self.password.require_digit,
+            flag,
+        );
+        overrides.set("SMTP_ADDRESS", &mut self.mail.smtp_address, |value| {
+            Ok(Some(value.to_string()))
+        });
+        overrides.set("MAIL_FROM", &mut self.mail.from, |value| {
+            Ok(value.to_string())
+        });
+        overrides.set("SITE_URL", &mut self.mail.site_url, |value| {
+            Ok(value.to_string())
+        });
+        overrides.set("DISCORD_CLIENT_ID", &mut self.discord.client_id, |value| {
+            Ok(Some(value.to_string()))
+        });
+        overrides.set(
+            "DISCORD_CLIENT_SECRET",
+            &mut self.discord.client_secret,
+            |value| Ok(Some(value.to_string())),
+        );
+        overrides.set(
+            "DISCORD_REDIRECT_URI",
+            &mut self.discord.redirect_uri,
+            |value| Ok(Some(value.to_string())),
+        );
+        overrides.set("DISCORD_API_URL", &mut self.discord.api_url, |value| {
+            Ok(value.to_string())
+        });
+
+        overrides.errors
+    }
+
+    /// The problems of the config
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if self.server.listen.parse::<SocketAddr>().is_err() {
+            errors.push(format!(
+                "server.listen: `{}` is not an address like 0.0.0.0:8080",
+                self.server.listen
+            ));
+        }
+        match self.server.allowed_origins.as_slice() {
+            [] => errors.push("server.allowed_origins: is empty".to_string()),
+            [any] if any == "*" => {}
+            origins => {
+                for origin in origins {
+                    if origin == "*" || !is_url(origin) {
+                        errors.push(format!(
+                            "server.allowed_origins: `{}` is not an origin like https://cors-school.com, \
+                             `*` can only be used alone",
+                            origin
+                        ));
+                    }
+                }
+            }
+        }
+
+        if self.jwt.access_token_minutes <= 0 {
+            errors.push("jwt.access_token_minutes: must be positive".to_string());
+        }
+        if self.jwt.refresh_token_days <= 0 {
+            errors.push("jwt.refresh_token_days: must be positive".to_string());
+        } else if self.jwt.access_lifetime() > self.jwt.refresh_lifetime() {
+            errors.push(
+                "jwt.access_token_minutes: tokens can't work longer than the refresh tokens"
+                    .to_string(),
+            );
+        }
+
+        if self.database.max_connections == 0 {
+            errors.push("database.max_connections: must be positive".to_string());
+        }
+        if let Some(min_idle) = self.database.min_idle {
+            if min_idle > self.database.max_connections {
+                errors.push(format!(
+                    "database.min_idle: {} is more than the {} max_connections",
+                    min_idle, self.database.max_connections
+                ));
+            }
+        }
+        if self.database.connection_timeout_seconds == 0 {
+            errors.push("database.connection_timeout_seconds: must be positive".to_string());
+        }
+
+        if let Err(err) = EnvFilter::try_new(&self.log.filter) {
+            errors.push(format!(
+                "log.filter: `{}` is invalid: {}",
+                self.log.filter, err
+            ));
+        }
+
+        let rate_limit = &self.rate_limit;
+        for (name, value) in [
+            ("requests", u64::from(rate_limit.requests)),
+            ("window_seconds", rate_limit.window_seconds),
+            ("lockout_failures", u64::from(rate_limit.lockout_failures)),
+            ("lockout_seconds", rate_limit.lockout_seconds),
+        ] {
+            if value == 0 {
+                errors.push(format!("rate_limit.{}: must be positive", name));
+            }
+        }
+
+        if self.password.min_length == 0 {
+            errors.push("password.min_length: must be positive".to_string());
+        }
+        if self.password.max_length < self.password.min_length {
+            errors.push(format!(
+                "password.max_length: {} is less than the min_length {}",
+                self.password.max_length, self.password.min_length
+            ));
+        }
+
+        if let Some(address) = &self.mail.smtp_address {
+            let port = address
+                .rsplit_once(':')
+                .map(|(_, port)| port.parse::<u16>());
+            if !matches!(port, Some(Ok(_))) {
+                errors.push(format!(
+                    "mail.smtp_address: `{}` is not an address like localhost:1025",
+                    address
+                ));
+            }
+        }
+        if !self.mail.from.contains('@') {
+            errors.push(format!(
+                "mail.from: `{}` is not an email address",
+                self.mail.from
+            ));
+        }
+        if !is_url(&self.mail.site_url) {
+            errors.push(format!(
+                "mail.site_url: `{}` is not a url like https://cors-school.com",
+                self.mail.site_url
+            ));
+        }
+
+        let discord = &self.discord;
+        match (
+            &discord.client_id,
+            &discord.client_secret,
+            &discord.redirect_uri,
+        ) {
+            (None, None, None) => {}
+            (Some(_), Some(_), Some(redirect_uri)) => {
+                if !is_url(redirect_uri) {
+                    errors.push(format!(
+                        "discord.redirect_uri: `{}` is not a url like https://cors-school.com/login/discord",
+                        redirect_uri
+                    ));
+                }
+            }
+            _ => errors.push(
+                "discord: client_id, client_secret and redirect_uri must be set together"
+                    .to_string(),
+            ),
+        }
+        if !is_url(&discord.api_url) {
+            errors.push(format!(
+                "discord.api_url: `{}` is not a url like https://discord.com/api",
+                discord.api_url
+            ));
+        }
+
+        errors
+    }
+}
+
+/// An absolute url with a scheme and a host, like `https://cors-school.com`
+fn is_url(value: &str) -> bool {
+    value
+        .parse::<Uri>()
+        .is_ok_and(|uri| uri.scheme().is_some() && uri.host().is_some_and(|host| !host.is_empty()))
+}
+
+/// Collects the invalid env vars
+struct Overrides<'a> {
+    var: &'a dyn Fn(&str) -> Option<String>,
+    errors: Vec<String>,
+}
+
+impl Overrides<'_> {
+    fn set<T>(
+        &mut self,
+        name: &str,
+        target: &mut T,
+        parse: impl FnOnce(&str) -> Result<T, String>,
+    ) {
+        if let Some(value) = (self.var)(name) {
+            match parse(&value) {
+                Ok(parsed) => *target = parsed,
+                Err(err) => self.errors.push(format!("{}: `{}` {}", name, value, err)),
+            }
+        }
+    }
+}
+
+fn number<T: FromStr>(value: &str) -> Result<T, String>
+where
+    T::Err: Display,
+{
+    value
+        .trim()
+        .parse()
+        .map_err(|err| format!("is not a valid number: {}", err))
+}
+
+fn flag(value: &str) -> Result<bool, String> {
+    match value.trim() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err("is not true or false".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let vars = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect::<HashMap<_, _>>();
+        move |name| vars.get(name).cloned()
+    }
+
+    #[test]
+    fn file_and_overrides() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [server]
+            listen = "127.0.0.1:9000"
+            allowed_origins = ["https://cors-school.com", "http://localhost:3000"]
+
+            [jwt]
+            access_token_minutes = 15
+
+            [features]
+            signup = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.server.listen, "127.0.0.1:9000");
+        assert_eq!(config.jwt.access_token_minutes, 15);
+        assert_eq!(config.jwt.refresh_token_days, 30);
+        assert!(!config.features.signup);
+        assert!(config.features.discord_login);
+
+        let errors = config.apply_overrides(env(&[
+            ("LISTEN_ADDRESS", "0.0.0.0:80"),
+            ("ALLOWED_ORIGINS", "https://a.com, https://b.com"),
+            ("DATABASE_MIN_IDLE", "2"),
+            ("LOG_FORMAT", "json"),
+            ("FEATURE_SIGNUP", "true"),
+        ]));
+        assert!(errors.is_empty());
+        assert_eq!(config.server.listen, "0.0.0.0:80");
+        assert_eq!(
+            config.server.allowed_origins,
+            vec!["https://a.com", "https://b.com"]
+        );
+        assert_eq!(config.database.min_idle, Some(2));
+        assert_eq!(config.log.format, LogFormat::Json);
+        assert!(config.features.signup);
+        assert!(config.validate().is_empty());
+    }
+
+    #[test]
+    fn invalid_configs() {
+        assert!(toml::from_str::<Config>("[server]\nlisten_address = \"0.0.0.0:80\"").is_err());
+        assert!(toml::from_str::<Config>("[log]\nformat = \"xml\"").is_err());
+
+        let mut config = Config::default();
+        let errors = config.apply_overrides(env(&[
+            ("JWT_ACCESS_TOKEN_MINUTES", "an hour"),
+            ("FEATURE_SIGNUP", "yes"),
+        ]));
+        assert_eq!(errors.len(), 2);
+
+        config.server.listen = "localhost".to_string();
+        config.server.allowed_origins = vec!["*".to_string(), "https://a.com".to_string()];
+        config.jwt.access_token_minutes = 60 * 24 * 31;
+        config.database.max_connections = 2;
+        config.database.min_idle = Some(3);
+        assert_eq!(config.validate().len(), 4);
+    }
+
+    #[test]
+    fn services() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [jwt]
+            signing_key = "keys/jwt.pem"
+
+            [rate_limit]
+            requests = 50
+
+            [password]
+            min_length = 10
+
+            [mail]
+            smtp_address = "localhost:1025"
+            "#,
+        )
+        .unwrap();
+        let errors = config.apply_overrides(env(&[
+            ("JWT_VERIFICATION_KEYS", "keys/old.pem, ,keys/older.pem"),
+            ("LOCKOUT_SECONDS", "60"),
+            ("PASSWORD_REQUIRE_DIGIT", "1"),
+            ("DISCORD_CLIENT_ID", "id"),
+            ("DISCORD_CLIENT_SECRET", "secret"),
+            (
+                "DISCORD_REDIRECT_URI",
+                "https://cors-school.com/login/discord",
+            ),
+        ]));
+        assert!(errors.is_empty());
+        assert_eq!(config.jwt.signing_key.as_deref(), Some("keys/jwt.pem"));
+        assert_eq!(
+            config.jwt.verification_keys,
+            vec!["keys/old.pem", "keys/older.pem"]
+        );
+        assert_eq!(config.rate_limit.requests, 50);
+        assert_eq!(config.rate_limit.lockout_seconds, 60);
+        assert_eq!(config.password.min_length, 10);
+        assert!(config.password.require_digit);
+        assert_eq!(config.discord.api_url, "https://discord.com/api");
+        assert!(config.validate().is_empty());
+
+        // invalid env vars are errors instead of falling back to the defaults
+        let errors = config.apply_overrides(env(&[
+            ("RATE_LIMIT_REQUESTS", "many"),
+            ("PASSWORD_REQUIRE_LETTER", "yes"),
+        ]));
+        assert_eq!(errors.len(), 2);
+
+        config.server.allowed_origins = vec!["cors-school.com".to_string()];
+        config.rate_limit.window_seconds = 0;
+        config.password.max_length = 5;
+        config.mail.smtp_address = Some("localhost".to_string());
+        config.mail.site_url = "/".to_string();
+        config.discord.client_secret = None;
+        assert_eq!(config.validate().len(), 6);
+    }
+}
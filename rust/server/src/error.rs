@@ -1,6 +1,8 @@
 use actix_web::error::BlockingError;
+use actix_web::http::StatusCode;
 use actix_web::{HttpResponse, ResponseError};
 use diesel::result::DatabaseErrorKind;
+use dto::{ApiError, ErrorCode};
 use std::error::Error;
 use std::fmt::{Display, Formatter};
 use tracing::{debug, error};
@@ -12,11 +14,16 @@ pub enum ServiceErr {
     // 400
     NotFound,
     NoAdminPermissions,
-    BadRequest(&'static str),
-    Conflict(&'static str),
-    Unauthorized(&'static str),
+    BadRequest(ErrorCode),
+    /// A json body, path or query that couldn't be parsed, with the reason
+    InvalidRequest(String),
+    Conflict(ErrorCode),
+    Unauthorized(ErrorCode),
+    Forbidden(ErrorCode),
     TokenExpiredError,
     JWTokenError,
+    /// 429, with the time until the next request can be made
+    TooManyRequests(std::time::Duration),
     // 500
     JWTCreationError(jsonwebtoken::errors::Error),
     ConnectionNotFound(r2d2::Error),
@@ -25,6 +32,54 @@ pub enum ServiceErr {
     IntoDTOError(String),
 }
 
+impl ServiceErr {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ServiceErr::NotFound => ErrorCode::NotFound,
+            ServiceErr::NoAdminPermissions => ErrorCode::NoAdmin,
+            ServiceErr::BadRequest(code)
+            | ServiceErr::Conflict(code)
+            | ServiceErr::Unauthorized(code)
+            | ServiceErr::Forbidden(code) => *code,
+            ServiceErr::InvalidRequest(_) => ErrorCode::InvalidRequest,
+            ServiceErr::TokenExpiredError => ErrorCode::TokenExpired,
+            ServiceErr::JWTokenError => ErrorCode::InvalidToken,
+            ServiceErr::TooManyRequests(_) => ErrorCode::TooManyRequests,
+            ServiceErr::JWTCreationError(_)
+            | ServiceErr::ConnectionNotFound(_)
+            | ServiceErr::DbActionFailed(_)
+            | ServiceErr::InternalServerError(_)
+            | ServiceErr::IntoDTOError(_) => ErrorCode::InternalError,
+        }
+    }
+
+    /// The json error response, with the id of the request if it is known
+    ///
+    /// The internal errors are only logged, their responses don't tell what went wrong.
+    pub fn response(&self, request_id: Option<&str>) -> HttpResponse {
+        let code = self.code();
+        let mut response = HttpResponse::build(self.status_code());
+
+        let details = match self {
+            ServiceErr::InvalidRequest(reason) => Some(serde_json::json!({ "reason": reason })),
+            ServiceErr::TooManyRequests(retry_after) => {
+                // whole seconds, rounded up
+                let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                response.header("Retry-After", seconds.to_string());
+                Some(serde_json::json!({ "retryAfter": seconds }))
+            }
+            _ => None,
+        };
+
+        response.json(ApiError {
+            code,
+            message: code.message().to_string(),
+            details,
+            request_id: request_id.map(str::to_string),
+        })
+    }
+}
+
 impl std::error::Error for ServiceErr {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match self {
@@ -38,43 +93,40 @@ impl std::error::Error for ServiceErr {
 
 impl Display for ServiceErr {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "{}",
-            match self {
-                ServiceErr::ConnectionNotFound(err) => format!("ConnectionNotFound: {}", err),
-                ServiceErr::DbActionFailed(err) => format!("DbActionFailed: {}", err),
-                ServiceErr::JWTCreationError(err) => format!("JWTCreationError: {}", err),
-                ServiceErr::TokenExpiredError => "token-expired".to_string(),
-                ServiceErr::JWTokenError => "invalid-token".to_string(),
-                ServiceErr::NotFound => "Not found".to_string(),
-                ServiceErr::InternalServerError(msg) => format!("Internal Server Error: {}", msg),
-                ServiceErr::Unauthorized(msg) => msg.to_string(),
-                ServiceErr::IntoDTOError(msg) => msg.to_string(),
-                ServiceErr::BadRequest(msg) => msg.to_string(),
-                ServiceErr::Conflict(msg) => msg.to_string(),
-                ServiceErr::NoAdminPermissions => "perms/no-admin".to_string(),
-            }
-        )
+        match self {
+            ServiceErr::ConnectionNotFound(err) => write!(f, "ConnectionNotFound: {}", err),
+            ServiceErr::DbActionFailed(err) => write!(f, "DbActionFailed: {}", err),
+            ServiceErr::JWTCreationError(err) => write!(f, "JWTCreationError: {}", err),
+            ServiceErr::InternalServerError(msg) => write!(f, "Internal Server Error: {}", msg),
+            ServiceErr::IntoDTOError(msg) => write!(f, "{}", msg),
+            ServiceErr::InvalidRequest(reason) => write!(f, "invalid-request: {}", reason),
+            err => write!(f, "{}", err.code()),
+        }
     }
 }
 
 impl ResponseError for ServiceErr {
-    fn error_response(&self) -> HttpResponse {
-        debug!(err = %self, "an error occurred");
+    fn status_code(&self) -> StatusCode {
         match self {
-            ServiceErr::TokenExpiredError => HttpResponse::Unauthorized().body("token-expired"),
-            ServiceErr::JWTokenError => HttpResponse::Unauthorized().body("invalid-token"),
-            ServiceErr::BadRequest(msg) => HttpResponse::BadRequest().body(*msg),
-            ServiceErr::NotFound => HttpResponse::NotFound().body("Not Found"),
-            ServiceErr::Unauthorized(msg) => HttpResponse::Unauthorized().body(*msg),
-            ServiceErr::NoAdminPermissions => HttpResponse::Unauthorized().body("no-admin"),
-            ServiceErr::Conflict(msg) => HttpResponse::Conflict().body(msg.to_string()),
-            err => {
-                error!(%err, "an error occurred");
-                HttpResponse::InternalServerError().finish()
-            }
+            ServiceErr::NotFound => StatusCode::NOT_FOUND,
+            ServiceErr::BadRequest(_) | ServiceErr::InvalidRequest(_) => StatusCode::BAD_REQUEST,
+            ServiceErr::NoAdminPermissions
+            | ServiceErr::Unauthorized(_)
+            | ServiceErr::TokenExpiredError
+            | ServiceErr::JWTokenError => StatusCode::UNAUTHORIZED,
+            ServiceErr::Forbidden(_) => StatusCode::FORBIDDEN,
+            ServiceErr::Conflict(_) => StatusCode::CONFLICT,
+            ServiceErr::TooManyRequests(_) => StatusCode::TOO_MANY_REQUESTS,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        match self.code() {
+            ErrorCode::InternalError => error!(err = %self, "an error occurred"),
+            _ => debug!(err = %self, "an error occurred"),
         }
+        self.response(None)
     }
 }
 
@@ -87,11 +139,11 @@ impl From<diesel::result::Error> for ServiceErr {
             }
             diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
                 debug!(%err, "Handled Db error occurred");
-                Self::Conflict("already-exists")
+                Self::Conflict(ErrorCode::AlreadyExists)
             }
             diesel::result::Error::DatabaseError(DatabaseErrorKind::ForeignKeyViolation, _) => {
                 debug!(%err, "Handled Db error occurred");
-                Self::Conflict("does-not-exist")
+                Self::Conflict(ErrorCode::DoesNotExist)
             }
             _ => Self::DbActionFailed(err),
         }
@@ -106,7 +158,7 @@ impl From<r2d2::Error> for ServiceErr {
 
 impl From<uuid::Error> for ServiceErr {
     fn from(_: uuid::Error) -> Self {
-        Self::BadRequest("invalid-uuid")
+        Self::BadRequest(ErrorCode::InvalidUuid)
     }
 }
 
@@ -1,19 +1,28 @@
 use crate::actions::{self, Pool};
+use crate::config::{Features, JwtConfig};
 use crate::error::ServiceErr;
+use crate::handlers::oauth::{DiscordOAuth, DiscordUser};
 use crate::handlers::HttpResult;
+use crate::jwt_keys::JwtKeys;
+use crate::mail::Mailer;
 use crate::models;
 use crate::models::conversion::IntoDto;
 use crate::models::NewUser;
+use crate::password::PasswordPolicy;
+use crate::rate_limit::{RateLimit, RateLimiter};
 use actix_web::http::header::Header;
 use actix_web::web::*;
 use actix_web::{web, HttpRequest, HttpResponse};
 use actix_web_httpauth::headers::authorization;
 use actix_web_httpauth::headers::authorization::Bearer;
 use chrono::Utc;
-use dto::{ChangePasswordReq, LoginResponse, PostUser, UserLogin, UserPostResponse};
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Validation};
+use dto::{
+    ChangePasswordReq, DiscordAuthorizeQuery, DiscordLoginRequest, ErrorCode, LoginResponse,
+    PasswordResetConfirm, PasswordResetRequest, PostUser, TwoFactorLogin, UserLogin,
+    UserPostResponse, VerifyEmail,
+};
 use serde::{Deserialize, Serialize};
-use tracing::debug;
+use tracing::{debug, error};
 use uuid::Uuid;
 
 /// The claims of the JWT
@@ -21,7 +30,7 @@ use uuid::Uuid;
 /// *Note: the claim extractor rejects valid JWTs, if their refresh field is set to `true`*
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Claims {
-    /// The expiration date of the token
+    /// The expiration date of the token, in seconds like every JWT (RFC 7519 NumericDate)
     pub exp: i64,
     /// The user id
     pub uid: Uuid,
@@ -30,85 +39,338 @@ pub struct Claims {
     /// 0 on Refresh tokens, non-null on normal tokens  
     /// The version of the token, must match the current version
     pub version: i32,
+    /// The session of the login, `None` on personal access tokens
+    #[serde(default)]
+    pub session: Option<Uuid>,
+    /// Only on refresh tokens, the generation of the token in its session
+    #[serde(default)]
+    pub generation: i32,
+}
+
+const UNKNOWN_DEVICE: &str = "unknown";
+/// How long the link in a password reset email works
+const PASSWORD_RESET_MINUTES: i64 = 60;
+/// How long the link in a verification email works
+const EMAIL_VERIFICATION_HOURS: i64 = 24;
+/// The length of the email column
+const MAX_EMAIL_LENGTH: usize = 50;
+/// How long the second step of a login with two-factor authentication can take
+const LOGIN_CHALLENGE_MINUTES: i64 = 5;
+/// Wrong codes until the challenge stops working and the login has to start again
+const MAX_TWO_FACTOR_ATTEMPTS: i32 = 5;
+
+/// The restrictions for users with an unverified email
+#[derive(Debug, Clone, Copy)]
+pub struct EmailVerification {
+    /// Users can only create and join classes after verifying their email
+    pub required: bool,
+}
+
+impl EmailVerification {
+    pub fn check(&self, user: &models::User) -> Result<(), ServiceErr> {
+        match self.required && !user.email_verified {
+            true => Err(ServiceErr::Unauthorized(ErrorCode::EmailNotVerified)),
+            false => Ok(()),
+        }
+    }
 }
 
 pub fn auth_config(cfg: &mut ServiceConfig) {
     cfg.route("/token", get().to(refresh_token))
-        .route("/login", post().to(login))
-        .route(
-            "/get-bot-token/{JWTSECRET}",
-            get().to(secret_get_bot_user_token),
-        );
+        .service(resource("/login").wrap(RateLimit).route(post().to(login)))
+        .route("/login/discord", get().to(discord_authorize))
+        .route("/login/discord", post().to(discord_login))
+        .service(
+            resource("/login/2fa")
+                .wrap(RateLimit)
+                .route(post().to(two_factor_login)),
+        )
+        .route("/logout", post().to(logout))
+        .service(
+            resource("/password-reset/request")
+                .wrap(RateLimit)
+                .route(post().to(request_password_reset)),
+        )
+        .service(
+            resource("/password-reset/confirm")
+                .wrap(RateLimit)
+                .route(post().to(confirm_password_reset)),
+        )
+        .route("/verify-email", post().to(verify_email));
 }
 
 /// `/token`
 async fn refresh_token(
     req: HttpRequest,
-    e_key: Data<EncodingKey>,
+    keys: Data<JwtKeys>,
+    jwt: Data<JwtConfig>,
     db: Data<Pool>,
-    d_key: Data<DecodingKey<'static>>,
 ) -> HttpResult {
     let auth = authorization::Authorization::<Bearer>::parse(&req)
-        .map_err(|_| ServiceErr::Unauthorized("no-token"))?;
+        .map_err(|_| ServiceErr::Unauthorized(ErrorCode::NoToken))?;
 
-    let claims = validate_token(auth.into_scheme().token(), &d_key)?;
+    let claims = validate_token(auth.into_scheme().token(), &keys)?;
     let uid = claims.uid;
 
     debug!(%uid, "refresh token");
 
-    let user = block(move || actions::user::get_user_by_id(&db, uid)).await?;
-
-    if claims.version != user.token_version {
-        return Err(ServiceErr::Unauthorized("old-token"));
+    if !claims.refresh {
+        return Err(ServiceErr::Unauthorized(ErrorCode::WrongTokenKind));
     }
+    // refresh tokens from before the sessions can't be rotated
+    let session_id = claims
+        .session
+        .ok_or(ServiceErr::Unauthorized(ErrorCode::OldToken))?;
+
+    let (user, session) = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_id(&db, uid)?;
+        if claims.version != user.token_version {
+            return Err(ServiceErr::Unauthorized(ErrorCode::OldToken));
+        }
+        let session = actions::session::rotate_session(&db, session_id, claims.generation)?;
+        Ok((user, session))
+    })
+    .await?;
 
-    if claims.refresh {
-        let new_token = create_normal_jwt(uid, &e_key)?;
-        Ok(HttpResponse::Ok()
-            .header("token", format!("Bearer {}", new_token.0))
-            .json(dto::RefreshResponse {
-                expires: new_token.1,
-            }))
-    } else {
-        Err(ServiceErr::Unauthorized("wrong-token-kind"))
-    }
+    let refresh_token = create_refresh_jwt(user.id, &keys, &jwt, user.token_version, &session)?;
+    let (token, expires) = create_normal_jwt(uid, Some(session.id), &keys, &jwt)?;
+    Ok(HttpResponse::Ok()
+        .header("token", format!("Bearer {}", token))
+        .header("refresh-token", format!("Bearer {}", refresh_token))
+        .json(dto::RefreshResponse { expires }))
 }
 
-async fn login(mut body: Json<UserLogin>, db: Data<Pool>, key: Data<EncodingKey>) -> HttpResult {
+/// `POST /logout`, ends the session of the token
+async fn logout(claims: Claims, db: Data<Pool>) -> HttpResult {
+    debug!(uid = %claims.uid, session = ?claims.session, "logout");
+
+    let session = claims
+        .session
+        .ok_or(ServiceErr::BadRequest(ErrorCode::NoSession))?;
+    block(move || actions::session::delete_session(&db, claims.uid, session)).await?;
+
+    Ok(HttpResponse::Ok().body("Logged out."))
+}
+
+async fn login(
+    req: HttpRequest,
+    mut body: Json<UserLogin>,
+    db: Data<Pool>,
+    keys: Data<JwtKeys>,
+    jwt: Data<JwtConfig>,
+    limiter: Data<RateLimiter>,
+) -> HttpResult {
     // to make the logging safe - we don't want to leak passwords
     let password = std::mem::replace(&mut body.password, "**********".to_string());
     debug!(?body, "login");
 
-    let user =
-        block(move || actions::user::validate_user_password(&db, &body.email, &password)).await?;
-
-    match user {
-        Some(user) => {
-            let refresh_token = create_refresh_jwt(user.id, &key, user.token_version)?;
-            let (token, expires) = create_normal_jwt(user.id, &key)?;
-            Ok(HttpResponse::Ok()
-                .header("token", format!("Bearer {}", token))
-                .header("refresh-token", format!("Bearer {}", refresh_token))
-                .json(LoginResponse {
-                    userid: user.id,
-                    expires,
-                }))
+    // the account is checked before the password, so that a locked account doesn't reveal it
+    limiter.check_account(&body.email)?;
+
+    let device = device(&req);
+    let session_jwt = jwt.clone();
+    let login = block::<_, _, ServiceErr>(move || {
+        match actions::user::validate_user_password(&db, &body.email, &password)? {
+            Some(user) => {
+                limiter.reset_account(&body.email);
+                Ok(Some(start_login(&db, user, &device, &session_jwt)?))
+            }
+            None => {
+                limiter.fail_account(&body.email);
+                Ok(None)
+            }
         }
-        None => Ok(HttpResponse::Forbidden().body("invalid-email-password")),
+    })
+    .await?;
+
+    match login {
+        Some(login) => login_step_response(login, &keys, &jwt),
+        None => Err(ServiceErr::Forbidden(ErrorCode::InvalidEmailPassword)),
     }
 }
 
+/// `POST /login/2fa`, completes a login with the challenge and a code of the second factor
+async fn two_factor_login(
+    req: HttpRequest,
+    body: Json<TwoFactorLogin>,
+    db: Data<Pool>,
+    keys: Data<JwtKeys>,
+    jwt: Data<JwtConfig>,
+    limiter: Data<RateLimiter>,
+) -> HttpResult {
+    debug!(challenge = %body.challenge, "two-factor login");
+
+    let device = device(&req);
+    let session_jwt = jwt.clone();
+    let (user, session) = block::<_, _, ServiceErr>(move || {
+        let challenge = match actions::two_factor::get_login_challenge(&db, body.challenge) {
+            Err(ServiceErr::NotFound) => Err(ServiceErr::Unauthorized(ErrorCode::InvalidChallenge)),
+            challenge => challenge,
+        }?;
+
+        // new challenges can be started with the password, so the codes are counted per account
+        let account = challenge.user_id.to_string();
+        limiter.check_account(&account)?;
+        if !actions::two_factor::verify_code(&db, challenge.user_id, &body.code)? {
+            limiter.fail_account(&account);
+            actions::two_factor::fail_login_challenge(&db, challenge.id, MAX_TWO_FACTOR_ATTEMPTS)?;
+            return Err(ServiceErr::Unauthorized(ErrorCode::InvalidCode));
+        }
+        limiter.reset_account(&account);
+        // a challenge can only complete one login
+        if actions::two_factor::delete_login_challenge(&db, challenge.id)? == 0 {
+            return Err(ServiceErr::Unauthorized(ErrorCode::InvalidChallenge));
+        }
+
+        let user = actions::user::get_user_by_id(&db, challenge.user_id)?;
+        let session = new_session(&db, user.id, &device, &session_jwt)?;
+        Ok((user, session))
+    })
+    .await?;
+
+    login_response(&user, &session, &keys, &jwt)
+}
+
+/// A login after the first factor
+enum LoginStep {
+    Done(models::User, models::Session),
+    /// The user has two-factor authentication, the login continues with `/login/2fa`
+    Challenge(models::LoginChallenge),
+}
+
+fn start_login(
+    db: &Pool,
+    user: models::User,
+    device: &str,
+    jwt: &JwtConfig,
+) -> Result<LoginStep, ServiceErr> {
+    if actions::two_factor::is_two_factor_enabled(db, user.id)? {
+        let challenge = actions::two_factor::create_login_challenge(
+            db,
+            user.id,
+            chrono::Duration::minutes(LOGIN_CHALLENGE_MINUTES),
+        )?;
+        return Ok(LoginStep::Challenge(challenge));
+    }
+
+    let session = new_session(db, user.id, device, jwt)?;
+    Ok(LoginStep::Done(user, session))
+}
+
+fn login_step_response(login: LoginStep, keys: &JwtKeys, jwt: &JwtConfig) -> HttpResult {
+    match login {
+        LoginStep::Done(user, session) => login_response(&user, &session, keys, jwt),
+        LoginStep::Challenge(challenge) => Ok(HttpResponse::Accepted().json(dto::LoginChallenge {
+            challenge: challenge.id,
+            expires: challenge.expires.timestamp_millis(),
+        })),
+    }
+}
+
+/// `GET /login/discord`, redirects to the login page of discord
+async fn discord_authorize(
+    query: Query<DiscordAuthorizeQuery>,
+    oauth: Data<Option<DiscordOAuth>>,
+) -> HttpResult {
+    let oauth = oauth.as_ref().as_ref().ok_or(ServiceErr::NotFound)?;
+    let url = oauth.authorize_url(query.state.as_deref())?;
+
+    Ok(HttpResponse::Found().header("Location", url).finish())
+}
+
+/// `POST /login/discord`, logs in with the authorization code that discord redirected with
+async fn discord_login(
+    req: HttpRequest,
+    body: Json<DiscordLoginRequest>,
+    db: Data<Pool>,
+    keys: Data<JwtKeys>,
+    jwt: Data<JwtConfig>,
+    features: Data<Features>,
+    oauth: Data<Option<DiscordOAuth>>,
+) -> HttpResult {
+    let oauth = oauth.as_ref().as_ref().ok_or(ServiceErr::NotFound)?;
+
+    let DiscordUser {
+        id: snowflake,
+        email,
+        verified,
+    } = oauth.fetch_user(&body.code).await?;
+    debug!(%snowflake, "login with discord");
+
+    // an unverified email could belong to someone else
+    let email = email.filter(|_| verified);
+    let device = device(&req);
+    let session_jwt = jwt.clone();
+    let login = block::<_, _, ServiceErr>(move || {
+        let user =
+            actions::user::login_with_discord(&db, &snowflake, email.as_deref(), features.signup)?;
+        start_login(&db, user, &device, &session_jwt)
+    })
+    .await?;
+
+    login_step_response(login, &keys, &jwt)
+}
+
+/// The device of a session, from the user agent
+fn device(req: &HttpRequest) -> String {
+    req.headers()
+        .get("User-Agent")
+        .and_then(|agent| agent.to_str().ok())
+        .unwrap_or(UNKNOWN_DEVICE)
+        .to_string()
+}
+
+fn new_session(
+    db: &Pool,
+    user: Uuid,
+    device: &str,
+    jwt: &JwtConfig,
+) -> Result<models::Session, ServiceErr> {
+    actions::session::create_session(db, user, device, jwt.refresh_lifetime())
+}
+
+/// The token headers and body of a successful login
+fn login_response(
+    user: &models::User,
+    session: &models::Session,
+    keys: &JwtKeys,
+    jwt: &JwtConfig,
+) -> HttpResult {
+    let refresh_token = create_refresh_jwt(user.id, keys, jwt, user.token_version, session)?;
+    let (token, expires) = create_normal_jwt(user.id, Some(session.id), keys, jwt)?;
+    Ok(HttpResponse::Ok()
+        .header("token", format!("Bearer {}", token))
+        .header("refresh-token", format!("Bearer {}", refresh_token))
+        .json(LoginResponse {
+            userid: user.id,
+            expires,
+        }))
+}
+
+#[allow(clippy::too_many_arguments)] // the extractors of the handler
 pub async fn create_user(
+    req: HttpRequest,
     mut body: Json<PostUser>,
     db: Data<Pool>,
-    key: Data<EncodingKey>,
+    keys: Data<JwtKeys>,
+    jwt: Data<JwtConfig>,
+    features: Data<Features>,
+    mailer: Data<Mailer>,
+    policy: Data<PasswordPolicy>,
 ) -> HttpResult {
     // to make the logging safe - we don't want to leak passwords
     let password = std::mem::replace(&mut body.password, "**********".to_string());
 
     debug!(?body, "create a user");
+    if !features.signup {
+        return Err(ServiceErr::Forbidden(ErrorCode::SignupDisabled));
+    }
+    validate_email(&body.email)?;
+    policy.check(&password)?;
 
-    let user = block(move || {
+    let device = device(&req);
+    let session_jwt = jwt.clone();
+    let (user, session) = block::<_, _, ServiceErr>(move || {
         let new_user = NewUser {
             id: uuid::Uuid::new_v4(),
             email: &body.email,
@@ -116,14 +378,21 @@ pub async fn create_user(
             description: &body.description,
             discord_id: None,
             token_version: 1,
+            email_verified: false,
         };
 
-        actions::user::insert_user(&db, new_user)
+        let user = actions::user::insert_user(&db, new_user)?;
+        let session = new_session(&db, user.id, &device, &session_jwt)?;
+        // the account works without the mail, a new one can be requested
+        if let Err(err) = send_verification_mail(&db, &mailer, &user) {
+            error!(%err, uid = %user.id, "could not send the verification mail");
+        }
+        Ok((user, session))
     })
     .await?;
 
-    let (token, expires) = create_normal_jwt(user.id, &key)?;
-    let refresh_token = create_refresh_jwt(user.id, &key, 1)?;
+    let (token, expires) = create_normal_jwt(user.id, Some(session.id), &keys, &jwt)?;
+    let refresh_token = create_refresh_jwt(user.id, &keys, &jwt, user.token_version, &session)?;
 
     Ok(HttpResponse::Created()
         .header("Token", format!("Bearer {}", token))
@@ -134,27 +403,43 @@ pub async fn create_user(
                 email: user.email,
                 description: user.description,
                 classes: None,
+                email_verified: user.email_verified,
+                two_factor_enabled: false,
             },
             expires,
         }))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn change_password(
+    req: HttpRequest,
     claims: Claims,
     db: Data<Pool>,
-    e_key: Data<EncodingKey>,
+    keys: Data<JwtKeys>,
+    jwt: Data<JwtConfig>,
+    limiter: Data<RateLimiter>,
+    policy: Data<PasswordPolicy>,
     password: Json<ChangePasswordReq>,
 ) -> HttpResult {
     debug!(uid = %claims.uid, "change user password");
 
-    let user = block(move || {
+    policy.check(&password.password)?;
+
+    let account = claims.uid.to_string();
+    limiter.check_account(&account)?;
+
+    let device = device(&req);
+    let session_jwt = jwt.clone();
+    let (user, session) = block(move || {
         let user = actions::user::get_user_by_id(&db, claims.uid)?;
         let validate =
             actions::user::validate_user_password(&db, &user.email, &password.old_password)?;
 
         if validate.is_none() {
-            return Err(ServiceErr::Unauthorized("wrong-password"));
+            limiter.fail_account(&account);
+            return Err(ServiceErr::Unauthorized(ErrorCode::WrongPassword));
         }
+        limiter.reset_account(&account);
 
         actions::user::change_user_password(
             &db,
@@ -165,54 +450,185 @@ pub async fn change_password(
                 description: "".to_string(),
                 discord_id: None,
                 token_version: 0,
+                email_verified: false,
             },
         )?;
 
-        actions::user::increment_token_version(&db, claims.uid)
+        // the other devices have to log in again
+        let user = actions::user::increment_token_version(&db, claims.uid)?;
+        actions::session::delete_sessions(&db, claims.uid)?;
+        let session = new_session(&db, claims.uid, &device, &session_jwt)?;
+        Ok((user, session))
     })
     .await?;
 
-    let refresh_token = create_refresh_jwt(user.id, &e_key, user.token_version)?;
+    let refresh_token = create_refresh_jwt(user.id, &keys, &jwt, user.token_version, &session)?;
+    let (token, _) = create_normal_jwt(user.id, Some(session.id), &keys, &jwt)?;
 
     Ok(HttpResponse::Ok()
         .header("Refresh-Token", format!("Bearer {}", refresh_token))
+        .header("Token", format!("Bearer {}", token))
         .json(user.into_dto()?))
 }
 
-async fn secret_get_bot_user_token(token: Path<String>, e_key: Data<EncodingKey>) -> HttpResult {
-    let secret = std::env::var("JWT_SECRET")
-        .map_err(|_| ServiceErr::InternalServerError("Secret not found".to_string()))?;
-
-    if *token == secret {
-        let uuid = uuid::Uuid::nil();
-
-        Ok(HttpResponse::Ok()
-            .header(
-                "Token",
-                format!(
-                    "Bearer {}",
-                    create_bot_jwt(uuid, &e_key, chrono::Duration::weeks(10000))?
-                ),
-            )
-            .finish())
-    } else {
-        Ok(HttpResponse::NotFound().finish())
+/// Rejects emails that are obviously invalid, whether they exist is checked by the verification
+pub(super) fn validate_email(email: &str) -> Result<(), ServiceErr> {
+    let valid = match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !domain.contains('@')
+        }
+        None => false,
+    };
+
+    if !valid
+        || email.chars().count() > MAX_EMAIL_LENGTH
+        || email
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '<' || c == '>')
+    {
+        return Err(ServiceErr::BadRequest(ErrorCode::InvalidEmail));
     }
+    Ok(())
+}
+
+/// Sends a link that verifies the current email of the user
+pub(super) fn send_verification_mail(
+    db: &Pool,
+    mailer: &Mailer,
+    user: &models::User,
+) -> Result<(), ServiceErr> {
+    let token = actions::email_verification::create_email_verification(
+        db,
+        user.id,
+        &user.email,
+        chrono::Duration::hours(EMAIL_VERIFICATION_HOURS),
+    )?;
+
+    let link = mailer.link(&format!("/verify-email?token={}", token));
+    mailer.send(
+        &user.email,
+        "CORS: E-Mail Adresse bestätigen",
+        format!(
+            "Hallo\n\nBitte bestätige deine E-Mail Adresse für CORS mit diesem Link:\n\
+            {}\n\nDer Link ist {} Stunden gültig.",
+            link, EMAIL_VERIFICATION_HOURS
+        ),
+    )
+}
+
+/// `POST /verify-email`, verifies the email with the token from the verification email
+async fn verify_email(body: Json<VerifyEmail>, db: Data<Pool>) -> HttpResult {
+    debug!("verify email");
+
+    let user = block(move || {
+        match actions::email_verification::redeem_email_verification(&db, &body.token) {
+            Err(ServiceErr::NotFound) => {
+                Err(ServiceErr::BadRequest(ErrorCode::InvalidVerificationToken))
+            }
+            result => result,
+        }
+    })
+    .await?;
+    debug!(uid = %user.id, "email was verified");
+
+    Ok(HttpResponse::Ok().json(user.into_dto()?))
+}
+
+/// `POST /password-reset/request`, sends a reset link to the email if it belongs to a user
+///
+/// The response is the same for unknown emails, so it can't be used to find accounts
+async fn request_password_reset(
+    body: Json<PasswordResetRequest>,
+    db: Data<Pool>,
+    mailer: Data<Mailer>,
+) -> HttpResult {
+    debug!(?body, "request password reset");
+
+    block::<_, _, ServiceErr>(move || {
+        let user = match actions::user::get_user_by_email(&db, &body.email)? {
+            Some(user) => user,
+            None => return Ok(()),
+        };
+        let token = actions::password_reset::create_password_reset(
+            &db,
+            user.id,
+            chrono::Duration::minutes(PASSWORD_RESET_MINUTES),
+        )?;
+
+        let link = mailer.link(&format!("/password-reset?token={}", token));
+        mailer.send(
+            &user.email,
+            "CORS: Neues Passwort setzen",
+            format!(
+                "Hallo\n\nÜber diesen Link kannst du ein neues Passwort für deinen CORS Account setzen:\n\
+                {}\n\nDer Link ist {} Minuten gültig. Falls du das nicht warst, kannst du diese Mail ignorieren.",
+                link, PASSWORD_RESET_MINUTES
+            ),
+        )
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().body("If the email belongs to an account, a reset link was sent."))
+}
+
+/// `POST /password-reset/confirm`, sets the new password and logs out everywhere
+async fn confirm_password_reset(
+    body: Json<PasswordResetConfirm>,
+    db: Data<Pool>,
+    policy: Data<PasswordPolicy>,
+) -> HttpResult {
+    debug!("confirm password reset");
+
+    let body = body.into_inner();
+    policy.check(&body.password)?;
+
+    let user = block::<_, _, ServiceErr>(move || {
+        let uid = match actions::password_reset::redeem_password_reset(&db, &body.token) {
+            Err(ServiceErr::NotFound) => {
+                return Err(ServiceErr::BadRequest(ErrorCode::InvalidResetToken))
+            }
+            uid => uid?,
+        };
+
+        actions::user::change_user_password(
+            &db,
+            models::User {
+                id: uid,
+                email: "".to_string(),
+                password: body.password,
+                description: "".to_string(),
+                discord_id: None,
+                token_version: 0,
+                email_verified: false,
+            },
+        )?;
+        let user = actions::user::increment_token_version(&db, uid)?;
+        actions::session::delete_sessions(&db, uid)?;
+        Ok(user)
+    })
+    .await?;
+    debug!(uid = %user.id, "password was reset");
+
+    Ok(HttpResponse::Ok().body("Password changed."))
 }
 
 impl Claims {
     /// The body of the fromRequest implementation, so it can be reused. (non-blocking, since it doesn't do any io)
     pub fn from_request_sync(req: &HttpRequest) -> Result<Self, ServiceErr> {
-        let key = req
-            .app_data::<web::Data<DecodingKey>>()
-            .expect("no decoding key found");
+        let keys = req
+            .app_data::<web::Data<JwtKeys>>()
+            .expect("no jwt keys found");
 
         match authorization::Authorization::<Bearer>::parse(req) {
-            Ok(auth) => validate_token(auth.into_scheme().token(), key),
-            Err(_) => Err(ServiceErr::Unauthorized("no-token")),
+            Ok(auth) => validate_token(auth.into_scheme().token(), keys),
+            Err(_) => Err(ServiceErr::Unauthorized(ErrorCode::NoToken)),
         }
         .and_then(|claims| match claims.refresh {
-            true => Err(ServiceErr::Unauthorized("wrong-token-kind")),
+            true => Err(ServiceErr::Unauthorized(ErrorCode::WrongTokenKind)),
             false => Ok(claims),
         })
     }
@@ -222,114 +638,147 @@ impl Claims {
 ////////// Tokens
 //////////
 
-fn validate_token(token: &str, key: &DecodingKey) -> Result<Claims, ServiceErr> {
-    let decoded = jsonwebtoken::decode::<Claims>(token, key, &Validation::new(Algorithm::HS512))
-        .map_err(|_| ServiceErr::JWTokenError)?
-        .claims;
+/// `GET /.well-known/jwks.json`, the public keys that verify the tokens
+pub async fn jwks(keys: Data<JwtKeys>) -> HttpResponse {
+    HttpResponse::Ok()
+        .header("Cache-Control", "public, max-age=3600")
+        .json(keys.jwks())
+}
+
+/// Tokens with a later `exp` are from before it was in seconds, it was in milliseconds
+const MAX_EXP_SECONDS: i64 = 100_000_000_000;
+
+/// The expiry is checked when the token is decoded
+fn validate_token(token: &str, keys: &JwtKeys) -> Result<Claims, ServiceErr> {
+    let decoded = keys.decode::<Claims>(token)?;
 
-    if decoded.exp < Utc::now().timestamp_millis() {
+    if decoded.exp > MAX_EXP_SECONDS {
         Err(ServiceErr::TokenExpiredError)
     } else {
         Ok(decoded)
     }
 }
 
-/// Returns the token and the expiration date
+/// Returns the token and the expiration date in milliseconds
 /// Create a JWT
-fn create_normal_jwt(user: Uuid, key: &EncodingKey) -> Result<(String, i64), ServiceErr> {
-    let lifetime;
-
-    // make the token last 24 hours for debugging
-    #[cfg(debug_assertions)]
-    {
-        lifetime = chrono::Duration::hours(24);
-    }
-    #[cfg(not(debug_assertions))]
-    {
-        lifetime = chrono::Duration::hours(1);
-    }
-    create_jwt(user, false, key, lifetime, 0)
-}
-
-/// Create a refresh JWT
-/// Returns the token and the expiration date
-fn create_refresh_jwt(user: Uuid, key: &EncodingKey, version: i32) -> Result<String, ServiceErr> {
-    let lifetime = chrono::Duration::weeks(1000); // several years, kind of a hack but ok
-
-    create_jwt(user, true, key, lifetime, version).map(|(token, _)| token)
+fn create_normal_jwt(
+    user: Uuid,
+    session: Option<Uuid>,
+    keys: &JwtKeys,
+    jwt: &JwtConfig,
+) -> Result<(String, i64), ServiceErr> {
+    create_jwt(user, false, keys, jwt.access_lifetime(), 0, session, 0)
 }
 
-/// Create a custom expiration date jwt
-/// Returns the token and the expiration date
-fn create_bot_jwt(
+/// Create a refresh JWT for the current generation of the session
+fn create_refresh_jwt(
     user: Uuid,
-    key: &EncodingKey,
-    time: chrono::Duration,
+    keys: &JwtKeys,
+    jwt: &JwtConfig,
+    version: i32,
+    session: &models::Session,
 ) -> Result<String, ServiceErr> {
-    create_jwt(user, false, key, time, 0).map(|(token, _)| token)
+    create_jwt(
+        user,
+        true,
+        keys,
+        jwt.refresh_lifetime(),
+        version,
+        Some(session.id),
+        session.generation,
+    )
+    .map(|(token, _)| token)
 }
 
 fn create_jwt(
     uid: Uuid,
     refresh: bool,
-    key: &EncodingKey,
+    keys: &JwtKeys,
     lifetime: chrono::Duration,
     version: i32,
+    session: Option<Uuid>,
+    generation: i32,
 ) -> Result<(String, i64), ServiceErr> {
     let exp = Utc::now()
         .checked_add_signed(lifetime)
-        .expect("valid timestamp")
-        .timestamp_millis();
+        .expect("valid timestamp");
 
     let claims = Claims {
-        exp,
+        exp: exp.timestamp(),
         uid,
         refresh,
         version,
+        session,
+        generation,
     };
 
-    let header = jsonwebtoken::Header::new(Algorithm::HS512);
-    jsonwebtoken::encode(&header, &claims, key)
-        .map(|str| (str, exp))
-        .map_err(ServiceErr::JWTCreationError)
+    keys.encode(&claims)
+        .map(|str| (str, exp.timestamp_millis()))
 }
 
 #[cfg(test)]
 mod test {
-    use crate::actions::Pool;
-    use crate::handlers::auth::{create_jwt, create_user, validate_token, Claims};
+    use crate::error::ServiceErr;
+    use crate::handlers::auth::{create_jwt, validate_email, validate_token, Claims};
+    use crate::jwt_keys::JwtKeys;
     use actix_web::dev::Payload;
     use actix_web::test::TestRequest;
-    use actix_web::{web, FromRequest, HttpMessage};
-    use jsonwebtoken::{DecodingKey, EncodingKey};
+    use actix_web::{web, FromRequest};
 
     #[test]
     fn create_and_validate_token() {
-        let encoding_key = EncodingKey::from_secret(b"cooles secret");
-        let decoding_key = DecodingKey::from_secret(b"cooles secret");
+        let keys = JwtKeys::from_secret(b"cooles secret");
         let uid = uuid::Uuid::new_v4();
 
-        let token = create_jwt(uid, false, &encoding_key, chrono::Duration::hours(1), 1).unwrap();
+        let token = create_jwt(uid, false, &keys, chrono::Duration::hours(1), 1, None, 0).unwrap();
 
         assert!(token.1 > chrono::Utc::now().timestamp_millis());
 
-        let decoded = validate_token(&token.0, &decoding_key).unwrap();
+        let decoded = validate_token(&token.0, &keys).unwrap();
         assert_eq!(decoded.uid, uid);
         assert_eq!(decoded.version, 1);
-        assert_eq!(decoded.refresh, false);
+        assert!(!decoded.refresh);
+        assert!(decoded.exp <= chrono::Utc::now().timestamp() + 3600);
+    }
+
+    #[test]
+    fn expired_tokens() {
+        let keys = JwtKeys::from_secret(b"cooles secret");
+        let uid = uuid::Uuid::new_v4();
+
+        let (token, _) =
+            create_jwt(uid, false, &keys, chrono::Duration::hours(-1), 1, None, 0).unwrap();
+        assert!(matches!(
+            validate_token(&token, &keys),
+            Err(ServiceErr::TokenExpiredError)
+        ));
+
+        // tokens from before the expiry was in seconds
+        let claims = Claims {
+            exp: chrono::Utc::now().timestamp_millis() + 3_600_000,
+            uid,
+            refresh: false,
+            version: 1,
+            session: None,
+            generation: 0,
+        };
+        let token = keys.encode(&claims).unwrap();
+        assert!(matches!(
+            validate_token(&token, &keys),
+            Err(ServiceErr::TokenExpiredError)
+        ));
     }
 
     #[actix_rt::test]
     async fn deserialize_token_from_request() {
-        let encoding_key = EncodingKey::from_secret(b"cooles secret");
-        let decoding_key = DecodingKey::from_secret(b"cooles secret");
+        let keys = JwtKeys::from_secret(b"cooles secret");
         let uid = uuid::Uuid::new_v4();
 
         let (token, _) =
-            create_jwt(uid, false, &encoding_key, chrono::Duration::hours(1), 1).unwrap();
+            create_jwt(uid, false, &keys, chrono::Duration::hours(1), 1, None, 0).unwrap();
 
         let req = TestRequest::with_header("Authorization", format!("Bearer {}", token))
-            .app_data(web::Data::new(decoding_key))
+            .app_data(web::Data::new(keys))
             .to_http_request();
 
         let claims = Claims::from_request(&req, &mut Payload::None)
@@ -340,13 +789,23 @@ mod test {
         assert_eq!(claims.version, 1);
     }
 
-    fn get_pool() -> Pool {
-        dotenv::dotenv().ok();
-        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-
-        let manager = r2d2::ConnectionManager::<diesel::pg::PgConnection>::new(database_url);
-        r2d2::Pool::builder()
-            .build(manager)
-            .expect("Failed to create pool.")
+    #[test]
+    fn email_validation() {
+        assert!(validate_email("hugo@cors-school.com").is_ok());
+        assert!(validate_email("hugo.boss+cors@mail.example.ch").is_ok());
+
+        for invalid in [
+            "",
+            "hugo",
+            "@cors-school.com",
+            "hugo@localhost",
+            "hugo@cors-school.",
+            "hugo@@cors-school.com",
+            "hu go@cors-school.com",
+            "hugo@cors-school.com\r\nRCPT TO:<x@y.z>",
+            "averyveryveryveryveryverylongaddress@cors-school.com",
+        ] {
+            assert!(validate_email(invalid).is_err(), "{}", invalid);
+        }
     }
 }
@@ -1,31 +1,51 @@
+use crate::actions::class::ClassImport;
 use crate::actions::{self, Pool};
 use crate::error::ServiceErr;
-use crate::handlers::auth::Claims;
-use crate::handlers::extractors::Role;
+use crate::handlers::auth::{Claims, EmailVerification};
+use crate::handlers::extractors::{member_role, scopes, Bot, NotArchived, Role};
 use crate::handlers::HttpResult;
 use crate::models;
 use crate::models::conversion::IntoDto;
-use crate::models::{NewClass, NewEvent, NewGuild, NewMember};
+use crate::models::{
+    NewClass, NewClassInvite, NewEvent, NewGuild, NewMember, NewNotifRoute, NewRoleMapping,
+};
 use actix_web::web::{
     block, delete, get, post, put, scope, Data, Json, Path, Query, ServiceConfig,
 };
 use actix_web::HttpResponse;
-use chrono::NaiveDateTime;
+use chrono::Utc;
+use chrono::{NaiveDate, NaiveDateTime, TimeZone};
 use dto::{
-    Class, Event, GetEventQueryParams, Guild, Member, MemberAcceptDto, MemberRole, SingleSnowflake,
-    Timetable,
+    ArchivedMember, Class, ClassArchive, ClassImportResponse, CloneClassRequest, DiscordLink,
+    ErrorCode, Event, GetEventQueryParams, Guild, JoinRequestRes, Member, MemberAcceptDto,
+    MemberRole, NotificationQueryParams, SingleSnowflake, Timetable, CLASS_ARCHIVE_VERSION,
 };
+use std::collections::HashSet;
 use tracing::debug;
 use uuid::Uuid;
 
+/// The length of the email and the display name columns
+const MAX_INVITE_LENGTH: usize = 50;
+/// Events of a cloned class are shifted by at most two school years
+const MAX_SHIFT_WEEKS: i64 = 104;
+
 pub(super) fn class_config(cfg: &mut ServiceConfig) {
     cfg.route("/classes", post().to(create_class))
+        .route("/classes/import", post().to(import_class))
         .route(
             "/classes/discord/{snowflake}",
             get().to(get_class_by_discord),
         )
         .route("/bot/guilds", put().to(edit_guild_settings))
+        .route(
+            "/bot/join-requests",
+            get().to(get_join_request_notifications),
+        )
         .route("/bot/guilds/{snowflake}", get().to(get_guild))
+        .route(
+            "/bot/guilds/{snowflake}/members",
+            get().to(get_guild_members),
+        )
         .service(
             scope("/classes/{classid}")
                 .route("", get().to(get_class))
@@ -47,7 +67,11 @@ pub(super) fn class_config(cfg: &mut ServiceConfig) {
                 .route("/timetable", post().to(create_timetable))
                 .route("/timetable", delete().to(delete_timetable))
                 .route("/timetable", put().to(edit_timetable))
-                .route("/link", post().to(link_class_with_discord)),
+                .route("/link", post().to(link_class_with_discord))
+                .route("/export", get().to(export_class))
+                .route("/clone", post().to(clone_class))
+                .route("/archive", post().to(archive_class))
+                .route("/archive", delete().to(unarchive_class)),
         );
 }
 
@@ -61,10 +85,24 @@ async fn get_class(class_path: Path<Uuid>, db: Data<Pool>, _role: Role) -> HttpR
     Ok(HttpResponse::Ok().json(class))
 }
 
-async fn create_class(class: Json<Class>, db: Data<Pool>, claims: Claims) -> HttpResult {
+async fn create_class(
+    class: Json<Class>,
+    db: Data<Pool>,
+    claims: Claims,
+    verification: Data<EmailVerification>,
+) -> HttpResult {
     debug!(?class, userid = %claims.uid, "create a new class");
 
+    let timezone = class
+        .timezone
+        .clone()
+        .unwrap_or_else(|| dto::DEFAULT_TIMEZONE.to_string());
+    validate_timezone(&timezone)?;
+
     let (result_class, owner) = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        verification.check(&user)?;
+
         let class_id = uuid::Uuid::new_v4();
 
         let new_class = NewClass {
@@ -73,10 +111,10 @@ async fn create_class(class: Json<Class>, db: Data<Pool>, claims: Claims) -> Htt
             name: &class.name,
             description: &class.description,
             discord_id: None,
+            timezone: &timezone,
         };
 
         let class = actions::class::insert_class(&db, new_class)?;
-        let user = actions::user::get_user_by_id(&db, claims.uid)?;
         let new_member = NewMember {
             user: user.id,
             class: class.id,
@@ -94,11 +132,370 @@ async fn create_class(class: Json<Class>, db: Data<Pool>, claims: Claims) -> Htt
     Ok(HttpResponse::Created().json(result_class))
 }
 
+/// Returns the class with everything in it as a json download, see [`import_class`]
+async fn export_class(class_id: Path<Uuid>, role: Role, db: Data<Pool>) -> HttpResult {
+    debug!(%class_id, ?role, "export class");
+
+    if !role.has_rights() {
+        return Err(ServiceErr::NoAdminPermissions);
+    }
+
+    let archive = block::<_, _, ServiceErr>(move || {
+        let (class, members) =
+            actions::class::get_class(&db, *class_id)?.ok_or(ServiceErr::NotFound)?;
+        let guild = match &class.discord_id {
+            Some(guild_id) => Some(
+                (
+                    actions::class::get_guild_settings(&db, guild_id)?,
+                    actions::class::get_notif_routes(&db, guild_id)?,
+                    actions::class::get_role_mappings(&db, guild_id)?,
+                )
+                    .into_dto()?,
+            ),
+            None => None,
+        };
+
+        Ok(ClassArchive {
+            version: CLASS_ARCHIVE_VERSION,
+            exported: Utc::now().timestamp_millis(),
+            members: members
+                .into_iter()
+                .map(|(member, user)| {
+                    Ok(ArchivedMember {
+                        email: user.email,
+                        display_name: member.display_name,
+                        role: member.role.into_dto()?,
+                    })
+                })
+                .collect::<Result<_, ServiceErr>>()?,
+            events: actions::event::get_events_by_class(&db, class.id)?.into_dto()?,
+            timetable: get_optional_timetable(&db, class.id)?,
+            guild,
+            class: class.into_dto()?,
+        })
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok()
+        .header(
+            "content-disposition",
+            format!(
+                "attachment; filename=\"cors-class-{}.json\"",
+                archive.class.id
+            ),
+        )
+        .json(archive))
+}
+
+/// Creates a new class from a [`ClassArchive`], the user becomes the owner
+///
+/// The members of the archive are invited by their email, they join when they accept. Whether
+/// an email has an account is never told, the owner of the archive is invited as an admin.
+/// The guild of the archive is not linked, only its owner can link it with a link code.
+async fn import_class(
+    archive: Json<ClassArchive>,
+    db: Data<Pool>,
+    claims: Claims,
+    verification: Data<EmailVerification>,
+) -> HttpResult {
+    let archive = archive.into_inner();
+    debug!(class = %archive.class.name, userid = %claims.uid, "import class");
+
+    if archive.version > CLASS_ARCHIVE_VERSION {
+        return Err(ServiceErr::BadRequest(ErrorCode::UnsupportedArchiveVersion));
+    }
+    let timezone = archive
+        .class
+        .timezone
+        .clone()
+        .unwrap_or_else(|| dto::DEFAULT_TIMEZONE.to_string());
+    validate_timezone(&timezone)?;
+    if let Some(member) = archive
+        .members
+        .iter()
+        .find(|member| matches!(member.role, MemberRole::CORS | MemberRole::Pending))
+    {
+        return Err(ServiceErr::InvalidRequest(format!(
+            "the member {} can't be imported with the role {:?}",
+            member.email, member.role
+        )));
+    }
+    if let Some(member) = archive.members.iter().find(|member| {
+        member.email.chars().count() > MAX_INVITE_LENGTH
+            || member.display_name.chars().count() > MAX_INVITE_LENGTH
+    }) {
+        return Err(ServiceErr::InvalidRequest(format!(
+            "the email and the display name of the member {} can't be longer than {}",
+            member.email, MAX_INVITE_LENGTH
+        )));
+    }
+
+    let (class, invited) = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        verification.check(&user)?;
+
+        let require_two_factor = archive.class.require_two_factor.unwrap_or(false);
+        // the owner can't lock themselves out
+        if require_two_factor && !actions::two_factor::is_two_factor_enabled(&db, claims.uid)? {
+            return Err(ServiceErr::BadRequest(ErrorCode::TwoFactorNotEnabled));
+        }
+
+        let own_display_name = archive
+            .members
+            .iter()
+            .find(|member| member.email == user.email)
+            .map_or(&user.email, |member| &member.display_name);
+        // bans are not imported, invites can only let someone in
+        let mut invited = HashSet::new();
+        invited.insert(user.email.as_str());
+        let invited_members = archive
+            .members
+            .iter()
+            .filter(|member| member.role != MemberRole::Banned)
+            .filter(|member| invited.insert(member.email.as_str()))
+            .collect::<Vec<_>>();
+
+        let class_id = Uuid::new_v4();
+        let members = vec![NewMember {
+            user: user.id,
+            class: class_id,
+            display_name: own_display_name,
+            role: models::MemberRole::OWNER,
+        }];
+        let invites = invited_members
+            .iter()
+            .map(|member| NewClassInvite {
+                class: class_id,
+                email: &member.email,
+                display_name: &member.display_name,
+                role: match member.role {
+                    MemberRole::Owner => models::MemberRole::ADMIN,
+                    role => role as i32,
+                },
+            })
+            .collect();
+
+        let times = archive
+            .events
+            .iter()
+            .map(|event| {
+                (
+                    NaiveDateTime::from_timestamp(event.start / 1000, 0),
+                    event
+                        .end
+                        .map(|ts| NaiveDateTime::from_timestamp(ts / 1000, 0)),
+                    event
+                        .notification
+                        .map(|ts| NaiveDateTime::from_timestamp(ts / 1000, 0)),
+                )
+            })
+            .collect::<Vec<_>>();
+        let events = archive
+            .events
+            .iter()
+            .zip(&times)
+            .map(|(event, (start, end, notification))| NewEvent {
+                id: Uuid::new_v4(),
+                class: class_id,
+                e_type: event.r#type as i32,
+                name: &event.name,
+                start,
+                end: end.as_ref(),
+                description: &event.description,
+                notification: notification.as_ref(),
+            })
+            .collect();
+
+        let timetable = archive
+            .timetable
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|_| ServiceErr::InternalServerError("serialize-timetable".to_string()))?;
+
+        let class = actions::class::import_class(
+            &db,
+            ClassImport {
+                class: NewClass {
+                    id: class_id,
+                    owner: user.id,
+                    name: &archive.class.name,
+                    description: &archive.class.description,
+                    discord_id: None,
+                    timezone: &timezone,
+                },
+                require_two_factor,
+                members,
+                invites,
+                events,
+                timetable: timetable.as_deref(),
+            },
+        )?;
+
+        let members = actions::class::get_all_members(&db, class.id)?;
+        Ok(((class, members), invited_members.len()))
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(ClassImportResponse {
+        class: class.into_dto()?,
+        invited,
+    }))
+}
+
+/// Creates a new class from one of the user, for the next school year, the user becomes the owner
+///
+/// What is copied is chosen in the [`CloneClassRequest`], the source class can be archived with it.
+async fn clone_class(
+    class_id: Path<Uuid>,
+    role: Role,
+    request: Json<CloneClassRequest>,
+    db: Data<Pool>,
+    claims: Claims,
+    verification: Data<EmailVerification>,
+) -> HttpResult {
+    let request = request.into_inner();
+    debug!(%class_id, ?role, ?request, "clone class");
+
+    if *role != MemberRole::Owner {
+        return Err(ServiceErr::Unauthorized(ErrorCode::NoOwner));
+    }
+    if request.shift_weeks.abs() > MAX_SHIFT_WEEKS {
+        return Err(ServiceErr::InvalidRequest(format!(
+            "events can be shifted by at most {} weeks",
+            MAX_SHIFT_WEEKS
+        )));
+    }
+
+    let class = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        verification.check(&user)?;
+
+        let (source, source_members) =
+            actions::class::get_class(&db, *class_id)?.ok_or(ServiceErr::NotFound)?;
+        let clone_id = Uuid::new_v4();
+
+        let own_display_name = source_members
+            .iter()
+            .find(|(_, member_user)| member_user.id == user.id)
+            .map_or(&user.email, |(member, _)| &member.display_name);
+        let members = std::iter::once(NewMember {
+            user: user.id,
+            class: clone_id,
+            display_name: own_display_name,
+            role: models::MemberRole::OWNER,
+        })
+        .chain(
+            source_members
+                .iter()
+                .filter(|(_, member_user)| request.copy_members && member_user.id != user.id)
+                .map(|(member, member_user)| NewMember {
+                    user: member_user.id,
+                    class: clone_id,
+                    display_name: &member.display_name,
+                    role: match member.role {
+                        models::MemberRole::BANNED => models::MemberRole::BANNED,
+                        _ if request.reset_roles => models::MemberRole::MEMBER,
+                        models::MemberRole::OWNER => models::MemberRole::ADMIN,
+                        role => role,
+                    },
+                }),
+        )
+        .collect();
+
+        let source_events = match request.copy_events {
+            true => actions::event::get_events_by_class(&db, source.id)?
+                .into_iter()
+                .filter(|event| {
+                    request.event_types.is_empty()
+                        || request
+                            .event_types
+                            .iter()
+                            .any(|&typ| typ as i32 == event.e_type)
+                })
+                .collect(),
+            false => Vec::new(),
+        };
+        let shift = chrono::Duration::weeks(request.shift_weeks);
+        let times = source_events
+            .iter()
+            .map(|event| {
+                (
+                    event.start + shift,
+                    event.end.map(|end| end + shift),
+                    event.notification.map(|notification| notification + shift),
+                )
+            })
+            .collect::<Vec<_>>();
+        let events = source_events
+            .iter()
+            .zip(&times)
+            .map(|(event, (start, end, notification))| NewEvent {
+                id: Uuid::new_v4(),
+                class: clone_id,
+                e_type: event.e_type,
+                name: &event.name,
+                start,
+                end: end.as_ref(),
+                description: &event.description,
+                notification: notification.as_ref(),
+            })
+            .collect();
+
+        let timetable = match request.copy_timetable {
+            true => match actions::class::get_timetable(&db, source.id) {
+                Ok(timetable) => Some(timetable.timetable),
+                Err(ServiceErr::NotFound) => None,
+                Err(err) => return Err(err),
+            },
+            false => None,
+        };
+
+        let class = actions::class::clone_class(
+            &db,
+            source.id,
+            ClassImport {
+                class: NewClass {
+                    id: clone_id,
+                    owner: user.id,
+                    name: &request.name,
+                    description: request
+                        .description
+                        .as_deref()
+                        .unwrap_or(&source.description),
+                    discord_id: match request.discord {
+                        DiscordLink::Keep => None,
+                        DiscordLink::Move => source.discord_id.as_deref(),
+                    },
+                    timezone: &source.timezone,
+                },
+                require_two_factor: source.require_two_factor,
+                members,
+                invites: Vec::new(),
+                events,
+                timetable: timetable.as_deref(),
+            },
+            request.archive_source,
+        )?;
+
+        let members = actions::class::get_class(&db, class.id)?
+            .ok_or(ServiceErr::NotFound)?
+            .1;
+        Ok((class, members))
+    })
+    .await?
+    .into_dto()?;
+
+    Ok(HttpResponse::Created().json(class))
+}
+
 async fn edit_class(
     class_id: Path<Uuid>,
     new_class: Json<Class>,
     db: Data<Pool>,
     role: Role,
+    _: NotArchived,
+    claims: Claims,
 ) -> HttpResult {
     debug!(%class_id, ?role, "edit class");
 
@@ -106,16 +503,35 @@ async fn edit_class(
         return Err(ServiceErr::NoAdminPermissions);
     }
 
+    if let Some(timezone) = &new_class.timezone {
+        validate_timezone(timezone)?;
+    }
+
     let class = block(move || {
         let update_class = NewClass {
-            id: class_id.into_inner(),
+            id: *class_id,
             owner: Default::default(), // doesn't matter
             name: &new_class.name,
             description: &new_class.description,
             discord_id: None,
+            timezone: "", // doesn't matter
         };
 
-        actions::class::update_class(&db, update_class)
+        // admins can't lock themselves out
+        if new_class.require_two_factor == Some(true)
+            && !actions::two_factor::is_two_factor_enabled(&db, claims.uid)?
+        {
+            return Err(ServiceErr::BadRequest(ErrorCode::TwoFactorNotEnabled));
+        }
+
+        let mut class = actions::class::update_class(&db, update_class)?;
+        if let Some(timezone) = &new_class.timezone {
+            class = actions::class::set_timezone_class(&db, *class_id, timezone)?;
+        }
+        if let Some(required) = new_class.require_two_factor {
+            class = actions::class::set_two_factor_required(&db, *class_id, required)?;
+        }
+        Ok(class)
     })
     .await?
     .into_dto()?;
@@ -127,18 +543,46 @@ async fn delete_class(class_id: Path<Uuid>, db: Data<Pool>, role: Role) -> HttpR
     debug!(%class_id, ?role, "delete class");
 
     if *role != MemberRole::Owner {
-        return Err(ServiceErr::Unauthorized("no-owner"));
+        return Err(ServiceErr::Unauthorized(ErrorCode::NoOwner));
     }
 
     let deleted_amount =
         block::<_, _, ServiceErr>(move || Ok(actions::class::delete_class(&db, *class_id)))
             .await??;
 
-    Ok(match deleted_amount {
-        0 => HttpResponse::NotFound().body("Class not found"),
-        1 => HttpResponse::Ok().body("Deleted class."),
+    match deleted_amount {
+        0 => Err(ServiceErr::NotFound),
+        1 => Ok(HttpResponse::Ok().body("Deleted class.")),
         _ => unreachable!(),
-    })
+    }
+}
+
+/// Archived classes can only be read, until they are unarchived
+async fn archive_class(class_id: Path<Uuid>, db: Data<Pool>, role: Role) -> HttpResult {
+    set_archived(class_id, db, role, true).await
+}
+
+async fn unarchive_class(class_id: Path<Uuid>, db: Data<Pool>, role: Role) -> HttpResult {
+    set_archived(class_id, db, role, false).await
+}
+
+async fn set_archived(
+    class_id: Path<Uuid>,
+    db: Data<Pool>,
+    role: Role,
+    archived: bool,
+) -> HttpResult {
+    debug!(%class_id, ?role, archived, "set class archived");
+
+    if *role != MemberRole::Owner {
+        return Err(ServiceErr::Unauthorized(ErrorCode::NoOwner));
+    }
+
+    let class = block(move || actions::class::set_archived(&db, *class_id, archived))
+        .await?
+        .into_dto()?;
+
+    Ok(HttpResponse::Ok().json(class))
 }
 
 async fn get_member(path: Path<(Uuid, Uuid)>, _role: Role, db: Data<Pool>) -> HttpResult {
@@ -155,6 +599,7 @@ async fn get_member(path: Path<(Uuid, Uuid)>, _role: Role, db: Data<Pool>) -> Ht
 async fn edit_member(
     path: Path<(Uuid, Uuid)>,
     own_role: Role,
+    _: NotArchived,
     member: Json<Member>,
     db: Data<Pool>,
     claims: Claims,
@@ -188,11 +633,11 @@ async fn edit_member(
             } else {
                 // Can only set target permissions lower than own
                 if member.role <= *own_role {
-                    return Err(ServiceErr::Unauthorized("not-enough-permissions"));
+                    return Err(ServiceErr::Unauthorized(ErrorCode::NotEnoughPermissions));
                 }
                 // Can only edit members lower than self
                 if old_member.role <= own_role.0 as i32 {
-                    return Err(ServiceErr::Unauthorized("not-enough-permissions"));
+                    return Err(ServiceErr::Unauthorized(ErrorCode::NotEnoughPermissions));
                 }
             }
         }
@@ -237,7 +682,7 @@ async fn delete_member(
 
     // Class must always have an owner
     if !delete_other && *role == MemberRole::Owner {
-        return Err(ServiceErr::BadRequest("must-have-owner"));
+        return Err(ServiceErr::BadRequest(ErrorCode::MustHaveOwner));
     }
 
     let deleted_amount = block(move || {
@@ -245,7 +690,7 @@ async fn delete_member(
 
         // Can only edit other members lower than self
         if delete_other && old_member.role <= role.0 as i32 {
-            return Err(ServiceErr::Unauthorized("not-enough-permissions"));
+            return Err(ServiceErr::Unauthorized(ErrorCode::NotEnoughPermissions));
         }
 
         actions::class::delete_member(&db, member_id, class_id)
@@ -273,11 +718,18 @@ async fn get_bans(class_id: Path<Uuid>, role: Role, db: Data<Pool>) -> HttpResul
     Ok(HttpResponse::Ok().json(bans))
 }
 
-async fn request_join(class_id: Path<Uuid>, claims: Claims, db: Data<Pool>) -> HttpResult {
+async fn request_join(
+    class_id: Path<Uuid>,
+    claims: Claims,
+    _: NotArchived,
+    db: Data<Pool>,
+    verification: Data<EmailVerification>,
+) -> HttpResult {
     debug!(%class_id, userid = %claims.uid, "request join");
 
     block(move || {
         let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        verification.check(&user)?;
         let member = NewMember {
             user: claims.uid,
             class: *class_id,
@@ -285,13 +737,37 @@ async fn request_join(class_id: Path<Uuid>, claims: Claims, db: Data<Pool>) -> H
             role: models::MemberRole::PENDING,
         };
 
-        actions::class::create_member(&db, member)
+        actions::class::create_join_request(&db, member)
     })
     .await?;
 
     Ok(HttpResponse::Created().body("Pending response..."))
 }
 
+async fn get_join_request_notifications(
+    params: Query<NotificationQueryParams>,
+    bot: Bot<scopes::Notifications>,
+    db: Data<Pool>,
+) -> HttpResult {
+    debug!(key = %bot.name, ?params, "get join request notifications");
+
+    let (time, requests) = block(move || {
+        actions::class::get_join_request_notifications(
+            &db,
+            NaiveDateTime::from_timestamp(
+                params.since.div_euclid(1000),
+                params.since.rem_euclid(1000) as u32 * 1_000_000,
+            ),
+        )
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(JoinRequestRes {
+        requests: requests.into_dto()?,
+        time: time.timestamp_millis(),
+    }))
+}
+
 async fn get_join_requests(class_id: Path<Uuid>, role: Role, db: Data<Pool>) -> HttpResult {
     debug!(%class_id, ?role, "get join requests");
 
@@ -309,6 +785,7 @@ async fn get_join_requests(class_id: Path<Uuid>, role: Role, db: Data<Pool>) ->
 async fn accept_member(
     path: Path<(Uuid, Uuid)>,
     role: Role,
+    _: NotArchived,
     db: Data<Pool>,
     accept: Json<MemberAcceptDto>,
 ) -> HttpResult {
@@ -321,11 +798,11 @@ async fn accept_member(
     }
 
     let response = block(move || {
+        let (member, _) = actions::class::get_member(&db, member_id, class_id)?;
+        if member.role != models::MemberRole::PENDING {
+            return Err(ServiceErr::BadRequest(ErrorCode::MemberNotPending));
+        }
         if accept.accept {
-            let (member, _) = actions::class::get_member(&db, member_id, class_id)?;
-            if member.role != models::MemberRole::PENDING {
-                return Err(ServiceErr::BadRequest("member-not-pending"));
-            }
             let new_member = NewMember {
                 user: member_id,
                 class: class_id,
@@ -333,6 +810,7 @@ async fn accept_member(
                 role: 2,
             };
             actions::class::update_member(&db, new_member)?;
+            actions::class::delete_join_request(&db, member_id, class_id)?;
             Ok("Accepted member.")
         } else {
             let deleted = actions::class::delete_member(&db, member_id, class_id)?;
@@ -364,9 +842,25 @@ async fn get_events(
     db: Data<Pool>,
     query: Query<GetEventQueryParams>,
 ) -> HttpResult {
-    let GetEventQueryParams { before, after } = query.into_inner();
+    let GetEventQueryParams { before, after, day } = query.into_inner();
 
-    debug!(%class_id, ?_role, ?before, ?after, "get events");
+    debug!(%class_id, ?_role, ?before, ?after, ?day, "get events");
+
+    if let Some(day) = day {
+        if before.is_some() || after.is_some() {
+            return Err(ServiceErr::BadRequest(ErrorCode::InvalidRequest));
+        }
+        let day = NaiveDate::parse_from_str(&day, "%Y-%m-%d")
+            .map_err(|_| ServiceErr::BadRequest(ErrorCode::InvalidDay))?;
+        let events = block(move || {
+            let timezone = actions::class::get_timezone_class(&db, *class_id)?;
+            let (from, until) = day_bounds(&timezone, day)?;
+            actions::event::get_events_by_class_between(&db, *class_id, from, until)
+        })
+        .await?
+        .into_dto()?;
+        return Ok(HttpResponse::Ok().json(events));
+    }
 
     let before = before.map(|b| b / 1000);
     let after = after.map(|a| a / 1000);
@@ -399,6 +893,7 @@ async fn get_events(
 async fn create_event(
     class_id: Path<Uuid>,
     role: Role,
+    _: NotArchived,
     db: Data<Pool>,
     event: Json<Event>,
 ) -> HttpResult {
@@ -438,6 +933,7 @@ async fn create_event(
 async fn edit_event(
     path: Path<(Uuid, Uuid)>,
     role: Role,
+    _: NotArchived,
     db: Data<Pool>,
     event: Json<Event>,
 ) -> HttpResult {
@@ -475,7 +971,12 @@ async fn edit_event(
     Ok(HttpResponse::Ok().json(event))
 }
 
-async fn delete_event(path: Path<(String, Uuid)>, role: Role, db: Data<Pool>) -> HttpResult {
+async fn delete_event(
+    path: Path<(String, Uuid)>,
+    role: Role,
+    _: NotArchived,
+    db: Data<Pool>,
+) -> HttpResult {
     debug!(class_id = ?path.0, event_id = ?path.1, ?role, "delete event");
 
     if !role.has_rights() {
@@ -484,11 +985,11 @@ async fn delete_event(path: Path<(String, Uuid)>, role: Role, db: Data<Pool>) ->
 
     let deleted = block(move || actions::event::delete_event(&db, path.1)).await?;
 
-    Ok(match deleted {
-        0 => HttpResponse::NotFound().body("Event not found"),
-        1 => HttpResponse::Ok().body("Deleted event."),
+    match deleted {
+        0 => Err(ServiceErr::NotFound),
+        1 => Ok(HttpResponse::Ok().body("Deleted event.")),
         _ => unreachable!(),
-    })
+    }
 }
 
 async fn get_timetable(class_id: Path<Uuid>, _role: Role, db: Data<Pool>) -> HttpResult {
@@ -506,6 +1007,7 @@ async fn get_timetable(class_id: Path<Uuid>, _role: Role, db: Data<Pool>) -> Htt
 async fn edit_timetable(
     class_id: Path<Uuid>,
     role: Role,
+    _: NotArchived,
     db: Data<Pool>,
     table: Json<Timetable>,
 ) -> HttpResult {
@@ -543,7 +1045,12 @@ async fn edit_timetable(
         .body(timetable))
 }
 
-async fn create_timetable(class_id: Path<Uuid>, role: Role, db: Data<Pool>) -> HttpResult {
+async fn create_timetable(
+    class_id: Path<Uuid>,
+    role: Role,
+    _: NotArchived,
+    db: Data<Pool>,
+) -> HttpResult {
     debug!(%class_id, ?role, "create timetable");
 
     if !role.has_rights() {
@@ -559,7 +1066,12 @@ async fn create_timetable(class_id: Path<Uuid>, role: Role, db: Data<Pool>) -> H
         .body(timetable))
 }
 
-async fn delete_timetable(class_id: Path<Uuid>, role: Role, db: Data<Pool>) -> HttpResult {
+async fn delete_timetable(
+    class_id: Path<Uuid>,
+    role: Role,
+    _: NotArchived,
+    db: Data<Pool>,
+) -> HttpResult {
     debug!(%class_id, ?role, "delete timetable");
 
     if !role.has_rights() {
@@ -569,29 +1081,30 @@ async fn delete_timetable(class_id: Path<Uuid>, role: Role, db: Data<Pool>) -> H
     let delete_count =
         block(move || actions::class::delete_timetable(&db, class_id.into_inner())).await?;
 
-    Ok(match delete_count {
-        0 => HttpResponse::NotFound().body("Timetable not found"),
-        1 => HttpResponse::Ok().body("Deleted timetable."),
+    match delete_count {
+        0 => Err(ServiceErr::NotFound),
+        1 => Ok(HttpResponse::Ok().body("Deleted timetable.")),
         _ => unreachable!(),
-    })
+    }
 }
 
 async fn link_class_with_discord(
     class_id: Path<Uuid>,
     role: Role,
+    _: NotArchived,
     db: Data<Pool>,
     id: Json<SingleSnowflake>,
 ) -> HttpResult {
     debug!(%class_id, ?role, ?id, "link class with discord");
 
     if *role != MemberRole::Owner {
-        return Err(ServiceErr::BadRequest("no-owner"));
+        return Err(ServiceErr::BadRequest(ErrorCode::NoOwner));
     }
 
     let snowflake = id.into_inner().snowflake;
     snowflake
         .parse::<u64>()
-        .map_err(|_| ServiceErr::BadRequest("invalid-snowflake"))?;
+        .map_err(|_| ServiceErr::BadRequest(ErrorCode::InvalidSnowflake))?;
 
     let class = block::<_, _, ServiceErr>(move || {
         let class = actions::class::set_discord_id_class(&db, *class_id, Some(&snowflake))?;
@@ -602,6 +1115,8 @@ async fn link_class_with_discord(
                 notif_channel: None,
                 notif_ping_role: None,
                 notif_ping_everyone: false,
+                language: None,
+                admin_channel: None,
             },
         )?;
         Ok(class)
@@ -614,14 +1129,10 @@ async fn link_class_with_discord(
 
 async fn get_class_by_discord(
     class_id: Path<String>,
-    claims: Claims,
+    bot: Bot<scopes::Guilds>,
     db: Data<Pool>,
 ) -> HttpResult {
-    debug!(%class_id, uid = %claims.uid, "get class by discord");
-
-    if !claims.uid.is_nil() {
-        return Err(ServiceErr::Unauthorized("bot-only"));
-    }
+    debug!(key = %bot.name, %class_id, "get class by discord");
 
     let class = block(move || actions::class::get_class_by_discord(&db, &class_id))
         .await?
@@ -630,38 +1141,222 @@ async fn get_class_by_discord(
     Ok(HttpResponse::Ok().json(class))
 }
 
-async fn edit_guild_settings(claims: Claims, db: Data<Pool>, guild: Json<Guild>) -> HttpResult {
-    debug!(?guild, uid = %claims.uid, "edit guild settings");
+async fn edit_guild_settings(
+    bot: Bot<scopes::Guilds>,
+    db: Data<Pool>,
+    guild: Json<Guild>,
+) -> HttpResult {
+    debug!(key = %bot.name, ?guild, "edit guild settings");
+
+    validate_guild(&guild)?;
 
-    if !claims.uid.is_nil() {
-        return Err(ServiceErr::Unauthorized("bot-only"));
-    }
+    let guild = block::<_, _, ServiceErr>(move || {
+        // the bot changes the settings on behalf of a linked discord user
+        if let Some(snowflake) = &bot.discord_user {
+            check_guild_admin(&db, &guild.id, snowflake)?;
+        }
 
-    let guild = block(move || {
-        actions::class::change_guild_settings(
+        let new_guild = actions::class::change_guild_settings(
             &db,
             NewGuild {
                 id: &guild.id,
                 notif_channel: guild.notif_channel.as_deref(),
                 notif_ping_role: guild.notif_ping_role.as_deref(),
                 notif_ping_everyone: guild.notif_ping_everyone,
+                language: guild.language.as_deref(),
+                admin_channel: guild.admin_channel.as_deref(),
             },
-        )
+        )?;
+
+        let routes = actions::class::set_notif_routes(&db, &guild.id, new_notif_routes(&guild))?;
+        let mappings =
+            actions::class::set_role_mappings(&db, &guild.id, new_role_mappings(&guild))?;
+
+        Ok((new_guild, routes, mappings))
     })
     .await?
     .into_dto()?;
     Ok(HttpResponse::Ok().json(guild))
 }
 
-async fn get_guild(guild_id: Path<String>, claims: Claims, db: Data<Pool>) -> HttpResult {
-    debug!(?guild_id, uid = %claims.uid, "get guild");
-
-    if !claims.uid.is_nil() {
-        return Err(ServiceErr::Unauthorized("bot-only"));
+/// Validates that the linked discord user is allowed to change the settings of the class of the guild
+fn check_guild_admin(db: &Pool, guild_id: &str, snowflake: &str) -> Result<(), ServiceErr> {
+    let class = actions::class::get_class_by_discord(db, guild_id)?;
+    let role = actions::user::get_user_by_discord(db, snowflake)
+        .and_then(|user| member_role(db, user.id, class.id))
+        .map_err(|err| match err {
+            ServiceErr::NotFound => ServiceErr::Unauthorized(ErrorCode::NoAccess),
+            err => err,
+        })?;
+    if !role.has_rights() {
+        return Err(ServiceErr::NoAdminPermissions);
     }
+    Ok(())
+}
 
-    let guild = block(move || actions::class::get_guild_settings(&db, &guild_id))
-        .await?
-        .into_dto()?;
+async fn get_guild(guild_id: Path<String>, bot: Bot<scopes::Guilds>, db: Data<Pool>) -> HttpResult {
+    debug!(key = %bot.name, ?guild_id, "get guild");
+
+    let guild = block::<_, _, ServiceErr>(move || {
+        let guild = actions::class::get_guild_settings(&db, &guild_id)?;
+        let routes = actions::class::get_notif_routes(&db, &guild_id)?;
+        let mappings = actions::class::get_role_mappings(&db, &guild_id)?;
+        Ok((guild, routes, mappings))
+    })
+    .await?
+    .into_dto()?;
     Ok(HttpResponse::Ok().json(guild))
 }
+
+async fn get_guild_members(
+    guild_id: Path<String>,
+    bot: Bot<scopes::Guilds>,
+    db: Data<Pool>,
+) -> HttpResult {
+    debug!(key = %bot.name, ?guild_id, "get guild members");
+
+    let members: Vec<dto::DiscordMember> =
+        block(move || actions::class::get_discord_members(&db, &guild_id))
+            .await?
+            .into_dto()?;
+    Ok(HttpResponse::Ok().json(members))
+}
+
+/// Routes that don't override anything are not stored
+fn new_notif_routes(guild: &Guild) -> Vec<NewNotifRoute<'_>> {
+    guild
+        .notif_routes
+        .iter()
+        .filter(|route| {
+            route.channel.is_some() || route.role_ping.is_some() || route.everyone_ping.is_some()
+        })
+        .map(|route| NewNotifRoute {
+            guild: &guild.id,
+            e_type: route.r#type as i32,
+            notif_channel: route.channel.as_deref(),
+            notif_ping_role: route.role_ping.as_deref(),
+            notif_ping_everyone: route.everyone_ping,
+        })
+        .collect()
+}
+
+fn new_role_mappings(guild: &Guild) -> Vec<NewRoleMapping<'_>> {
+    guild
+        .role_mappings
+        .iter()
+        .map(|mapping| NewRoleMapping {
+            guild: &guild.id,
+            member_role: mapping.role as i32,
+            discord_role: &mapping.discord_role,
+        })
+        .collect()
+}
+
+/// The timetable of the class, if it has one
+pub(super) fn get_optional_timetable(
+    db: &Pool,
+    class_id: Uuid,
+) -> Result<Option<Timetable>, ServiceErr> {
+    match actions::class::get_timetable(db, class_id) {
+        Ok(timetable) => serde_json::from_str(&timetable.timetable)
+            .map(Some)
+            .map_err(|_| ServiceErr::InternalServerError("deserialize-timetable".to_string())),
+        Err(ServiceErr::NotFound) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn validate_guild(guild: &Guild) -> Result<(), ServiceErr> {
+    if let Some(language) = &guild.language {
+        validate_language(language)?;
+    }
+    validate_role_mappings(&guild.role_mappings)?;
+    if let Some(channel) = &guild.admin_channel {
+        channel
+            .parse::<u64>()
+            .map_err(|_| ServiceErr::BadRequest(ErrorCode::InvalidSnowflake))?;
+    }
+    Ok(())
+}
+
+/// Only the roles of actual members can be mapped, every role at most once
+fn validate_role_mappings(mappings: &[dto::RoleMapping]) -> Result<(), ServiceErr> {
+    for (index, mapping) in mappings.iter().enumerate() {
+        let mappable = matches!(
+            mapping.role,
+            MemberRole::Owner | MemberRole::Admin | MemberRole::Member
+        );
+        let duplicate = mappings[..index]
+            .iter()
+            .any(|other| other.role == mapping.role);
+        if !mappable || duplicate || mapping.discord_role.parse::<u64>().is_err() {
+            return Err(ServiceErr::BadRequest(ErrorCode::InvalidRoleMapping));
+        }
+    }
+    Ok(())
+}
+
+fn validate_timezone(timezone: &str) -> Result<(), ServiceErr> {
+    timezone
+        .parse::<chrono_tz::Tz>()
+        .map(|_| ())
+        .map_err(|_| ServiceErr::BadRequest(ErrorCode::InvalidTimezone))
+}
+
+/// The start and the end of a day in the timezone of a class, in UTC
+///
+/// Days that start with a daylight saving time change start at the first local time that exists.
+fn day_bounds(
+    timezone: &str,
+    day: NaiveDate,
+) -> Result<(NaiveDateTime, NaiveDateTime), ServiceErr> {
+    let tz = timezone
+        .parse::<chrono_tz::Tz>()
+        .map_err(|_| ServiceErr::BadRequest(ErrorCode::InvalidTimezone))?;
+    let start_of = |day: NaiveDate| {
+        (0..24)
+            .find_map(|hour| tz.from_local_datetime(&day.and_hms(hour, 0, 0)).earliest())
+            .map(|start| start.naive_utc())
+            .ok_or(ServiceErr::BadRequest(ErrorCode::InvalidDay))
+    };
+    Ok((start_of(day)?, start_of(day.succ())?))
+}
+
+fn validate_language(language: &str) -> Result<(), ServiceErr> {
+    if dto::LANGUAGES.contains(&language) {
+        Ok(())
+    } else {
+        Err(ServiceErr::BadRequest(ErrorCode::InvalidLanguage))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::day_bounds;
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn utc(time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    #[test]
+    fn days_in_the_class_timezone() {
+        let day = |day| NaiveDate::parse_from_str(day, "%Y-%m-%d").unwrap();
+
+        let winter = day_bounds("Europe/Zurich", day("2021-01-04")).unwrap();
+        assert_eq!(winter, (utc("2021-01-03 23:00"), utc("2021-01-04 23:00")));
+        let summer = day_bounds("Europe/Zurich", day("2021-08-05")).unwrap();
+        assert_eq!(summer, (utc("2021-08-04 22:00"), utc("2021-08-05 22:00")));
+        // the day of the change to summer time has 23 hours
+        let change = day_bounds("Europe/Zurich", day("2021-03-28")).unwrap();
+        assert_eq!(change, (utc("2021-03-27 23:00"), utc("2021-03-28 22:00")));
+        // midnight doesn't exist in Santiago when the summer time starts
+        let no_midnight = day_bounds("America/Santiago", day("2021-09-05")).unwrap();
+        assert_eq!(
+            no_midnight,
+            (utc("2021-09-05 04:00"), utc("2021-09-06 03:00"))
+        );
+
+        assert!(day_bounds("Mars/Olympus_Mons", day("2021-08-05")).is_err());
+    }
+}
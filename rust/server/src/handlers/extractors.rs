@@ -1,20 +1,187 @@
+use crate::actions::access_token::ACCESS_TOKEN_PREFIX;
+use crate::actions::api_key::API_KEY_PREFIX;
 use crate::actions::Pool;
 use crate::error::ServiceErr;
 use crate::handlers::auth::Claims;
 use crate::models::conversion::IntoDto;
+use crate::models::{AccessTokenScope, ApiKey};
 use actix_web::dev::Payload;
+use actix_web::http::header::Header;
+use actix_web::http::Method;
 use actix_web::{web, FromRequest, HttpRequest};
+use actix_web_httpauth::headers::authorization::{Authorization, Bearer};
+use dto::ErrorCode;
 use dto::MemberRole;
+use std::fmt::{Display, Formatter};
 use std::future;
 use std::future::Future;
+use std::marker::PhantomData;
 use std::ops::Deref;
 use std::pin::Pin;
+use std::str::FromStr;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// The header the bot uses to act on behalf of a linked discord user
+pub const DISCORD_USER_HEADER: &str = "Discord-User";
+
+/// What an api key is allowed to do
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ApiScope {
+    /// Get the notifications and join requests
+    Notifications,
+    /// Read and change the guild settings and the classes linked with guilds
+    Guilds,
+    /// Find, link and unlink users by their discord account and act on their behalf
+    UsersLookup,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 3] = [
+        ApiScope::Notifications,
+        ApiScope::Guilds,
+        ApiScope::UsersLookup,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ApiScope::Notifications => "bot:notifications",
+            ApiScope::Guilds => "bot:guilds",
+            ApiScope::UsersLookup => "bot:users-lookup",
+        }
+    }
+}
+
+impl Display for ApiScope {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ApiScope {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|scope| scope.as_str() == s)
+            .ok_or(())
+    }
+}
+
+/// A scope that a [`Bot`] extractor requires
+pub trait Scope {
+    const SCOPE: ApiScope;
+}
+
+pub mod scopes {
+    use super::{ApiScope, Scope};
+
+    #[derive(Debug)]
+    pub struct Notifications;
+    #[derive(Debug)]
+    pub struct Guilds;
+    #[derive(Debug)]
+    pub struct UsersLookup;
+
+    impl Scope for Notifications {
+        const SCOPE: ApiScope = ApiScope::Notifications;
+    }
+    impl Scope for Guilds {
+        const SCOPE: ApiScope = ApiScope::Guilds;
+    }
+    impl Scope for UsersLookup {
+        const SCOPE: ApiScope = ApiScope::UsersLookup;
+    }
+}
+
+/// Extract an api key that has the scope `S`
+///
+/// Routes with this extractor can only be used by the bot or other services, not by users
+#[derive(Debug)]
+pub struct Bot<S> {
+    /// The name of the key
+    pub name: String,
+    /// The snowflake of the `Discord-User` header, needs the `bot:users-lookup` scope
+    pub discord_user: Option<String>,
+    scope: PhantomData<S>,
+}
+
+impl<S: Scope> FromRequest for Bot<S> {
+    type Error = ServiceErr;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let key = api_key_from_request(req);
+        let discord_user = discord_user_from_request(req);
+
+        Box::pin(async move {
+            let key = key
+                .ok_or(ServiceErr::Unauthorized(ErrorCode::BotOnly))?
+                .await?;
+            if !has_scope(&key, S::SCOPE) {
+                return Err(ServiceErr::Unauthorized(ErrorCode::MissingScope));
+            }
+            let discord_user = discord_user?;
+            if discord_user.is_some() && !has_scope(&key, ApiScope::UsersLookup) {
+                return Err(ServiceErr::Unauthorized(ErrorCode::MissingScope));
+            }
+            Ok(Bot {
+                name: key.name,
+                discord_user,
+                scope: PhantomData,
+            })
+        })
+    }
+}
+
+/// The snowflake of the `Discord-User` header, if the request has one
+fn discord_user_from_request(req: &HttpRequest) -> Result<Option<String>, ServiceErr> {
+    req.headers()
+        .get(DISCORD_USER_HEADER)
+        .map(|value| value.to_str().map(str::to_string))
+        .transpose()
+        .map_err(|_| ServiceErr::BadRequest(ErrorCode::InvalidDiscordUser))
+}
+
+fn has_scope(key: &ApiKey, scope: ApiScope) -> bool {
+    key.scopes.iter().any(|s| s == scope.as_str())
+}
+
+/// Looks up the api key of the request, `None` if the request doesn't use an api key
+fn api_key_from_request(
+    req: &HttpRequest,
+) -> Option<impl Future<Output = Result<ApiKey, ServiceErr>>> {
+    let auth = Authorization::<Bearer>::parse(req).ok()?;
+    let key = auth.into_scheme().token().to_string();
+    if !key.starts_with(API_KEY_PREFIX) {
+        return None;
+    }
+
+    let db = req
+        .app_data::<web::Data<Pool>>()
+        .expect("db pool in app data")
+        .clone();
+
+    Some(async move {
+        web::block(move || crate::actions::api_key::get_api_key(&db, &key))
+            .await
+            .map_err(|err| match ServiceErr::from(err) {
+                ServiceErr::NotFound => ServiceErr::Unauthorized(ErrorCode::InvalidApiKey),
+                err => err,
+            })
+    })
+}
+
 /// Extract the role of a member in a class
 /// - Validate that a user belongs to a class
-/// Also makes sure that a user is logged in
+///
+/// Also makes sure that a user is logged in.
+/// Api keys with the `bot:guilds` scope have access to all classes. If the bot sends the
+/// `Discord-User` header, the role of that linked user is used instead, which needs the
+/// `bot:users-lookup` scope as well
 #[derive(Debug, Clone)]
 pub struct Role(pub MemberRole);
 
@@ -26,13 +193,77 @@ impl Deref for Role {
     }
 }
 
+/// Also accepts personal access tokens, see [`check_token_access`]
 impl FromRequest for Claims {
     type Error = actix_web::Error;
-    type Future = future::Ready<Result<Self, Self::Error>>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
     type Config = ();
 
     fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
-        future::ready(Self::from_request_sync(req).map_err(|err| err.into()))
+        let claims = claims_from_request(req);
+        Box::pin(async move { claims.await.map_err(|err| err.into()) })
+    }
+}
+
+fn claims_from_request(
+    req: &HttpRequest,
+) -> Pin<Box<dyn Future<Output = Result<Claims, ServiceErr>>>> {
+    let token = match Authorization::<Bearer>::parse(req) {
+        Ok(auth) if auth.as_ref().token().starts_with(ACCESS_TOKEN_PREFIX) => {
+            auth.into_scheme().token().to_string()
+        }
+        _ => return Box::pin(future::ready(Claims::from_request_sync(req))),
+    };
+
+    let db = req
+        .app_data::<web::Data<Pool>>()
+        .expect("db pool in app data")
+        .clone();
+    let class_id = req
+        .match_info()
+        .get("classid")
+        .and_then(|id| Uuid::parse_str(id).ok());
+    let method = req.method().clone();
+
+    Box::pin(async move {
+        let (token, scopes) =
+            web::block(move || crate::actions::access_token::get_access_token(&db, &token))
+                .await
+                .map_err(|err| match ServiceErr::from(err) {
+                    ServiceErr::NotFound => ServiceErr::Unauthorized(ErrorCode::InvalidToken),
+                    err => err,
+                })?;
+        check_token_access(&scopes, class_id, &method)?;
+
+        Ok(Claims {
+            exp: token
+                .expires
+                .map_or(i64::MAX, |expires| expires.timestamp()),
+            uid: token.user_id,
+            refresh: false,
+            version: 0,
+            session: None,
+            generation: 0,
+        })
+    })
+}
+
+/// Personal access tokens can only read, except in the classes that they can write to.
+/// In classes, they need a scope for the class
+fn check_token_access(
+    scopes: &[AccessTokenScope],
+    class_id: Option<Uuid>,
+    method: &Method,
+) -> Result<(), ServiceErr> {
+    let read_only = *method == Method::GET;
+    match class_id {
+        Some(class_id) => match scopes.iter().find(|scope| scope.class == class_id) {
+            Some(scope) if scope.write || read_only => Ok(()),
+            Some(_) => Err(ServiceErr::Unauthorized(ErrorCode::TokenReadOnly)),
+            None => Err(ServiceErr::Unauthorized(ErrorCode::TokenNoAccess)),
+        },
+        None if read_only => Ok(()),
+        None => Err(ServiceErr::Unauthorized(ErrorCode::TokenReadOnly)),
     }
 }
 
@@ -51,43 +282,208 @@ impl FromRequest for Role {
         let class_id = req
             .match_info()
             .get("classid")
-            .ok_or(ServiceErr::BadRequest("no-class-id"))
+            .ok_or(ServiceErr::BadRequest(ErrorCode::NoClassId))
             .and_then(|id| uuid::Uuid::parse_str(id).map_err(|e| e.into()));
 
-        let claims = Claims::from_request_sync(req);
+        let auth = match api_key_from_request(req) {
+            Some(key) => Auth::ApiKey(Box::pin(key)),
+            None => Auth::User(claims_from_request(req)),
+        };
+        let discord_user = discord_user_from_request(req);
 
         Box::pin(async move {
-            get_member_role(db, class_id, claims)
+            get_member_role(db, class_id, auth, discord_user)
                 .await
                 .map_err(|err| match err {
-                    ServiceErr::NotFound => ServiceErr::Unauthorized("no-access"),
+                    ServiceErr::NotFound => ServiceErr::Unauthorized(ErrorCode::NoAccess),
                     err => err,
                 })
         })
     }
 }
 
+/// Validates that the class of the route is not archived, archived classes can only be read
+///
+/// Put it after [`Role`], so that users without access get the same error for archived classes.
+#[derive(Debug, Clone)]
+pub struct NotArchived;
+
+impl FromRequest for NotArchived {
+    type Error = ServiceErr;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+    type Config = ();
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let db = req
+            .app_data::<web::Data<Pool>>()
+            .expect("db pool in app data")
+            .clone()
+            .into_inner();
+
+        let class_id = req
+            .match_info()
+            .get("classid")
+            .ok_or(ServiceErr::BadRequest(ErrorCode::NoClassId))
+            .and_then(|id| uuid::Uuid::parse_str(id).map_err(|e| e.into()));
+
+        Box::pin(async move {
+            let class_id = class_id?;
+            match web::block(move || crate::actions::class::is_archived(&db, class_id)).await? {
+                true => Err(ServiceErr::Forbidden(ErrorCode::ClassArchived)),
+                false => Ok(NotArchived),
+            }
+        })
+    }
+}
+
+/// How the request is authenticated
+enum Auth {
+    User(Pin<Box<dyn Future<Output = Result<Claims, ServiceErr>>>>),
+    ApiKey(Pin<Box<dyn Future<Output = Result<ApiKey, ServiceErr>>>>),
+}
+
 async fn get_member_role(
     db: Arc<Pool>,
     class_id: Result<Uuid, ServiceErr>,
-    claims: Result<Claims, ServiceErr>,
+    auth: Auth,
+    discord_user: Result<Option<String>, ServiceErr>,
 ) -> Result<Role, ServiceErr> {
-    let claims = claims?;
-    Ok(Role(if claims.uid.is_nil() {
+    let (uid, discord_user) = match auth {
+        Auth::User(claims) => (Some(claims.await?.uid), None),
+        Auth::ApiKey(key) => {
+            let key = key.await?;
+            if !has_scope(&key, ApiScope::Guilds) {
+                return Err(ServiceErr::Unauthorized(ErrorCode::MissingScope));
+            }
+            let discord_user = discord_user?;
+            if discord_user.is_some() && !has_scope(&key, ApiScope::UsersLookup) {
+                return Err(ServiceErr::Unauthorized(ErrorCode::MissingScope));
+            }
+            (None, discord_user)
+        }
+    };
+
+    Ok(Role(if uid.is_none() && discord_user.is_none() {
         MemberRole::CORS
     } else {
-        let role =
-            web::block(move || crate::actions::class::get_member(&db, claims.uid, class_id?))
-                .await?
-                .0
-                .role
-                .into_dto()?;
-        if let MemberRole::Banned = role {
-            return Err(ServiceErr::Unauthorized("banned"));
-        }
-        if MemberRole::Member < role {
-            return Err(ServiceErr::Unauthorized("no-access"));
-        }
-        role
+        web::block(move || {
+            let uid = match (uid, discord_user) {
+                (Some(uid), _) => uid,
+                (None, Some(snowflake)) => {
+                    crate::actions::user::get_user_by_discord(&db, &snowflake)?.id
+                }
+                (None, None) => unreachable!(),
+            };
+            member_role(&db, uid, class_id?)
+        })
+        .await?
     }))
 }
+
+/// The role of a user in a class, with the same checks as [`Role`]
+///
+/// Banned and pending members have no access, and in classes that require two-factor
+/// authentication, members with rights need to have it enabled.
+pub fn member_role(db: &Pool, uid: Uuid, class_id: Uuid) -> Result<MemberRole, ServiceErr> {
+    let (member, _) = crate::actions::class::get_member(db, uid, class_id)?;
+
+    // classes can require two-factor authentication for everyone with rights
+    if member.role <= crate::models::MemberRole::ADMIN
+        && crate::actions::class::is_two_factor_required(db, class_id)?
+        && !crate::actions::two_factor::is_two_factor_enabled(db, uid)?
+    {
+        return Err(ServiceErr::Unauthorized(ErrorCode::TwoFactorRequired));
+    }
+
+    let role = member.role.into_dto()?;
+    if let MemberRole::Banned = role {
+        return Err(ServiceErr::Unauthorized(ErrorCode::Banned));
+    }
+    if MemberRole::Member < role {
+        return Err(ServiceErr::Unauthorized(ErrorCode::NoAccess));
+    }
+    Ok(role)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_token_access, scopes, Bot};
+    use crate::error::ServiceErr;
+    use crate::models::AccessTokenScope;
+    use actix_web::dev::Payload;
+    use actix_web::http::Method;
+    use actix_web::test::TestRequest;
+    use actix_web::{web, FromRequest};
+    use diesel::r2d2::ConnectionManager;
+    use diesel::PgConnection;
+    use dto::ErrorCode;
+
+    #[actix_rt::test]
+    async fn api_key_scopes() {
+        dotenv::dotenv().ok();
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let db = r2d2::Pool::builder()
+            .build(ConnectionManager::<PgConnection>::new(database_url))
+            .expect("Failed to create pool.");
+
+        let name = uuid::Uuid::new_v4().to_string();
+        let (_, key) =
+            crate::actions::api_key::create_api_key(&db, &name, vec!["bot:guilds".to_string()])
+                .unwrap();
+
+        let req = TestRequest::with_header("Authorization", format!("Bearer {}", key))
+            .app_data(web::Data::new(db.clone()))
+            .to_http_request();
+
+        let bot = Bot::<scopes::Guilds>::from_request(&req, &mut Payload::None)
+            .await
+            .unwrap();
+        assert_eq!(bot.name, name);
+        assert!(matches!(
+            Bot::<scopes::Notifications>::from_request(&req, &mut Payload::None).await,
+            Err(ServiceErr::Unauthorized(ErrorCode::MissingScope))
+        ));
+
+        // acting on behalf of a discord user needs the users lookup scope
+        let req = TestRequest::with_header("Authorization", format!("Bearer {}", key))
+            .header(super::DISCORD_USER_HEADER, "1234")
+            .app_data(web::Data::new(db.clone()))
+            .to_http_request();
+        assert!(matches!(
+            Bot::<scopes::Guilds>::from_request(&req, &mut Payload::None).await,
+            Err(ServiceErr::Unauthorized(ErrorCode::MissingScope))
+        ));
+
+        crate::actions::api_key::delete_api_key(&db, &name).unwrap();
+        assert!(matches!(
+            Bot::<scopes::Guilds>::from_request(&req, &mut Payload::None).await,
+            Err(ServiceErr::Unauthorized(ErrorCode::InvalidApiKey))
+        ));
+    }
+
+    #[test]
+    fn access_token_scopes() {
+        let read = uuid::Uuid::new_v4();
+        let write = uuid::Uuid::new_v4();
+        let scopes = [
+            AccessTokenScope {
+                token: uuid::Uuid::nil(),
+                class: read,
+                write: false,
+            },
+            AccessTokenScope {
+                token: uuid::Uuid::nil(),
+                class: write,
+                write: true,
+            },
+        ];
+
+        assert!(check_token_access(&scopes, Some(read), &Method::GET).is_ok());
+        assert!(check_token_access(&scopes, Some(read), &Method::POST).is_err());
+        assert!(check_token_access(&scopes, Some(write), &Method::PUT).is_ok());
+        assert!(check_token_access(&scopes, Some(uuid::Uuid::new_v4()), &Method::GET).is_err());
+        // outside of classes, tokens can only read
+        assert!(check_token_access(&scopes, None, &Method::GET).is_ok());
+        assert!(check_token_access(&scopes, None, &Method::DELETE).is_err());
+    }
+}
@@ -1,19 +1,39 @@
 use crate::actions::{self, Pool};
 use crate::error::ServiceErr;
-use crate::handlers::auth::{change_password, create_user, Claims};
+use crate::handlers::auth::{
+    change_password, create_user, send_verification_mail, validate_email, Claims,
+};
+use crate::handlers::extractors::{scopes, Bot};
+use crate::mail::Mailer;
+use crate::models;
 use crate::models::conversion::IntoDto;
-use actix_web::web::{block, delete, get, post, put, scope, Data, Json, Path, Query};
+use crate::rate_limit::RateLimit;
+use actix_web::web::{block, delete, get, post, put, resource, scope, Data, Json, Path, Query};
 use actix_web::web::{patch, ServiceConfig};
 use actix_web::HttpResponse;
-use dto::{NotificationQueryParams, NotificationRes, SingleSnowflake, User};
-use tracing::debug;
+use chrono::Utc;
+use dto::{
+    AccessTokenPostResponse, ClassData, DeleteUserRequest, DiscordLinkRequest, ErrorCode,
+    Membership, NotificationQueryParams, NotificationRes, OwnedClassDeletion, OwnedClassesAction,
+    PostAccessToken, SingleSnowflake, User, UserExport, UserLinkRequest, USER_EXPORT_VERSION,
+};
+use tracing::{debug, error};
+use uuid::Uuid;
 
 mod auth;
 mod class;
-mod extractors;
+pub mod extractors;
+pub mod oauth;
+mod two_factor;
+
+pub use auth::{jwks, EmailVerification};
 
 pub type HttpResult = Result<HttpResponse, ServiceErr>;
 
+/// How long a code to link a user with discord can be used
+const LINK_CODE_VALIDITY_MINUTES: i64 = 10;
+const MAX_ACCESS_TOKEN_NAME_LENGTH: usize = 50;
+
 pub fn config(cfg: &mut ServiceConfig) {
     other_config(cfg);
     class::class_config(cfg);
@@ -23,15 +43,58 @@ pub fn config(cfg: &mut ServiceConfig) {
 pub fn other_config(cfg: &mut ServiceConfig) {
     cfg.route("/hugo", get().to(get_hugo))
         .route("/bot/notifications", get().to(get_notifications))
+        .route("/bot/link-codes", post().to(create_discord_link_code))
+        .route("/bot/link", post().to(link_discord_with_user))
         .service(
             scope("/users")
-                .route("", post().to(create_user))
+                .service(resource("").wrap(RateLimit).route(post().to(create_user)))
                 .route("/me", get().to(get_own_user))
                 .route("/me", put().to(edit_own_user))
                 .route("/me", delete().to(delete_own_user))
-                .route("/me/password", patch().to(change_password))
+                .route("/me/export", get().to(export_own_user))
+                .route("/me/deletion", get().to(get_own_user_deletion))
+                .service(
+                    resource("/me/password")
+                        .wrap(RateLimit)
+                        .route(patch().to(change_password)),
+                )
+                .service(
+                    resource("/me/verify-email")
+                        .wrap(RateLimit)
+                        .route(post().to(resend_verification_mail)),
+                )
+                .service(
+                    resource("/me/2fa")
+                        .wrap(RateLimit)
+                        .route(post().to(two_factor::enroll_two_factor))
+                        .route(delete().to(two_factor::disable_two_factor)),
+                )
+                .service(
+                    resource("/me/2fa/verify")
+                        .wrap(RateLimit)
+                        .route(post().to(two_factor::verify_two_factor)),
+                )
+                .service(
+                    resource("/me/2fa/recovery-codes")
+                        .wrap(RateLimit)
+                        .route(post().to(two_factor::regenerate_recovery_codes)),
+                )
                 .route("/me/link", post().to(link_user_with_discord))
-                .route("/discord/{snowflake}", get().to(get_user_by_discord)),
+                .route("/me/link", delete().to(unlink_own_user))
+                .route("/me/link-codes", post().to(create_user_link_code))
+                .route("/me/tokens", get().to(get_access_tokens))
+                .route("/me/tokens", post().to(create_access_token))
+                .route("/me/tokens/{uuid}", delete().to(delete_access_token))
+                .route("/me/sessions", get().to(get_sessions))
+                .route("/me/sessions/{uuid}", delete().to(delete_session))
+                .route("/me/invites", get().to(get_invites))
+                .route("/me/invites/{classid}", post().to(accept_invite))
+                .route("/me/invites/{classid}", delete().to(decline_invite))
+                .route("/discord/{snowflake}", get().to(get_user_by_discord))
+                .route(
+                    "/discord/{snowflake}/link",
+                    delete().to(unlink_discord_user),
+                ),
         );
 }
 
@@ -47,9 +110,13 @@ async fn get_own_user(claims: Claims, db: Data<Pool>) -> HttpResult {
     let (mut user, classes) = block::<_, _, ServiceErr>(move || {
         let user = actions::user::get_user_by_id(&db, claims.uid)?;
         let classes = actions::class::get_classes_by_user(&db, claims.uid)?;
+        let two_factor_enabled = actions::two_factor::is_two_factor_enabled(&db, claims.uid)?;
 
         Ok((
-            user.into_dto()?,
+            dto::User {
+                two_factor_enabled,
+                ..user.into_dto()?
+            },
             classes
                 .into_iter()
                 .map(IntoDto::into_dto)
@@ -63,54 +130,459 @@ async fn get_own_user(claims: Claims, db: Data<Pool>) -> HttpResult {
     Ok(HttpResponse::Ok().json(user))
 }
 
-async fn edit_own_user(claims: Claims, db: Data<Pool>, mut new_user: Json<User>) -> HttpResult {
+async fn edit_own_user(
+    claims: Claims,
+    db: Data<Pool>,
+    mailer: Data<Mailer>,
+    mut new_user: Json<User>,
+) -> HttpResult {
     debug!(uid = %claims.uid, ?new_user, "edit own user");
+    validate_email(&new_user.email)?;
 
     new_user.id = claims.uid; // always update the own user
-    let user = block(move || actions::user::update_user(&db, new_user.into_inner().into()))
-        .await?
-        .into_dto()?;
+    let user = block::<_, _, ServiceErr>(move || {
+        let old = actions::user::get_user_by_id(&db, claims.uid)?;
+        let user = actions::user::update_user(&db, new_user.into_inner().into())?;
+        // a new email has to be verified again, the change is stored even if the mail fails
+        if user.email != old.email {
+            if let Err(err) = send_verification_mail(&db, &mailer, &user) {
+                error!(%err, uid = %user.id, "could not send the verification mail");
+            }
+        }
+        Ok(user)
+    })
+    .await?
+    .into_dto()?;
 
     Ok(HttpResponse::Ok().json(user))
 }
 
-async fn delete_own_user(claims: Claims, db: Data<Pool>) -> HttpResult {
-    debug!(uid = %claims.uid, "delete own user 😔 rip");
+async fn resend_verification_mail(
+    claims: Claims,
+    db: Data<Pool>,
+    mailer: Data<Mailer>,
+) -> HttpResult {
+    debug!(uid = %claims.uid, "resend verification mail");
+
+    block(move || {
+        let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        if user.email_verified {
+            return Err(ServiceErr::BadRequest(ErrorCode::AlreadyVerified));
+        }
+        send_verification_mail(&db, &mailer, &user)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().body("Sent verification mail."))
+}
 
-    let amount = block(move || actions::user::delete_user(&db, claims.uid)).await?;
+/// Returns all data of the own user as a json download
+///
+/// Only with the token of a login, personal access tokens would get classes outside of their scopes
+async fn export_own_user(claims: Claims, db: Data<Pool>) -> HttpResult {
+    debug!(uid = %claims.uid, "export own user");
 
-    Ok(match amount {
-        0 => HttpResponse::NotFound().body("User not found"),
-        1 => HttpResponse::Ok().body("Deleted user."),
-        _ => unreachable!(),
+    let session = claims.session;
+    if session.is_none() {
+        return Err(ServiceErr::Unauthorized(ErrorCode::WrongTokenKind));
+    }
+    let mut export = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        let two_factor_enabled = actions::two_factor::is_two_factor_enabled(&db, claims.uid)?;
+        let memberships = actions::class::get_memberships(&db, claims.uid)?
+            .into_iter()
+            .map(|(class, member)| {
+                Ok(Membership {
+                    class: class.id,
+                    class_name: class.name,
+                    display_name: member.display_name,
+                    role: member.role.into_dto()?,
+                })
+            })
+            .collect::<Result<_, ServiceErr>>()?;
+        let owned_classes = actions::class::get_owned_classes(&db, claims.uid)?
+            .into_iter()
+            .map(|class| class_data(&db, class))
+            .collect::<Result<_, _>>()?;
+
+        Ok(UserExport {
+            version: USER_EXPORT_VERSION,
+            exported: Utc::now().timestamp_millis(),
+            discord_id: user.discord_id.clone(),
+            user: dto::User {
+                two_factor_enabled,
+                ..user.into_dto()?
+            },
+            memberships,
+            owned_classes,
+            access_tokens: actions::access_token::get_access_tokens(&db, claims.uid)?.into_dto()?,
+            sessions: actions::session::get_sessions(&db, claims.uid)?.into_dto()?,
+        })
+    })
+    .await?;
+    for exported in &mut export.sessions {
+        exported.current = session == Some(exported.id);
+    }
+
+    Ok(HttpResponse::Ok()
+        .header(
+            "content-disposition",
+            "attachment; filename=\"cors-export.json\"",
+        )
+        .json(export))
+}
+
+/// The class with all members, its events and its timetable
+fn class_data(db: &Pool, class: models::Class) -> Result<ClassData, ServiceErr> {
+    let members = actions::class::get_all_members(db, class.id)?;
+    let events = actions::event::get_events_by_class(db, class.id)?.into_dto()?;
+    let timetable = class::get_optional_timetable(db, class.id)?;
+
+    Ok(ClassData {
+        class: (class, members).into_dto()?,
+        events,
+        timetable,
     })
 }
 
+async fn get_own_user_deletion(claims: Claims, db: Data<Pool>) -> HttpResult {
+    debug!(uid = %claims.uid, "get own user deletion");
+
+    let deletions = block(move || actions::class::get_owned_class_deletions(&db, claims.uid))
+        .await?
+        .into_iter()
+        .map(|(class, next_owner)| {
+            Ok(OwnedClassDeletion {
+                class: class.id,
+                name: class.name,
+                next_owner: next_owner.map(IntoDto::into_dto).transpose()?,
+            })
+        })
+        .collect::<Result<Vec<_>, ServiceErr>>()?;
+
+    Ok(HttpResponse::Ok().json(deletions))
+}
+
+/// Users that own classes have to confirm what happens to them, see `GET /users/me/deletion`
+async fn delete_own_user(
+    claims: Claims,
+    db: Data<Pool>,
+    request: Option<Json<DeleteUserRequest>>,
+) -> HttpResult {
+    let request = request.map(Json::into_inner).unwrap_or_default();
+    debug!(uid = %claims.uid, ?request, "delete own user 😔 rip");
+
+    let amount = block(move || {
+        let owned = actions::class::get_owned_class_deletions(&db, claims.uid)?;
+
+        let mut owned_ids = owned.iter().map(|(class, _)| class.id).collect::<Vec<_>>();
+        let DeleteUserRequest {
+            owned_classes,
+            mut confirm,
+        } = request;
+        owned_ids.sort();
+        confirm.sort();
+        confirm.dedup();
+        if owned_ids != confirm {
+            return Err(ServiceErr::Conflict(ErrorCode::DeletionNotConfirmed));
+        }
+
+        let owned = owned
+            .into_iter()
+            .map(|(class, next_owner)| match owned_classes {
+                OwnedClassesAction::Transfer => {
+                    (class.id, next_owner.map(|(member, _)| member.user))
+                }
+                OwnedClassesAction::Delete => (class.id, None),
+            })
+            .collect::<Vec<_>>();
+        actions::user::delete_user_with_classes(&db, claims.uid, &owned)
+    })
+    .await?;
+
+    match amount {
+        0 => Err(ServiceErr::NotFound),
+        1 => Ok(HttpResponse::Ok().body("Deleted user.")),
+        _ => unreachable!(),
+    }
+}
+
 async fn link_user_with_discord(
     claims: Claims,
     db: Data<Pool>,
-    id: Json<SingleSnowflake>,
+    request: Json<UserLinkRequest>,
 ) -> HttpResult {
-    debug!(uid = %claims.uid, ?id, "link own user with discord");
+    debug!(uid = %claims.uid, "link own user with discord");
 
-    let snowflake = id.into_inner().snowflake;
-    snowflake
-        .parse::<u64>()
-        .map_err(|_| ServiceErr::BadRequest("invalid-snowflake"))?;
+    let user =
+        block(move || actions::user::redeem_link_code(&db, &request.code, Some(claims.uid), None))
+            .await?
+            .into_dto()?;
+
+    Ok(HttpResponse::Ok().json(user))
+}
 
-    let user = block(move || actions::user::set_discord_id_user(&db, claims.uid, Some(&snowflake)))
+async fn unlink_own_user(claims: Claims, db: Data<Pool>) -> HttpResult {
+    debug!(uid = %claims.uid, "unlink own user from discord");
+
+    let user = block(move || actions::user::set_discord_id_user(&db, claims.uid, None))
         .await?
         .into_dto()?;
 
     Ok(HttpResponse::Ok().json(user))
 }
 
-async fn get_user_by_discord(user_id: Path<String>, db: Data<Pool>, claims: Claims) -> HttpResult {
-    debug!(uid = %claims.uid, ?user_id, "get user by discord");
+async fn create_user_link_code(claims: Claims, db: Data<Pool>) -> HttpResult {
+    debug!(uid = %claims.uid, "create link code for user");
+
+    let code = block(move || {
+        actions::user::create_link_code(
+            &db,
+            Some(claims.uid),
+            None,
+            chrono::Duration::minutes(LINK_CODE_VALIDITY_MINUTES),
+        )
+    })
+    .await?
+    .into_dto()?;
+
+    Ok(HttpResponse::Created().json(code))
+}
+
+async fn get_access_tokens(claims: Claims, db: Data<Pool>) -> HttpResult {
+    debug!(uid = %claims.uid, "get access tokens");
+
+    let tokens: Vec<dto::AccessToken> =
+        block(move || actions::access_token::get_access_tokens(&db, claims.uid))
+            .await?
+            .into_dto()?;
 
-    if !claims.uid.is_nil() {
-        return Err(ServiceErr::Unauthorized("bot-only"));
+    Ok(HttpResponse::Ok().json(tokens))
+}
+
+async fn create_access_token(
+    claims: Claims,
+    db: Data<Pool>,
+    token: Json<PostAccessToken>,
+) -> HttpResult {
+    debug!(uid = %claims.uid, ?token, "create access token");
+
+    let name = token.name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_ACCESS_TOKEN_NAME_LENGTH {
+        return Err(ServiceErr::BadRequest(ErrorCode::InvalidTokenName));
     }
+    let expires = match token.expires {
+        Some(expires) if expires <= chrono::Utc::now().timestamp_millis() => {
+            return Err(ServiceErr::BadRequest(ErrorCode::InvalidExpiry))
+        }
+        Some(expires) => Some(
+            chrono::NaiveDateTime::from_timestamp_opt(
+                expires.div_euclid(1000),
+                expires.rem_euclid(1000) as u32 * 1_000_000,
+            )
+            .ok_or(ServiceErr::BadRequest(ErrorCode::InvalidExpiry))?,
+        ),
+        None => None,
+    };
+    let scopes = token
+        .into_inner()
+        .scopes
+        .into_iter()
+        .map(|scope| (scope.class, scope.write))
+        .collect::<Vec<_>>();
+
+    let (access_token, token) = block::<_, _, ServiceErr>(move || {
+        // tokens can only get access to the classes of the user
+        for (class, _) in &scopes {
+            let member = match actions::class::get_member(&db, claims.uid, *class) {
+                Ok((member, _)) => member,
+                Err(ServiceErr::NotFound) => {
+                    return Err(ServiceErr::BadRequest(ErrorCode::InvalidScope))
+                }
+                Err(err) => return Err(err),
+            };
+            if member.role > models::MemberRole::MEMBER {
+                return Err(ServiceErr::BadRequest(ErrorCode::InvalidScope));
+            }
+        }
+        actions::access_token::create_access_token(&db, claims.uid, &name, expires, scopes)
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(AccessTokenPostResponse {
+        token,
+        access_token: access_token.into_dto()?,
+    }))
+}
+
+async fn delete_access_token(claims: Claims, db: Data<Pool>, token_id: Path<Uuid>) -> HttpResult {
+    debug!(uid = %claims.uid, %token_id, "delete access token");
+
+    let amount = block(move || {
+        actions::access_token::delete_access_token(&db, claims.uid, token_id.into_inner())
+    })
+    .await?;
+
+    match amount {
+        0 => Err(ServiceErr::NotFound),
+        _ => Ok(HttpResponse::Ok().body("Deleted token.")),
+    }
+}
+
+async fn get_sessions(claims: Claims, db: Data<Pool>) -> HttpResult {
+    debug!(uid = %claims.uid, "get sessions");
+
+    let uid = claims.uid;
+    let mut sessions: Vec<dto::Session> = block(move || actions::session::get_sessions(&db, uid))
+        .await?
+        .into_dto()?;
+    for session in &mut sessions {
+        session.current = claims.session == Some(session.id);
+    }
+
+    Ok(HttpResponse::Ok().json(sessions))
+}
+
+async fn delete_session(claims: Claims, db: Data<Pool>, session_id: Path<Uuid>) -> HttpResult {
+    debug!(uid = %claims.uid, %session_id, "delete session");
+
+    let amount =
+        block(move || actions::session::delete_session(&db, claims.uid, session_id.into_inner()))
+            .await?;
+
+    match amount {
+        0 => Err(ServiceErr::NotFound),
+        _ => Ok(HttpResponse::Ok().body("Deleted session.")),
+    }
+}
+
+/// The invites of imported classes for the email of the user
+async fn get_invites(claims: Claims, db: Data<Pool>) -> HttpResult {
+    debug!(uid = %claims.uid, "get invites");
+
+    let invites = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        actions::class::get_invites(&db, &user.email)?
+            .into_iter()
+            .map(|(invite, class)| {
+                Ok(Membership {
+                    class: class.id,
+                    class_name: class.name,
+                    display_name: invite.display_name,
+                    role: invite.role.into_dto()?,
+                })
+            })
+            .collect::<Result<Vec<_>, ServiceErr>>()
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(invites))
+}
+
+/// Only users with a verified email can accept, anyone can sign up with an email
+async fn accept_invite(claims: Claims, db: Data<Pool>, class_id: Path<Uuid>) -> HttpResult {
+    debug!(uid = %claims.uid, %class_id, "accept invite");
+
+    let member = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        if !user.email_verified {
+            return Err(ServiceErr::Unauthorized(ErrorCode::EmailNotVerified));
+        }
+        actions::class::accept_invite(&db, &user, *class_id)
+    })
+    .await?
+    .into_dto()?;
+
+    Ok(HttpResponse::Ok().json(member))
+}
+
+async fn decline_invite(claims: Claims, db: Data<Pool>, class_id: Path<Uuid>) -> HttpResult {
+    debug!(uid = %claims.uid, %class_id, "decline invite");
+
+    let amount = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        actions::class::decline_invite(&db, &user.email, *class_id)
+    })
+    .await?;
+
+    match amount {
+        0 => Err(ServiceErr::NotFound),
+        _ => Ok(HttpResponse::Ok().body("Declined invite.")),
+    }
+}
+
+async fn create_discord_link_code(
+    bot: Bot<scopes::UsersLookup>,
+    db: Data<Pool>,
+    id: Json<SingleSnowflake>,
+) -> HttpResult {
+    debug!(key = %bot.name, ?id, "create link code for discord");
+
+    let snowflake = id.into_inner().snowflake;
+    validate_snowflake(&snowflake)?;
+
+    let code = block(move || {
+        actions::user::create_link_code(
+            &db,
+            None,
+            Some(&snowflake),
+            chrono::Duration::minutes(LINK_CODE_VALIDITY_MINUTES),
+        )
+    })
+    .await?
+    .into_dto()?;
+
+    Ok(HttpResponse::Created().json(code))
+}
+
+async fn link_discord_with_user(
+    bot: Bot<scopes::UsersLookup>,
+    db: Data<Pool>,
+    request: Json<DiscordLinkRequest>,
+) -> HttpResult {
+    debug!(key = %bot.name, snowflake = %request.snowflake, "link discord with user");
+
+    validate_snowflake(&request.snowflake)?;
+
+    let user = block(move || {
+        actions::user::redeem_link_code(&db, &request.code, None, Some(&request.snowflake))
+    })
+    .await?
+    .into_dto()?;
+
+    Ok(HttpResponse::Ok().json(user))
+}
+
+async fn unlink_discord_user(
+    snowflake: Path<String>,
+    db: Data<Pool>,
+    bot: Bot<scopes::UsersLookup>,
+) -> HttpResult {
+    debug!(key = %bot.name, ?snowflake, "unlink discord user");
+
+    let user = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_discord(&db, &snowflake)?;
+        actions::user::set_discord_id_user(&db, user.id, None)
+    })
+    .await?
+    .into_dto()?;
+
+    Ok(HttpResponse::Ok().json(user))
+}
+
+fn validate_snowflake(snowflake: &str) -> Result<(), ServiceErr> {
+    snowflake
+        .parse::<u64>()
+        .map(|_| ())
+        .map_err(|_| ServiceErr::BadRequest(ErrorCode::InvalidSnowflake))
+}
+
+async fn get_user_by_discord(
+    user_id: Path<String>,
+    db: Data<Pool>,
+    bot: Bot<scopes::UsersLookup>,
+) -> HttpResult {
+    debug!(key = %bot.name, ?user_id, "get user by discord");
 
     let user = block(move || actions::user::get_user_by_discord(&db, &user_id))
         .await?
@@ -122,12 +594,9 @@ async fn get_user_by_discord(user_id: Path<String>, db: Data<Pool>, claims: Clai
 async fn get_notifications(
     params: Query<NotificationQueryParams>,
     db: Data<Pool>,
-    claims: Claims,
+    bot: Bot<scopes::Notifications>,
 ) -> HttpResult {
-    debug!(?params, "Called get notifications");
-    if !claims.uid.is_nil() {
-        return Err(ServiceErr::Unauthorized("bot-only"));
-    }
+    debug!(key = %bot.name, ?params, "Called get notifications");
 
     let (time, notifications) = block(move || {
         actions::event::get_notifications(
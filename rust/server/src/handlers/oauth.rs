@@ -0,0 +1,209 @@
+use crate::config::DiscordConfig;
+use crate::error::{ServiceErr, ServiceResult};
+use actix_web::client::Client;
+use actix_web::http::StatusCode;
+use dto::ErrorCode;
+use serde::Deserialize;
+use tracing::{debug, error};
+
+pub const DISCORD_API_URL: &str = "https://discord.com/api";
+
+/// The OAuth2 application used to log in with discord
+#[derive(Debug, Clone)]
+pub struct DiscordOAuth {
+    pub client_id: String,
+    pub client_secret: String,
+    /// The page of the website that receives the code, must be registered in the discord application
+    pub redirect_uri: String,
+    /// The discord api, can be replaced by a mock provider for tests
+    pub api_url: String,
+}
+
+/// The discord user that logged in
+#[derive(Debug, Clone, Deserialize)]
+pub struct DiscordUser {
+    pub id: String,
+    pub email: Option<String>,
+    #[serde(default)]
+    pub verified: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+impl DiscordOAuth {
+    /// The application of the `[discord]` section of the config, the login is disabled without it
+    pub fn from_config(config: &DiscordConfig) -> Option<Self> {
+        Some(Self {
+            client_id: config.client_id.clone()?,
+            client_secret: config.client_secret.clone()?,
+            redirect_uri: config.redirect_uri.clone()?,
+            api_url: config.api_url.clone(),
+        })
+    }
+
+    /// The url that the user is redirected to for the login
+    pub fn authorize_url(&self, state: Option<&str>) -> ServiceResult<String> {
+        let query = serde_urlencoded::to_string([
+            ("client_id", self.client_id.as_str()),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("response_type", "code"),
+            ("scope", "identify email"),
+            ("prompt", "none"),
+            ("state", state.unwrap_or_default()),
+        ])
+        .map_err(|err| ServiceErr::InternalServerError(err.to_string()))?;
+
+        Ok(format!("{}/oauth2/authorize?{}", self.api_url, query))
+    }
+
+    /// Exchanges the authorization code for an access token and gets the user with it
+    pub async fn fetch_user(&self, code: &str) -> ServiceResult<DiscordUser> {
+        let client = Client::default();
+
+        let mut res = client
+            .post(format!("{}/oauth2/token", self.api_url))
+            .send_form(&[
+                ("client_id", self.client_id.as_str()),
+                ("client_secret", self.client_secret.as_str()),
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", self.redirect_uri.as_str()),
+            ])
+            .await
+            .map_err(|err| ServiceErr::InternalServerError(format!("discord token: {}", err)))?;
+
+        debug!(status = %res.status(), "discord token exchange");
+        match res.status() {
+            StatusCode::OK => {}
+            StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED => {
+                return Err(ServiceErr::BadRequest(ErrorCode::InvalidOauthCode))
+            }
+            status => {
+                error!(%status, "discord token exchange failed");
+                return Err(ServiceErr::InternalServerError(format!(
+                    "discord token: {}",
+                    status
+                )));
+            }
+        }
+
+        let token = res
+            .json::<TokenResponse>()
+            .await
+            .map_err(|err| ServiceErr::InternalServerError(format!("discord token: {}", err)))?;
+
+        let mut res = client
+            .get(format!("{}/users/@me", self.api_url))
+            .bearer_auth(token.access_token)
+            .send()
+            .await
+            .map_err(|err| ServiceErr::InternalServerError(format!("discord user: {}", err)))?;
+
+        if !res.status().is_success() {
+            return Err(ServiceErr::InternalServerError(format!(
+                "discord user: {}",
+                res.status()
+            )));
+        }
+
+        res.json::<DiscordUser>()
+            .await
+            .map_err(|err| ServiceErr::InternalServerError(format!("discord user: {}", err)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DiscordOAuth;
+    use crate::error::ServiceErr;
+    use actix_web::web::{get, post, Form};
+    use actix_web::{test, App, HttpRequest, HttpResponse};
+    use dto::ErrorCode;
+    use std::collections::HashMap;
+
+    /// A minimal discord that knows a single code and user
+    fn mock_provider() -> test::TestServer {
+        test::start(|| {
+            App::new()
+                .route(
+                    "/oauth2/token",
+                    post().to(|form: Form<HashMap<String, String>>| {
+                        let valid = form.get("code").map(String::as_str) == Some("valid-code")
+                            && form.get("client_secret").map(String::as_str) == Some("secret")
+                            && form.get("grant_type").map(String::as_str)
+                                == Some("authorization_code");
+                        if valid {
+                            HttpResponse::Ok().json(serde_json::json!({
+                                "access_token": "access",
+                                "token_type": "Bearer",
+                            }))
+                        } else {
+                            HttpResponse::BadRequest().json(serde_json::json!({
+                                "error": "invalid_grant",
+                            }))
+                        }
+                    }),
+                )
+                .route(
+                    "/users/@me",
+                    get().to(
+                        |req: HttpRequest| match req.headers().get("Authorization") {
+                            Some(auth) if auth == "Bearer access" => {
+                                HttpResponse::Ok().json(serde_json::json!({
+                                    "id": "80351110224678912",
+                                    "username": "Nelly",
+                                    "email": "nelly@discord.com",
+                                    "verified": true,
+                                }))
+                            }
+                            _ => HttpResponse::Unauthorized().finish(),
+                        },
+                    ),
+                )
+        })
+    }
+
+    fn oauth(server: &test::TestServer) -> DiscordOAuth {
+        DiscordOAuth {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "https://cors-school.com/login/discord".to_string(),
+            api_url: server.url("").trim_end_matches('/').to_string(),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn fetch_user_with_code() {
+        let server = mock_provider();
+        let oauth = oauth(&server);
+
+        let user = oauth.fetch_user("valid-code").await.unwrap();
+        assert_eq!(user.id, "80351110224678912");
+        assert_eq!(user.email.as_deref(), Some("nelly@discord.com"));
+        assert!(user.verified);
+
+        match oauth.fetch_user("wrong-code").await {
+            Err(ServiceErr::BadRequest(ErrorCode::InvalidOauthCode)) => {}
+            other => panic!("expected invalid code, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn authorize_url_contains_state() {
+        let oauth = DiscordOAuth {
+            client_id: "client".to_string(),
+            client_secret: "secret".to_string(),
+            redirect_uri: "https://cors-school.com/login/discord".to_string(),
+            api_url: super::DISCORD_API_URL.to_string(),
+        };
+
+        let url = oauth.authorize_url(Some("abc")).unwrap();
+        assert!(url.starts_with("https://discord.com/api/oauth2/authorize?client_id=client&"));
+        assert!(url.contains("redirect_uri=https%3A%2F%2Fcors-school.com%2Flogin%2Fdiscord"));
+        assert!(url.contains("scope=identify+email"));
+        assert!(url.ends_with("state=abc"));
+    }
+}
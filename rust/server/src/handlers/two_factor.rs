@@ -0,0 +1,125 @@
+//! Enrollment of the two-factor authentication, the login with it is in [`super::auth`]
+
+use crate::actions::{self, Pool};
+use crate::error::ServiceErr;
+use crate::handlers::auth::Claims;
+use crate::handlers::HttpResult;
+use crate::rate_limit::RateLimiter;
+use crate::totp;
+use actix_web::web::{block, Data, Json};
+use actix_web::HttpResponse;
+use dto::ErrorCode;
+use dto::{RecoveryCodes, TwoFactorCode, TwoFactorEnrollment};
+use tracing::debug;
+
+/// The name of the account in authenticator apps
+const TOTP_ISSUER: &str = "CORS";
+
+/// `POST /users/me/2fa`, creates a new secret that has to be verified with a code
+pub(super) async fn enroll_two_factor(claims: Claims, db: Data<Pool>) -> HttpResult {
+    debug!(uid = %claims.uid, "enroll two-factor");
+
+    let secret = totp::generate_secret();
+    let enrollment = block::<_, _, ServiceErr>(move || {
+        let user = actions::user::get_user_by_id(&db, claims.uid)?;
+        actions::two_factor::start_enrollment(&db, claims.uid, &secret)?;
+
+        Ok(TwoFactorEnrollment {
+            secret: totp::encode_secret(&secret),
+            uri: totp::otpauth_uri(&secret, TOTP_ISSUER, &user.email),
+        })
+    })
+    .await?;
+
+    Ok(HttpResponse::Created().json(enrollment))
+}
+
+/// `POST /users/me/2fa/verify`, enables the two-factor authentication with the first code
+pub(super) async fn verify_two_factor(
+    claims: Claims,
+    db: Data<Pool>,
+    limiter: Data<RateLimiter>,
+    body: Json<TwoFactorCode>,
+) -> HttpResult {
+    debug!(uid = %claims.uid, "verify two-factor");
+
+    let recovery_codes = block(move || {
+        match actions::two_factor::get_two_factor(&db, claims.uid)? {
+            None => return Err(ServiceErr::BadRequest(ErrorCode::NoEnrollment)),
+            Some(factor) if factor.enabled => {
+                return Err(ServiceErr::Conflict(ErrorCode::TwoFactorEnabled))
+            }
+            Some(_) => {}
+        }
+        if !check_code(&limiter, &claims, || {
+            actions::two_factor::verify_totp(&db, claims.uid, &body.code)
+        })? {
+            return Err(ServiceErr::Unauthorized(ErrorCode::InvalidCode));
+        }
+        actions::two_factor::enable_two_factor(&db, claims.uid)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(RecoveryCodes { recovery_codes }))
+}
+
+/// `POST /users/me/2fa/recovery-codes`, replaces the recovery codes
+pub(super) async fn regenerate_recovery_codes(
+    claims: Claims,
+    db: Data<Pool>,
+    limiter: Data<RateLimiter>,
+    body: Json<TwoFactorCode>,
+) -> HttpResult {
+    debug!(uid = %claims.uid, "regenerate recovery codes");
+
+    let recovery_codes = block(move || {
+        if !check_code(&limiter, &claims, || {
+            actions::two_factor::verify_code(&db, claims.uid, &body.code)
+        })? {
+            return Err(ServiceErr::Unauthorized(ErrorCode::InvalidCode));
+        }
+        actions::two_factor::regenerate_recovery_codes(&db, claims.uid)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().json(RecoveryCodes { recovery_codes }))
+}
+
+/// `DELETE /users/me/2fa`, needs a code, so that a stolen token can't turn it off
+pub(super) async fn disable_two_factor(
+    claims: Claims,
+    db: Data<Pool>,
+    limiter: Data<RateLimiter>,
+    body: Json<TwoFactorCode>,
+) -> HttpResult {
+    debug!(uid = %claims.uid, "disable two-factor");
+
+    block(move || {
+        if !check_code(&limiter, &claims, || {
+            actions::two_factor::verify_code(&db, claims.uid, &body.code)
+        })? {
+            return Err(ServiceErr::Unauthorized(ErrorCode::InvalidCode));
+        }
+        actions::two_factor::disable_two_factor(&db, claims.uid)
+    })
+    .await?;
+
+    Ok(HttpResponse::Ok().body("Disabled two-factor authentication."))
+}
+
+/// Checks a code, the wrong codes count towards the lockout of the account
+fn check_code(
+    limiter: &RateLimiter,
+    claims: &Claims,
+    verify: impl FnOnce() -> Result<bool, ServiceErr>,
+) -> Result<bool, ServiceErr> {
+    let account = claims.uid.to_string();
+    limiter.check_account(&account)?;
+
+    let valid = verify()?;
+    match valid {
+        true => limiter.reset_account(&account),
+        false => limiter.fail_account(&account),
+    }
+    Ok(valid)
+}
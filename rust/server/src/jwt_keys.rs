@@ -0,0 +1,313 @@
+//! The keys that sign and verify the JWTs
+//!
+//! Tokens are signed with an RSA key (RS256) and carry its id in the `kid` header. After a rotation,
+//! the old public key can stay as a verification key until its tokens expired. The public keys are
+//! served at `/.well-known/jwks.json`, so other services can verify tokens without a secret.
+//!
+//! The old `jwt.secret` (HS512) still works: it signs when there is no RSA key, otherwise it only
+//! verifies the tokens without a `kid` that were signed with it.
+
+use crate::config::JwtConfig;
+use crate::error::ServiceErr;
+use dto::{Jwk, Jwks};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use ring::digest;
+use rsa::pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey};
+use rsa::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use rsa::traits::PublicKeyParts;
+use rsa::{RsaPrivateKey, RsaPublicKey};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{info, warn};
+
+struct SigningKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: EncodingKey,
+}
+
+struct VerificationKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey<'static>,
+    /// Only for public keys, secrets are never published
+    jwk: Option<Jwk>,
+}
+
+pub struct JwtKeys {
+    signing: SigningKey,
+    verification: Vec<VerificationKey>,
+}
+
+impl JwtKeys {
+    /// Signs and verifies with a shared secret (HS512), the tokens have no `kid`
+    pub fn from_secret(secret: &[u8]) -> Self {
+        let mut keys = Self {
+            signing: SigningKey {
+                kid: None,
+                algorithm: Algorithm::HS512,
+                key: EncodingKey::from_secret(secret),
+            },
+            verification: Vec::new(),
+        };
+        keys.add_secret_verification_key(secret);
+        keys
+    }
+
+    /// Signs with an RSA private key in PEM format (PKCS#1 or PKCS#8).
+    /// The `kid` of every key is its RFC 7638 thumbprint, so it stays the same after a rotation
+    pub fn from_rsa_pem(pem: &[u8]) -> Result<Self, String> {
+        let parsed = pem::parse(pem).map_err(|err| format!("invalid pem: {}", err))?;
+        if !parsed.tag.ends_with("PRIVATE KEY") {
+            return Err(format!("expected a private key, got {}", parsed.tag));
+        }
+        let verification = rsa_verification_key(&parsed)?;
+        let encoding =
+            EncodingKey::from_rsa_pem(pem).map_err(|err| format!("invalid rsa key: {}", err))?;
+
+        let keys = Self {
+            signing: SigningKey {
+                kid: verification.kid.clone(),
+                algorithm: Algorithm::RS256,
+                key: encoding,
+            },
+            verification: vec![verification],
+        };
+        // the key is only checked by ring when it signs, a broken key should stop the startup
+        keys.encode(&())
+            .map_err(|err| format!("can't sign with the key: {}", err))?;
+        Ok(keys)
+    }
+
+    /// Adds a key that only verifies tokens, like the key before a rotation.
+    /// `pem` can be a public or private RSA key
+    pub fn add_rsa_verification_key(&mut self, pem: &[u8]) -> Result<(), String> {
+        let parsed = pem::parse(pem).map_err(|err| format!("invalid pem: {}", err))?;
+        let key = rsa_verification_key(&parsed)?;
+
+        if self
+            .verification
+            .iter()
+            .any(|existing| existing.jwk == key.jwk)
+        {
+            return Ok(());
+        }
+        self.verification.push(key);
+        Ok(())
+    }
+
+    /// Accepts tokens without a `kid` that were signed with the secret
+    pub fn add_secret_verification_key(&mut self, secret: &[u8]) {
+        self.verification.push(VerificationKey {
+            kid: None,
+            algorithm: Algorithm::HS512,
+            key: DecodingKey::from_secret(secret).into_static(),
+            jwk: None,
+        });
+    }
+
+    /// Reads the keys of the `[jwt]` section of the config, the keys are paths to PEM files
+    pub fn from_config(config: &JwtConfig) -> Result<Self, String> {
+        let mut keys = match &config.signing_key {
+            Some(path) => {
+                let mut keys = Self::from_rsa_pem(&read_key(path)?)
+                    .map_err(|err| format!("jwt.signing_key {}: {}", path, err))?;
+                if let Some(secret) = &config.secret {
+                    info!(
+                        "jwt.secret only verifies tokens that were signed before jwt.signing_key"
+                    );
+                    keys.add_secret_verification_key(secret.as_bytes());
+                }
+                keys
+            }
+            None => {
+                let secret = config
+                    .secret
+                    .as_ref()
+                    .ok_or("jwt.signing_key or jwt.secret must be set")?;
+                warn!("jwt.signing_key is not set, tokens are signed with jwt.secret and can't be verified by other services");
+                Self::from_secret(secret.as_bytes())
+            }
+        };
+
+        for path in &config.verification_keys {
+            keys.add_rsa_verification_key(&read_key(path)?)
+                .map_err(|err| format!("jwt.verification_keys {}: {}", path, err))?;
+        }
+
+        Ok(keys)
+    }
+
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, ServiceErr> {
+        let mut header = Header::new(self.signing.algorithm);
+        header.kid = self.signing.kid.clone();
+
+        jsonwebtoken::encode(&header, claims, &self.signing.key)
+            .map_err(ServiceErr::JWTCreationError)
+    }
+
+    /// Verifies the token with the key of its `kid` and checks its `exp`, in seconds
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<T, ServiceErr> {
+        let header = jsonwebtoken::decode_header(token).map_err(|_| ServiceErr::JWTokenError)?;
+        let key = self
+            .verification
+            .iter()
+            .find(|key| key.kid == header.kid && key.algorithm == header.alg)
+            .ok_or(ServiceErr::JWTokenError)?;
+
+        jsonwebtoken::decode::<T>(token, &key.key, &Validation::new(key.algorithm))
+            .map(|data| data.claims)
+            .map_err(|err| match err.kind() {
+                ErrorKind::ExpiredSignature => ServiceErr::TokenExpiredError,
+                _ => ServiceErr::JWTokenError,
+            })
+    }
+
+    /// The public keys, for `/.well-known/jwks.json`
+    pub fn jwks(&self) -> Jwks {
+        Jwks {
+            keys: self
+                .verification
+                .iter()
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+fn read_key(path: &str) -> Result<Vec<u8>, String> {
+    std::fs::read(path).map_err(|err| format!("can't read {}: {}", path, err))
+}
+
+fn rsa_verification_key(pem: &pem::Pem) -> Result<VerificationKey, String> {
+    let key = rsa_public_key(pem)?;
+    let n = base64::encode_config(key.n().to_bytes_be(), base64::URL_SAFE_NO_PAD);
+    let e = base64::encode_config(key.e().to_bytes_be(), base64::URL_SAFE_NO_PAD);
+
+    let kid = thumbprint(&n, &e);
+    let key = DecodingKey::from_rsa_components(&n, &e).into_static();
+
+    Ok(VerificationKey {
+        kid: Some(kid.clone()),
+        algorithm: Algorithm::RS256,
+        key,
+        jwk: Some(Jwk {
+            kty: "RSA".to_string(),
+            use_: "sig".to_string(),
+            alg: "RS256".to_string(),
+            kid,
+            n,
+            e,
+        }),
+    })
+}
+
+/// The RFC 7638 thumbprint of an RSA key
+fn thumbprint(n: &str, e: &str) -> String {
+    // the required members in lexicographic order, without whitespace
+    let json = format!(r#"{{"e":"{}","kty":"RSA","n":"{}"}}"#, e, n);
+    let hash = digest::digest(&digest::SHA256, json.as_bytes());
+    base64::encode_config(hash.as_ref(), base64::URL_SAFE_NO_PAD)
+}
+
+/// The public key of an RSA key in the DER of the PEM, private keys are read for their public part
+fn rsa_public_key(pem: &pem::Pem) -> Result<RsaPublicKey, String> {
+    let der = pem.contents.as_slice();
+    match pem.tag.as_str() {
+        "RSA PRIVATE KEY" => RsaPrivateKey::from_pkcs1_der(der)
+            .map(|key| key.to_public_key())
+            .map_err(|err| err.to_string()),
+        "RSA PUBLIC KEY" => RsaPublicKey::from_pkcs1_der(der).map_err(|err| err.to_string()),
+        "PRIVATE KEY" => RsaPrivateKey::from_pkcs8_der(der)
+            .map(|key| key.to_public_key())
+            .map_err(|err| err.to_string()),
+        "PUBLIC KEY" => RsaPublicKey::from_public_key_der(der).map_err(|err| err.to_string()),
+        _ => Err("unsupported key".to_string()),
+    }
+    .map_err(|err| format!("invalid rsa key: {}: {}", pem.tag, err))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde::Deserialize;
+
+    const PRIVATE_KEY: &[u8] = include_bytes!("../test-data/jwt-rs256.pem");
+    const PUBLIC_KEY: &[u8] = include_bytes!("../test-data/jwt-rs256.pub.pem");
+    /// In the PKCS#1 format
+    const OLD_PRIVATE_KEY: &[u8] = include_bytes!("../test-data/jwt-rs256-old.pem");
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestClaims {
+        exp: i64,
+        uid: String,
+    }
+
+    fn claims() -> TestClaims {
+        TestClaims {
+            exp: chrono::Utc::now().timestamp() + 60,
+            uid: "hugo".to_string(),
+        }
+    }
+
+    #[test]
+    fn rsa_sign_and_verify() {
+        let keys = JwtKeys::from_rsa_pem(PRIVATE_KEY).unwrap();
+        let token = keys.encode(&claims()).unwrap();
+
+        let header = jsonwebtoken::decode_header(&token).unwrap();
+        assert_eq!(header.alg, Algorithm::RS256);
+        let jwks = keys.jwks();
+        assert_eq!(jwks.keys.len(), 1);
+        assert_eq!(header.kid.as_ref(), Some(&jwks.keys[0].kid));
+        assert_eq!(jwks.keys[0].e, "AQAB");
+
+        assert_eq!(keys.decode::<TestClaims>(&token).unwrap().uid, "hugo");
+
+        // the public key has the same thumbprint, so its jwk is the same
+        let mut other = JwtKeys::from_secret(b"cooles secret");
+        other.add_rsa_verification_key(PUBLIC_KEY).unwrap();
+        assert_eq!(other.jwks().keys, jwks.keys);
+        assert_eq!(other.decode::<TestClaims>(&token).unwrap().uid, "hugo");
+    }
+
+    #[test]
+    fn rotation() {
+        let old = JwtKeys::from_rsa_pem(OLD_PRIVATE_KEY).unwrap();
+        let old_token = old.encode(&claims()).unwrap();
+        let secret_token = JwtKeys::from_secret(b"cooles secret")
+            .encode(&claims())
+            .unwrap();
+
+        let mut keys = JwtKeys::from_rsa_pem(PRIVATE_KEY).unwrap();
+        assert!(keys.decode::<TestClaims>(&old_token).is_err());
+        assert!(keys.decode::<TestClaims>(&secret_token).is_err());
+
+        keys.add_rsa_verification_key(OLD_PRIVATE_KEY).unwrap();
+        keys.add_secret_verification_key(b"cooles secret");
+        assert_eq!(
+            keys.decode::<TestClaims>(&old_token).unwrap(),
+            old.decode(&old_token).unwrap()
+        );
+        assert!(keys.decode::<TestClaims>(&secret_token).is_ok());
+
+        // new tokens are signed with the new key, both public keys are published
+        let token = keys.encode(&claims()).unwrap();
+        assert!(old.decode::<TestClaims>(&token).is_err());
+        let jwks = keys.jwks().keys;
+        assert_eq!(jwks.len(), 2);
+        assert_eq!(jwks[1], old.jwks().keys[0]);
+    }
+
+    #[test]
+    fn invalid_keys() {
+        assert!(JwtKeys::from_rsa_pem(PUBLIC_KEY).is_err());
+        assert!(JwtKeys::from_rsa_pem(b"not a key").is_err());
+        assert!(JwtKeys::from_secret(b"secret")
+            .add_rsa_verification_key(
+                b"-----BEGIN PUBLIC KEY-----\nAAAA\n-----END PUBLIC KEY-----\n"
+            )
+            .is_err());
+    }
+}
@@ -0,0 +1,25 @@
+//! The CORS server, shared by the `server` binary and the `cors-admin` tool
+
+#[macro_use]
+extern crate diesel;
+#[macro_use]
+extern crate diesel_migrations;
+
+// the diesel 1.x macros generate impls inside of functions and constants
+#[allow(non_local_definitions)]
+pub mod actions;
+pub mod config;
+pub mod error;
+pub mod handlers;
+pub mod jwt_keys;
+pub mod mail;
+pub mod migrations;
+#[allow(non_local_definitions)]
+pub mod models;
+pub mod password;
+pub mod rate_limit;
+pub mod request_id;
+#[allow(non_local_definitions)]
+pub mod schema;
+pub mod token;
+pub mod totp;
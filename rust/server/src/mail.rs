@@ -0,0 +1,255 @@
+//! Sending emails to users
+//!
+//! The transport is pluggable: with `mail.smtp_address` set, mails are sent to that smtp server
+//! (for example a local smtp sink while developing), otherwise they are only logged
+
+use crate::config::MailConfig;
+use crate::error::{ServiceErr, ServiceResult};
+use dto::ErrorCode;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+use tracing::{debug, info};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivers mails, implementations must not block for long, they are called from the thread pool
+pub trait Transport: Send + Sync {
+    fn send(&self, mail: &Mail) -> ServiceResult<()>;
+}
+
+pub struct Mailer {
+    transport: Box<dyn Transport>,
+    from: String,
+    /// The website, links in mails point to it
+    site_url: String,
+}
+
+impl Mailer {
+    pub fn new(transport: Box<dyn Transport>, from: String, site_url: String) -> Self {
+        Self {
+            transport,
+            from,
+            site_url,
+        }
+    }
+
+    /// A mailer with the `[mail]` section of the config
+    pub fn from_config(config: &MailConfig) -> Self {
+        let transport: Box<dyn Transport> = match &config.smtp_address {
+            Some(address) => Box::new(SmtpTransport {
+                address: address.clone(),
+            }),
+            None => {
+                info!("mail.smtp_address is not set, mails are only logged");
+                Box::new(LogTransport)
+            }
+        };
+
+        Self::new(transport, config.from.clone(), config.site_url.clone())
+    }
+
+    /// A link to a page of the website
+    pub fn link(&self, path: &str) -> String {
+        format!("{}{}", self.site_url.trim_end_matches('/'), path)
+    }
+
+    pub fn send(&self, to: &str, subject: &str, body: String) -> ServiceResult<()> {
+        debug!(%to, %subject, "send mail");
+
+        // the address ends up in smtp commands and headers
+        if to.chars().any(|c| c.is_control() || c == '<' || c == '>') {
+            return Err(ServiceErr::BadRequest(ErrorCode::InvalidEmail));
+        }
+
+        self.transport.send(&Mail {
+            from: self.from.clone(),
+            to: to.to_string(),
+            subject: subject.to_string(),
+            body,
+        })
+    }
+}
+
+/// Writes the mails into the log instead of sending them
+pub struct LogTransport;
+
+impl Transport for LogTransport {
+    fn send(&self, mail: &Mail) -> ServiceResult<()> {
+        info!(to = %mail.to, subject = %mail.subject, body = %mail.body, "mail");
+        Ok(())
+    }
+}
+
+/// Sends the mails to an smtp server without authentication or tls, like a local relay or sink
+pub struct SmtpTransport {
+    /// `host:port` of the server
+    pub address: String,
+}
+
+impl Transport for SmtpTransport {
+    fn send(&self, mail: &Mail) -> ServiceResult<()> {
+        self.send_mail(mail)
+            .map_err(|err| ServiceErr::InternalServerError(format!("smtp: {}", err)))
+    }
+}
+
+impl SmtpTransport {
+    fn send_mail(&self, mail: &Mail) -> std::io::Result<()> {
+        let stream = TcpStream::connect(&self.address)?;
+        stream.set_read_timeout(Some(SMTP_TIMEOUT))?;
+        stream.set_write_timeout(Some(SMTP_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut stream = stream;
+
+        expect_reply(&mut reader, 220)?;
+        command(&mut stream, &mut reader, "EHLO cors-school.com", 250)?;
+        command(
+            &mut stream,
+            &mut reader,
+            &format!("MAIL FROM:<{}>", mail.from),
+            250,
+        )?;
+        command(
+            &mut stream,
+            &mut reader,
+            &format!("RCPT TO:<{}>", mail.to),
+            250,
+        )?;
+        command(&mut stream, &mut reader, "DATA", 354)?;
+
+        let message = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n{}",
+            mail.from, mail.to, mail.subject, mail.body
+        );
+        command(
+            &mut stream,
+            &mut reader,
+            &format!("{}\r\n.", dot_stuff(&message)),
+            250,
+        )?;
+        command(&mut stream, &mut reader, "QUIT", 221)
+    }
+}
+
+fn command(
+    stream: &mut TcpStream,
+    reader: &mut impl BufRead,
+    line: &str,
+    code: u16,
+) -> std::io::Result<()> {
+    stream.write_all(line.as_bytes())?;
+    stream.write_all(b"\r\n")?;
+    expect_reply(reader, code)
+}
+
+/// Reads a (multiline) reply of the server and checks its code
+fn expect_reply(reader: &mut impl BufRead, code: u16) -> std::io::Result<()> {
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "connection closed",
+            ));
+        }
+        if !line.starts_with(&code.to_string()) {
+            return Err(std::io::Error::other(format!(
+                "expected {}, got {}",
+                code,
+                line.trim_end()
+            )));
+        }
+        // `250-...` continues, `250 ...` is the last line
+        if line.as_bytes().get(3) != Some(&b'-') {
+            return Ok(());
+        }
+    }
+}
+
+/// Normalizes the line endings and escapes lines starting with a dot, which would end the data
+fn dot_stuff(message: &str) -> String {
+    message
+        .replace("\r\n", "\n")
+        .split('\n')
+        .map(|line| match line.starts_with('.') {
+            true => format!(".{}", line),
+            false => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\r\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn smtp_conversation() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut received = Vec::new();
+            let mut in_data = false;
+            stream.write_all(b"220 sink\r\n").unwrap();
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = if in_data {
+                    if line == "." {
+                        in_data = false;
+                        b"250 queued\r\n"
+                    } else {
+                        received.push(line);
+                        continue;
+                    }
+                } else if line.starts_with("EHLO") {
+                    b"250-sink\r\n250 8BITMIME\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go on\r\n"
+                } else if line == "QUIT" {
+                    stream.write_all(b"221 bye\r\n").unwrap();
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                stream.write_all(reply).unwrap();
+            }
+            received
+        });
+
+        let mailer = Mailer::new(
+            Box::new(SmtpTransport { address }),
+            "from@cors-school.com".to_string(),
+            "https://cors-school.com/".to_string(),
+        );
+        assert_eq!(
+            mailer.link("/password-reset"),
+            "https://cors-school.com/password-reset"
+        );
+        mailer
+            .send("to@example.com", "Hi", "first\n.second".to_string())
+            .unwrap();
+
+        let received = server.join().unwrap();
+        assert!(received.contains(&"Subject: Hi".to_string()));
+        assert!(received.contains(&"first".to_string()));
+        assert!(received.contains(&"..second".to_string()));
+    }
+}
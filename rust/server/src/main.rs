@@ -1,79 +1,128 @@
-#[macro_use]
-extern crate diesel;
-
 use std::env;
 
 use actix_cors::Cors;
 use actix_web::web::Data;
-use actix_web::{web, App, HttpServer};
+use actix_web::{web, App, HttpResponse, HttpServer};
 use color_eyre::Report;
-use diesel::prelude::*;
-use diesel::r2d2;
-use diesel::r2d2::ConnectionManager;
-use jsonwebtoken::{DecodingKey, EncodingKey};
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
-use crate::actions::Pool;
-use crate::handlers::config;
+use server::config::{Config, LogConfig, LogFormat, ServerConfig};
+use server::error::ServiceErr;
+use server::handlers::oauth::DiscordOAuth;
+use server::handlers::EmailVerification;
+use server::jwt_keys::JwtKeys;
+use server::mail::Mailer;
+use server::rate_limit::RateLimiter;
+use server::request_id::RequestIds;
+use server::{actions, handlers, migrations};
 
-pub mod actions;
-mod error;
-mod handlers;
-mod models;
-mod schema;
+mod cli;
 
 #[actix_rt::main]
 async fn main() -> Result<(), Report> {
     dotenv::dotenv().ok();
-    let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    setup_errors()?;
+
+    let config = Config::load().map_err(Report::msg)?;
+    setup_logging(&config.log);
+
+    let pool = actions::create_pool(&config.database)?;
+
+    let mut args = env::args().skip(1).collect::<Vec<_>>();
+    let migrate_flag = args.iter().any(|arg| arg == "--migrate");
+    args.retain(|arg| arg != "--migrate");
+    if !args.is_empty() {
+        return cli::run(&pool, &args);
+    }
+
+    migrations::prepare(&pool, migrate_flag || config.database.migrate)?;
 
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
-    let pool: Pool = r2d2::Pool::builder()
-        .build(manager)
-        .expect("Failed to create pool.");
+    let jwt_keys = Data::new(JwtKeys::from_config(&config.jwt).map_err(Report::msg)?);
+
+    let discord_oauth =
+        DiscordOAuth::from_config(&config.discord).filter(|_| config.features.discord_login);
+    if discord_oauth.is_none() {
+        info!("Discord login is disabled, it needs the discord_login feature and the [discord] application");
+    }
 
-    setup()?;
+    let mailer = Data::new(Mailer::from_config(&config.mail));
+    let email_verification = EmailVerification {
+        required: config.features.require_verified_email,
+    };
+    let rate_limiter = Data::new(RateLimiter::from_config(&config.rate_limit));
+    let password_policy = config.password;
 
-    let secret = env::var("JWT_SECRET").expect("JWT_SECRET env var");
-    let secret = Box::leak(Box::new(secret)); // leak the secret, it will be needed for the entire lifetime
-    let encoding_key = EncodingKey::from_secret(secret.as_bytes());
-    let decoding_key = DecodingKey::from_secret(secret.as_bytes());
+    let ServerConfig {
+        listen,
+        allowed_origins,
+    } = config.server;
+    let (jwt, features) = (Data::new(config.jwt), config.features);
 
-    info!("Starting Server");
+    info!(%listen, "Starting Server");
 
     HttpServer::new(move || {
-        let cors = Cors::default()
-            .allow_any_method()
-            .allowed_origin("cors-school.com")
-            .expose_headers(["token", "refresh-token"]);
+        let cors = Cors::default().allow_any_method().expose_headers([
+            "token",
+            "refresh-token",
+            "retry-after",
+            "x-request-id",
+        ]);
+        let cors = match allowed_origins.as_slice() {
+            [any] if any == "*" => cors.allow_any_origin(),
+            origins => origins
+                .iter()
+                .fold(cors, |cors, origin| cors.allowed_origin(origin)),
+        };
 
         App::new()
+            // inside of cors, the error responses it renders again still get the cors headers
+            .wrap(RequestIds)
             .wrap(cors)
             .app_data(Data::new(pool.clone()))
-            .data(encoding_key.clone())
-            .app_data(Data::new(decoding_key.clone()))
-            .service(web::scope("/api").configure(config))
+            .app_data(jwt_keys.clone())
+            .app_data(Data::new(discord_oauth.clone()))
+            .app_data(mailer.clone())
+            .app_data(Data::new(email_verification))
+            .app_data(rate_limiter.clone())
+            .app_data(Data::new(password_policy))
+            .app_data(jwt.clone())
+            .app_data(Data::new(features))
+            .app_data(web::JsonConfig::default().error_handler(|err, _| invalid_request(err)))
+            .app_data(web::PathConfig::default().error_handler(|err, _| invalid_request(err)))
+            .app_data(web::QueryConfig::default().error_handler(|err, _| invalid_request(err)))
+            .route("/.well-known/jwks.json", web::get().to(handlers::jwks))
+            .service(web::scope("/api").configure(handlers::config))
+            .default_service(
+                web::route().to(|| async { Err::<HttpResponse, _>(ServiceErr::NotFound) }),
+            )
     })
-    .bind("0.0.0.0:8080")?
+    .bind(&listen)?
     .run()
     .await?;
 
     Ok(())
 }
 
-fn setup() -> std::result::Result<(), Report> {
+fn invalid_request(err: impl std::fmt::Display) -> actix_web::Error {
+    ServiceErr::InvalidRequest(err.to_string()).into()
+}
+
+fn setup_errors() -> std::result::Result<(), Report> {
     if env::var("RUST_LIB_BACKTRACE").is_err() {
         env::set_var("RUST_LIB_BACKTRACE", "1")
     }
     color_eyre::install()?;
 
-    if env::var("RUST_LOG").is_err() {
-        env::set_var("RUST_LOG", "debug")
-    }
-    tracing_subscriber::fmt::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .init();
-
     Ok(())
 }
+
+fn setup_logging(config: &LogConfig) {
+    let logs = tracing_subscriber::fmt::fmt().with_env_filter(EnvFilter::new(&config.filter));
+    match config.format {
+        LogFormat::Full => logs.init(),
+        LogFormat::Compact => logs.compact().init(),
+        LogFormat::Pretty => logs.pretty().init(),
+        LogFormat::Json => logs.json().init(),
+    }
+}
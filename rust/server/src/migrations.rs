@@ -0,0 +1,129 @@
+//! The migrations of the database, they are embedded into the binary
+//!
+//! With `--migrate` or `database.migrate`, the pending migrations are run at startup. Otherwise
+//! the server refuses to start while the database is behind, instead of failing on queries later.
+
+use crate::actions::Pool;
+use color_eyre::eyre::bail;
+use color_eyre::Report;
+use diesel::sql_types::BigInt;
+use diesel::{PgConnection, RunQueryDsl};
+use diesel_migrations::{MigrationConnection, RunMigrationsError};
+use std::collections::HashSet;
+use std::io;
+use tracing::{info, warn};
+
+/// Only one server runs the migrations at a time, the others wait for it
+const MIGRATION_LOCK: i64 = 0x636f_7273_6d69_6772;
+
+#[allow(bare_trait_objects)]
+mod embedded {
+    #[derive(EmbedMigrations)]
+    struct _Dummy;
+
+    /// The migrations of the `migrations` directory, sorted by version
+    pub fn all() -> &'static [&'static dyn Migration] {
+        ALL_MIGRATIONS
+    }
+}
+
+#[derive(Debug)]
+pub struct Status {
+    pub applied: Vec<&'static str>,
+    pub pending: Vec<&'static str>,
+    /// Versions in the database that this binary doesn't know, from a newer server
+    pub unknown: Vec<String>,
+}
+
+pub fn status(conn: &PgConnection) -> Result<Status, RunMigrationsError> {
+    diesel_migrations::setup_database(conn)?;
+    let mut already_run = conn.previously_run_migration_versions()?;
+
+    let (applied, pending) = embedded::all()
+        .iter()
+        .map(|migration| migration.version())
+        .partition(|version| already_run.contains(*version));
+    for migration in embedded::all() {
+        already_run.remove(migration.version());
+    }
+    let mut unknown = already_run.into_iter().collect::<Vec<_>>();
+    unknown.sort();
+
+    Ok(Status {
+        applied,
+        pending,
+        unknown,
+    })
+}
+
+/// Runs the pending migrations and returns their versions
+pub fn run(conn: &PgConnection) -> Result<Vec<&'static str>, RunMigrationsError> {
+    diesel::sql_query("SELECT pg_advisory_lock($1)")
+        .bind::<BigInt, _>(MIGRATION_LOCK)
+        .execute(conn)?;
+
+    // another server may have run them while this one waited for the lock
+    let result = status(conn).and_then(|status| {
+        let pending = status.pending.iter().collect::<HashSet<_>>();
+        diesel_migrations::run_migrations(
+            conn,
+            embedded::all()
+                .iter()
+                .copied()
+                .filter(|migration| pending.contains(&migration.version())),
+            &mut io::sink(),
+        )?;
+        Ok(status.pending)
+    });
+
+    diesel::sql_query("SELECT pg_advisory_unlock($1)")
+        .bind::<BigInt, _>(MIGRATION_LOCK)
+        .execute(conn)?;
+    result
+}
+
+/// Runs the pending migrations if `migrate` is set, otherwise fails if there are any
+pub fn prepare(db: &Pool, migrate: bool) -> Result<(), Report> {
+    let conn = db.get()?;
+
+    if migrate {
+        for version in run(&conn)? {
+            info!(%version, "Ran migration");
+        }
+    }
+
+    let status = status(&conn)?;
+    if !status.pending.is_empty() {
+        bail!(
+            "the database is behind the server, the migrations {} are pending\n\
+             run them with `server --migrate`, `server migrate run` or `database.migrate = true`",
+            status.pending.join(", ")
+        );
+    }
+    if !status.unknown.is_empty() {
+        warn!(
+            versions = %status.unknown.join(", "),
+            "The database has migrations this server doesn't know, it may be outdated"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_database_is_migrated() {
+        dotenv::dotenv().ok();
+        let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+        let conn = diesel::Connection::establish(&database_url).unwrap();
+
+        let status = status(&conn).unwrap();
+        assert_eq!(status.applied.len(), embedded::all().len());
+        assert!(status.applied.contains(&"20210719115842"));
+        assert!(status.pending.is_empty());
+
+        assert!(run(&conn).unwrap().is_empty());
+    }
+}
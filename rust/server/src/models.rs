@@ -10,6 +10,7 @@ pub struct User {
     pub description: String,
     pub discord_id: Option<String>,
     pub token_version: i32,
+    pub email_verified: bool,
 }
 
 #[derive(Debug, Insertable)]
@@ -21,6 +22,7 @@ pub struct NewUser<'a> {
     pub description: &'a str,
     pub discord_id: Option<&'a str>,
     pub token_version: i32,
+    pub email_verified: bool,
 }
 
 #[derive(Debug, Clone, Queryable, Identifiable)]
@@ -45,6 +47,9 @@ pub struct Class {
     pub name: String,
     pub description: String,
     pub discord_id: Option<String>,
+    pub timezone: String,
+    pub require_two_factor: bool,
+    pub archived: bool,
 }
 
 #[derive(Debug, Insertable, Queryable, Identifiable)]
@@ -55,6 +60,7 @@ pub struct NewClass<'a> {
     pub name: &'a str,
     pub description: &'a str,
     pub discord_id: Option<&'a str>,
+    pub timezone: &'a str,
 }
 
 #[derive(Debug, Clone, Queryable)]
@@ -75,6 +81,24 @@ pub struct NewMember<'a> {
     pub role: i32,
 }
 
+/// An invite of an imported class for the user with the email
+#[derive(Debug, Clone, Queryable)]
+pub struct ClassInvite {
+    pub class: Uuid,
+    pub email: String,
+    pub display_name: String,
+    pub role: i32,
+}
+
+#[derive(Debug, Clone, Insertable)]
+#[table_name = "class_invites"]
+pub struct NewClassInvite<'a> {
+    pub class: Uuid,
+    pub email: &'a str,
+    pub display_name: &'a str,
+    pub role: i32,
+}
+
 #[derive(Debug, Clone, Queryable)]
 pub struct Event {
     pub id: Uuid,
@@ -125,20 +149,137 @@ pub struct Guild {
     pub notif_channel: Option<String>,
     pub notif_ping_role: Option<String>,
     pub notif_ping_everyone: bool,
+    pub language: Option<String>,
+    pub admin_channel: Option<String>,
 }
 
 #[derive(Debug, Insertable, Queryable, Identifiable, AsChangeset)]
 #[table_name = "guilds"]
+#[changeset_options(treat_none_as_null = "true")]
 pub struct NewGuild<'a> {
     pub id: &'a str,
     pub notif_channel: Option<&'a str>,
     pub notif_ping_role: Option<&'a str>,
     pub notif_ping_everyone: bool,
+    pub language: Option<&'a str>,
+    pub admin_channel: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Queryable)]
+pub struct NotifRoute {
+    pub guild: String,
+    pub e_type: i32,
+    pub notif_channel: Option<String>,
+    pub notif_ping_role: Option<String>,
+    pub notif_ping_everyone: Option<bool>,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "guild_notif_routes"]
+pub struct NewNotifRoute<'a> {
+    pub guild: &'a str,
+    pub e_type: i32,
+    pub notif_channel: Option<&'a str>,
+    pub notif_ping_role: Option<&'a str>,
+    pub notif_ping_everyone: Option<bool>,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable)]
+#[table_name = "join_requests"]
+pub struct JoinRequest {
+    pub user: Uuid,
+    pub class: Uuid,
+    pub requested: chrono::NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Queryable)]
+pub struct RoleMapping {
+    pub guild: String,
+    pub member_role: i32,
+    pub discord_role: String,
+}
+
+#[derive(Debug, Insertable)]
+#[table_name = "guild_role_mappings"]
+pub struct NewRoleMapping<'a> {
+    pub guild: &'a str,
+    pub member_role: i32,
+    pub discord_role: &'a str,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable)]
+#[table_name = "discord_link_codes"]
+pub struct LinkCode {
+    pub code: String,
+    pub user_id: Option<Uuid>,
+    pub discord_id: Option<String>,
+    pub expires: chrono::NaiveDateTime,
+}
+
+/// A key for the bot or another service, the key itself is only known to its owner
+#[derive(Debug, Clone, Queryable)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub key_hash: Vec<u8>,
+    pub scopes: Vec<String>,
+    pub created: chrono::NaiveDateTime,
+}
+
+/// A personal access token of a user
+#[derive(Debug, Clone, Queryable)]
+pub struct AccessToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_hash: Vec<u8>,
+    pub created: chrono::NaiveDateTime,
+    pub expires: Option<chrono::NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Queryable, Insertable)]
+pub struct AccessTokenScope {
+    pub token: Uuid,
+    pub class: Uuid,
+    pub write: bool,
+}
+
+/// A login of a user on a device
+#[derive(Debug, Clone, Queryable)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub device: String,
+    pub created: chrono::NaiveDateTime,
+    pub last_used: chrono::NaiveDateTime,
+    pub generation: i32,
+}
+
+/// The totp secret of a user, it is only used for logins once `enabled` is set
+#[derive(Debug, Clone, Queryable)]
+pub struct TwoFactor {
+    pub user_id: Uuid,
+    pub secret: Vec<u8>,
+    pub enabled: bool,
+    /// The last used time step, older codes can't be used anymore
+    pub last_step: i64,
+}
+
+/// A login that checked the password and waits for the second factor
+#[derive(Debug, Clone, Queryable)]
+pub struct LoginChallenge {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub expires: chrono::NaiveDateTime,
+    pub attempts: i32,
 }
 
 pub mod conversion {
     use crate::error::{ServiceErr, ServiceResult};
-    use crate::models::{Class, Event, Guild, Member, MemberRole, User};
+    use crate::models::{
+        AccessToken, AccessTokenScope, Class, Event, Guild, JoinRequest, LinkCode, Member,
+        MemberRole, NotifRoute, RoleMapping, Session, User,
+    };
 
     pub trait IntoDto<T> {
         fn into_dto(self) -> ServiceResult<T>;
@@ -152,6 +293,9 @@ pub mod conversion {
                 name: self.name,
                 description: self.description,
                 discord_id: self.discord_id,
+                timezone: Some(self.timezone),
+                require_two_factor: Some(self.require_two_factor),
+                archived: self.archived,
             })
         }
     }
@@ -172,6 +316,9 @@ pub mod conversion {
                 name: class.name,
                 description: class.description,
                 discord_id: class.discord_id,
+                timezone: Some(class.timezone),
+                require_two_factor: Some(class.require_two_factor),
+                archived: class.archived,
             })
         }
     }
@@ -258,6 +405,8 @@ pub mod conversion {
                 email: self.email,
                 description: self.description,
                 classes: None,
+                email_verified: self.email_verified,
+                two_factor_enabled: false,
             })
         }
     }
@@ -270,6 +419,8 @@ pub mod conversion {
                 email: user.email,
                 description: user.description,
                 classes: Some(classes),
+                email_verified: user.email_verified,
+                two_factor_enabled: false,
             })
         }
     }
@@ -291,15 +442,34 @@ pub mod conversion {
         }
     }
 
-    impl IntoDto<dto::Notification> for (Event, (Class, Guild)) {
+    impl IntoDto<dto::Notification> for (Event, (Class, Guild), Option<NotifRoute>) {
         fn into_dto(self) -> ServiceResult<dto::Notification> {
-            let (event, (_, guild)) = self;
+            let (event, (class, guild), route) = self;
+
+            // every setting of the route falls back to the default setting of the guild
+            let (channel, role_ping, everyone_ping) = match route {
+                Some(route) => (
+                    route.notif_channel.or(guild.notif_channel),
+                    route.notif_ping_role.or(guild.notif_ping_role),
+                    route
+                        .notif_ping_everyone
+                        .unwrap_or(guild.notif_ping_everyone),
+                ),
+                None => (
+                    guild.notif_channel,
+                    guild.notif_ping_role,
+                    guild.notif_ping_everyone,
+                ),
+            };
+
             Ok(dto::Notification {
                 event: event.into_dto()?,
                 guild: guild.id,
-                channel: guild.notif_channel.expect("Notif channel"),
-                role_ping: guild.notif_ping_role,
-                everyone_ping: guild.notif_ping_everyone,
+                channel: channel.expect("Notif channel"),
+                role_ping,
+                everyone_ping,
+                timezone: class.timezone,
+                language: guild.language,
             })
         }
     }
@@ -311,6 +481,107 @@ pub mod conversion {
                 notif_channel: self.notif_channel,
                 notif_ping_role: self.notif_ping_role,
                 notif_ping_everyone: self.notif_ping_everyone,
+                notif_routes: vec![],
+                role_mappings: vec![],
+                language: self.language,
+                admin_channel: self.admin_channel,
+            })
+        }
+    }
+
+    impl IntoDto<dto::Guild> for (Guild, Vec<NotifRoute>, Vec<RoleMapping>) {
+        fn into_dto(self) -> ServiceResult<dto::Guild> {
+            let (guild, routes, mappings) = self;
+            let mut guild = guild.into_dto()?;
+            guild.notif_routes = routes.into_dto()?;
+            guild.role_mappings = mappings.into_dto()?;
+            Ok(guild)
+        }
+    }
+
+    impl IntoDto<dto::JoinRequestNotification> for (JoinRequest, Member, Class, Guild) {
+        fn into_dto(self) -> ServiceResult<dto::JoinRequestNotification> {
+            let (request, member, class, guild) = self;
+            Ok(dto::JoinRequestNotification {
+                class_id: class.id,
+                class_name: class.name,
+                user_id: member.user,
+                display_name: member.display_name,
+                requested: request.requested.timestamp_millis(),
+                channel: guild.admin_channel.ok_or_else(|| {
+                    ServiceErr::IntoDTOError("Join request without admin channel".to_string())
+                })?,
+                language: guild.language,
+            })
+        }
+    }
+
+    impl IntoDto<dto::RoleMapping> for RoleMapping {
+        fn into_dto(self) -> ServiceResult<dto::RoleMapping> {
+            Ok(dto::RoleMapping {
+                role: self.member_role.into_dto()?,
+                discord_role: self.discord_role,
+            })
+        }
+    }
+
+    impl IntoDto<dto::DiscordMember> for (String, Option<i32>) {
+        fn into_dto(self) -> ServiceResult<dto::DiscordMember> {
+            let (snowflake, role) = self;
+            Ok(dto::DiscordMember {
+                snowflake,
+                role: role.map(IntoDto::into_dto).transpose()?,
+            })
+        }
+    }
+
+    impl IntoDto<dto::NotificationRoute> for NotifRoute {
+        fn into_dto(self) -> ServiceResult<dto::NotificationRoute> {
+            Ok(dto::NotificationRoute {
+                r#type: self.e_type.into_dto()?,
+                channel: self.notif_channel,
+                role_ping: self.notif_ping_role,
+                everyone_ping: self.notif_ping_everyone,
+            })
+        }
+    }
+
+    impl IntoDto<dto::LinkCode> for LinkCode {
+        fn into_dto(self) -> ServiceResult<dto::LinkCode> {
+            Ok(dto::LinkCode {
+                code: self.code,
+                expires: self.expires.timestamp_millis(),
+            })
+        }
+    }
+
+    impl IntoDto<dto::AccessToken> for (AccessToken, Vec<AccessTokenScope>) {
+        fn into_dto(self) -> ServiceResult<dto::AccessToken> {
+            let (token, scopes) = self;
+            Ok(dto::AccessToken {
+                id: token.id,
+                name: token.name,
+                created: token.created.timestamp_millis(),
+                expires: token.expires.map(|expires| expires.timestamp_millis()),
+                scopes: scopes
+                    .into_iter()
+                    .map(|scope| dto::AccessTokenScope {
+                        class: scope.class,
+                        write: scope.write,
+                    })
+                    .collect(),
+            })
+        }
+    }
+
+    impl IntoDto<dto::Session> for Session {
+        fn into_dto(self) -> ServiceResult<dto::Session> {
+            Ok(dto::Session {
+                id: self.id,
+                device: self.device,
+                created: self.created.timestamp_millis(),
+                last_used: self.last_used.timestamp_millis(),
+                current: false,
             })
         }
     }
@@ -337,6 +608,7 @@ pub mod conversion {
                 description: user.description,
                 discord_id: None,
                 token_version: 0,
+                email_verified: false,
             }
         }
     }
@@ -0,0 +1,189 @@
+//! Hashing and the policy of passwords
+//!
+//! Passwords are hashed with argon2id in the server, so they never end up in sql queries.
+//! Older accounts still have bcrypt hashes from pgcrypto, they are verified as well and
+//! replaced with argon2id on the next successful login.
+
+use crate::error::{ServiceErr, ServiceResult};
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use dto::ErrorCode;
+use once_cell::sync::Lazy;
+use rand::RngCore;
+use serde::Deserialize;
+use std::convert::TryFrom;
+
+const SALT_LENGTH: usize = 16;
+const DEFAULT_MIN_LENGTH: usize = 6;
+/// Long passwords are fine, but hashing megabytes for every login attempt is not
+const DEFAULT_MAX_LENGTH: usize = 128;
+
+/// The argon2id instance with the current parameters, hashes with other parameters are upgraded
+fn argon2() -> Argon2<'static> {
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, Params::default())
+}
+
+pub fn hash(password: &str) -> ServiceResult<String> {
+    let mut salt = [0; SALT_LENGTH];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let salt = SaltString::encode_b64(&salt)
+        .map_err(|err| ServiceErr::InternalServerError(format!("salt: {}", err)))?;
+
+    argon2()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|err| ServiceErr::InternalServerError(format!("hash password: {}", err)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verified {
+    Invalid,
+    Valid,
+    /// The password is right, but the hash is outdated and should be replaced
+    NeedsRehash,
+}
+
+pub fn verify(password: &str, stored: &str) -> Verified {
+    // bcrypt hashes of pgcrypto, like `$2a$08$...`
+    if stored.starts_with("$2") {
+        return match bcrypt::verify(password, stored) {
+            Ok(true) => Verified::NeedsRehash,
+            _ => Verified::Invalid,
+        };
+    }
+
+    let parsed = match PasswordHash::new(stored) {
+        Ok(parsed) => parsed,
+        Err(_) => return Verified::Invalid,
+    };
+    if argon2()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_err()
+    {
+        return Verified::Invalid;
+    }
+
+    let current = parsed.algorithm == Algorithm::Argon2id.ident()
+        && Params::try_from(&parsed).is_ok_and(|params| {
+            let default = Params::default();
+            params.m_cost() == default.m_cost()
+                && params.t_cost() == default.t_cost()
+                && params.p_cost() == default.p_cost()
+        });
+    match current {
+        true => Verified::Valid,
+        false => Verified::NeedsRehash,
+    }
+}
+
+/// Hashed once with the current parameters, so that verifying against it costs the same as a real login
+static DUMMY_HASH: Lazy<Option<String>> = Lazy::new(|| hash("dummy").ok());
+
+/// Takes about as long as a verification, for logins of unknown emails
+pub fn dummy_verify(password: &str) {
+    if let Some(hash) = DUMMY_HASH.as_deref() {
+        verify(password, hash);
+    }
+}
+
+/// The rules for new passwords, the `[password]` section of the config
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PasswordPolicy {
+    /// In characters
+    pub min_length: usize,
+    pub max_length: usize,
+    pub require_letter: bool,
+    pub require_digit: bool,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: DEFAULT_MIN_LENGTH,
+            max_length: DEFAULT_MAX_LENGTH,
+            require_letter: false,
+            require_digit: false,
+        }
+    }
+}
+
+impl PasswordPolicy {
+    pub fn check(&self, password: &str) -> ServiceResult<()> {
+        let length = password.chars().count();
+        if length < self.min_length {
+            return Err(ServiceErr::BadRequest(ErrorCode::PasswordTooShort));
+        }
+        if length > self.max_length {
+            return Err(ServiceErr::BadRequest(ErrorCode::PasswordTooLong));
+        }
+        if self.require_letter && !password.chars().any(char::is_alphabetic) {
+            return Err(ServiceErr::BadRequest(ErrorCode::PasswordNeedsLetter));
+        }
+        if self.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            return Err(ServiceErr::BadRequest(ErrorCode::PasswordNeedsDigit));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn hash_and_verify() {
+        let hash = hash("pass_wort").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert_ne!(hash, super::hash("pass_wort").unwrap());
+
+        assert_eq!(verify("pass_wort", &hash), Verified::Valid);
+        assert_eq!(verify("pass_wrt", &hash), Verified::Invalid);
+        assert_eq!(verify("pass_wort", "not a hash"), Verified::Invalid);
+    }
+
+    #[test]
+    fn outdated_hashes() {
+        // bcrypt with cost 8, like pgcrypto's `gen_salt('bf', 8)`
+        let bcrypt = bcrypt::hash("pass_wort", 8).unwrap();
+        assert_eq!(verify("pass_wort", &bcrypt), Verified::NeedsRehash);
+        assert_eq!(verify("pass_wrt", &bcrypt), Verified::Invalid);
+
+        let weak = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(1024, 1, 1, None).unwrap(),
+        )
+        .hash_password(
+            b"pass_wort",
+            &SaltString::encode_b64(b"saltsaltsalt").unwrap(),
+        )
+        .unwrap()
+        .to_string();
+        assert_eq!(verify("pass_wort", &weak), Verified::NeedsRehash);
+    }
+
+    #[test]
+    fn policy() {
+        let policy = PasswordPolicy::default();
+        assert!(policy.check("123456").is_ok());
+        assert!(policy.check("12345").is_err());
+        assert!(policy.check(&"a".repeat(129)).is_err());
+
+        let strict = PasswordPolicy {
+            min_length: 8,
+            require_letter: true,
+            require_digit: true,
+            ..policy
+        };
+        assert!(strict.check("hugoboss58").is_ok());
+        assert!(matches!(
+            strict.check("hugoboss"),
+            Err(ServiceErr::BadRequest(ErrorCode::PasswordNeedsDigit))
+        ));
+        assert!(matches!(
+            strict.check("12345678"),
+            Err(ServiceErr::BadRequest(ErrorCode::PasswordNeedsLetter))
+        ));
+    }
+}
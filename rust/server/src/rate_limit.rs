@@ -0,0 +1,310 @@
+//! Throttling of the auth routes against brute-force attacks
+//!
+//! The [`RateLimit`] middleware counts the requests of every ip, and the handlers count the failed
+//! password and code checks of every account, which is locked for a while after too many of them.
+//! The counters are kept in a [`Store`], [`MemoryStore`] keeps them in the process, so every
+//! server instance has its own counters.
+
+use crate::config::RateLimitConfig;
+use crate::error::ServiceErr;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::web::Data;
+use std::collections::HashMap;
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// Expired counters are removed when the memory store grows larger than this
+const MEMORY_STORE_CLEANUP_SIZE: usize = 10_000;
+
+/// At most `max` hits in a fixed window of `window`
+#[derive(Debug, Clone, Copy)]
+pub struct Limit {
+    pub max: u32,
+    pub window: Duration,
+}
+
+/// The hits of a key in the current window
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Counter {
+    pub hits: u32,
+    /// The time until the window ends and the counter starts again
+    pub reset_in: Duration,
+}
+
+/// Keeps the counters, implementations must be shared between the workers
+pub trait Store: Send + Sync {
+    /// Counts a hit for the key, a new window of `window` is started if there is none
+    fn hit(&self, key: &str, window: Duration) -> Counter;
+    /// The counter of the key, `None` if there were no hits in the current window
+    fn get(&self, key: &str) -> Option<Counter>;
+    fn reset(&self, key: &str);
+}
+
+#[derive(Default)]
+pub struct MemoryStore {
+    counters: Mutex<HashMap<String, (u32, Instant)>>,
+}
+
+impl Store for MemoryStore {
+    fn hit(&self, key: &str, window: Duration) -> Counter {
+        let now = Instant::now();
+        let mut counters = self.counters.lock().expect("rate limit store poisoned");
+
+        if counters.len() > MEMORY_STORE_CLEANUP_SIZE {
+            counters.retain(|_, (_, end)| *end > now);
+        }
+
+        let (hits, end) = counters
+            .entry(key.to_string())
+            .and_modify(|(hits, end)| match *end > now {
+                true => *hits += 1,
+                false => *hits = 1,
+            })
+            .or_insert((1, now + window));
+        if *end <= now {
+            *end = now + window;
+        }
+
+        Counter {
+            hits: *hits,
+            reset_in: *end - now,
+        }
+    }
+
+    fn get(&self, key: &str) -> Option<Counter> {
+        let now = Instant::now();
+        let counters = self.counters.lock().expect("rate limit store poisoned");
+
+        counters
+            .get(key)
+            .filter(|(_, end)| *end > now)
+            .map(|(hits, end)| Counter {
+                hits: *hits,
+                reset_in: *end - now,
+            })
+    }
+
+    fn reset(&self, key: &str) {
+        self.counters
+            .lock()
+            .expect("rate limit store poisoned")
+            .remove(key);
+    }
+}
+
+pub struct RateLimiter {
+    store: Box<dyn Store>,
+    /// The requests of an ip to the throttled routes
+    requests: Limit,
+    /// The failed attempts of an account until it is locked for the rest of the window
+    failures: Limit,
+    /// Takes the ip from the `Forwarded` and `X-Forwarded-For` headers, only behind a proxy
+    trust_proxy: bool,
+}
+
+impl RateLimiter {
+    pub fn new(store: Box<dyn Store>, requests: Limit, failures: Limit, trust_proxy: bool) -> Self {
+        Self {
+            store,
+            requests,
+            failures,
+            trust_proxy,
+        }
+    }
+
+    /// A limiter with the `[rate_limit]` section of the config, the counters are kept in memory
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        if !config.trust_proxy {
+            info!("rate_limit.trust_proxy is not set, requests are limited by their peer address");
+        }
+
+        Self::new(
+            Box::new(MemoryStore::default()),
+            Limit {
+                max: config.requests,
+                window: Duration::from_secs(config.window_seconds),
+            },
+            Limit {
+                max: config.lockout_failures,
+                window: Duration::from_secs(config.lockout_seconds),
+            },
+            config.trust_proxy,
+        )
+    }
+
+    /// Counts a request of the ip
+    pub fn check_ip(&self, ip: &str) -> Result<(), ServiceErr> {
+        let counter = self.store.hit(&format!("ip:{}", ip), self.requests.window);
+        match counter.hits > self.requests.max {
+            true => {
+                warn!(%ip, "ip is rate limited");
+                Err(ServiceErr::TooManyRequests(counter.reset_in))
+            }
+            false => Ok(()),
+        }
+    }
+
+    /// Fails if the account is locked after too many failures
+    pub fn check_account(&self, account: &str) -> Result<(), ServiceErr> {
+        match self.store.get(&account_key(account)) {
+            Some(counter) if counter.hits >= self.failures.max => {
+                Err(ServiceErr::TooManyRequests(counter.reset_in))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Counts a failed password or code of the account
+    pub fn fail_account(&self, account: &str) {
+        let counter = self.store.hit(&account_key(account), self.failures.window);
+        if counter.hits == self.failures.max {
+            warn!(%account, "account is locked after too many failures");
+        }
+    }
+
+    /// Forgets the failures after a successful attempt
+    pub fn reset_account(&self, account: &str) {
+        self.store.reset(&account_key(account));
+    }
+
+    fn ip(&self, req: &ServiceRequest) -> Option<String> {
+        match self.trust_proxy {
+            true => req
+                .connection_info()
+                .realip_remote_addr()
+                .map(str::to_string),
+            false => req.peer_addr().map(|addr| addr.ip().to_string()),
+        }
+    }
+}
+
+fn account_key(account: &str) -> String {
+    format!("account:{}", account.to_lowercase())
+}
+
+/// Middleware that limits the requests of every ip, with the [`RateLimiter`] from the app data
+pub struct RateLimit;
+
+impl<S, B> Transform<S> for RateLimit
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware { service }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service for RateLimitMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let checked = match req.app_data::<Data<RateLimiter>>() {
+            Some(limiter) => match limiter.ip(&req) {
+                Some(ip) => limiter.check_ip(&ip),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        };
+
+        match checked {
+            Ok(()) => Box::pin(self.service.call(req)),
+            // as a response, so the middlewares around can still change it
+            Err(err) => Box::pin(ready(Ok(req.error_response(err)))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn limiter() -> RateLimiter {
+        RateLimiter::new(
+            Box::new(MemoryStore::default()),
+            Limit {
+                max: 2,
+                window: Duration::from_secs(60),
+            },
+            Limit {
+                max: 3,
+                window: Duration::from_secs(60),
+            },
+            false,
+        )
+    }
+
+    #[test]
+    fn memory_store_windows() {
+        let store = MemoryStore::default();
+        assert_eq!(store.get("key"), None);
+        assert_eq!(store.hit("key", Duration::from_millis(50)).hits, 1);
+        assert_eq!(store.hit("key", Duration::from_millis(50)).hits, 2);
+        assert_eq!(store.get("key").unwrap().hits, 2);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(store.get("key"), None);
+        assert_eq!(store.hit("key", Duration::from_millis(50)).hits, 1);
+
+        store.reset("key");
+        assert_eq!(store.get("key"), None);
+    }
+
+    #[test]
+    fn ip_limit() {
+        let limiter = limiter();
+        assert!(limiter.check_ip("127.0.0.1").is_ok());
+        assert!(limiter.check_ip("127.0.0.1").is_ok());
+        assert!(matches!(
+            limiter.check_ip("127.0.0.1"),
+            Err(ServiceErr::TooManyRequests(_))
+        ));
+        assert!(limiter.check_ip("127.0.0.2").is_ok());
+    }
+
+    #[test]
+    fn account_lockout() {
+        let limiter = limiter();
+        for _ in 0..3 {
+            assert!(limiter.check_account("Hugo@Boss.com").is_ok());
+            limiter.fail_account("hugo@boss.com");
+        }
+        assert!(matches!(
+            limiter.check_account("hugo@boss.com"),
+            Err(ServiceErr::TooManyRequests(_))
+        ));
+        assert!(limiter.check_account("other@boss.com").is_ok());
+
+        limiter.reset_account("hugo@boss.com");
+        assert!(limiter.check_account("hugo@boss.com").is_ok());
+    }
+}
@@ -0,0 +1,156 @@
+//! Ids of requests, to find the logs of an error response
+//!
+//! The [`RequestIds`] middleware gives every request an id, which is in the `X-Request-Id` header
+//! of the response, in the `requestId` of error responses and in the span of all its logs.
+
+use crate::error::ServiceErr;
+use actix_web::body::{Body, MessageBody, ResponseBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{HeaderName, HeaderValue};
+use actix_web::HttpMessage;
+use std::future::{ready, Future, Ready};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tracing::Instrument;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+/// Ids from a proxy in front of the server are kept if they are short and simple
+const MAX_FORWARDED_ID_LENGTH: usize = 64;
+
+/// The id of the current request, in the extensions of the request
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+impl RequestId {
+    fn from_request(req: &ServiceRequest) -> Self {
+        let forwarded = req
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_FORWARDED_ID_LENGTH
+                    && id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            });
+
+        match forwarded {
+            Some(id) => RequestId(id.to_string()),
+            None => RequestId(uuid::Uuid::new_v4().to_simple().to_string()),
+        }
+    }
+}
+
+/// Middleware that assigns the [`RequestId`] and adds it to the responses
+pub struct RequestIds;
+
+impl<S, B> Transform<S> for RequestIds
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + Unpin + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<Body>;
+    type Error = actix_web::Error;
+    type Transform = RequestIdMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdMiddleware { service }))
+    }
+}
+
+pub struct RequestIdMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service for RequestIdMiddleware<S>
+where
+    S: Service<Request = ServiceRequest, Response = ServiceResponse<B>, Error = actix_web::Error>,
+    S::Future: 'static,
+    B: MessageBody + Unpin + 'static,
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<Body>;
+    type Error = actix_web::Error;
+    #[allow(clippy::type_complexity)]
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let id = RequestId::from_request(&req);
+        req.extensions_mut().insert(id.clone());
+
+        let span = tracing::info_span!("request", id = %id.0);
+        let fut = self.service.call(req).instrument(span);
+
+        Box::pin(async move {
+            let res = fut
+                .await?
+                .map_body(|_, body| ResponseBody::Other(Body::from_message(body)));
+
+            // the errors of the handlers were rendered without the id, so they are rendered again
+            let error_response = res
+                .response()
+                .error()
+                .and_then(|err| err.as_error::<ServiceErr>())
+                .map(|err| err.response(Some(&id.0)));
+            let mut res = match error_response {
+                Some(response) => res.into_response(response),
+                None => res,
+            };
+
+            if let Ok(value) = HeaderValue::from_str(&id.0) {
+                res.headers_mut()
+                    .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use actix_web::{test, web, App, HttpResponse};
+    use dto::{ApiError, ErrorCode};
+
+    #[actix_rt::test]
+    async fn error_responses_have_the_id() {
+        let mut app = test::init_service(App::new().wrap(RequestIds).route(
+            "/",
+            web::get().to(|| async {
+                Err::<HttpResponse, _>(ServiceErr::BadRequest(ErrorCode::InvalidEmail))
+            }),
+        ))
+        .await;
+
+        let res =
+            test::call_service(&mut app, test::TestRequest::get().uri("/").to_request()).await;
+        assert_eq!(res.status(), 400);
+        let id = res
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+
+        let body: ApiError = test::read_body_json(res).await;
+        assert_eq!(body.code, ErrorCode::InvalidEmail);
+        assert_eq!(body.request_id, Some(id));
+
+        let req = test::TestRequest::get()
+            .uri("/")
+            .header(REQUEST_ID_HEADER, "from-the-proxy")
+            .to_request();
+        let body: ApiError = test::read_response_json(&mut app, req).await;
+        assert_eq!(body.request_id.as_deref(), Some("from-the-proxy"));
+    }
+}
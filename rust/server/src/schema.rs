@@ -1,3 +1,41 @@
+table! {
+    access_token_scopes (token, class) {
+        token -> Uuid,
+        class -> Uuid,
+        write -> Bool,
+    }
+}
+
+table! {
+    access_tokens (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        name -> Varchar,
+        token_hash -> Bytea,
+        created -> Timestamp,
+        expires -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    api_keys (id) {
+        id -> Uuid,
+        name -> Varchar,
+        key_hash -> Bytea,
+        scopes -> Array<Text>,
+        created -> Timestamp,
+    }
+}
+
+table! {
+    class_invites (class, email) {
+        class -> Uuid,
+        email -> Varchar,
+        display_name -> Varchar,
+        role -> Int4,
+    }
+}
+
 table! {
     classes (id) {
         id -> Uuid,
@@ -5,6 +43,27 @@ table! {
         name -> Varchar,
         description -> Varchar,
         discord_id -> Nullable<Varchar>,
+        timezone -> Varchar,
+        require_two_factor -> Bool,
+        archived -> Bool,
+    }
+}
+
+table! {
+    discord_link_codes (code) {
+        code -> Varchar,
+        user_id -> Nullable<Uuid>,
+        discord_id -> Nullable<Varchar>,
+        expires -> Timestamp,
+    }
+}
+
+table! {
+    email_verifications (token_hash) {
+        token_hash -> Bytea,
+        user_id -> Uuid,
+        email -> Varchar,
+        expires -> Timestamp,
     }
 }
 
@@ -28,12 +87,56 @@ table! {
     }
 }
 
+table! {
+    former_members (user, class) {
+        user -> Uuid,
+        class -> Uuid,
+    }
+}
+
+table! {
+    guild_notif_routes (guild, e_type) {
+        guild -> Varchar,
+        e_type -> Int4,
+        notif_channel -> Nullable<Varchar>,
+        notif_ping_role -> Nullable<Varchar>,
+        notif_ping_everyone -> Nullable<Bool>,
+    }
+}
+
+table! {
+    guild_role_mappings (guild, member_role) {
+        guild -> Varchar,
+        member_role -> Int4,
+        discord_role -> Varchar,
+    }
+}
+
 table! {
     guilds (id) {
         id -> Varchar,
         notif_channel -> Nullable<Varchar>,
         notif_ping_role -> Nullable<Varchar>,
         notif_ping_everyone -> Bool,
+        language -> Nullable<Varchar>,
+        admin_channel -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    join_requests (user, class) {
+        user -> Uuid,
+        class -> Uuid,
+        requested -> Timestamp,
+    }
+}
+
+table! {
+    login_challenges (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        expires -> Timestamp,
+        attempts -> Int4,
     }
 }
 
@@ -53,6 +156,32 @@ table! {
     }
 }
 
+table! {
+    password_resets (token_hash) {
+        token_hash -> Bytea,
+        user_id -> Uuid,
+        expires -> Timestamp,
+    }
+}
+
+table! {
+    recovery_codes (user_id, code_hash) {
+        user_id -> Uuid,
+        code_hash -> Bytea,
+    }
+}
+
+table! {
+    sessions (id) {
+        id -> Uuid,
+        user_id -> Uuid,
+        device -> Varchar,
+        created -> Timestamp,
+        last_used -> Timestamp,
+        generation -> Int4,
+    }
+}
+
 table! {
     timetables (class) {
         class -> Uuid,
@@ -60,6 +189,15 @@ table! {
     }
 }
 
+table! {
+    two_factor (user_id) {
+        user_id -> Uuid,
+        secret -> Bytea,
+        enabled -> Bool,
+        last_step -> Int8,
+    }
+}
+
 table! {
     users (id) {
         id -> Uuid,
@@ -68,24 +206,58 @@ table! {
         description -> Varchar,
         discord_id -> Nullable<Varchar>,
         token_version -> Int4,
+        email_verified -> Bool,
     }
 }
 
+joinable!(class_invites -> classes (class));
+joinable!(class_invites -> member_roles (role));
 joinable!(classes -> users (owner));
+joinable!(discord_link_codes -> users (user_id));
+joinable!(access_token_scopes -> access_tokens (token));
+joinable!(access_token_scopes -> classes (class));
+joinable!(access_tokens -> users (user_id));
+joinable!(email_verifications -> users (user_id));
 joinable!(events -> classes (class));
 joinable!(events -> event_types (e_type));
+joinable!(former_members -> classes (class));
+joinable!(former_members -> users (user));
+joinable!(guild_notif_routes -> event_types (e_type));
+joinable!(guild_notif_routes -> guilds (guild));
+joinable!(guild_role_mappings -> guilds (guild));
+joinable!(guild_role_mappings -> member_roles (member_role));
 joinable!(members -> classes (class));
+joinable!(login_challenges -> users (user_id));
 joinable!(members -> member_roles (role));
 joinable!(members -> users (user));
+joinable!(password_resets -> users (user_id));
+joinable!(recovery_codes -> users (user_id));
+joinable!(sessions -> users (user_id));
 joinable!(timetables -> classes (class));
+joinable!(two_factor -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    access_token_scopes,
+    access_tokens,
+    api_keys,
+    class_invites,
     classes,
+    discord_link_codes,
+    email_verifications,
     event_types,
     events,
+    former_members,
+    guild_notif_routes,
+    guild_role_mappings,
     guilds,
+    join_requests,
+    login_challenges,
     member_roles,
     members,
+    password_resets,
+    recovery_codes,
+    sessions,
     timetables,
+    two_factor,
     users,
 );
@@ -0,0 +1,63 @@
+//! Random secrets like access tokens, api keys and the tokens of emailed links
+//!
+//! Only the sha256 hash of a secret is stored. It is hashed in the server, so the secret never ends
+//! up in sql queries. The hashes are the same as the ones of pgcrypto's `digest`, which older rows
+//! were hashed with.
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use ring::digest;
+
+/// A new random token of `length` alphanumeric characters after `prefix`, and its hash
+pub fn generate(prefix: &str, length: usize) -> (String, Vec<u8>) {
+    let token = prefix.to_string()
+        + &rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(length)
+            .map(char::from)
+            .collect::<String>();
+    let hash = hash(&token);
+    (token, hash)
+}
+
+/// A random string of `length` characters out of `chars`, for codes that are typed by hand
+pub fn random_string(chars: &[u8], length: usize) -> String {
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| chars[rng.gen_range(0..chars.len())] as char)
+        .collect()
+}
+
+/// The hash that is stored for a token, to look it up again
+pub fn hash(token: &str) -> Vec<u8> {
+    digest::digest(&digest::SHA256, token.as_bytes())
+        .as_ref()
+        .to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{generate, hash};
+
+    #[test]
+    fn tokens() {
+        let (token, token_hash) = generate("cors_", 40);
+        assert!(token.starts_with("cors_"));
+        assert_eq!(token.len(), 45);
+        assert_eq!(token_hash, hash(&token));
+        assert_ne!(generate("cors_", 40).0, token);
+
+        // the same as `digest('abc', 'sha256')` of pgcrypto
+        assert_eq!(
+            hash("abc"),
+            from_hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
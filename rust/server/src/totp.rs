@@ -0,0 +1,133 @@
+//! Time-based one-time passwords (RFC 6238) for the two-factor authentication,
+//! with the defaults that authenticator apps expect: SHA1, 6 digits and 30 second steps
+
+use rand::RngCore;
+use ring::hmac;
+
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+const SECRET_LENGTH: usize = 20;
+/// Codes from the previous and next step are accepted as well, for clocks that are a bit off
+const ALLOWED_DRIFT: i64 = 1;
+
+pub fn generate_secret() -> Vec<u8> {
+    let mut secret = vec![0; SECRET_LENGTH];
+    rand::thread_rng().fill_bytes(&mut secret);
+    secret
+}
+
+/// The secret in the form that can be typed into an authenticator app
+pub fn encode_secret(secret: &[u8]) -> String {
+    base32::encode(base32::Alphabet::RFC4648 { padding: false }, secret)
+}
+
+/// The uri for the QR code that authenticator apps scan
+pub fn otpauth_uri(secret: &[u8], issuer: &str, account: &str) -> String {
+    let label = serde_urlencoded::to_string([("", format!("{}:{}", issuer, account))])
+        .expect("string can be encoded");
+    let query = serde_urlencoded::to_string([
+        ("secret", encode_secret(secret).as_str()),
+        ("issuer", issuer),
+        ("algorithm", "SHA1"),
+        ("digits", &DIGITS.to_string()),
+        ("period", &STEP_SECONDS.to_string()),
+    ])
+    .expect("strings can be encoded");
+
+    // the label is encoded like a query value, `=` is stripped and spaces must be `%20`
+    format!(
+        "otpauth://totp/{}?{}",
+        label.trim_start_matches('=').replace('+', "%20"),
+        query
+    )
+}
+
+/// The time step of a unix timestamp in seconds
+pub fn time_step(timestamp: i64) -> i64 {
+    timestamp.div_euclid(STEP_SECONDS)
+}
+
+pub fn code_at(secret: &[u8], step: i64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let tag = hmac::sign(&key, &step.to_be_bytes());
+    let hash = tag.as_ref();
+
+    // dynamic truncation
+    let offset = (hash[hash.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        hash[offset] & 0x7f,
+        hash[offset + 1],
+        hash[offset + 2],
+        hash[offset + 3],
+    ]);
+
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+/// Checks a code for the current time, returns the matching time step.
+/// Steps up to `last_step` were already used and are rejected
+pub fn verify(secret: &[u8], code: &str, timestamp: i64, last_step: i64) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != DIGITS as usize {
+        return None;
+    }
+    let now = time_step(timestamp);
+
+    (now - ALLOWED_DRIFT..=now + ALLOWED_DRIFT)
+        .filter(|step| *step > last_step)
+        .find(|step| constant_time_eq(code_at(secret, *step).as_bytes(), code.as_bytes()))
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// The SHA1 test vectors of RFC 6238, with the last 6 of the 8 digits
+    #[test]
+    fn rfc_test_vectors() {
+        let secret = b"12345678901234567890";
+        for (time, code) in [
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+        ] {
+            assert_eq!(code_at(secret, time_step(time)), code);
+        }
+    }
+
+    #[test]
+    fn verify_codes() {
+        let secret = b"12345678901234567890";
+        let step = time_step(1111111111);
+
+        assert_eq!(verify(secret, "050471", 1111111111, 0), Some(step));
+        assert_eq!(verify(secret, " 050471 ", 1111111111, 0), Some(step));
+        // the previous step is still accepted
+        assert_eq!(verify(secret, "050471", 1111111111 + 30, 0), Some(step));
+        assert_eq!(verify(secret, "050471", 1111111111 + 90, 0), None);
+        // a used code can't be used again
+        assert_eq!(verify(secret, "050471", 1111111111, step), None);
+        assert_eq!(verify(secret, "000000", 1111111111, 0), None);
+        assert_eq!(verify(secret, "50471", 1111111111, 0), None);
+    }
+
+    #[test]
+    fn uri() {
+        let uri = otpauth_uri(b"12345678901234567890", "CORS", "hugo boss@cors-school.com");
+        assert_eq!(
+            uri,
+            "otpauth://totp/CORS%3Ahugo%20boss%40cors-school.com?\
+            secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&issuer=CORS&algorithm=SHA1&digits=6&period=30"
+        );
+    }
+}